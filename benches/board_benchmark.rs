@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_chess_engine::{agent::MinmaxAgent, board::{Board, BoardConfiguration, EvaluationWeights, Move, MoveData, Square}};
+
+/// A handful of standard positions to run the benchmarks on.
+const POSITIONS: [(&str, &str); 3] = [
+    ("startpos", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+    ("middlegame", "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQ1RK1 b kq - 0 5"),
+    ("endgame", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+];
+
+fn board_from_fen(fen: &str) -> Board
+{
+    Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap())
+}
+
+pub fn move_generation_benchmark(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("generate_moves_for_side");
+    for (name, fen) in POSITIONS
+    {
+        let board = board_from_fen(fen);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board,
+            |b, board| b.iter(|| black_box(board).generate_moves_for_side(board.active_color())));
+    }
+    group.finish();
+}
+
+pub fn attempt_move_benchmark(c: &mut Criterion)
+{
+    let board = Board::new_default_starting_board();
+    // 1. e4
+    let r#move = Move::NormalMove(MoveData::new(Square::new(1, 4), Square::new(3, 4), false));
+    c.bench_function("attempt_move e4", |b| b.iter(|| black_box(&board).attempt_move(black_box(&r#move)).unwrap()));
+}
+
+pub fn perft_benchmark(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+    let board = Board::new_default_starting_board();
+    group.bench_function("perft(4) startpos", |b| b.iter(|| black_box(&board).perft(4)));
+    group.bench_function("perft_parallel(4) startpos", |b| b.iter(|| black_box(&board).perft_parallel(4)));
+    group.finish();
+}
+
+pub fn search_benchmark(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("fixed depth search");
+    group.sample_size(10);
+    let evaluation_weights = EvaluationWeights::default();
+    for (name, fen) in POSITIONS
+    {
+        let board = board_from_fen(fen);
+        group.bench_with_input(BenchmarkId::new("depth 3", name), &board,
+            |b, board| b.iter(|| MinmaxAgent::evaluate(black_box(board), &evaluation_weights, 3)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, move_generation_benchmark, attempt_move_benchmark, perft_benchmark, search_benchmark);
+criterion_main!(benches);
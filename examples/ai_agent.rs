@@ -2,9 +2,13 @@ use rust_chess_engine::{agent::{LocalAgent, MinmaxAgent}, game::Game};
 
 fn main()
 {
-    let agent_white = LocalAgent;
+    // Show the result of the game and what the engine is thinking.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let agent_white = LocalAgent::new();
     let agent_black = MinmaxAgent::new(5);
 
     let mut game = Game::new(agent_white, agent_black);
-    game.run();
+    let record = game.run();
+    println!("{}", record);
 }
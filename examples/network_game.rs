@@ -24,5 +24,6 @@ fn main()
     };
 
     let mut game = Game::new(agent_1, agent_2);
-    game.run();
+    let record = game.run();
+    println!("{}", record);
 }
@@ -7,9 +7,7 @@ mod random_agent
 
     use rust_chess_engine::game::GameState;
 
-    use rust_chess_engine::board::Move;
-
-    use rust_chess_engine::agent::Agent;
+    use rust_chess_engine::agent::{Agent, AgentAction};
 
     /// A [RandomAgent] is an [Agent] that selects moves at random.
     /// Essentially the worst possible algorithm for a chess AI.
@@ -18,11 +16,10 @@ mod random_agent
 
     impl Agent for RandomAgent
     {
-        fn agent_move_request(&mut self, game_state: &GameState) -> Move {
-            let moving_color = game_state.current_board().active_color();
-            let moves = game_state.current_board().generate_moves_for_side(moving_color);
+        fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+            let moves = game_state.legal_moves();
             let move_index: usize = rand::random_range(0..moves.len());
-            return moves[move_index]
+            return AgentAction::Move(moves[move_index])
         }
     }
 }
@@ -32,9 +29,13 @@ use rust_chess_engine::{agent::LocalAgent, game::Game};
 
 fn main()
 {
-    let agent_white = LocalAgent;
+    // Show the result of the game and what the engine is thinking.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let agent_white = LocalAgent::new();
     let agent_black = RandomAgent;
 
     let mut game = Game::new(agent_white, agent_black);
-    game.run();
+    let record = game.run();
+    println!("{}", record);
 }
@@ -1,30 +1,96 @@
 //! This module defines the [Agent] trait as well as a collection
 //! of agents.
 
-use crate::{board::Move, game::GameState};
+use crate::{board::{BoardResult, Move, PlayerColor}, game::GameState};
 
+mod agent_action;
 mod local_agent;
 mod network_agent;
 mod minmax_agent;
+mod human_like_agent;
+mod worst_move_agent;
+mod greedy_agent;
+mod scripted_agent;
+mod pipeline_agent;
+mod spectator_agent;
 
+pub use agent_action::AgentAction;
 pub use local_agent::LocalAgent;
 pub use network_agent::{host, connect, LocalNetworkAgent, RemoteNetworkAgent};
-pub use minmax_agent::MinmaxAgent;
+pub use minmax_agent::{IterationCallback, MinmaxAgent};
+pub use human_like_agent::HumanLikeAgent;
+pub use worst_move_agent::WorstMoveAgent;
+pub use greedy_agent::GreedyAgent;
+pub use scripted_agent::ScriptedAgent;
+pub use pipeline_agent::{PipelineAgent, MoveSource};
+pub use spectator_agent::{spectate, SpectatorAgent, Verbosity};
 
 /// This trait defines an agent, which takes a `&mut self` and
-/// an &[Board] and returns the [Move] that it has selected so the game can progress.
+/// an &[Board] and returns the [Move] that it has selected so the game can progress. Instead of
+/// moving, an agent can also resign or end the game in a draw, see [AgentAction].
+///
+/// Besides being asked for moves, an agent is told when the game starts, when its opponent moves
+/// and when the game ends, so it can i.e think on the opponent's time or clean up afterwards.
+/// These hooks all do nothing by default.
 pub trait Agent
 {
-    /// This method requests for an agent to make a move. Once it has selected its move,
-    /// the game will handle implementing the move and such.
-    fn agent_move_request(&mut self, game_state: &GameState) -> Move;
+    /// This method requests for an agent to make a move. Once it has selected its move (or
+    /// decided to resign, offer a draw, etc.), the game will handle implementing the move and
+    /// such.
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction;
+
+    /// Called by the [Game](crate::game::Game) when a game starts or is resumed, to tell the
+    /// agent which color it's playing. Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color this agent is playing as.
+    fn on_game_start(&mut self, color: PlayerColor)
+    {
+        let _ = color;
+    }
+
+    /// Called by the [Game](crate::game::Game) right after the opponent made a move, before the
+    /// agent is asked for its own. Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game after the opponent's move.
+    /// * `r#move` - The move the opponent made.
+    fn on_opponent_move(&mut self, game_state: &GameState, r#move: &Move)
+    {
+        let _ = (game_state, r#move);
+    }
+
+    /// Called by the [Game](crate::game::Game) once the game is over, whoever made the last move.
+    /// Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - How the game ended.
+    fn on_game_end(&mut self, result: &BoardResult)
+    {
+        let _ = result;
+    }
 }
 
 // We implement T for Box<dyn T> to basically convert dynamic dispatch
 // to static dispatch.
 impl<A: Agent + ?Sized> Agent for Box<A>
 {
-    fn agent_move_request(&mut self, game_state: &GameState) -> Move {
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
         (**self).agent_move_request(game_state)
     }
+
+    fn on_game_start(&mut self, color: PlayerColor) {
+        (**self).on_game_start(color)
+    }
+
+    fn on_opponent_move(&mut self, game_state: &GameState, r#move: &Move) {
+        (**self).on_opponent_move(game_state, r#move)
+    }
+
+    fn on_game_end(&mut self, result: &BoardResult) {
+        (**self).on_game_end(result)
+    }
 }
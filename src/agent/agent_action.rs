@@ -0,0 +1,64 @@
+//! This module defines [AgentAction], what an [Agent](super::Agent) does when it's its turn.
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::Move;
+
+/// What an [Agent](super::Agent) does on its turn. Usually that's making a move, but like in a
+/// real game of chess, a player can also resign, or end the game in a draw.
+///
+/// A [Game](crate::game::Game) checks every action before carrying it out (see
+/// [GameState::apply_action](crate::game::GameState::apply_action)), and asks the agent again if
+/// the action isn't allowed, the same as for an illegal move.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::AgentAction;
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::game::GameState;
+/// let game_state = GameState::new(Board::new_default_starting_board());
+/// let r#move = game_state.legal_moves()[0];
+/// assert_eq!(AgentAction::from(r#move), AgentAction::Move(r#move));
+/// assert_eq!(AgentAction::OfferDraw(r#move).played_move(), Some(r#move));
+/// assert_eq!(AgentAction::Resign.played_move(), None);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AgentAction
+{
+    /// Make a move.
+    Move(Move),
+    /// Make a move and offer a draw with it. The offer stands until the opponent's next action,
+    /// and they can take it with [AgentAction::AcceptDraw]. Anything else declines it.
+    OfferDraw(Move),
+    /// Accept the draw the opponent offered with their last move.
+    AcceptDraw,
+    /// Claim a draw by threefold repetition or the fifty-move rule. See
+    /// [GameState::claimable_draw](crate::game::GameState::claimable_draw).
+    ClaimDraw,
+    /// Give up, and let the opponent win.
+    Resign,
+    /// Take back your last move, along with the opponent's reply to it, and play something else
+    /// instead. See [GameState::undo](crate::game::GameState::undo).
+    Takeback,
+}
+
+impl AgentAction
+{
+    /// Returns the move this action makes on the board, if it makes one.
+    pub fn played_move(&self) -> Option<Move>
+    {
+        match self
+        {
+            Self::Move(r#move) | Self::OfferDraw(r#move) => Some(*r#move),
+            Self::AcceptDraw | Self::ClaimDraw | Self::Resign | Self::Takeback => None,
+        }
+    }
+}
+
+impl From<Move> for AgentAction
+{
+    fn from(r#move: Move) -> Self {
+        Self::Move(r#move)
+    }
+}
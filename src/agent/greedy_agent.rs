@@ -0,0 +1,120 @@
+//! This module implements [GreedyAgent], an [Agent] that plays whichever move looks best right
+//! away, without looking any further ahead.
+
+use crate::{board::{EvaluationWeights, PlayerColor}, game::GameState};
+
+use super::{Agent, AgentAction};
+
+/// A [GreedyAgent] is an [Agent] that plays the move whose resulting position has the best
+/// [approximate evaluation](crate::board::Board::evaluate_approximate), looking only one ply
+/// ahead.
+///
+/// It never sees the recapture coming, so it happily trades its queen for a defended pawn, but it
+/// does grab free material and play mate in one. That makes it a cheap and predictable baseline
+/// for testing evaluation changes: a new set of [EvaluationWeights] should beat the greedy agent
+/// at least as often as the old one did, before it's worth pitting against a
+/// [MinmaxAgent](super::MinmaxAgent).
+pub struct GreedyAgent
+{
+    evaluation_weights: EvaluationWeights,
+}
+
+impl GreedyAgent
+{
+    /// Creates a new [GreedyAgent] using the default [EvaluationWeights].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::GreedyAgent;
+    /// let greedy_agent = GreedyAgent::new();
+    /// ```
+    pub fn new() -> Self
+    {
+        Self::with_evaluation_weights(EvaluationWeights::default())
+    }
+
+    /// Creates a new [GreedyAgent] that judges positions with the given weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `evaluation_weights` - How to evaluate the position after each move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::GreedyAgent;
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// let greedy_agent = GreedyAgent::with_evaluation_weights(EvaluationWeights::default().with_king_safety_weight(2.0));
+    /// ```
+    pub fn with_evaluation_weights(evaluation_weights: EvaluationWeights) -> Self
+    {
+        Self
+        {
+            evaluation_weights,
+        }
+    }
+}
+
+impl Default for GreedyAgent
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for GreedyAgent
+{
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        let board = game_state.current_board();
+        let evaluated_moves = game_state.legal_moves().iter().map(|r#move|
+        {
+            let next_board = board.attempt_move(r#move).expect("Legal moves should always be valid");
+            (*r#move, next_board.evaluate_approximate(&self.evaluation_weights))
+        });
+        // White wants the highest score and Black the lowest.
+        let best_move = match board.active_color()
+        {
+            PlayerColor::White => evaluated_moves.max_by_key(|(_, evaluation)| *evaluation),
+            PlayerColor::Black => evaluated_moves.min_by_key(|(_, evaluation)| *evaluation),
+        };
+        best_move.map(|(r#move, _)| AgentAction::Move(r#move)).expect("No moves generated!")
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::{Board, BoardConfiguration};
+
+    use super::*;
+
+    fn greedy_move(fen: &str) -> String
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+        let r#move = GreedyAgent::new().agent_move_request(&GameState::new(board.clone())).played_move().unwrap();
+        board.to_san(&r#move)
+    }
+
+    #[test]
+    fn takes_free_material()
+    {
+        assert_eq!(greedy_move("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1"), "Rxd5");
+        assert_eq!(greedy_move("4k3/8/8/3q4/8/8/3R4/4K3 b - - 0 1"), "Qxd2+");
+    }
+
+    #[test]
+    fn plays_mate_in_one()
+    {
+        assert_eq!(greedy_move("k7/8/1K6/8/8/8/8/7R w - - 0 1"), "Rh8#");
+    }
+
+    #[test]
+    fn does_not_see_recaptures()
+    {
+        // The c6 pawn takes the queen right back, but that's one ply too far to see.
+        assert_eq!(greedy_move("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1"), "Qxd5");
+    }
+}
@@ -0,0 +1,135 @@
+//! This module implements [HumanLikeAgent], an [Agent] that plays the moves people actually play.
+
+use rand::{distr::{weighted::WeightedIndex, Distribution}, rngs::StdRng, SeedableRng};
+
+use crate::{board::Move, game::GameState, opening_explorer::OpeningExplorer};
+
+use super::{Agent, AgentAction, MinmaxAgent};
+
+/// How deep the fallback search looks once the agent is out of book.
+const DEFAULT_FALLBACK_DEPTH: usize = 1;
+
+/// A [HumanLikeAgent] is an [Agent] that picks moves at random, weighted by how often they were
+/// played in a collection of games (see [OpeningExplorer]). A move that was played in 70% of the
+/// games reaching a position gets played 70% of the time.
+///
+/// Once the game reaches a position that isn't in the explorer, the agent falls back to a shallow
+/// [MinmaxAgent] search. This makes for a sparring partner that plays like a person in the opening
+/// and doesn't fall apart completely afterwards, which is a lot more fun than a RandomAgent.
+pub struct HumanLikeAgent
+{
+    explorer: OpeningExplorer,
+    fallback: MinmaxAgent,
+    rng: StdRng,
+}
+
+impl HumanLikeAgent
+{
+    /// Creates a new [HumanLikeAgent] which samples moves from the given [OpeningExplorer].
+    ///
+    /// # Arguments
+    ///
+    /// * `explorer` - The move statistics to sample moves from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::HumanLikeAgent;
+    /// # use rust_chess_engine::opening_explorer::OpeningExplorer;
+    /// let mut explorer = OpeningExplorer::new();
+    /// explorer.add_pgn("1. e4 e5 2. Nf3 Nc6 *").unwrap();
+    /// let agent = HumanLikeAgent::new(explorer);
+    /// ```
+    pub fn new(explorer: OpeningExplorer) -> Self
+    {
+        Self::with_rng(explorer, StdRng::from_rng(&mut rand::rng()))
+    }
+
+    /// Same as [Self::new], but uses a fixed seed so the same moves get picked every time.
+    ///
+    /// # Arguments
+    ///
+    /// * `explorer` - The move statistics to sample moves from.
+    /// * `seed` - The seed for the random number generator.
+    pub fn with_seed(explorer: OpeningExplorer, seed: u64) -> Self
+    {
+        Self::with_rng(explorer, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(explorer: OpeningExplorer, rng: StdRng) -> Self
+    {
+        Self
+        {
+            explorer,
+            fallback: MinmaxAgent::new(DEFAULT_FALLBACK_DEPTH),
+            rng,
+        }
+    }
+
+    /// Sets how many moves deep the fallback search looks once the agent is out of book.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The fallback search depth.
+    pub fn set_fallback_depth(&mut self, depth: usize)
+    {
+        self.fallback = MinmaxAgent::new(depth);
+    }
+
+    /// Picks a move from the explorer, weighted by how often each move was played. Returns [None]
+    /// if the position isn't in the explorer.
+    fn sample_book_move(&mut self, game_state: &GameState) -> Option<Move>
+    {
+        let board = game_state.current_board();
+        // The explorer only ever records legal moves, but we double check in case it was built
+        // from a corrupt game.
+        let moves: Vec<(Move, u32)> = self.explorer.moves(board).into_iter()
+            .filter(|(r#move, _)| board.attempt_move(r#move).is_ok())
+            .map(|(r#move, statistics)| (r#move, statistics.games()))
+            .collect();
+        let distribution = WeightedIndex::new(moves.iter().map(|(_, games)| *games)).ok()?;
+        Some(moves[distribution.sample(&mut self.rng)].0)
+    }
+}
+
+impl Agent for HumanLikeAgent
+{
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        match self.sample_book_move(game_state)
+        {
+            Some(r#move) => AgentAction::Move(r#move),
+            None => self.fallback.agent_move_request(game_state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::board::Board;
+
+    use super::*;
+
+    #[test]
+    fn only_plays_book_moves_while_in_book()
+    {
+        let mut explorer = OpeningExplorer::new();
+        explorer.add_pgn("1. e4 *\n\n1. e4 *\n\n1. d4 *").unwrap();
+        let book_moves: Vec<Move> = explorer.moves(&Board::new_default_starting_board()).into_iter().map(|(r#move, _)| r#move).collect();
+        let mut agent = HumanLikeAgent::with_seed(explorer, 7);
+        let game_state = GameState::new(Board::new_default_starting_board());
+        for _ in 0..20
+        {
+            assert!(book_moves.contains(&agent.agent_move_request(&game_state).played_move().unwrap()));
+        }
+    }
+
+    #[test]
+    fn falls_back_to_search_out_of_book()
+    {
+        let mut agent = HumanLikeAgent::with_seed(OpeningExplorer::new(), 7);
+        let game_state = GameState::new(Board::new_default_starting_board());
+        let r#move = agent.agent_move_request(&game_state).played_move().unwrap();
+        assert!(game_state.legal_moves().contains(&r#move));
+    }
+}
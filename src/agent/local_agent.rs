@@ -2,23 +2,67 @@
 //! a local player typing moves into their keyboard.
 
 use std::io;
-use std::str::FromStr;
 
-use crate::{board::Move, game::GameState, parse::MoveCommand};
+use crate::{board::PlayerColor, game::GameState, parse::{MoveCommand, ParseMode}};
 
-use super::Agent;
+use super::{Agent, AgentAction};
 
 /// A [LocalAgent] is an [Agent] that makes moves based on 
 /// user input provided via stdin.
-pub struct LocalAgent;
+///
+/// The board is printed from the side of the color the agent is playing, which it learns when
+/// the game starts, so a player playing Black sees their own pieces at the bottom.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalAgent
+{
+    perspective: PlayerColor,
+}
+
+impl LocalAgent
+{
+    /// Creates a new [LocalAgent]. Until the game starts the board is shown from White's side.
+    pub fn new() -> Self
+    {
+        Self
+        {
+            perspective: PlayerColor::White,
+        }
+    }
+
+    /// Returns the color whose side the board is printed from.
+    pub fn perspective(&self) -> PlayerColor
+    {
+        self.perspective
+    }
+}
+
+impl Default for LocalAgent
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Agent for LocalAgent
 {
-    fn agent_move_request(&mut self, game_state: &GameState) -> Move {
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        let last_move = game_state.history().pop().map(|(r#move, board)| board.to_san(&r#move));
         loop 
         {
-            println!("{}", game_state.current_board());
-            println!("Please enter your next move: ");
+            println!("{}", game_state.current_board().view_from(self.perspective));
+            if let Some(status) = game_state.rules().status(game_state)
+            {
+                println!("{}", status);
+            }
+            if let Some(last_move) = &last_move
+            {
+                println!("Your opponent played {}.", last_move);
+            }
+            if game_state.draw_offered()
+            {
+                println!("Your opponent offers a draw. Enter `accept` to take it, or play a move to decline.");
+            }
+            println!("Please enter your next move (add `(=)` after it to offer a draw, or enter `resign`, `claim` for a draw or `takeback` to take back your last move): ");
             let mut input = String::new();
             let result = io::stdin().read_line(&mut input);
             if result.is_err()
@@ -26,7 +70,21 @@ impl Agent for LocalAgent
                 println!("Error: {}", result.unwrap_err());
                 continue;
             }
-            let move_command = MoveCommand::from_str(&input);
+            match input.trim()
+            {
+                "resign" => return AgentAction::Resign,
+                "accept" => return AgentAction::AcceptDraw,
+                "claim" => return AgentAction::ClaimDraw,
+                "takeback" => return AgentAction::Takeback,
+                _ => (),
+            }
+            let (input, offers_draw) = match input.trim().strip_suffix("(=)")
+            {
+                Some(input) => (input, true),
+                None => (input.as_str(), false),
+            };
+            // People type moves in all sorts of ways, so don't hold them to strict notation.
+            let move_command = MoveCommand::parse(input, ParseMode::Lenient);
             if let Err(error) = move_command
             {
                 println!("Badly formatted move! {}", error);
@@ -39,7 +97,31 @@ impl Agent for LocalAgent
                 println!("Impossible move: {}", error);
                 continue;
             }
-            return r#move.unwrap()
+            return match offers_draw
+            {
+                true => AgentAction::OfferDraw(r#move.unwrap()),
+                false => AgentAction::Move(r#move.unwrap()),
+            }
         }
     }
+
+    fn on_game_start(&mut self, color: PlayerColor) {
+        self.perspective = color;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{agent::MinmaxAgent, game::Game};
+
+    use super::*;
+
+    #[test]
+    fn local_agent_playing_black_sees_the_board_from_blacks_side()
+    {
+        let game = Game::new(MinmaxAgent::new(1), LocalAgent::new());
+        let (_, _, black) = game.adjourn();
+        assert_eq!(black.perspective(), PlayerColor::Black);
+    }
 }
@@ -1,13 +1,16 @@
 //! This module implements [MinmaxAgent], a CPU/AI/chess engine [Agent] that
 //! attempts to predict what the best move to make is.
 
-use std::{collections::HashMap, sync::RwLock};
+use std::{sync::Arc, thread::{self, JoinHandle}, time::{Duration, Instant}};
 
+use log::{debug, info, warn};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::{board::{Board, Evaluation, EvaluationWeights, Move, PlayerColor}, game::GameState};
+use crate::{board::{Board, BoardResult, Evaluation, EvaluationWeights, Move, PlayerColor}, game::GameState, opening_book::OpeningBook, search::{allocate_time, LOG_TARGET, ClockState, Deadline, MoveOrdering, MoveStack, NodeType, NodeTypeCounts, SearchResult, SearchStats, TimeAllocation, TimeManager, DEFAULT_MOVE_OVERHEAD, Bound, TranspositionEntry, TranspositionTable}};
 
-use super::Agent;
+use crate::tablebase::{best_root_move, Tablebase};
+
+use super::{Agent, AgentAction};
 
 /// An [Agent] that uses the minimax algorithm
 /// to determine the best move to play.
@@ -15,33 +18,198 @@ pub struct MinmaxAgent
 {
     evaluation_weights: EvaluationWeights,
     evaluation_depth: usize,
-    board_memory: RwLock<HashMap<Board, Evaluation>>
+    /// Shared with the background search while pondering.
+    transposition_table: Arc<TranspositionTable>,
+    time_limit: TimeLimit,
+    move_overhead: Duration,
+    last_search_result: Option<SearchResult>,
+    iteration_callback: Option<IterationCallback>,
+    opening_book: Option<OpeningBook>,
+    book_plies: usize,
+    tablebase: Option<Arc<dyn Tablebase>>,
+    pondering: bool,
+    ponder_worker: Option<PonderWorker>,
+}
+
+/// A function [MinmaxAgent] calls after every iteration of its search. See
+/// [MinmaxAgent::set_iteration_callback].
+pub type IterationCallback = Box<dyn Fn(&SearchResult) + Send + Sync>;
+
+/// How the agent decides when to stop searching.
+enum TimeLimit
+{
+    /// Always search to the full evaluation depth.
+    Depth,
+    /// Think for a fixed amount of time on every move.
+    MoveTime(Duration),
+    /// Budget time from a clock, which the agent keeps up to date itself.
+    Clock(ClockState),
+}
+
+/// A search running on another thread on the position after the agent's last move, while the
+/// opponent thinks about their reply. See [MinmaxAgent::set_pondering].
+struct PonderWorker
+{
+    /// Reached when the search should stop.
+    deadline: Arc<Deadline>,
+    handle: JoinHandle<()>,
+}
+
+impl PonderWorker
+{
+    /// Starts searching `game_state` with `ponderer` on a new thread.
+    fn start(ponderer: MinmaxAgent, game_state: GameState) -> Self
+    {
+        let deadline = Arc::new(Deadline::none());
+        let worker_deadline = Arc::clone(&deadline);
+        let handle = thread::spawn(move || ponderer.ponder(&game_state, &worker_deadline));
+        Self
+        {
+            deadline,
+            handle,
+        }
+    }
+
+    /// Returns true if the search is still going.
+    fn is_running(&self) -> bool
+    {
+        !self.handle.is_finished()
+    }
+
+    /// Stops the search and waits for its thread to finish.
+    fn stop(self)
+    {
+        self.deadline.stop();
+        if self.handle.join().is_err()
+        {
+            warn!(target: LOG_TARGET, "The pondering thread panicked");
+        }
+    }
 }
 
-/// Contains information about how this board state was evaluated.
-struct BoardEvaluationContext
+/// The parts of a search that stay the same at every node.
+struct SearchContext<'a>
 {
-    // How many boards past this board have been evaluated.
-    depth: usize,
-    // The evaluation outcome
-    evaluation: Evaluation
+    /// The weights to use at the end of the evaluation when we use heuristics to evaluate how
+    /// good a position is.
+    evaluation_weights: &'a EvaluationWeights,
+    /// When to give up. Once the deadline is reached the returned evaluation is meaningless and
+    /// should be thrown away.
+    deadline: &'a Deadline,
+    /// Positions that were already searched, shared between all search threads.
+    transposition_table: &'a TranspositionTable,
+    /// Looked up before searching any position with few enough pieces.
+    tablebase: Option<&'a dyn Tablebase>,
+}
+
+/// What searching one root move found. Folding these together keeps the best move, and adds up
+/// the counters from every root move.
+struct RootMoveResult
+{
+    r#move: Move,
+    evaluation: Evaluation,
+    pv: Vec<Move>,
+    node_types: NodeTypeCounts,
+    stats: SearchStats,
+}
+
+/// The parts of a search that each thread keeps to itself and updates as it goes.
+struct ThreadState
+{
+    /// How many plies the thread's [MoveStack] has buffers for.
+    max_ply: usize,
+    /// Which quiet moves caused cutoffs so far. See [MoveOrdering].
+    move_ordering: MoveOrdering,
+    /// Counts the type of every node that isn't a leaf. See [NodeTypeCounts].
+    node_types: NodeTypeCounts,
+    /// Counts every node, and how often the transposition table had the position.
+    stats: SearchStats,
+    /// The best line found from the node currently being searched at each ply, so the lines can
+    /// be built back up into the principal variation from the leaves to the root.
+    lines: Vec<Vec<Move>>,
+}
+
+impl ThreadState
+{
+    fn new(max_ply: usize) -> Self
+    {
+        Self
+        {
+            max_ply,
+            move_ordering: MoveOrdering::with_max_ply(max_ply),
+            node_types: NodeTypeCounts::default(),
+            stats: SearchStats::default(),
+            lines: vec![Vec::new(); max_ply + 1],
+        }
+    }
+
+    /// Returns the best line found from the last node searched at a ply.
+    fn line(&self, ply: usize) -> &[Move]
+    {
+        self.lines.get(ply).map_or(&[], Vec::as_slice)
+    }
+
+    /// Forgets the line at a ply, before searching a new node there.
+    fn clear_line(&mut self, ply: usize)
+    {
+        if let Some(line) = self.lines.get_mut(ply)
+        {
+            line.clear();
+        }
+    }
+
+    /// Makes `r#move` followed by the line one ply deeper the new best line at `ply`.
+    fn update_line(&mut self, ply: usize, r#move: Move)
+    {
+        let split = (ply + 1).min(self.lines.len());
+        let (shallower, deeper) = self.lines.split_at_mut(split);
+        if let Some(line) = shallower.get_mut(ply)
+        {
+            line.clear();
+            line.push(r#move);
+            line.extend_from_slice(deeper.first().map_or(&[], Vec::as_slice));
+        }
+    }
+
+    /// Returns how far from the root a node is. Each node takes one buffer off the move stack,
+    /// so this is how many buffers are gone.
+    fn ply(&self, move_stack: &[Vec<Move>]) -> usize
+    {
+        self.max_ply.saturating_sub(move_stack.len())
+    }
 }
 
+/// How many captures past the end of the normal search the quiescence search looks at, at most.
+/// Captures run out on their own eventually, this just keeps wild positions from taking forever.
+const MAX_QUIESCENCE_PLY: usize = 8;
+
+/// How many entries the throwaway table used by [MinmaxAgent::evaluate] has room for.
+const EVALUATE_TRANSPOSITION_TABLE_SIZE: usize = 1 << 12;
+
 impl Agent for MinmaxAgent {
-    fn agent_move_request(&mut self, game_state: &GameState) -> Move {
-        let agent_color = game_state.current_board().active_color();
-        let moves = game_state.current_board().generate_moves_for_side(agent_color);
-        let best_move = 
-        moves.par_iter().map(|r#move| (r#move, self.evaluate_next_move(game_state, r#move)))
-        .reduce_with(|a, b|
-            match is_new_score_better_than_old_score(agent_color, a.1, b.1)
-            {
-                true => b,
-                false => a,
-            }
-        ).expect("No moves generated!");
-        println!("Best move score: {:?}", best_move.1);
-        *(best_move.0)
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        self.stop_pondering();
+        let best_move = self.choose_move(game_state);
+        if self.pondering
+        {
+            self.start_pondering(game_state, &best_move);
+        }
+        AgentAction::Move(best_move)
+    }
+
+    fn on_game_end(&mut self, _result: &BoardResult) {
+        self.stop_pondering();
+        // Nothing else holds on to the table once pondering stopped.
+        if let Some(transposition_table) = Arc::get_mut(&mut self.transposition_table)
+        {
+            transposition_table.clear();
+        }
+    }
+}
+
+impl Drop for MinmaxAgent {
+    fn drop(&mut self) {
+        self.stop_pondering();
     }
 }
 
@@ -66,18 +234,546 @@ impl MinmaxAgent
         {
             evaluation_weights: EvaluationWeights::default(),
             evaluation_depth,
-            board_memory: RwLock::new(HashMap::new()),
+            transposition_table: Arc::new(TranspositionTable::default()),
+            time_limit: TimeLimit::Depth,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            last_search_result: None,
+            iteration_callback: None,
+            opening_book: None,
+            book_plies: 0,
+            tablebase: None,
+            pondering: false,
+            ponder_worker: None,
         }
     }
 
-    fn evaluate_next_move(&self, current_game_state: &GameState, next_move: &Move) -> Evaluation
+    /// Sets how the agent judges the positions at the end of its search, i.e to compare two sets
+    /// of weights against each other with a [MatchRunner](crate::match_runner::MatchRunner).
+    /// Searches remembered in the transposition table were judged with the old weights, so the
+    /// table is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `evaluation_weights` - How much to weight different factors of a position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// let mut minmax_agent = MinmaxAgent::new(4);
+    /// minmax_agent.set_evaluation_weights(EvaluationWeights::default().with_king_safety_weight(2.0));
+    /// ```
+    pub fn set_evaluation_weights(&mut self, evaluation_weights: EvaluationWeights)
+    {
+        self.stop_pondering();
+        self.evaluation_weights = evaluation_weights;
+        if let Some(transposition_table) = Arc::get_mut(&mut self.transposition_table)
+        {
+            transposition_table.clear();
+        }
+    }
+
+    /// Picks the move to play: from the opening book or the tablebase if they have one,
+    /// otherwise by searching.
+    fn choose_move(&mut self, game_state: &GameState) -> Move
+    {
+        let start = Instant::now();
+        if let Some(book_move) = self.book_move(game_state)
+        {
+            self.charge_clock(start.elapsed());
+            info!(target: LOG_TARGET, "Playing {} from the opening book", game_state.current_board().to_san(&book_move));
+            return book_move;
+        }
+        if let Some(tablebase_move) = self.tablebase_move(game_state)
+        {
+            self.charge_clock(start.elapsed());
+            return tablebase_move;
+        }
+        let search_result = self.search(game_state);
+        // If we didn't even finish the shallowest search in time, any legal move beats losing on
+        // time.
+        let best_move = search_result.as_ref().map_or_else(||
+            *game_state.legal_moves().first().expect("No moves generated!"),
+            |search_result| search_result.best_move()
+        );
+        self.charge_clock(start.elapsed());
+        match search_result
+        {
+            Some(search_result) => info!(target: LOG_TARGET, "Best move {:?} with score {:?} at depth {} ({} nodes, took {:?}), expecting {}",
+                best_move, search_result.score(), search_result.depth(), search_result.nodes(), start.elapsed(),
+                search_result.pv_to_san(game_state.current_board()).join(" ")),
+            None => info!(target: LOG_TARGET, "Search ran out of time, playing {:?}", best_move),
+        }
+        best_move
+    }
+
+    /// Returns what the last search found, i.e how deep it got, how often the best move changed
+    /// and how many nodes of each type it visited. [None] before the first move, or if the last
+    /// search didn't finish in time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::{Agent, MinmaxAgent};
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let mut minmax_agent = MinmaxAgent::new(2);
+    /// minmax_agent.agent_move_request(&GameState::new(Board::new_default_starting_board()));
+    /// let search_result = minmax_agent.last_search_result().unwrap();
+    /// assert_eq!(search_result.depth(), 2);
+    /// assert!(search_result.node_types().total() > 0);
+    /// ```
+    pub fn last_search_result(&self) -> Option<&SearchResult>
+    {
+        self.last_search_result.as_ref()
+    }
+
+    /// Searches the position for the best move, the same way the agent does when it's asked for a
+    /// move, but returns everything the search found instead of just the move. Also updates
+    /// [Self::last_search_result].
+    ///
+    /// Unlike asking for a move, this doesn't count against the agent's clock, so it can be used
+    /// to analyze positions. Returns [None] if the search ran out of time before finishing even
+    /// the shallowest depth.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The position to search. The side to move needs to have a legal move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let mut minmax_agent = MinmaxAgent::new(3);
+    /// let search_result = minmax_agent.search(&GameState::new(Board::new_default_starting_board())).unwrap();
+    /// assert_eq!(search_result.depth(), 3);
+    /// // The best move, and the three plies searched after it.
+    /// assert_eq!(search_result.pv().len(), 4);
+    /// ```
+    pub fn search(&mut self, game_state: &GameState) -> Option<SearchResult>
+    {
+        let allocation = match &self.time_limit
+        {
+            TimeLimit::Depth => None,
+            TimeLimit::MoveTime(move_time) => Some(TimeAllocation::fixed(*move_time, self.move_overhead)),
+            TimeLimit::Clock(clock) => Some(allocate_time(clock, game_state.current_board().fullmove_number(), self.move_overhead)),
+        };
+        let search_result = match allocation
+        {
+            None =>
+            {
+                let start = Instant::now();
+                let (mut node_types, mut stats) = (NodeTypeCounts::default(), SearchStats::default());
+                self.search_to_depth(game_state, self.evaluation_depth, &Deadline::none(), &mut node_types, &mut stats)
+                    .map(|(best_move, evaluation, pv)|
+                    {
+                        let search_result = SearchResult::new(best_move, evaluation, pv, stats.at_depth(self.evaluation_depth, start.elapsed()), node_types);
+                        self.report_iteration(&search_result);
+                        search_result
+                    })
+            },
+            Some(allocation) => self.iterative_deepening(game_state, allocation),
+        };
+        self.last_search_result = search_result.clone();
+        search_result
+    }
+
+    /// Sets a function to call every time an iteration of the search finishes, with what the
+    /// search found so far. This is how a GUI can show the search making progress, i.e with UCI
+    /// `info` lines. A search to a fixed depth counts as one iteration. Passing [None] removes the
+    /// callback.
+    ///
+    /// The callback is called on the thread that asked for the move, between iterations, so it
+    /// should return quickly.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The function to call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::{sync::{Arc, Mutex}, time::Duration};
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let mut minmax_agent = MinmaxAgent::new(2);
+    /// minmax_agent.set_move_time(Some(Duration::from_secs(60)));
+    /// let depths = Arc::new(Mutex::new(Vec::new()));
+    /// let reported_depths = depths.clone();
+    /// minmax_agent.set_iteration_callback(Some(Box::new(move |search_result| {
+    ///     let stats = search_result.stats();
+    ///     println!("info depth {} seldepth {} nodes {} nps {}", stats.depth(), stats.seldepth(), stats.nodes(), stats.nps());
+    ///     reported_depths.lock().unwrap().push(stats.depth());
+    /// })));
+    /// minmax_agent.search(&GameState::new(Board::new_default_starting_board()));
+    /// assert_eq!(*depths.lock().unwrap(), [0, 1, 2]);
+    /// ```
+    pub fn set_iteration_callback(&mut self, callback: Option<IterationCallback>)
+    {
+        self.iteration_callback = callback;
+    }
+
+    fn report_iteration(&self, search_result: &SearchResult)
+    {
+        let stats = search_result.stats();
+        debug!(target: LOG_TARGET, "Depth {} (seldepth {}): best move {:?} with score {:?} ({} nodes so far, {} nps, {:.0}% TT hits)",
+            stats.depth(), stats.seldepth(), search_result.best_move(), search_result.score(), stats.nodes(), stats.nps(), stats.tt_hit_rate() * 100.0);
+        if let Some(callback) = &self.iteration_callback
+        {
+            callback(search_result);
+        }
+    }
+
+    /// Gives the agent an opening book to play from for the first `book_plies` plies of the game,
+    /// i.e 10 for the first five moves of each side. Positions that aren't in the book are
+    /// searched as usual. How the book picks between moves is set on the book itself, see
+    /// [OpeningBook::with_selection]. Passing [None] (the default) always searches.
+    ///
+    /// Plies are counted from the fullmove number, so a game set up from a FEN halfway through the
+    /// opening only plays from the book for what's left of the `book_plies`.
+    ///
+    /// # Arguments
+    ///
+    /// * `opening_book` - The book to play from.
+    /// * `book_plies` - How many plies into the game the book is used for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::{Agent, MinmaxAgent};
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::opening_book::{polyglot_key, OpeningBook};
+    /// let board = Board::new_default_starting_board();
+    /// // A book with only 1. b3 in it (b2 to b3 is encoded as 0x0251).
+    /// let bytes = [&polyglot_key(&board).to_be_bytes()[..], &0x0251_u16.to_be_bytes(), &1_u16.to_be_bytes(), &[0; 4]].concat();
+    /// let mut minmax_agent = MinmaxAgent::new(2);
+    /// minmax_agent.set_opening_book(Some(OpeningBook::from_bytes(&bytes).unwrap()), 10);
+    /// let r#move = minmax_agent.agent_move_request(&GameState::new(board.clone())).played_move().unwrap();
+    /// assert_eq!(board.to_san(&r#move), "b3");
+    /// ```
+    pub fn set_opening_book(&mut self, opening_book: Option<OpeningBook>, book_plies: usize)
+    {
+        self.opening_book = opening_book;
+        self.book_plies = book_plies;
+    }
+
+    /// Returns a move from the opening book, if there is one and we're still early enough in the
+    /// game to use it.
+    fn book_move(&mut self, game_state: &GameState) -> Option<Move>
+    {
+        let board = game_state.current_board();
+        let ply = (board.fullmove_number() as usize).saturating_sub(1) * 2 + board.active_color().index();
+        if ply >= self.book_plies
+        {
+            return None;
+        }
+        self.opening_book.as_mut()?.choose_move(board)
+    }
+
+    /// Gives the agent an endgame tablebase. Positions the tablebase covers are played perfectly
+    /// instead of searched, and covered positions inside the search are scored as exact wins,
+    /// draws and losses. Passing [None] (the default) goes back to searching everything.
+    ///
+    /// # Arguments
+    ///
+    /// * `tablebase` - The tablebase to look positions up in. It can be shared between agents.
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<dyn Tablebase>>)
+    {
+        self.tablebase = tablebase;
+    }
+
+    /// Returns the move the tablebase says keeps the best result, if there is a tablebase and it
+    /// covers the position.
+    fn tablebase_move(&self, game_state: &GameState) -> Option<Move>
+    {
+        let board = game_state.current_board();
+        let (r#move, wdl) = best_root_move(self.tablebase.as_deref()?, board)?;
+        info!(target: LOG_TARGET, "Playing {} from the tablebase, which says the position is a {:?}", board.to_san(&r#move), wdl);
+        Some(r#move)
+    }
+
+    /// Looks the position up in the tablebase, if there is one and it covers the position.
+    fn probe_tablebase(board: &Board, context: &SearchContext) -> Option<Evaluation>
+    {
+        let tablebase = context.tablebase.filter(|tablebase| tablebase.covers(board))?;
+        Some(tablebase.probe_wdl(board)?.to_evaluation(board.active_color()))
+    }
+
+    /// Takes the time spent on a move off the agent's clock, if it has one.
+    fn charge_clock(&mut self, elapsed: Duration)
+    {
+        if let TimeLimit::Clock(clock) = &mut self.time_limit
+        {
+            *clock = clock.after_move(elapsed);
+        }
+    }
+
+    /// Lets the agent keep thinking on the opponent's time (pondering). After every move it
+    /// makes, the agent searches the position the opponent now has to answer on a background
+    /// thread, filling the transposition table with what each reply leads to, so the next search
+    /// starts out knowing most of the tree. The background search stops as soon as the agent is
+    /// asked for its next move, or once it's searched one ply deeper than the evaluation depth.
+    /// Off by default.
+    ///
+    /// Turning pondering off stops the background search if it's running.
+    ///
+    /// # Arguments
+    ///
+    /// * `pondering` - Whether to think on the opponent's time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::{Agent, MinmaxAgent};
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let mut minmax_agent = MinmaxAgent::new(2);
+    /// minmax_agent.set_pondering(true);
+    /// minmax_agent.agent_move_request(&GameState::new(Board::new_default_starting_board()));
+    /// // Done analyzing, stop thinking.
+    /// minmax_agent.stop_pondering();
+    /// assert!(!minmax_agent.is_pondering());
+    /// ```
+    pub fn set_pondering(&mut self, pondering: bool)
+    {
+        self.pondering = pondering;
+        if !pondering
+        {
+            self.stop_pondering();
+        }
+    }
+
+    /// Returns true while a background search started by pondering is still running. See
+    /// [Self::set_pondering].
+    pub fn is_pondering(&self) -> bool
+    {
+        self.ponder_worker.as_ref().is_some_and(PonderWorker::is_running)
+    }
+
+    /// Stops the background search started by pondering, if there is one, and waits for its
+    /// thread to finish. A [Game](crate::game::Game) does this when the game ends (see
+    /// [Agent::on_game_end]), so the agent doesn't keep thinking about a game that's over. It also
+    /// happens on the next move request, and when the agent is dropped.
+    pub fn stop_pondering(&mut self)
+    {
+        if let Some(ponder_worker) = self.ponder_worker.take()
+        {
+            ponder_worker.stop();
+        }
+    }
+
+    /// Starts pondering on the position after `r#move`, unless the move ends the game.
+    fn start_pondering(&mut self, game_state: &GameState, r#move: &Move)
+    {
+        let Ok(next_game_state) = game_state.update(r#move) else { return };
+        if next_game_state.game_result().is_over()
+        {
+            return;
+        }
+        self.ponder_worker = Some(PonderWorker::start(self.ponderer(), next_game_state));
+    }
+
+    /// Returns an agent for the background search, which shares this agent's transposition
+    /// table and searches one ply deeper, since its root is a ply before the next real search.
+    fn ponderer(&self) -> Self
+    {
+        MinmaxAgent
+        {
+            evaluation_weights: self.evaluation_weights.clone(),
+            evaluation_depth: self.evaluation_depth + 1,
+            transposition_table: Arc::clone(&self.transposition_table),
+            time_limit: TimeLimit::Depth,
+            move_overhead: self.move_overhead,
+            last_search_result: None,
+            iteration_callback: None,
+            opening_book: None,
+            book_plies: 0,
+            tablebase: self.tablebase.clone(),
+            pondering: false,
+            ponder_worker: None,
+        }
+    }
+
+    /// Searches deeper and deeper until the evaluation depth or the deadline is reached, only
+    /// to fill the transposition table.
+    fn ponder(&self, game_state: &GameState, deadline: &Deadline)
+    {
+        let (mut node_types, mut stats) = (NodeTypeCounts::default(), SearchStats::default());
+        let mut depth_reached = None;
+        for depth in 0..=self.evaluation_depth
+        {
+            if self.search_to_depth(game_state, depth, deadline, &mut node_types, &mut stats).is_none()
+            {
+                break;
+            }
+            depth_reached = Some(depth);
+        }
+        debug!(target: LOG_TARGET, "Pondered to depth {:?} ({} nodes)", depth_reached, stats.nodes());
+    }
+
+    /// Limits how long the agent may think about each move. When set, the agent searches
+    /// deeper and deeper until the time is up, treating the evaluation depth as a maximum.
+    /// [None] (the default) always searches to the full evaluation depth, however long that takes.
+    ///
+    /// # Arguments
+    ///
+    /// * `move_time` - How long to think about each move, including the move overhead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// let mut minmax_agent = MinmaxAgent::new(10);
+    /// minmax_agent.set_move_time(Some(Duration::from_secs(1)));
+    /// ```
+    pub fn set_move_time(&mut self, move_time: Option<Duration>)
+    {
+        self.time_limit = match move_time
+        {
+            Some(move_time) => TimeLimit::MoveTime(move_time),
+            None => TimeLimit::Depth,
+        };
+    }
+
+    /// Gives the agent a clock to budget its time from. The agent spends more time on moves
+    /// when it has more time left, and when the best move keeps changing as it searches deeper.
+    /// See [allocate_time].
+    ///
+    /// The agent keeps the clock up to date itself after every move it makes. As with
+    /// [Self::set_move_time], the evaluation depth is treated as a maximum. Passing [None] goes
+    /// back to always searching to the full evaluation depth.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The agent's clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::search::ClockState;
+    /// let mut minmax_agent = MinmaxAgent::new(10);
+    /// // 5 minutes plus 3 seconds per move.
+    /// minmax_agent.set_clock(Some(ClockState::new(Duration::from_secs(300), Duration::from_secs(3), None)));
+    /// ```
+    pub fn set_clock(&mut self, clock: Option<ClockState>)
+    {
+        self.time_limit = match clock
+        {
+            Some(clock) => TimeLimit::Clock(clock),
+            None => TimeLimit::Depth,
+        };
+    }
+
+    /// Searches deeper and deeper until the [TimeManager] decides it's time to stop, returning the
+    /// result of the deepest search that finished.
+    fn iterative_deepening(&self, game_state: &GameState, allocation: TimeAllocation) -> Option<SearchResult>
+    {
+        let start = Instant::now();
+        let mut time_manager = TimeManager::new(allocation);
+        let deadline = time_manager.deadline();
+        let mut node_types = NodeTypeCounts::default();
+        let mut stats = SearchStats::default();
+        let mut best: Option<SearchResult> = None;
+        let mut iterations = 0;
+        for depth in 0..=self.evaluation_depth
+        {
+            match self.search_to_depth(game_state, depth, &deadline, &mut node_types, &mut stats)
+            {
+                Some((r#move, evaluation, pv)) =>
+                {
+                    time_manager.record_iteration(r#move);
+                    iterations += 1;
+                    let search_result = SearchResult::new(r#move, evaluation, pv, stats.at_depth(depth, start.elapsed()), node_types)
+                        .with_iterations(iterations, time_manager.best_move_changes());
+                    self.report_iteration(&search_result);
+                    best = Some(search_result);
+                },
+                None => break,
+            }
+            if !time_manager.should_start_next_iteration()
+            {
+                break;
+            }
+        }
+        best
+    }
+
+    /// Sets how much of the move time to set aside for network or GUI latency, so the agent
+    /// doesn't run out of time in fast games. See [crate::search::apply_move_overhead].
+    ///
+    /// # Arguments
+    ///
+    /// * `move_overhead` - The time to set aside on every move.
+    pub fn set_move_overhead(&mut self, move_overhead: Duration)
+    {
+        self.move_overhead = move_overhead;
+    }
+
+    /// Finds the best move by evaluating every legal move `depth` moves deep, and returns it with
+    /// its evaluation and principal variation. Returns [None] if the deadline was reached before
+    /// the search finished. The visited nodes are added to `node_types` and `stats` either way.
+    fn search_to_depth(&self, game_state: &GameState, depth: usize, deadline: &Deadline, node_types: &mut NodeTypeCounts, stats: &mut SearchStats) -> Option<(Move, Evaluation, Vec<Move>)>
+    {
+        let agent_color = game_state.current_board().active_color();
+        let max_ply = depth + MAX_QUIESCENCE_PLY;
+        let best = game_state.legal_moves().par_iter()
+        // Each thread gets its own move buffers and move ordering, which are reused for every root
+        // move it searches.
+        .map_init(|| (MoveStack::with_max_ply(max_ply), ThreadState::new(max_ply)), |(move_stack, thread), r#move|
+        {
+            let evaluation = self.evaluate_next_move(game_state, r#move, depth, deadline, move_stack, thread);
+            RootMoveResult
+            {
+                r#move: *r#move,
+                evaluation,
+                // The line after the root move is whatever the search below it settled on.
+                pv: std::iter::once(*r#move).chain(thread.line(0).iter().copied()).collect(),
+                node_types: std::mem::take(&mut thread.node_types),
+                stats: std::mem::take(&mut thread.stats),
+            }
+        })
+        .reduce_with(|a, b|
+        {
+            let (node_types, stats) = (a.node_types + b.node_types, a.stats + b.stats);
+            let best = match is_new_score_better_than_old_score(agent_color, a.evaluation, b.evaluation)
+            {
+                true => b,
+                false => a,
+            };
+            RootMoveResult { node_types, stats, ..best }
+        }).expect("No moves generated!");
+        *node_types += best.node_types;
+        *stats += best.stats;
+        match deadline.was_reached()
+        {
+            true => None,
+            false => Some((best.r#move, best.evaluation, best.pv)),
+        }
+    }
+
+    fn evaluate_next_move(&self, current_game_state: &GameState, next_move: &Move, depth: usize, deadline: &Deadline, move_stack: &mut MoveStack, thread: &mut ThreadState) -> Evaluation
     {
         let next_move = current_game_state.update(next_move)
             .expect("Somehow we gave the board an illegal move in the Minmax Agent");
 
         // If we want to we can add to our evaluation with stuff like
         // "Are we moving a piece twice in a row" or whatever. Hopefully.
-        return Self::evaluate(next_move.current_board(), &self.evaluation_weights, self.evaluation_depth);
+        let context = SearchContext
+        {
+            evaluation_weights: &self.evaluation_weights,
+            deadline,
+            transposition_table: &self.transposition_table,
+            tablebase: self.tablebase.as_deref(),
+        };
+        return Self::evaluate_recursive(&mut next_move.current_board().clone(), &context, Evaluation::BlackWin, Evaluation::WhiteWin, depth, move_stack.plies(), thread);
     }
 
     /// Evaluates a position. 
@@ -96,10 +792,16 @@ impl MinmaxAgent
     /// ```
     pub fn evaluate(board: &Board, evaluation_weights: &EvaluationWeights, depth: usize) -> Evaluation
     {
+        let context = SearchContext
+        {
+            evaluation_weights,
+            deadline: &Deadline::none(),
+            transposition_table: &TranspositionTable::new(EVALUATE_TRANSPOSITION_TABLE_SIZE),
+            tablebase: None,
+        };
+        let max_ply = depth + MAX_QUIESCENCE_PLY;
         // Start at negative and positive "infinity"
-        let mut alpha = Evaluation::BlackWin;
-        let mut beta = Evaluation::WhiteWin;
-        Self::evaluate_recursive(board, evaluation_weights, &mut alpha, &mut beta, depth)
+        Self::evaluate_recursive(&mut board.clone(), &context, Evaluation::BlackWin, Evaluation::WhiteWin, depth, MoveStack::with_max_ply(max_ply).plies(), &mut ThreadState::new(max_ply))
     }
 
     /// Recursively evaluate all possible moves up to `depth` moves in the future.
@@ -108,36 +810,102 @@ impl MinmaxAgent
     ///
     /// # Arguments
     ///
-    /// * `evaluation_weights` - The weights to use at the end of the evaluation when we use
-    /// heuristics to evaluate how good a position is.
+    /// * `context` - The evaluation weights and the deadline. See [SearchContext].
     /// * `alpha` - The minimum score that the maximizing player is assured of.
     /// * `beta` - The maximum score that the minimizing player is assured of.
     /// * `depth` - How many moves in the future to continue evaluating
+    /// * `move_stack` - Buffers to generate the moves into, one per remaining ply. See
+    ///   [MoveStack].
+    /// * `thread` - The move ordering and node counts of the searching thread. See
+    ///   [ThreadState].
     ///
     /// # Examples
     ///
     /// ```
     /// ```
-    fn evaluate_recursive(board: &Board, evaluation_weights: &EvaluationWeights, alpha: &mut Evaluation, beta: &mut Evaluation, depth: usize) -> Evaluation
+    fn evaluate_recursive(board: &mut Board, context: &SearchContext, mut alpha: Evaluation, mut beta: Evaluation, depth: usize, move_stack: &mut [Vec<Move>], thread: &mut ThreadState) -> Evaluation
     {
-        // Once we reach a depth of 0, just approximate the position and return the score
+        // Whatever line was found here last time belongs to some other position.
+        let ply = thread.ply(move_stack);
+        thread.clear_line(ply);
+        if depth > 0
+        {
+            // Leaves are counted by the quiescence search.
+            thread.stats.record_node(ply);
+        }
+        if context.deadline.is_reached()
+        {
+            return Evaluation::Draw;
+        }
+
+        // The tablebase knows the real result, which beats anything we could work out.
+        if let Some(evaluation) = Self::probe_tablebase(board, context)
+        {
+            return evaluation;
+        }
+
+        // Once we reach a depth of 0, play out any captures that are still hanging and
+        // approximate the positions after that.
         if depth == 0
         {
-            return board.evaluate_approximate(evaluation_weights);
+            return Self::quiescence(board, context, alpha, beta, MAX_QUIESCENCE_PLY, move_stack, thread);
         }
 
-        let possible_moves = board.generate_moves_for_side(board.active_color());
-        match board.active_color()
+        // If we've already searched this position at least this deep, we might not have to
+        // search it again.
+        let key = board.zobrist_key();
+        let stored_entry = context.transposition_table.probe(key);
+        thread.stats.record_tt_probe(stored_entry.is_some());
+        if let Some(entry) = stored_entry.filter(|entry| entry.depth() >= depth)
+        {
+            match entry.bound()
+            {
+                Bound::Exact => return entry.evaluation(),
+                Bound::Lower if entry.evaluation() >= beta => return entry.evaluation(),
+                Bound::Upper if entry.evaluation() <= alpha => return entry.evaluation(),
+                _ => (),
+            }
+        }
+
+        let mut fallback_moves = Vec::new();
+        let (possible_moves, deeper_plies) = match move_stack.split_first_mut()
+        {
+            Some((moves, deeper_plies)) => (moves, deeper_plies),
+            // The stack wasn't sized for this deep a search, so we have to allocate after all.
+            None => (&mut fallback_moves, &mut [][..]),
+        };
+        board.generate_moves_for_side_into(board.active_color(), possible_moves);
+        // The best move from a previous search is the most likely to cause a cutoff, so it goes
+        // first, followed by captures and the quiet moves that caused cutoffs elsewhere.
+        thread.move_ordering.order_moves(board, possible_moves, stored_entry.and_then(|entry| entry.best_move()), ply);
+
+        let alpha_at_start = alpha;
+        let beta_at_start = beta;
+        let mut best_move = None;
+        let mut node_type = NodeType::All;
+        let best_value = match board.active_color()
         {
             // White is trying to MAXIMIZE score.
             PlayerColor::White =>
             {
                 let mut best_value = Evaluation::BlackWin;
-                for r#move in possible_moves
+                for r#move in possible_moves.iter()
                 {
-                    best_value = Ord::max(best_value, Self::evaluate_recursive(&board.attempt_move(&r#move).expect("Expected move to be valid."), evaluation_weights, alpha, beta, depth - 1));
-                    if best_value >= *beta
+                    let value = Self::evaluate_after_move(board, r#move, |board| Self::evaluate_recursive(board, context, alpha, beta, depth - 1, deeper_plies, thread));
+                    if best_move.is_none() || value > best_value
+                    {
+                        best_value = value;
+                        best_move = Some(*r#move);
+                        thread.update_line(ply, *r#move);
+                    }
+                    if best_value > alpha_at_start
                     {
+                        node_type = NodeType::Pv;
+                    }
+                    if best_value >= beta
+                    {
+                        node_type = NodeType::Cut;
+                        thread.move_ordering.record_cutoff(board, *r#move, ply, depth);
                         // No need to evaluate further, we already know this is more or
                         // less "too good to be true" because black is assured of a lower
                         // score.
@@ -146,53 +914,160 @@ impl MinmaxAgent
                         break;
                     }
                     // We update our minimum score to the greater of these two values.
-                    *alpha = Ord::max(*alpha, best_value);
-                }
-                return match best_value
-                {
-                    // We update our evaluation based on going "back" a step. So if White
-                    // wins in the next board, on this board we return a score of
-                    // WhiteCheckmateIn(1), so on and so forth.
-                    Evaluation::WhiteWin => Evaluation::WhiteCheckmateIn(1),
-                    Evaluation::WhiteCheckmateIn(x) => Evaluation::WhiteCheckmateIn(x+1),
-                    Evaluation::BlackWin => Evaluation::BlackCheckmateIn(1),
-                    Evaluation::BlackCheckmateIn(x) => Evaluation::BlackCheckmateIn(x+1),
-                    Evaluation::Draw => Evaluation::Score(0.0),
-                    Evaluation::Score(x) => Evaluation::Score(x),
+                    alpha = Ord::max(alpha, best_value);
                 }
+                best_value
             }
             // Black is trying to MINIMIZE score.
             PlayerColor::Black =>
             {
                 let mut best_value = Evaluation::WhiteWin;
-                for r#move in possible_moves
+                for r#move in possible_moves.iter()
                 {
-                    best_value = Ord::min(best_value, Self::evaluate_recursive(&board.attempt_move(&r#move).expect("Expected move to be valid"), evaluation_weights, alpha, beta, depth - 1));
-                    if best_value <= *alpha
+                    let value = Self::evaluate_after_move(board, r#move, |board| Self::evaluate_recursive(board, context, alpha, beta, depth - 1, deeper_plies, thread));
+                    if best_move.is_none() || value < best_value
+                    {
+                        best_value = value;
+                        best_move = Some(*r#move);
+                        thread.update_line(ply, *r#move);
+                    }
+                    if best_value < beta_at_start
                     {
+                        node_type = NodeType::Pv;
+                    }
+                    if best_value <= alpha
+                    {
+                        node_type = NodeType::Cut;
+                        thread.move_ordering.record_cutoff(board, *r#move, ply, depth);
                         // No need to evaluate further, see above case for beta cutoff.
                         //
                         // This is an alpha cutoff.
                         break;
                     }
                     // The maximum score that black is assured of.
-                    *beta = Ord::min(*beta, best_value);
+                    beta = Ord::min(beta, best_value);
                 }
-                return match best_value
+                best_value
+            }
+        };
+        thread.node_types.record(node_type);
+
+        let evaluation = match best_value
+        {
+            // We update our evaluation based on going "back" a step. So if White
+            // wins in the next board, on this board we return a score of
+            // WhiteCheckmateIn(1), so on and so forth.
+            Evaluation::WhiteWin => Evaluation::WhiteCheckmateIn(1),
+            Evaluation::WhiteCheckmateIn(x) => Evaluation::WhiteCheckmateIn(x+1),
+            Evaluation::BlackWin => Evaluation::BlackCheckmateIn(1),
+            Evaluation::BlackCheckmateIn(x) => Evaluation::BlackCheckmateIn(x+1),
+            Evaluation::Draw => Evaluation::Score(0.0),
+            Evaluation::Score(x) => Evaluation::Score(x),
+            tablebase_win @ (Evaluation::WhiteTablebaseWin | Evaluation::BlackTablebaseWin) => tablebase_win,
+        };
+
+        // A search that ran out of time returns nonsense, which we don't want to remember.
+        if !context.deadline.was_reached()
+        {
+            let bound = match (node_type, board.active_color())
+            {
+                // Checkmate or stalemate, there's nothing else to search.
+                _ if best_move.is_none() => Bound::Exact,
+                (NodeType::Cut, PlayerColor::White) => Bound::Lower,
+                (NodeType::Cut, PlayerColor::Black) => Bound::Upper,
+                (NodeType::All, PlayerColor::White) => Bound::Upper,
+                (NodeType::All, PlayerColor::Black) => Bound::Lower,
+                (NodeType::Pv, _) => Bound::Exact,
+            };
+            context.transposition_table.store(TranspositionEntry::new(key, depth, evaluation, bound, best_move));
+        }
+        evaluation
+    }
+
+    /// Keeps searching captures past the end of the normal search until the position is quiet.
+    ///
+    /// Approximating a position in the middle of an exchange is badly wrong, i.e right after
+    /// QxP it looks like we won a pawn even if the queen can be taken back next move (the horizon
+    /// effect). Here the side to move can either stop and take the approximate score (the "stand
+    /// pat") or try any capture, so exchanges are only counted once they're over. Captures that
+    /// lose material even after every recapture is played out ([Board::see]) are skipped, since
+    /// standing pat is always at least as good.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The evaluation weights and the deadline. See [SearchContext].
+    /// * `alpha` - The minimum score that the maximizing player is assured of.
+    /// * `beta` - The maximum score that the minimizing player is assured of.
+    /// * `plies_left` - How many more captures to look at before stopping no matter what.
+    /// * `move_stack` - Buffers to generate the captures into, one per remaining ply.
+    /// * `thread` - The node counts of the searching thread.
+    fn quiescence(board: &mut Board, context: &SearchContext, mut alpha: Evaluation, mut beta: Evaluation, plies_left: usize, move_stack: &mut [Vec<Move>], thread: &mut ThreadState) -> Evaluation
+    {
+        thread.stats.record_node(thread.ply(move_stack));
+        let stand_pat = board.evaluate_approximate(context.evaluation_weights);
+        // Games that are already over don't have anything left to play out.
+        if plies_left == 0 || !matches!(stand_pat, Evaluation::Score(_)) || context.deadline.is_reached()
+        {
+            return stand_pat;
+        }
+
+        let mut fallback_captures = Vec::new();
+        let (captures, deeper_plies) = match move_stack.split_first_mut()
+        {
+            Some((captures, deeper_plies)) => (captures, deeper_plies),
+            None => (&mut fallback_captures, &mut [][..]),
+        };
+        board.generate_captures_for_side_into(board.active_color(), captures);
+        captures.retain(|capture| match capture
+        {
+            Move::NormalMove(move_data) => board.see(move_data.target_square(), capture) >= 0,
+            Move::Castle(_) => true,
+        });
+        MoveOrdering::order_captures(board, captures);
+        match board.active_color()
+        {
+            PlayerColor::White =>
+            {
+                let mut best_value = stand_pat;
+                for capture in captures.iter()
                 {
-                    // We update our evaluation based on going "back" a step. So if White
-                    // wins in the next board, on this board we return a score of
-                    // WhiteCheckmateIn(1), so on and so forth.
-                    Evaluation::WhiteWin => Evaluation::WhiteCheckmateIn(1),
-                    Evaluation::WhiteCheckmateIn(x) => Evaluation::WhiteCheckmateIn(x+1),
-                    Evaluation::BlackWin => Evaluation::BlackCheckmateIn(1),
-                    Evaluation::BlackCheckmateIn(x) => Evaluation::BlackCheckmateIn(x+1),
-                    Evaluation::Draw => Evaluation::Score(0.0),
-                    Evaluation::Score(x) => Evaluation::Score(x),
+                    if best_value >= beta
+                    {
+                        break;
+                    }
+                    alpha = Ord::max(alpha, best_value);
+                    let value = Self::evaluate_after_move(board, capture, |board| Self::quiescence(board, context, alpha, beta, plies_left - 1, deeper_plies, thread));
+                    best_value = Ord::max(best_value, value);
                 }
-            }
+                best_value
+            },
+            PlayerColor::Black =>
+            {
+                let mut best_value = stand_pat;
+                for capture in captures.iter()
+                {
+                    if best_value <= alpha
+                    {
+                        break;
+                    }
+                    beta = Ord::min(beta, best_value);
+                    let value = Self::evaluate_after_move(board, capture, |board| Self::quiescence(board, context, alpha, beta, plies_left - 1, deeper_plies, thread));
+                    best_value = Ord::min(best_value, value);
+                }
+                best_value
+            },
         }
     }
+
+    /// Makes `r#move` on the board, evaluates the position after it with `evaluate`, and takes
+    /// the move back again, so the search never has to copy the board.
+    fn evaluate_after_move(board: &mut Board, r#move: &Move, evaluate: impl FnOnce(&mut Board) -> Evaluation) -> Evaluation
+    {
+        let undo_info = board.make_move_in_place(r#move).expect("Generated moves should always be valid");
+        let value = evaluate(board);
+        board.unmake_move(&undo_info);
+        value
+    }
 }
 
 fn is_new_score_better_than_old_score(player_color: PlayerColor, old_score: Evaluation, new_score: Evaluation) -> bool
@@ -204,3 +1079,156 @@ fn is_new_score_better_than_old_score(player_color: PlayerColor, old_score: Eval
     }
 }
 
+
+#[cfg(test)]
+mod tests
+{
+    use std::{str::FromStr, time::Instant};
+
+    use crate::{board::BoardConfiguration, parse::MoveCommand};
+
+    use super::*;
+
+    #[test]
+    fn move_time_stops_the_search_early()
+    {
+        // Depth 50 would never finish, so the agent has to stop on time.
+        let mut agent = MinmaxAgent::new(50);
+        agent.set_move_time(Some(Duration::from_millis(200)));
+        agent.set_move_overhead(Duration::from_millis(100));
+        let game_state = GameState::new(Board::new_default_starting_board());
+
+        let start = Instant::now();
+        let r#move = agent.agent_move_request(&game_state).played_move().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(game_state.legal_moves().contains(&r#move));
+    }
+
+    #[test]
+    fn clock_is_updated_after_each_move()
+    {
+        let mut agent = MinmaxAgent::new(50);
+        agent.set_move_overhead(Duration::ZERO);
+        agent.set_clock(Some(ClockState::new(Duration::from_secs(2), Duration::from_millis(100), None)));
+        let game_state = GameState::new(Board::new_default_starting_board());
+
+        agent.agent_move_request(&game_state);
+        match agent.time_limit
+        {
+            TimeLimit::Clock(clock) =>
+            {
+                assert!(clock.remaining() < Duration::from_millis(2100));
+                // We shouldn't have used anywhere near a third of the clock on the first move.
+                assert!(clock.remaining() > Duration::from_millis(1300));
+            },
+            _ => panic!("Expected the agent to still be using a clock"),
+        }
+    }
+
+    #[test]
+    fn iterative_deepening_reports_each_iteration()
+    {
+        let mut agent = MinmaxAgent::new(2);
+        agent.set_move_time(Some(Duration::from_secs(60)));
+        let game_state = GameState::new(Board::new_default_starting_board());
+
+        agent.agent_move_request(&game_state);
+        let search_result = agent.last_search_result().expect("The search should have finished");
+        // Depths 0, 1 and 2.
+        assert_eq!(search_result.iterations(), 3);
+        assert_eq!(search_result.depth(), 2);
+        assert!(search_result.best_move_changes() <= 2);
+        assert!((0.0..=1.0).contains(&search_result.best_move_stability()));
+    }
+
+    #[test]
+    fn searched_positions_are_remembered()
+    {
+        let mut agent = MinmaxAgent::new(1);
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap());
+        let game_state = GameState::new(board);
+
+        let r#move = agent.agent_move_request(&game_state).played_move().unwrap();
+        assert_eq!(r#move, game_state.legal_move(&MoveCommand::from_str("Rh8").unwrap()).unwrap());
+        let after_move = game_state.update(&r#move).unwrap();
+        let entry = agent.transposition_table.probe(after_move.current_board().zobrist_key()).expect("The position after the best move should be stored");
+        assert_eq!(entry.depth(), 1);
+        assert_eq!(entry.bound(), Bound::Exact);
+        // Black is checkmated, so there's no best move.
+        assert!(entry.best_move().is_none());
+    }
+
+    #[test]
+    fn quiescence_plays_out_recaptures()
+    {
+        // White just grabbed a pawn with the queen, but the c6 pawn takes it back.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/2p5/3Q4/8/8/8/4K3 b - - 0 1").unwrap());
+        let weights = EvaluationWeights::default();
+        assert!(board.evaluate_approximate(&weights) > Evaluation::Score(0.0));
+        assert!(MinmaxAgent::evaluate(&board, &weights, 0) < Evaluation::Score(0.0));
+    }
+
+    #[test]
+    fn principal_variation_is_a_legal_line()
+    {
+        let mut agent = MinmaxAgent::new(2);
+        agent.set_move_time(Some(Duration::from_secs(60)));
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/8/4k3/8/2K5/8/3R4/8 w - - 0 1").unwrap());
+        let search_result = agent.search(&GameState::new(board.clone())).expect("The search should have finished");
+        assert_eq!(search_result.depth(), 2);
+        assert_eq!(search_result.pv().first(), Some(&search_result.best_move()));
+        assert_eq!(search_result.pv_to_san(&board).len(), search_result.pv().len());
+        // Every node that isn't a leaf has a type, and leaves are nodes too.
+        assert!(search_result.nodes() > search_result.node_types().total());
+        assert_eq!(agent.last_search_result(), Some(&search_result));
+        assert!(search_result.stats().seldepth() > search_result.depth());
+    }
+
+    #[test]
+    fn pondering_fills_the_table_and_stops_when_asked()
+    {
+        let game_state = GameState::new(Board::new_default_starting_board());
+        let mut agent = MinmaxAgent::new(1);
+        agent.set_pondering(true);
+        let r#move = agent.agent_move_request(&game_state).played_move().unwrap();
+        let start = Instant::now();
+        while agent.is_pondering() && start.elapsed() < Duration::from_secs(60)
+        {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        agent.stop_pondering();
+        // The position after any reply was searched two plies deep in the background, deeper
+        // than the search for the move itself went.
+        let after_move = game_state.update(&r#move).unwrap();
+        let after_reply = after_move.update(&after_move.legal_moves()[0]).unwrap();
+        let entry = agent.transposition_table.probe(after_reply.current_board().zobrist_key()).expect("The reply should have been pondered");
+        assert_eq!(entry.depth(), 2);
+
+        // A search that would never finish on its own still stops right away.
+        let mut agent = MinmaxAgent::new(50);
+        agent.set_move_time(Some(Duration::from_millis(50)));
+        agent.set_pondering(true);
+        agent.agent_move_request(&game_state);
+        assert!(agent.is_pondering());
+        agent.stop_pondering();
+        assert!(!agent.is_pondering());
+    }
+
+    #[test]
+    fn tablebase_moves_are_played_and_scored()
+    {
+        use crate::tablebase::tests::MaterialTablebase;
+
+        // Taking the queen wins, anything else is a draw.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/8/8/2k5/8/8/2r1Q3/K7 b - - 0 1").unwrap());
+        let game_state = GameState::new(board.clone());
+        let mut agent = MinmaxAgent::new(1);
+        agent.set_tablebase(Some(Arc::new(MaterialTablebase)));
+        assert_eq!(board.to_san(&agent.agent_move_request(&game_state).played_move().unwrap()), "Rxe2");
+        // The search doesn't use the tablebase at the root, but every position below it is
+        // covered.
+        let search_result = agent.search(&game_state).unwrap();
+        assert_eq!(board.to_san(&search_result.best_move()), "Rxe2");
+        assert_eq!(search_result.score(), Evaluation::BlackTablebaseWin);
+    }
+}
@@ -2,11 +2,13 @@
 //! which allow two players to play over a network. Each player locally has a [LocalNetworkAgent]
 //! and the other player is treated as a [RemoteNetworkAgent], waiting for moves to come in.
 
-use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::{io, net::{TcpListener, TcpStream, ToSocketAddrs}, str::FromStr};
 
-use crate::{board::Move, game::GameState};
+use serde::{Deserialize, Serialize};
 
-use super::{Agent, LocalAgent};
+use crate::{board::{BoardResult, Move, PlayerColor}, game::GameState, parse::MoveCommand};
+
+use super::{Agent, AgentAction, LocalAgent};
 
 /// Opens up a [TcpListener] and blocks and waits for a connection.
 ///
@@ -102,14 +104,32 @@ pub fn connect<A: ToSocketAddrs>(addr: A) -> (RemoteNetworkAgent, LocalNetworkAg
     }
 }
 
+/// The messages sent between a [LocalNetworkAgent] and its paired [RemoteNetworkAgent].
+#[derive(Debug, Serialize, Deserialize)]
+enum NetworkMessage
+{
+    /// What the player did on their turn: usually a move, but they can also resign or offer,
+    /// accept or claim a draw.
+    Action(AgentAction),
+    /// A move the player queued up while waiting for their opponent, played instantly
+    /// once it turned out to still be legal.
+    Premove(Move),
+}
+
 /// A [LocalNetworkAgent] is an [Agent](super::Agent) that functions exactly
 /// like a [LocalAgent](super::LocalAgent) but also sends the [Move](crate::board::Move)s made 
 /// to the paired [RemoteNetworkAgent].
+///
+/// After making a move, the local player can queue up a premove to play on their next turn. When
+/// the opponent's move arrives the premove is checked again, and if it's still legal it is sent
+/// instantly without asking the player for a move. Otherwise it is thrown away and the player is
+/// asked for a move as usual.
 pub struct LocalNetworkAgent
 {
     // We wrap an inner agent so that we can use the local agent code to do things
     inner_agent: LocalAgent,
     stream: TcpStream,
+    premove: Option<MoveCommand>,
 }
 
 impl LocalNetworkAgent
@@ -118,38 +138,113 @@ impl LocalNetworkAgent
     {
         Self
         {
-            inner_agent: LocalAgent,
+            inner_agent: LocalAgent::new(),
             stream,
+            premove: None,
+        }
+    }
+
+    /// Returns the currently queued premove, if any.
+    pub fn premove(&self) -> Option<&MoveCommand>
+    {
+        self.premove.as_ref()
+    }
+
+    /// Queues up a premove to be played on this agent's next turn if it's still legal then, or
+    /// clears the queued premove if `premove` is [None].
+    ///
+    /// # Arguments
+    ///
+    /// * `premove` - The move to play next turn.
+    pub fn set_premove(&mut self, premove: Option<MoveCommand>)
+    {
+        self.premove = premove;
+    }
+
+    fn send(&self, message: &NetworkMessage)
+    {
+        postcard::to_io(message, &self.stream).expect("Unable to write move to stream!");
+    }
+
+    /// Asks the player for an (optional) premove to play on their next turn.
+    fn prompt_for_premove(&mut self)
+    {
+        println!("Enter a premove to play next turn, or leave blank for none: ");
+        let mut input = String::new();
+        if let Err(error) = io::stdin().read_line(&mut input)
+        {
+            println!("Error: {}", error);
+            return;
+        }
+        if input.trim().is_empty()
+        {
+            return;
+        }
+        match MoveCommand::from_str(&input)
+        {
+            Ok(move_command) => self.premove = Some(move_command),
+            Err(error) => println!("Badly formatted premove, ignoring it. {}", error),
         }
     }
 }
 
 impl Agent for LocalNetworkAgent
 {
-    fn agent_move_request(&mut self, game_state: &GameState) -> Move {
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        if let Some(premove) = self.premove.take()
+        {
+            // The premove was entered before the opponent moved, so we have to check it again.
+            match game_state.legal_move(&premove)
+            {
+                Ok(r#move) =>
+                {
+                    println!("Playing premove {:?}", premove);
+                    self.send(&NetworkMessage::Premove(r#move));
+                    return AgentAction::Move(r#move);
+                },
+                Err(error) => println!("Premove {:?} is no longer possible, discarding it. {}", premove, error),
+            }
+        }
+
         loop 
         {
-            let r#move = self.inner_agent.agent_move_request(game_state);
+            let action = self.inner_agent.agent_move_request(game_state);
             // This may be a mistake having the local agent validate its own move before sending it
             // instead of just improving the board error check code...
             //
             // But there's no reason we can't just validate the move here.
-            let new_board_result = game_state.current_board().attempt_move(&r#move);
-            match new_board_result
+            match game_state.apply_action(&action)
             {
                 Err(error) => {
                     println!("Error making move! {}", error);
                     continue;
                 },
-                Ok(_) =>
+                Ok(new_game_state) =>
                 {
-                    // Return the move we made and also send it to the RemoteNetworkAgent.
-                    postcard::to_io(&r#move, &self.stream).expect("Unable to write move to stream!");
-                    return r#move;
+                    // Return the action we took and also send it to the RemoteNetworkAgent.
+                    self.send(&NetworkMessage::Action(action));
+                    // No point asking for a premove if the game is over.
+                    if new_game_state.game_result().is_in_progress()
+                    {
+                        self.prompt_for_premove();
+                    }
+                    return action;
                 }
             }
         }
     }
+
+    fn on_game_start(&mut self, color: PlayerColor) {
+        self.inner_agent.on_game_start(color);
+    }
+
+    fn on_opponent_move(&mut self, game_state: &GameState, r#move: &Move) {
+        self.inner_agent.on_opponent_move(game_state, r#move);
+    }
+
+    fn on_game_end(&mut self, result: &BoardResult) {
+        self.inner_agent.on_game_end(result);
+    }
 }
 
 /// A [RemoteNetworkAgent] is an [Agent](super::Agent) that listens for [Move](crate::board::Move)s
@@ -173,11 +268,48 @@ impl RemoteNetworkAgent
 
 impl Agent for RemoteNetworkAgent
 {
-    fn agent_move_request(&mut self, game_state: &GameState) -> Move {
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
         println!("{}", game_state.current_board());
         println!("Waiting for player's move...");
         let mut buffer: Vec<u8> = Vec::new();
-        let r#move: Move = postcard::from_io((&self.stream, &mut buffer)).expect("Couldn't read move from stream!").0;
-        r#move
+        let message: NetworkMessage = postcard::from_io((&self.stream, &mut buffer)).expect("Couldn't read move from stream!").0;
+        match message
+        {
+            NetworkMessage::Action(action) => action,
+            NetworkMessage::Premove(r#move) =>
+            {
+                println!("Your opponent premoved!");
+                AgentAction::Move(r#move)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::board::Board;
+
+    use super::*;
+
+    fn connected_agents() -> (LocalNetworkAgent, RemoteNetworkAgent)
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (LocalNetworkAgent::new(server), RemoteNetworkAgent::new(client))
+    }
+
+    #[test]
+    fn legal_premove_is_sent_without_prompting()
+    {
+        let (mut local_agent, mut remote_agent) = connected_agents();
+        let game_state = GameState::new(Board::new_default_starting_board());
+        local_agent.set_premove(Some(MoveCommand::from_str("Nf3").unwrap()));
+
+        let r#move = local_agent.agent_move_request(&game_state);
+        assert!(local_agent.premove().is_none());
+        assert_eq!(r#move, AgentAction::Move(game_state.legal_move(&MoveCommand::from_str("Nf3").unwrap()).unwrap()));
+        assert_eq!(remote_agent.agent_move_request(&game_state), r#move);
     }
 }
@@ -0,0 +1,164 @@
+//! This module implements [PipelineAgent], an [Agent] made by chaining together other sources of
+//! moves, like an opening book followed by a tablebase followed by a search.
+
+use crate::{board::{BoardResult, Move, PlayerColor}, game::GameState, opening_explorer::OpeningExplorer};
+
+use super::{Agent, AgentAction};
+
+/// A source of moves that may or may not have an answer for a given position, like an opening
+/// book (which only knows about opening positions) or an endgame tablebase (which only knows
+/// about positions with few pieces left).
+///
+/// Unlike an [Agent], a [MoveSource] is allowed to give up and return [None], in which case a
+/// [PipelineAgent] moves on to the next source.
+pub trait MoveSource
+{
+    /// Returns the move this source suggests for the current position, or [None] if it doesn't
+    /// know what to play.
+    ///
+    /// The returned move should be legal on the current board.
+    fn probe(&mut self, game_state: &GameState) -> Option<Move>;
+}
+
+impl<S: MoveSource + ?Sized> MoveSource for Box<S>
+{
+    fn probe(&mut self, game_state: &GameState) -> Option<Move> {
+        (**self).probe(game_state)
+    }
+}
+
+impl MoveSource for OpeningExplorer
+{
+    /// Plays the most popular move from the current position, if the position is in the book.
+    fn probe(&mut self, game_state: &GameState) -> Option<Move> {
+        let board = game_state.current_board();
+        self.moves(board).into_iter()
+            .map(|(r#move, _)| r#move)
+            .find(|r#move| board.attempt_move(r#move).is_ok())
+    }
+}
+
+/// A [PipelineAgent] asks each of its [MoveSource]s for a move in order, playing the first move
+/// it gets. If none of the sources have an answer, it falls back to a search [Agent] which always
+/// has one.
+///
+/// The usual setup is an opening book, then an endgame tablebase, then a search, which is how a
+/// "full strength" engine is put together from its parts.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::{MinmaxAgent, PipelineAgent};
+/// # use rust_chess_engine::opening_explorer::OpeningExplorer;
+/// let mut book = OpeningExplorer::new();
+/// book.add_pgn("1. e4 e5 2. Nf3 Nc6 3. Bb5 *").unwrap();
+/// let agent = PipelineAgent::new(MinmaxAgent::new(3))
+///     .add_stage(book);
+/// ```
+pub struct PipelineAgent<A: Agent>
+{
+    stages: Vec<Box<dyn MoveSource>>,
+    search: A,
+}
+
+impl<A: Agent> PipelineAgent<A>
+{
+    /// Creates a new [PipelineAgent] with no stages, which will always use `search`.
+    ///
+    /// # Arguments
+    ///
+    /// * `search` - The [Agent] used when none of the stages have a move.
+    pub fn new(search: A) -> Self
+    {
+        Self
+        {
+            stages: Vec::new(),
+            search,
+        }
+    }
+
+    /// Adds a stage to the end of the pipeline. Stages are asked for moves in the order they
+    /// were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - The [MoveSource] to add.
+    pub fn add_stage<S: MoveSource + 'static>(mut self, stage: S) -> Self
+    {
+        self.stages.push(Box::new(stage));
+        self
+    }
+}
+
+impl<A: Agent> Agent for PipelineAgent<A>
+{
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        self.stages.iter_mut()
+            .find_map(|stage| stage.probe(game_state))
+            .map_or_else(|| self.search.agent_move_request(game_state), AgentAction::Move)
+    }
+
+    fn on_game_start(&mut self, color: PlayerColor) {
+        self.search.on_game_start(color);
+    }
+
+    fn on_opponent_move(&mut self, game_state: &GameState, r#move: &Move) {
+        self.search.on_opponent_move(game_state, r#move);
+    }
+
+    fn on_game_end(&mut self, result: &BoardResult) {
+        self.search.on_game_end(result);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::Board, parse::MoveCommand};
+
+    use super::*;
+
+    /// A stage that never knows what to play.
+    struct EmptyStage;
+
+    impl MoveSource for EmptyStage
+    {
+        fn probe(&mut self, _game_state: &GameState) -> Option<Move> {
+            None
+        }
+    }
+
+    /// A "search" that always plays the first legal move.
+    struct FirstMoveAgent;
+
+    impl Agent for FirstMoveAgent
+    {
+        fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+            AgentAction::Move(game_state.legal_moves()[0])
+        }
+    }
+
+    #[test]
+    fn uses_first_stage_with_an_answer()
+    {
+        let mut book = OpeningExplorer::new();
+        book.add_pgn("1. d4 *").unwrap();
+        let mut agent = PipelineAgent::new(FirstMoveAgent)
+            .add_stage(EmptyStage)
+            .add_stage(book);
+        let game_state = GameState::new(Board::new_default_starting_board());
+        assert_eq!(agent.agent_move_request(&game_state).played_move().unwrap(), game_state.legal_move(&MoveCommand::from_str("d4").unwrap()).unwrap());
+    }
+
+    #[test]
+    fn falls_back_to_search()
+    {
+        let mut agent = PipelineAgent::new(FirstMoveAgent)
+            .add_stage(EmptyStage)
+            .add_stage(OpeningExplorer::new());
+        let game_state = GameState::new(Board::new_default_starting_board());
+        assert_eq!(agent.agent_move_request(&game_state).played_move().unwrap(), game_state.legal_moves()[0]);
+    }
+}
@@ -0,0 +1,193 @@
+//! This module implements [ScriptedAgent], an [Agent] that plays a fixed list of moves, for
+//! driving a [Game](crate::game::Game) through a known sequence in tests.
+
+use std::{collections::VecDeque, str::FromStr};
+
+use crate::{game::GameState, parse::{MoveCommand, NotationParseError}};
+
+use super::{Agent, AgentAction};
+
+/// One move in the script.
+#[derive(Debug, Clone)]
+enum ScriptedMove
+{
+    /// An action that's taken as is.
+    Action(AgentAction),
+    /// A move in algebraic notation, which is only turned into a [Move](crate::board::Move) once the position it's
+    /// played in is known.
+    Command(MoveCommand),
+}
+
+/// A [ScriptedAgent] is an [Agent] that plays the moves it was given, in order, no matter what
+/// its opponent does.
+///
+/// This lets tests play through known games (castling, en passant, stalemates, ...) with a real
+/// [Game](crate::game::Game) instead of typing the moves in. A script going wrong is a bug in the
+/// test, so the agent panics if it runs out of moves, or if the next move isn't legal. Returning
+/// an illegal move instead would just get it asked again, forever.
+///
+/// Besides moves, a script can resign or offer, accept or claim a draw (see [AgentAction]), to
+/// test games that end off the board.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::ScriptedAgent;
+/// # use rust_chess_engine::board::PlayerColor;
+/// # use rust_chess_engine::game::Game;
+/// // Fool's mate.
+/// let (white, black) = ScriptedAgent::pair_from_san(&["f3", "e5", "g4", "Qh4#"]).unwrap();
+/// let mut game = Game::new(white, black);
+/// game.run();
+/// assert_eq!(game.game_state().game_result().get_winner(), Ok(PlayerColor::Black));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScriptedAgent
+{
+    moves: VecDeque<ScriptedMove>,
+}
+
+impl ScriptedAgent
+{
+    /// Creates a [ScriptedAgent] that takes the given actions, which can also just be [Move](crate::board::Move)s.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - The actions to take, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::{AgentAction, ScriptedAgent};
+    /// # use rust_chess_engine::board::{Move, MoveData, Square};
+    /// // e2-e4
+    /// let agent = ScriptedAgent::new([Move::NormalMove(MoveData::new(Square::new(1, 4), Square::new(3, 4), false))]);
+    /// assert_eq!(agent.moves_left(), 1);
+    /// let agent = ScriptedAgent::new([AgentAction::Resign]);
+    /// assert_eq!(agent.moves_left(), 1);
+    /// ```
+    pub fn new<A: Into<AgentAction>>(actions: impl IntoIterator<Item = A>) -> Self
+    {
+        Self
+        {
+            moves: actions.into_iter().map(|action| ScriptedMove::Action(action.into())).collect(),
+        }
+    }
+
+    /// Creates a [ScriptedAgent] that plays moves written in standard algebraic notation, like
+    /// `Nf3` or `O-O`. Each move is looked up in the position it's played in.
+    ///
+    /// # Arguments
+    ///
+    /// * `sans` - The moves to play, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [NotationParseError] if any of the moves can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::ScriptedAgent;
+    /// let agent = ScriptedAgent::from_san(&["e4", "Nf3", "Bc4", "O-O"]).unwrap();
+    /// assert_eq!(agent.moves_left(), 4);
+    /// assert!(ScriptedAgent::from_san(&["e0"]).is_err());
+    /// ```
+    pub fn from_san(sans: &[&str]) -> Result<Self, NotationParseError>
+    {
+        let moves = sans.iter()
+            .map(|san| MoveCommand::from_str(san).map(ScriptedMove::Command))
+            .collect::<Result<_, _>>()?;
+        Ok(Self
+        {
+            moves,
+        })
+    }
+
+    /// Splits a whole game written in standard algebraic notation into a [ScriptedAgent] for
+    /// White and one for Black, so both sides of a [Game](crate::game::Game) can be scripted.
+    /// The first move is White's.
+    ///
+    /// # Arguments
+    ///
+    /// * `sans` - Every move of the game, alternating between White and Black.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [NotationParseError] if any of the moves can't be parsed.
+    pub fn pair_from_san(sans: &[&str]) -> Result<(Self, Self), NotationParseError>
+    {
+        let white: Vec<&str> = sans.iter().step_by(2).copied().collect();
+        let black: Vec<&str> = sans.iter().skip(1).step_by(2).copied().collect();
+        Ok((Self::from_san(&white)?, Self::from_san(&black)?))
+    }
+
+    /// Returns how many moves of the script haven't been played yet.
+    pub fn moves_left(&self) -> usize
+    {
+        self.moves.len()
+    }
+}
+
+impl Agent for ScriptedAgent
+{
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        let board = game_state.current_board();
+        let action = match self.moves.pop_front().expect("The scripted agent ran out of moves")
+        {
+            ScriptedMove::Action(action) => action,
+            ScriptedMove::Command(move_command) => game_state.legal_move(&move_command)
+                .map(AgentAction::Move)
+                .unwrap_or_else(|error| panic!("The scripted move {:?} can't be played: {}\n{}", move_command, error, board)),
+        };
+        if let Err(error) = game_state.apply_action(&action)
+        {
+            panic!("The scripted action {:?} can't be taken: {}\n{}", action, error, board);
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{board::{DrawReason, Piece, PieceType, PlayerColor, Square}, game::Game};
+
+    use super::*;
+
+    #[test]
+    fn plays_castling_and_en_passant()
+    {
+        let (white, black) = ScriptedAgent::pair_from_san(&["e4", "a6", "e5", "d5", "exd6", "Nf6", "Nf3", "Bg4", "Be2", "Bxf3", "O-O"]).unwrap();
+        let mut game = Game::new(white, black);
+        for _ in 0..11
+        {
+            game.next_turn();
+        }
+        let board = game.game_state().current_board();
+        assert_eq!(board.piece_at(&Square::new(5, 3)), Some(&Piece::new(PlayerColor::White, PieceType::Pawn)));
+        assert_eq!(board.piece_at(&Square::new(4, 3)), None);
+        assert_eq!(board.piece_at(&Square::new(0, 6)), Some(&Piece::new(PlayerColor::White, PieceType::King)));
+    }
+
+    #[test]
+    fn plays_into_stalemate()
+    {
+        // Sam Loyd's ten-move stalemate.
+        let (white, black) = ScriptedAgent::pair_from_san(&[
+            "e3", "a5", "Qh5", "Ra6", "Qxa5", "h5", "h4", "Rah6", "Qxc7", "f6",
+            "Qxd7+", "Kf7", "Qxb7", "Qd3", "Qxb8", "Qh7", "Qxc8", "Kg6", "Qe6",
+        ]).unwrap();
+        let mut game = Game::new(white, black);
+        game.run();
+        assert_eq!(game.game_state().game_result().get_draw_reason(), Some(DrawReason::Stalemate));
+    }
+
+    #[test]
+    #[should_panic(expected = "ran out of moves")]
+    fn running_out_of_moves_panics()
+    {
+        let mut game = Game::new(ScriptedAgent::from_san(&["e4"]).unwrap(), ScriptedAgent::from_san(&["e5"]).unwrap());
+        game.run();
+    }
+}
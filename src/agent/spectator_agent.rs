@@ -0,0 +1,209 @@
+//! This module implements [SpectatorAgent], which wraps another [Agent] and narrates its moves to
+//! the console so games between engines can be followed.
+
+use std::io::{self, Stdout, Write};
+
+use crate::{board::{BoardResult, Move, PlayerColor}, game::GameState};
+
+use super::{Agent, AgentAction};
+
+/// How much a [SpectatorAgent] prints.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Verbosity
+{
+    /// Nothing is printed.
+    Quiet,
+    /// Each move is printed in algebraic notation, i.e `12. Nf3` or `12... Nf6`.
+    #[default]
+    Moves,
+    /// Each move is printed along with the board after it.
+    Boards,
+}
+
+/// A [SpectatorAgent] plays exactly like the [Agent] it wraps, but prints every move it makes
+/// (and optionally the board) as it goes.
+///
+/// Engine vs engine games otherwise run silently, so wrapping both players with
+/// [spectate] lets you watch the whole game move by move.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::{spectate, MinmaxAgent, Verbosity};
+/// # use rust_chess_engine::game::Game;
+/// let (white, black) = spectate(MinmaxAgent::new(1), MinmaxAgent::new(1), Verbosity::Moves);
+/// let mut game = Game::new(white, black);
+/// // Prints "1. ..." and "1... ..."
+/// game.next_round();
+/// ```
+pub struct SpectatorAgent<A: Agent, W: Write = Stdout>
+{
+    inner_agent: A,
+    verbosity: Verbosity,
+    output: W,
+}
+
+impl<A: Agent> SpectatorAgent<A>
+{
+    /// Wraps an [Agent] so its moves are printed to stdout.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner_agent` - The agent that actually picks the moves.
+    /// * `verbosity` - How much to print.
+    pub fn new(inner_agent: A, verbosity: Verbosity) -> Self
+    {
+        Self::with_output(inner_agent, verbosity, io::stdout())
+    }
+}
+
+impl<A: Agent, W: Write> SpectatorAgent<A, W>
+{
+    /// Wraps an [Agent] so its moves are written to `output` instead of stdout, i.e a log file.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner_agent` - The agent that actually picks the moves.
+    /// * `verbosity` - How much to print.
+    /// * `output` - Where to write to.
+    pub fn with_output(inner_agent: A, verbosity: Verbosity, output: W) -> Self
+    {
+        Self
+        {
+            inner_agent,
+            verbosity,
+            output,
+        }
+    }
+
+    /// Returns how much is being printed.
+    pub fn verbosity(&self) -> Verbosity
+    {
+        self.verbosity
+    }
+
+    /// Changes how much is printed, i.e to only show the boards near the end of a long game.
+    ///
+    /// # Arguments
+    ///
+    /// * `verbosity` - How much to print from now on.
+    pub fn set_verbosity(&mut self, verbosity: Verbosity)
+    {
+        self.verbosity = verbosity;
+    }
+
+    /// Unwraps the [SpectatorAgent], returning the inner agent and the output.
+    pub fn into_inner(self) -> (A, W)
+    {
+        (self.inner_agent, self.output)
+    }
+
+    fn narrate(&mut self, game_state: &GameState, action: &AgentAction) -> io::Result<()>
+    {
+        let board = game_state.current_board();
+        let move_number = board.fullmove_number();
+        let description = match action
+        {
+            AgentAction::Move(r#move) => board.to_san(r#move),
+            AgentAction::OfferDraw(r#move) => format!("{} (=)", board.to_san(r#move)),
+            AgentAction::AcceptDraw => format!("{:?} accepts the draw", board.active_color()),
+            AgentAction::ClaimDraw => format!("{:?} claims a draw", board.active_color()),
+            AgentAction::Resign => format!("{:?} resigns", board.active_color()),
+            AgentAction::Takeback => format!("{:?} takes back their last move", board.active_color()),
+        };
+        match board.active_color()
+        {
+            PlayerColor::White => writeln!(self.output, "{}. {}", move_number, description)?,
+            PlayerColor::Black => writeln!(self.output, "{}... {}", move_number, description)?,
+        }
+        if self.verbosity == Verbosity::Boards
+        {
+            if let Some(Ok(next_board)) = action.played_move().map(|r#move| board.attempt_move(&r#move))
+            {
+                write!(self.output, "{}", next_board)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A: Agent, W: Write> Agent for SpectatorAgent<A, W>
+{
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        let action = self.inner_agent.agent_move_request(game_state);
+        if self.verbosity != Verbosity::Quiet
+        {
+            // Failing to print shouldn't stop the game.
+            let _ = self.narrate(game_state, &action);
+        }
+        action
+    }
+
+    fn on_game_start(&mut self, color: PlayerColor) {
+        self.inner_agent.on_game_start(color);
+    }
+
+    fn on_opponent_move(&mut self, game_state: &GameState, r#move: &Move) {
+        self.inner_agent.on_opponent_move(game_state, r#move);
+    }
+
+    fn on_game_end(&mut self, result: &BoardResult) {
+        self.inner_agent.on_game_end(result);
+    }
+}
+
+/// Wraps both players of a game in [SpectatorAgent]s with the same verbosity, so every move of
+/// the game is printed.
+///
+/// # Arguments
+///
+/// * `agent_white` - The white player [Agent].
+/// * `agent_black` - The black player [Agent].
+/// * `verbosity` - How much to print.
+pub fn spectate<A1: Agent, A2: Agent>(agent_white: A1, agent_black: A2, verbosity: Verbosity) -> (SpectatorAgent<A1>, SpectatorAgent<A2>)
+{
+    (SpectatorAgent::new(agent_white, verbosity), SpectatorAgent::new(agent_black, verbosity))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{agent::MinmaxAgent, board::Board, game::Game};
+
+    use super::*;
+
+    fn play_one_round(verbosity: Verbosity) -> (String, String)
+    {
+        let white = SpectatorAgent::with_output(MinmaxAgent::new(1), verbosity, Vec::new());
+        let black = SpectatorAgent::with_output(MinmaxAgent::new(1), verbosity, Vec::new());
+        let mut game = Game::new(white, black);
+        game.next_round();
+        let (_, white, black) = game.adjourn();
+        (String::from_utf8(white.into_inner().1).unwrap(), String::from_utf8(black.into_inner().1).unwrap())
+    }
+
+    #[test]
+    fn moves_are_numbered_for_each_side()
+    {
+        let (white_output, black_output) = play_one_round(Verbosity::Moves);
+        assert!(white_output.starts_with("1. "));
+        assert!(black_output.starts_with("1... "));
+        assert_eq!(white_output.lines().count(), 1);
+    }
+
+    #[test]
+    fn boards_verbosity_prints_the_board()
+    {
+        let (white_output, _) = play_one_round(Verbosity::Boards);
+        let board_lines = Board::new_default_starting_board().to_string().lines().count();
+        assert_eq!(white_output.lines().count(), 1 + board_lines);
+    }
+
+    #[test]
+    fn quiet_prints_nothing()
+    {
+        let (white_output, black_output) = play_one_round(Verbosity::Quiet);
+        assert!(white_output.is_empty());
+        assert!(black_output.is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! This module implements [WorstMoveAgent], an [Agent] that always plays the worst move it can
+//! find.
+
+use crate::{board::{EvaluationWeights, PlayerColor}, game::GameState};
+
+use super::{Agent, AgentAction, MinmaxAgent};
+
+/// A [WorstMoveAgent] is an [Agent] that evaluates every legal move and deliberately plays the
+/// one that's worst for itself.
+///
+/// This makes for a gentle opponent for beginners (it happily hangs its pieces), and it's a good
+/// sanity check for the evaluation: if the worst move it finds doesn't look terrible, the
+/// evaluation probably isn't ordering positions the way it should.
+pub struct WorstMoveAgent
+{
+    evaluation_weights: EvaluationWeights,
+    evaluation_depth: usize,
+}
+
+impl WorstMoveAgent
+{
+    /// Creates a new [WorstMoveAgent].
+    ///
+    /// # Arguments
+    ///
+    /// * `evaluation_depth` - How many moves past each candidate move to evaluate. 0 only looks at
+    ///   the position right after the move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::WorstMoveAgent;
+    /// let worst_move_agent = WorstMoveAgent::new(1);
+    /// ```
+    pub fn new(evaluation_depth: usize) -> Self
+    {
+        Self
+        {
+            evaluation_weights: EvaluationWeights::default(),
+            evaluation_depth,
+        }
+    }
+}
+
+impl Agent for WorstMoveAgent
+{
+    fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+        let board = game_state.current_board();
+        let evaluated_moves = game_state.legal_moves().iter().map(|r#move|
+        {
+            let next_board = board.attempt_move(r#move).expect("Legal moves should always be valid");
+            (*r#move, MinmaxAgent::evaluate(&next_board, &self.evaluation_weights, self.evaluation_depth))
+        });
+        // White wants the highest score, so the worst move for White has the lowest score and
+        // vice versa.
+        let worst_move = match board.active_color()
+        {
+            PlayerColor::White => evaluated_moves.min_by_key(|(_, evaluation)| *evaluation),
+            PlayerColor::Black => evaluated_moves.max_by_key(|(_, evaluation)| *evaluation),
+        };
+        worst_move.map(|(r#move, _)| AgentAction::Move(r#move)).expect("No moves generated!")
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::{Board, BoardConfiguration, Move, Square};
+
+    use super::*;
+
+    #[test]
+    fn does_not_take_a_free_queen()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap());
+        let game_state = GameState::new(board);
+        let r#move = WorstMoveAgent::new(0).agent_move_request(&game_state).played_move().unwrap();
+        match r#move
+        {
+            Move::NormalMove(move_data) => assert_ne!(move_data.target_square(), Square::new(4, 3)),
+            Move::Castle(_) => panic!("Castling isn't possible here"),
+        }
+    }
+
+    #[test]
+    fn black_gives_away_its_queen()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/3q4/8/8/3R4/4K3 b - - 0 1").unwrap());
+        let game_state = GameState::new(board);
+        let r#move = WorstMoveAgent::new(1).agent_move_request(&game_state).played_move().unwrap();
+        let next_board = game_state.current_board().attempt_move(&r#move).unwrap();
+        // The worst move for Black is whichever one is best for White.
+        let evaluation = MinmaxAgent::evaluate(&next_board, &EvaluationWeights::default(), 1);
+        let best_evaluation = game_state.legal_moves().iter()
+            .map(|r#move| MinmaxAgent::evaluate(&game_state.current_board().attempt_move(r#move).unwrap(), &EvaluationWeights::default(), 1))
+            .max()
+            .unwrap();
+        assert_eq!(evaluation, best_evaluation);
+    }
+}
@@ -100,12 +100,17 @@
 use std::fmt::Display;
 
 use bitboard_square_iterator::BitboardSquareIterator;
+use serde::{Deserialize, Serialize};
 use derive_more::{Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, From, Into, Mul, MulAssign, Not, Shl, ShlAssign, Shr, ShrAssign};
 use thiserror::Error;
 
 use crate::board::Square;
 
 mod bitboard_square_iterator;
+mod wide_bitboard;
+mod kogge_stone;
+
+pub use wide_bitboard::{CapablancaBitboard, WideBitboard};
 
 /// The error type passed by a bitboard when invalid coordinates were used.
 ///
@@ -189,7 +194,11 @@ impl Display for OutOfBoundsTypeError
 }
 
 /// See the [module-level documentation](./index.html) for more information on bitboards in general.
-#[derive(Debug, Clone, Copy, From, Add, Mul, Into, AddAssign, MulAssign, BitOrAssign, BitOr, BitAndAssign, BitAnd, BitXor, BitXorAssign, Default, Shl, Shr, ShlAssign, ShrAssign, PartialEq, Not, Eq, Hash)]
+///
+/// Both [Display] and [Debug](std::fmt::Debug) print the bitboard as an 8×8 grid of 1s and 0s,
+/// with the eighth rank at the top and the a-file on the left, like the diagrams above. See
+/// [Bitboard::pretty] for other characters.
+#[derive(Clone, Copy, From, Add, Mul, Into, AddAssign, MulAssign, BitOrAssign, BitOr, BitAndAssign, BitAnd, BitXor, BitXorAssign, Default, Shl, Shr, ShlAssign, ShrAssign, PartialEq, Not, Eq, Hash, Serialize, Deserialize)]
 pub struct Bitboard(u64);
 
 impl Bitboard
@@ -440,10 +449,166 @@ impl Bitboard
         files.into_iter().map(Self::file_mask).fold(Self::default(), |acc, v| acc | v)
     }
 
+    /// Generates a bitmask of the diagonal running through `square` from the bottom left to the
+    /// top right (the direction of a1-h8), with every other bit set to 0.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the square's rank or file is `>= 8`.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - Any square on the diagonal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(0x8040201008040201_u64, Bitboard::diagonal_mask(Square::new(3, 3)).into());
+    /// // b1 to h7.
+    /// assert_eq!(0x0080402010080402_u64, Bitboard::diagonal_mask(Square::new(0, 1)).into());
+    /// // Just the corner.
+    /// assert_eq!(0x0100000000000000_u64, Bitboard::diagonal_mask(Square::new(7, 0)).into());
+    /// ```
+    pub const fn diagonal_mask(square: Square) -> Self
+    {
+        assert!(square.rank < 8 && square.file < 8, "Error: Square provided was out of bounds.");
+        const MAIN_DIAGONAL: u64 = 0x8040201008040201;
+        // Moving the a1-h8 diagonal down a rank moves it a file to the right, and vice versa.
+        Bitboard::new(match square.file >= square.rank
+        {
+            true => MAIN_DIAGONAL >> (8 * (square.file - square.rank)),
+            false => MAIN_DIAGONAL << (8 * (square.rank - square.file)),
+        })
+    }
+
+    /// Generates a bitmask of the anti-diagonal running through `square` from the bottom right to
+    /// the top left (the direction of h1-a8), with every other bit set to 0.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the square's rank or file is `>= 8`.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - Any square on the anti-diagonal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(0x0102040810204080_u64, Bitboard::anti_diagonal_mask(Square::new(3, 4)).into());
+    /// // a1 is on its own.
+    /// assert_eq!(0x1_u64, Bitboard::anti_diagonal_mask(Square::new(0, 0)).into());
+    /// ```
+    pub const fn anti_diagonal_mask(square: Square) -> Self
+    {
+        assert!(square.rank < 8 && square.file < 8, "Error: Square provided was out of bounds.");
+        const MAIN_ANTI_DIAGONAL: u64 = 0x0102040810204080;
+        let sum = square.rank + square.file;
+        Bitboard::new(match sum <= 7
+        {
+            true => MAIN_ANTI_DIAGONAL >> (8 * (7 - sum)),
+            false => MAIN_ANTI_DIAGONAL << (8 * (sum - 7)),
+        })
+    }
+
+    /// Flips the bitboard upside down, so the first rank swaps with the eighth, the second with
+    /// the seventh and so on. Files stay the same. This turns a mask from White's point of view
+    /// into the same mask from Black's.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Bitboard::rank_mask(1).flip_vertical(), Bitboard::rank_mask(6));
+    /// assert_eq!(Bitboard::from(Square::new(0, 2)).flip_vertical(), Bitboard::from(Square::new(7, 2)));
+    /// ```
+    pub const fn flip_vertical(&self) -> Self
+    {
+        // Each rank is one byte.
+        Self(self.0.swap_bytes())
+    }
+
+    /// Mirrors the bitboard left to right, so the a-file swaps with the h-file, the b-file with
+    /// the g-file and so on. Ranks stay the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Bitboard::file_mask(0).mirror_horizontal(), Bitboard::file_mask(7));
+    /// assert_eq!(Bitboard::from(Square::new(3, 1)).mirror_horizontal(), Bitboard::from(Square::new(3, 6)));
+    /// ```
+    pub const fn mirror_horizontal(&self) -> Self
+    {
+        // Reversing every bit reverses the files within each rank but also the order of the
+        // ranks, so swapping the bytes back puts the ranks where they were.
+        Self(self.0.reverse_bits().swap_bytes())
+    }
+
+    /// Rotates the bitboard by 180 degrees, so a1 swaps with h8, b1 with g8 and so on. The same
+    /// as [Self::flip_vertical] followed by [Self::mirror_horizontal]; this is how the board
+    /// looks from Black's side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Bitboard::from(Square::new(0, 0)).rotate_180(), Bitboard::from(Square::new(7, 7)));
+    /// assert_eq!(Bitboard::from(Square::new(1, 4)).rotate_180(), Bitboard::from(Square::new(6, 3)));
+    /// ```
+    pub const fn rotate_180(&self) -> Self
+    {
+        Self(self.0.reverse_bits())
+    }
+
+    /// Returns the bitboard as an 8×8 grid, with `set` on every square whose bit is set and `.`
+    /// everywhere else. The eighth rank comes first, and the a-file is on the left, so the grid
+    /// looks like the board from White's side. Ranks are separated by newlines.
+    ///
+    /// # Arguments
+    ///
+    /// * `set` - The character to print on set squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// let bitboard = Bitboard::from(Square::new(7, 0)) | Bitboard::rank_mask(0);
+    /// assert_eq!(bitboard.pretty('x'), "x.......\n........\n........\n........\n........\n........\n........\nxxxxxxxx");
+    /// ```
+    pub fn pretty(&self, set: char) -> String
+    {
+        self.grid(set, '.')
+    }
+
+    /// Returns the bitboard as an 8×8 grid of `set` and `unset`, see [Self::pretty].
+    fn grid(&self, set: char, unset: char) -> String
+    {
+        (0..8).rev()
+            .map(|rank| (0..8).map(|file| match self.is_bit_set(rank * 8 + file)
+            {
+                true => set,
+                false => unset,
+            }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Returns an iterator over the current bitboard. This iterator
     /// passes through the bitboard from LSB to MSB, finds each currently active 1 bit,
     /// and returns the Square that that bit represents.
     ///
+    /// Only the set bits are visited, so iterating over a sparse bitboard is cheap. The iterator
+    /// knows how many squares are left, and can be reversed to go from MSB to LSB instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -457,6 +622,8 @@ impl Bitboard
     /// let bitboard = Bitboard::new(u64::MAX);
     /// let squares: Vec<Square> = bitboard.squares().collect();
     /// assert_eq!(squares.len(), 64);
+    /// // Backwards, from h8 down.
+    /// assert_eq!(bitboard.squares().rev().next(), Some(Square::new(7, 7)));
     /// ```
     pub fn squares(&self) -> BitboardSquareIterator
     {
@@ -540,6 +707,110 @@ impl Bitboard
     {
         self.0 == 0
     }
+
+    /// Returns how many bits are set, i.e how many squares are in the bitboard. This is much
+    /// faster than counting [Self::squares].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// assert_eq!(Bitboard::new(0).count(), 0);
+    /// assert_eq!(Bitboard::rank_mask(1).count(), 8);
+    /// ```
+    pub const fn count(&self) -> u32
+    {
+        self.0.count_ones()
+    }
+
+    /// Returns the square of the least significant set bit, i.e the set square closest to a1
+    /// going along the ranks, or [None] if the bitboard is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Bitboard::rank_mask(1).lsb(), Some(Square::new(1, 0)));
+    /// assert_eq!(Bitboard::new(0).lsb(), None);
+    /// ```
+    pub fn lsb(&self) -> Option<Square>
+    {
+        match self.is_empty()
+        {
+            true => None,
+            false => Some(Self::index_to_coords_unchecked(self.0.trailing_zeros() as u8)),
+        }
+    }
+
+    /// Returns the square of the most significant set bit, i.e the set square closest to h8
+    /// going along the ranks, or [None] if the bitboard is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Bitboard::rank_mask(1).msb(), Some(Square::new(1, 7)));
+    /// assert_eq!(Bitboard::new(0).msb(), None);
+    /// ```
+    pub fn msb(&self) -> Option<Square>
+    {
+        match self.is_empty()
+        {
+            true => None,
+            false => Some(Self::index_to_coords_unchecked(63 - self.0.leading_zeros() as u8)),
+        }
+    }
+
+    /// Clears the least significant set bit and returns its square, or returns [None] if the
+    /// bitboard is empty. Popping until [None] visits the same squares as [Self::squares], in
+    /// the same order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// let mut bitboard = Bitboard::new(0b101);
+    /// assert_eq!(bitboard.pop_lsb(), Some(Square::new(0, 0)));
+    /// assert_eq!(bitboard.pop_lsb(), Some(Square::new(0, 2)));
+    /// assert_eq!(bitboard.pop_lsb(), None);
+    /// assert!(bitboard.is_empty());
+    /// ```
+    pub fn pop_lsb(&mut self) -> Option<Square>
+    {
+        let square = self.lsb()?;
+        // Clearing the lowest set bit.
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+impl Display for Bitboard
+{
+    /// Prints the bitboard as an 8×8 grid of 1s and 0s, see [Bitboard::pretty].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// let grid = Bitboard::file_mask(0).to_string();
+    /// assert_eq!(grid.lines().next(), Some("10000000"));
+    /// assert_eq!(grid.lines().count(), 8);
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.grid('1', '0'))
+    }
+}
+
+impl std::fmt::Debug for Bitboard
+{
+    /// Prints the bitboard's value in hex, followed by the same grid as [Display], so failing
+    /// assertions on masks show where the bits actually are.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bitboard({:#018x})\n{}", self.0, self)
+    }
 }
 
 impl From<Square> for Bitboard
@@ -781,5 +1052,66 @@ mod tests
         let bitboard = Bitboard::new(0);
         bitboard.is_bit_set(64);
     }
-}
 
+    #[test]
+    fn test_pop_lsb_matches_squares()
+    {
+        for bits in [0, 1, 0x8000_0000_0000_0000, 0xFFFF_0000_0000_FFFF, 0x0123_4567_89AB_CDEF]
+        {
+            let mut bitboard = Bitboard::new(bits);
+            let expected: Vec<Square> = bitboard.squares().collect();
+            assert_eq!(bitboard.count() as usize, expected.len());
+            assert_eq!(bitboard.lsb(), expected.first().copied());
+            assert_eq!(bitboard.msb(), expected.last().copied());
+            let popped: Vec<Square> = std::iter::from_fn(|| bitboard.pop_lsb()).collect();
+            assert_eq!(popped, expected);
+        }
+    }
+
+    #[test]
+    fn test_diagonal_masks_match_squares()
+    {
+        for index in 0..64
+        {
+            let square = Bitboard::index_to_coords_unchecked(index);
+            let diagonal: Vec<Square> = Bitboard::diagonal_mask(square).squares().collect();
+            let anti_diagonal: Vec<Square> = Bitboard::anti_diagonal_mask(square).squares().collect();
+            assert!(diagonal.contains(&square) && anti_diagonal.contains(&square));
+            assert!(diagonal.iter().all(|other| other.file as i8 - other.rank as i8 == square.file as i8 - square.rank as i8));
+            assert!(anti_diagonal.iter().all(|other| other.file + other.rank == square.file + square.rank));
+            assert_eq!(diagonal.len(), 8 - (square.file as i8 - square.rank as i8).unsigned_abs() as usize);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_diagonal_mask_out_of_bounds()
+    {
+        Bitboard::diagonal_mask(Square::new(8, 0));
+    }
+
+    #[test]
+    fn test_debug_shows_value_and_grid()
+    {
+        let debug = format!("{:?}", Bitboard::new(1));
+        let mut lines = debug.lines();
+        assert_eq!(lines.next(), Some("Bitboard(0x0000000000000001)"));
+        assert_eq!(lines.last(), Some("10000000"));
+    }
+
+    #[test]
+    fn test_flips_and_rotations_move_every_square()
+    {
+        for index in 0..64
+        {
+            let square = Bitboard::index_to_coords_unchecked(index);
+            let bitboard = Bitboard::from(square);
+            assert_eq!(bitboard.flip_vertical(), Bitboard::from(Square::new(7 - square.rank, square.file)));
+            assert_eq!(bitboard.mirror_horizontal(), Bitboard::from(Square::new(square.rank, 7 - square.file)));
+            assert_eq!(bitboard.rotate_180(), bitboard.flip_vertical().mirror_horizontal());
+        }
+        let bitboard = Bitboard::new(0x0123_4567_89AB_CDEF);
+        assert_eq!(bitboard.flip_vertical().flip_vertical(), bitboard);
+        assert_eq!(bitboard.mirror_horizontal().mirror_horizontal(), bitboard);
+    }
+}
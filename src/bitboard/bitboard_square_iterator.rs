@@ -5,59 +5,51 @@ use crate::board::Square;
 
 use super::Bitboard;
 
-// Bitboard with all the bits set to 0 except for the LSB.
-const LSB_MASK: Bitboard = Bitboard(1);
-
-pub struct BitboardSquareIterator<'a>
+pub struct BitboardSquareIterator
 {
-    // The underlying bitboard reference.
-    // The iterator must last at least as long as the bitboard does,
-    // since the iterator does not own the bitboard.
-    bitboard: &'a Bitboard,
-    // The current index we are checking.
-    cur_index: u8
+    // The squares that haven't been returned yet, from either end. The iterator owns a copy,
+    // so it doesn't have to borrow the bitboard it came from.
+    remaining: Bitboard,
 }
 
-impl<'a> BitboardSquareIterator<'a>
+impl BitboardSquareIterator
 {
-    pub fn new(bitboard: &'a Bitboard) -> Self
+    pub fn new(bitboard: &Bitboard) -> Self
     {
         BitboardSquareIterator
         {
-            bitboard,
-            cur_index: 0,
+            remaining: *bitboard,
         }
     }
 }
 
-impl Iterator for BitboardSquareIterator<'_>
+impl Iterator for BitboardSquareIterator
 {
     type Item = Square;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Initial sanity check I suppose.
-        if self.cur_index >= 64
-        {
-            return None;
-        }
-        // Check cur_index bitmask
-        let mut bitmask = (*self.bitboard >> self.cur_index) & LSB_MASK;
-        self.cur_index += 1;
-        while bitmask.0 == 0
-        {
-            if self.cur_index >= 64
-            {
-                return None;
-            }
-            bitmask = (*self.bitboard >> self.cur_index) & LSB_MASK;
-            self.cur_index += 1;
-        }
-        // Once we find a valid index, convert it to a square and return it.
-        // We subtract one since the current index is 1 higher than the valid bitmask we found.
-        Some(Bitboard::index_to_coords_unchecked(self.cur_index - 1))
+        // Jumps straight to the lowest set bit and clears it, so empty squares are never looked
+        // at.
+        self.remaining.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.remaining.count() as usize;
+        (count, Some(count))
+    }
+}
+
+impl DoubleEndedIterator for BitboardSquareIterator
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let square = self.remaining.msb()?;
+        self.remaining &= !Bitboard::from(square);
+        Some(square)
     }
 }
 
+impl ExactSizeIterator for BitboardSquareIterator {}
+
 #[cfg(test)]
 mod tests{
     use super::*;
@@ -132,4 +124,19 @@ mod tests{
             cur_rank += 1;
         }
     }
+
+    #[test]
+    fn test_iterates_from_both_ends()
+    {
+        let bitboard = Bitboard::new(0x8100_0000_0000_0081);
+        let mut squares = bitboard.squares();
+        assert_eq!(squares.len(), 4);
+        assert_eq!(squares.next(), Some(Square::new(0, 0)));
+        assert_eq!(squares.next_back(), Some(Square::new(7, 7)));
+        assert_eq!(squares.len(), 2);
+        assert_eq!(squares.next_back(), Some(Square::new(7, 0)));
+        assert_eq!(squares.next(), Some(Square::new(0, 7)));
+        assert_eq!(squares.next(), None);
+        assert_eq!(squares.next_back(), None);
+    }
 }
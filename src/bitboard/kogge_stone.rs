@@ -0,0 +1,149 @@
+//! Sliding piece attacks worked out with Kogge-Stone occluded fills.
+//!
+//! Instead of looking up one slider at a time like the [magic tables](crate::board::rook_attack_mask),
+//! an occluded fill smears a whole set of sliders along one direction at once, stopping at the
+//! first occupied square, in three shift-and-mask steps. Doing that for each direction gives the
+//! attacks of every slider in the set together, which is handy when only the union is needed
+//! (i.e every square the opponent's rooks attack) and needs no tables at all. See
+//! [ChessProgramming.org](https://www.chessprogramming.org/Kogge-Stone_Algorithm).
+
+use super::Bitboard;
+
+/// Every square except the ones on the a-file.
+const NOT_A_FILE: u64 = !0x0101_0101_0101_0101;
+/// Every square except the ones on the h-file.
+const NOT_H_FILE: u64 = !0x8080_8080_8080_8080;
+
+/// The directions a rook slides in, as (shift, mask) pairs. A positive shift moves towards h8.
+/// The mask clears the squares that a shift would wrap onto from the other side of the board.
+const ORTHOGONAL_DIRECTIONS: [(i8, u64); 4] = [(8, u64::MAX), (-8, u64::MAX), (1, NOT_A_FILE), (-1, NOT_H_FILE)];
+/// The directions a bishop slides in, as (shift, mask) pairs. See [ORTHOGONAL_DIRECTIONS].
+const DIAGONAL_DIRECTIONS: [(i8, u64); 4] = [(9, NOT_A_FILE), (7, NOT_H_FILE), (-7, NOT_A_FILE), (-9, NOT_H_FILE)];
+
+impl Bitboard
+{
+    /// Returns every square attacked by a rook on any of the squares set in this bitboard,
+    /// stopping at (and including) the first occupied square in each direction, like
+    /// [rook_attack_mask](crate::board::rook_attack_mask) does for a single rook.
+    ///
+    /// # Arguments
+    ///
+    /// * `occupied` - Every occupied square on the board, of either color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// // Rooks on a1 and h8 on an empty board cover every edge square but their own.
+    /// let rooks = Bitboard::from(Square::new(0, 0)) | Bitboard::from(Square::new(7, 7));
+    /// let attacks = rooks.rook_fill_attacks(rooks);
+    /// let edges = Bitboard::rank_mask(0) | Bitboard::rank_mask(7) | Bitboard::file_mask(0) | Bitboard::file_mask(7);
+    /// assert_eq!(attacks, edges & !rooks);
+    /// ```
+    pub fn rook_fill_attacks(&self, occupied: Bitboard) -> Bitboard
+    {
+        fill_attacks(self.0, !occupied.0, &ORTHOGONAL_DIRECTIONS)
+    }
+
+    /// Returns every square attacked by a bishop on any of the squares set in this bitboard,
+    /// stopping at (and including) the first occupied square in each direction, like
+    /// [bishop_attack_mask](crate::board::bishop_attack_mask) does for a single bishop.
+    ///
+    /// # Arguments
+    ///
+    /// * `occupied` - Every occupied square on the board, of either color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// // A bishop on d4 blocked by a piece on f6.
+    /// let bishop = Bitboard::from(Square::new(3, 3));
+    /// let attacks = bishop.bishop_fill_attacks(bishop | Bitboard::from(Square::new(5, 5)));
+    /// assert_eq!(attacks.count(), 11);
+    /// ```
+    pub fn bishop_fill_attacks(&self, occupied: Bitboard) -> Bitboard
+    {
+        fill_attacks(self.0, !occupied.0, &DIAGONAL_DIRECTIONS)
+    }
+
+    /// Returns every square attacked by a queen on any of the squares set in this bitboard. The
+    /// same as [Self::rook_fill_attacks] and [Self::bishop_fill_attacks] together.
+    ///
+    /// # Arguments
+    ///
+    /// * `occupied` - Every occupied square on the board, of either color.
+    pub fn queen_fill_attacks(&self, occupied: Bitboard) -> Bitboard
+    {
+        self.rook_fill_attacks(occupied) | self.bishop_fill_attacks(occupied)
+    }
+}
+
+/// Returns the attacks of every slider in `sliders` in each of `directions`.
+fn fill_attacks(sliders: u64, empty: u64, directions: &[(i8, u64)]) -> Bitboard
+{
+    let attacks = directions.iter().fold(0, |attacks, (shift_by, mask)|
+    {
+        // The fill covers the sliders and the empty squares they reach. One more step takes it
+        // onto the blocker (or off the edge of the board).
+        attacks | shift(occluded_fill(sliders, empty, *shift_by, *mask), *shift_by) & mask
+    });
+    Bitboard::new(attacks)
+}
+
+/// Smears `generator` along one direction for as long as it runs over `empty` squares, doubling
+/// the distance covered at each step.
+fn occluded_fill(mut generator: u64, empty: u64, shift_by: i8, mask: u64) -> u64
+{
+    // The squares the fill can pass through, without wrapping around the board.
+    let mut propagator = empty & mask;
+    generator |= propagator & shift(generator, shift_by);
+    propagator &= shift(propagator, shift_by);
+    generator |= propagator & shift(generator, 2 * shift_by);
+    propagator &= shift(propagator, 2 * shift_by);
+    generator | propagator & shift(generator, 4 * shift_by)
+}
+
+/// Shifts towards h8 for positive amounts, and towards a1 for negative ones.
+fn shift(bits: u64, shift_by: i8) -> u64
+{
+    match shift_by >= 0
+    {
+        true => bits << shift_by,
+        false => bits >> -shift_by,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::board::{bishop_attack_mask, rook_attack_mask};
+
+    use super::*;
+
+    #[test]
+    fn fills_match_the_magic_tables()
+    {
+        // A small xorshift generator, so the test positions are the same on every run.
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = ||
+        {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..200
+        {
+            let occupied = Bitboard::new(next() & next());
+            let sliders = occupied & Bitboard::new(next());
+            let rook_attacks = sliders.squares().fold(Bitboard::default(), |attacks, square| attacks | rook_attack_mask(square, occupied));
+            let bishop_attacks = sliders.squares().fold(Bitboard::default(), |attacks, square| attacks | bishop_attack_mask(square, occupied));
+            assert_eq!(sliders.rook_fill_attacks(occupied), rook_attacks);
+            assert_eq!(sliders.bishop_fill_attacks(occupied), bishop_attacks);
+            assert_eq!(sliders.queen_fill_attacks(occupied), rook_attacks | bishop_attacks);
+        }
+    }
+}
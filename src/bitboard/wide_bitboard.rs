@@ -0,0 +1,386 @@
+//! Implements [WideBitboard], a bitboard for boards bigger than 8×8, and the attacks of every
+//! piece on it, including the compound pieces of Capablanca chess.
+
+use derive_more::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+
+use crate::{board::{directions::{DIAGONAL_DIRECTIONS, ORTHOGONAL_DIRECTIONS}, Square}, UInt};
+
+use super::{OutOfBoundsError, OutOfBoundsTypeError};
+
+/// The squares a knight jumps to, as (rank, file) offsets.
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+/// The squares next to a king, as (rank, file) offsets.
+const KING_OFFSETS: [(i8, i8); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+
+/// The 10×8 board of Capablanca chess and its relatives (Gothic chess, Embassy chess, ...).
+pub type CapablancaBitboard = WideBitboard<10, 8>;
+
+/// A bitboard for a board with `FILES` files and `RANKS` ranks, up to 128 squares in all, backed
+/// by a [u128]. This is what boards that don't fit a [Bitboard](super::Bitboard) are built on,
+/// i.e the 10×8 [CapablancaBitboard] that a [CapablancaBoard](crate::board::CapablancaBoard)
+/// keeps its pieces in.
+///
+/// Squares are numbered the same way as on a [Bitboard](super::Bitboard): a1 is bit 0, the rest
+/// of the first rank follows, and then each rank above it in turn. Bits past the last square are
+/// always clear, so shifting pieces off the edge of the board drops them instead of wrapping
+/// them around to the other side.
+///
+/// Besides the usual chess pieces, the attacks of the two compound pieces of Capablanca chess
+/// are supported: the chancellor, which moves like a rook or a knight, and the archbishop, which
+/// moves like a bishop or a knight.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::bitboard::CapablancaBitboard;
+/// # use rust_chess_engine::board::Square;
+/// // A chancellor in the corner of an empty board covers the whole first rank and the a-file,
+/// // and jumps to b3 and c2 like a knight.
+/// let attacks = CapablancaBitboard::chancellor_attacks(Square::new(0, 0), CapablancaBitboard::default());
+/// assert_eq!(attacks.count(), 9 + 7 + 2);
+/// assert!(attacks.contains(Square::new(0, 9)));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign)]
+pub struct WideBitboard<const FILES: UInt, const RANKS: UInt>(u128);
+
+impl<const FILES: UInt, const RANKS: UInt> WideBitboard<FILES, RANKS>
+{
+    /// Stops the build if the board doesn't fit in 128 bits.
+    const FITS: () = assert!(FILES > 0 && RANKS > 0 && (FILES as u32) * (RANKS as u32) <= 128, "A WideBitboard can't have more than 128 squares");
+
+    /// Every square on the board.
+    const BOARD_MASK: u128 = match (FILES as u32) * (RANKS as u32)
+    {
+        128 => u128::MAX,
+        squares => (1 << squares) - 1,
+    };
+
+    /// Returns a new bitboard with the given bits set. Bits past the last square are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The squares to set, one bit per square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::CapablancaBitboard;
+    /// assert_eq!(CapablancaBitboard::new(u128::MAX).count(), 80);
+    /// ```
+    pub const fn new(bits: u128) -> Self
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::FITS;
+        Self(bits & Self::BOARD_MASK)
+    }
+
+    /// Returns the bits of the bitboard, one per square.
+    pub const fn bits(&self) -> u128
+    {
+        self.0
+    }
+
+    /// Returns the index of a square's bit, or an [OutOfBoundsError] if the square isn't on the
+    /// board.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to look up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [OutOfBoundsError] if the square's rank or file is past the edge of the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::CapablancaBitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(CapablancaBitboard::index(Square::new(1, 0)).unwrap(), 10);
+    /// assert!(CapablancaBitboard::index(Square::new(0, 10)).is_err());
+    /// ```
+    pub fn index(square: Square) -> Result<u8, OutOfBoundsError>
+    {
+        if square.rank >= RANKS
+        {
+            return Err(OutOfBoundsError { max: RANKS - 1, given: square.rank, type_of_index: OutOfBoundsTypeError::Rank });
+        }
+        if square.file >= FILES
+        {
+            return Err(OutOfBoundsError { max: FILES - 1, given: square.file, type_of_index: OutOfBoundsTypeError::File });
+        }
+        Ok(square.rank * FILES + square.file)
+    }
+
+    /// Returns a bitboard with only `square` set, or an empty one if the square isn't on the
+    /// board.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to set.
+    pub fn from_square(square: Square) -> Self
+    {
+        match Self::index(square)
+        {
+            Ok(index) => Self(1 << index),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Returns true if `square` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to look at.
+    pub fn contains(&self, square: Square) -> bool
+    {
+        !(*self & Self::from_square(square)).is_empty()
+    }
+
+    /// Returns true if no squares are set.
+    pub const fn is_empty(&self) -> bool
+    {
+        self.0 == 0
+    }
+
+    /// Returns how many squares are set.
+    pub const fn count(&self) -> u32
+    {
+        self.0.count_ones()
+    }
+
+    /// Returns an iterator over every set square, from a1 up.
+    pub fn squares(&self) -> impl Iterator<Item = Square>
+    {
+        let mut bits = self.0;
+        std::iter::from_fn(move ||
+        {
+            if bits == 0
+            {
+                return None;
+            }
+            let index = bits.trailing_zeros() as UInt;
+            // Clear the lowest set bit.
+            bits &= bits - 1;
+            Some(Square::new(index / FILES, index % FILES))
+        })
+    }
+
+    /// Returns every square on `file`.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The file, starting from 0 for the a-file.
+    pub fn file_mask(file: UInt) -> Self
+    {
+        (0..RANKS).fold(Self::default(), |mask, rank| mask | Self::from_square(Square::new(rank, file)))
+    }
+
+    /// Returns every square on `rank`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank` - The rank, starting from 0 for the first rank.
+    pub fn rank_mask(rank: UInt) -> Self
+    {
+        (0..FILES).fold(Self::default(), |mask, file| mask | Self::from_square(Square::new(rank, file)))
+    }
+
+    /// Moves every set square by the given number of ranks and files. Squares that would end up
+    /// off the board are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranks` - How many ranks to move up (or down, if negative).
+    /// * `files` - How many files to move right (or left, if negative).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::CapablancaBitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// let j1 = CapablancaBitboard::from_square(Square::new(0, 9));
+    /// assert_eq!(j1.shift(1, 0), CapablancaBitboard::from_square(Square::new(1, 9)));
+    /// // There's nothing right of the j-file.
+    /// assert!(j1.shift(0, 1).is_empty());
+    /// ```
+    pub fn shift(self, ranks: i8, files: i8) -> Self
+    {
+        // Drop the squares that would go over the left or right edge first, since they'd wrap
+        // around onto the next rank instead.
+        let staying = (0..FILES)
+            .filter(|file| (0..FILES as i16).contains(&(*file as i16 + files as i16)))
+            .fold(Self::default(), |mask, file| mask | Self::file_mask(file));
+        let bits = (self & staying).0;
+        let offset = ranks as i32 * FILES as i32 + files as i32;
+        let shifted = match offset
+        {
+            offset if offset.unsigned_abs() >= 128 => 0,
+            offset if offset >= 0 => bits << offset,
+            offset => bits >> -offset,
+        };
+        Self::new(shifted)
+    }
+
+    /// Returns the squares a piece on `square` jumps to with each of `offsets`.
+    fn leaper_attacks(square: Square, offsets: &[(i8, i8)]) -> Self
+    {
+        let from = Self::from_square(square);
+        offsets.iter().fold(Self::default(), |attacks, (ranks, files)| attacks | from.shift(*ranks, *files))
+    }
+
+    /// Returns the squares a piece on `square` slides to in each of `directions`, up to and
+    /// including the first occupied square in each direction.
+    fn slider_attacks(square: Square, directions: &[(i8, i8)], occupied: Self) -> Self
+    {
+        let mut attacks = Self::default();
+        for (ranks, files) in directions
+        {
+            let mut ray = Self::from_square(square).shift(*ranks, *files);
+            while !ray.is_empty()
+            {
+                attacks |= ray;
+                if !(ray & occupied).is_empty()
+                {
+                    break;
+                }
+                ray = ray.shift(*ranks, *files);
+            }
+        }
+        attacks
+    }
+
+    /// Returns the squares a knight on `square` attacks.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The knight's square.
+    pub fn knight_attacks(square: Square) -> Self
+    {
+        Self::leaper_attacks(square, &KNIGHT_OFFSETS)
+    }
+
+    /// Returns the squares a king on `square` attacks.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The king's square.
+    pub fn king_attacks(square: Square) -> Self
+    {
+        Self::leaper_attacks(square, &KING_OFFSETS)
+    }
+
+    /// Returns the squares a rook on `square` attacks, given the occupied squares.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The rook's square.
+    /// * `occupied` - Every occupied square, of either color.
+    pub fn rook_attacks(square: Square, occupied: Self) -> Self
+    {
+        Self::slider_attacks(square, &ORTHOGONAL_DIRECTIONS, occupied)
+    }
+
+    /// Returns the squares a bishop on `square` attacks, given the occupied squares.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The bishop's square.
+    /// * `occupied` - Every occupied square, of either color.
+    pub fn bishop_attacks(square: Square, occupied: Self) -> Self
+    {
+        Self::slider_attacks(square, &DIAGONAL_DIRECTIONS, occupied)
+    }
+
+    /// Returns the squares a queen on `square` attacks, given the occupied squares.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The queen's square.
+    /// * `occupied` - Every occupied square, of either color.
+    pub fn queen_attacks(square: Square, occupied: Self) -> Self
+    {
+        Self::rook_attacks(square, occupied) | Self::bishop_attacks(square, occupied)
+    }
+
+    /// Returns the squares a chancellor (a rook that can also jump like a knight) on `square`
+    /// attacks, given the occupied squares.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The chancellor's square.
+    /// * `occupied` - Every occupied square, of either color.
+    pub fn chancellor_attacks(square: Square, occupied: Self) -> Self
+    {
+        Self::rook_attacks(square, occupied) | Self::knight_attacks(square)
+    }
+
+    /// Returns the squares an archbishop (a bishop that can also jump like a knight) on `square`
+    /// attacks, given the occupied squares.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The archbishop's square.
+    /// * `occupied` - Every occupied square, of either color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::bitboard::CapablancaBitboard;
+    /// # use rust_chess_engine::board::Square;
+    /// // An archbishop on e4 of an empty 10×8 board.
+    /// let attacks = CapablancaBitboard::archbishop_attacks(Square::new(3, 4), CapablancaBitboard::default());
+    /// assert_eq!(attacks.count(), 14 + 8);
+    /// ```
+    pub fn archbishop_attacks(square: Square, occupied: Self) -> Self
+    {
+        Self::bishop_attacks(square, occupied) | Self::knight_attacks(square)
+    }
+}
+
+impl<const FILES: UInt, const RANKS: UInt> std::ops::Not for WideBitboard<FILES, RANKS>
+{
+    type Output = Self;
+
+    /// Flips every square on the board. The bits past the last square stay clear.
+    fn not(self) -> Self::Output {
+        Self::new(!self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn leapers_do_not_wrap_around_the_edges()
+    {
+        // A knight on j1 only has two squares, both to its left.
+        let attacks = CapablancaBitboard::knight_attacks(Square::new(0, 9));
+        assert_eq!(attacks.squares().collect::<Vec<_>>(), vec![Square::new(1, 7), Square::new(2, 8)]);
+        // A king on a8 only has three.
+        assert_eq!(CapablancaBitboard::king_attacks(Square::new(7, 0)).count(), 3);
+        assert!(CapablancaBitboard::king_attacks(Square::new(0, 10)).is_empty());
+    }
+
+    #[test]
+    fn sliders_stop_at_the_first_piece()
+    {
+        let occupied = CapablancaBitboard::from_square(Square::new(0, 7)) | CapablancaBitboard::from_square(Square::new(4, 0));
+        let attacks = CapablancaBitboard::rook_attacks(Square::new(0, 0), occupied);
+        // b1 to h1, and a2 to a5.
+        assert_eq!(attacks.count(), 7 + 4);
+        assert!(attacks.contains(Square::new(0, 7)));
+        assert!(!attacks.contains(Square::new(0, 8)));
+    }
+
+    #[test]
+    fn not_stays_on_the_board()
+    {
+        let everything = !CapablancaBitboard::default();
+        assert_eq!(everything.count(), 80);
+        assert_eq!(!CapablancaBitboard::file_mask(0), everything ^ CapablancaBitboard::file_mask(0));
+        assert_eq!(WideBitboard::<16, 8>::new(u128::MAX).count(), 128);
+    }
+}
@@ -9,6 +9,8 @@
 //! - A [Piece] is a combination of a [PieceType] and a [PlayerColor].
 //! - A [Move] is used to transition a [Board] from one state into a new state.
 //! - A [BoardConfiguration] is used to set up a [Board].
+//! - A [WideBoard] holds the pieces of a bigger board, like the 10×8 board of Capablanca chess,
+//!   including the [WidePieceType]s that only exist there.
 //!
 //! # Examples
 //!
@@ -69,15 +71,31 @@ mod board_config;
 mod player_color;
 mod error;
 mod board_result;
+mod attack_info;
+mod pawn_tables;
+mod magic_tables;
+mod zobrist;
+mod undo_info;
+mod line_tables;
+mod draw_rules;
+mod wide_board;
+pub(crate) mod directions;
 
 pub use line::Line;
 pub use square::Square;
-pub use piece_type::PieceType;
+pub use piece_type::{PieceType, PROMOTION_PIECE_TYPES};
 pub use r#move::MoveData;
 pub use r#move::{Move, CastlingDirection};
-pub use board::{Board, EvaluationWeights, Evaluation};
+pub use board::{Board, BoardView, EvaluationWeights, Evaluation, PieceSquareTables, ENCODED_POSITION_LENGTH};
 pub use player_color::PlayerColor;
 pub use piece::Piece;
-pub use board_config::{BoardConfiguration, CastlingAvailability, BoardConfigurationBuilder, InvalidFENError};
+pub use board_config::{BoardConfiguration, CastlingAvailability, BoardConfigurationBuilder, InvalidFENError, InvalidBoardConfigurationError};
 pub use board_result::{BoardResult, DrawReason};
-pub use error::MoveError;
+pub use draw_rules::DrawRules;
+pub use error::{MoveError, ApplyMovesError, DecodePositionError, SanMoveError};
+pub use attack_info::AttackInfo;
+pub use undo_info::UndoInfo;
+pub use pawn_tables::{pawn_attack_mask, pawn_push_mask, pawn_double_push_mask};
+pub use magic_tables::{rook_attack_mask, bishop_attack_mask};
+pub use line_tables::{between_mask, line_mask};
+pub use wide_board::{CapablancaBoard, InvalidWideFENError, WideBoard, WidePieceType, WIDE_PIECE_TYPES};
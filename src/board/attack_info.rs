@@ -0,0 +1,405 @@
+//! Specifies the [AttackInfo] type, which records which squares each side attacks in a position.
+//!
+//! Both evaluation (i.e center control) and move generation need to know which squares are
+//! attacked and which pieces are pinned. Working that out is expensive, so it's computed once per
+//! position with [Board::attack_info] and then shared.
+
+use crate::bitboard::Bitboard;
+
+use super::{between_mask, bishop_attack_mask, piece_type::PIECE_TYPES, rook_attack_mask, Board, PieceType, PlayerColor, Square};
+
+/// The squares attacked by each side's pieces and which pieces are pinned, for a single position.
+///
+/// A square counts as attacked if a piece could capture on it, which includes squares occupied
+/// by the attacker's own pieces (i.e pieces it defends). Pawns attack the squares diagonally in
+/// front of them whether or not anything is there.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Board, PlayerColor, Square};
+/// let board = Board::new_default_starting_board();
+/// let attack_info = board.attack_info();
+/// // The knight on b1 covers c3, and so do the pawns on b2 and d2.
+/// let c3 = Square::from_str("c3").unwrap();
+/// assert!(attack_info.is_attacked(c3, PlayerColor::White));
+/// assert_eq!(attack_info.attacker_count(c3, PlayerColor::White), 3);
+/// assert!(!attack_info.is_attacked(c3, PlayerColor::Black));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AttackInfo
+{
+    /// The attacked squares indexed by color, then piece type.
+    attacks: [[Bitboard; 6]; 2],
+    /// How many pieces attack each square, indexed by color, then square index.
+    attack_counts: [[u8; 64]; 2],
+    /// The pieces of each color that are pinned to their own king.
+    pinned: [Bitboard; 2],
+    /// The enemy pieces giving check to each color's king.
+    checkers: [Bitboard; 2],
+    /// Where each color's king is, if it has exactly one.
+    king_squares: [Option<Square>; 2],
+}
+
+impl AttackInfo
+{
+    /// Works out the [AttackInfo] for the given board.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look at.
+    pub fn new(board: &Board) -> Self
+    {
+        let mut attack_info = Self
+        {
+            attacks: [[Bitboard::default(); 6]; 2],
+            attack_counts: [[0; 64]; 2],
+            pinned: [Bitboard::default(); 2],
+            checkers: [Bitboard::default(); 2],
+            king_squares: [None; 2],
+        };
+
+        for color in [PlayerColor::White, PlayerColor::Black]
+        {
+            for piece_type in PIECE_TYPES
+            {
+                let pieces = board.pieces_of_color(color) & board.pieces_of_type(piece_type);
+                for square in pieces.squares()
+                {
+                    let attacks = piece_attacks(board, color, piece_type, square);
+                    attack_info.attacks[color.index()][piece_type_index(piece_type)] |= attacks;
+                    for attacked_square in attacks.squares()
+                    {
+                        attack_info.attack_counts[color.index()][attacked_square.index() as usize] += 1;
+                    }
+                }
+            }
+
+            if let Some(king_square) = only_king_square(board, color)
+            {
+                attack_info.king_squares[color.index()] = Some(king_square);
+                attack_info.pinned[color.index()] = pinned_pieces(board, color, king_square);
+                attack_info.checkers[color.index()] = checkers(board, color, king_square);
+            }
+        }
+        attack_info
+    }
+
+    /// Returns every square attacked by `color`'s pieces of type `piece_type`.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The attacking side.
+    /// * `piece_type` - The type of the attacking pieces.
+    pub fn attacks(&self, color: PlayerColor, piece_type: PieceType) -> Bitboard
+    {
+        self.attacks[color.index()][piece_type_index(piece_type)]
+    }
+
+    /// Returns every square attacked by any of `color`'s pieces.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The attacking side.
+    pub fn attacked_by(&self, color: PlayerColor) -> Bitboard
+    {
+        self.attacks[color.index()].iter().fold(Bitboard::default(), |all, attacks| all | *attacks)
+    }
+
+    /// Returns true if any of `color`'s pieces attack `square`.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to check.
+    /// * `color` - The attacking side.
+    pub fn is_attacked(&self, square: Square, color: PlayerColor) -> bool
+    {
+        self.attacker_count(square, color) > 0
+    }
+
+    /// Returns how many of `color`'s pieces attack `square`.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to check.
+    /// * `color` - The attacking side.
+    pub fn attacker_count(&self, square: Square, color: PlayerColor) -> u8
+    {
+        self.attack_counts[color.index()][square.index() as usize]
+    }
+
+    /// Returns `color`'s pieces that are pinned to their king, i.e that can't leave the line
+    /// between their king and an enemy slider without exposing the king. Empty if `color` doesn't
+    /// have exactly one king.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose pinned pieces to return.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor, Square};
+    /// // The knight on e2 is pinned by the rook on e8.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap());
+    /// let pinned = board.attack_info().pinned(PlayerColor::White);
+    /// assert_eq!(pinned, Bitboard::from(Square::from_str("e2").unwrap()));
+    /// ```
+    pub fn pinned(&self, color: PlayerColor) -> Bitboard
+    {
+        self.pinned[color.index()]
+    }
+
+    /// Returns the enemy pieces attacking `color`'s king. Empty if the king isn't in check or
+    /// `color` doesn't have exactly one king.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose king to check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor, Square};
+    /// // Double check from the rook on e8 and the knight on f3.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4r1k1/8/8/8/8/5n2/8/4K3 w - - 0 1").unwrap());
+    /// let checkers = board.attack_info().checkers(PlayerColor::White);
+    /// assert_eq!(checkers, Bitboard::from(Square::from_str("e8").unwrap()) | Bitboard::from(Square::from_str("f3").unwrap()));
+    /// ```
+    pub fn checkers(&self, color: PlayerColor) -> Bitboard
+    {
+        self.checkers[color.index()]
+    }
+
+    /// Returns the square `color`'s king is on, or [None] if `color` doesn't have exactly one
+    /// king.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose king to find.
+    pub fn king_square(&self, color: PlayerColor) -> Option<Square>
+    {
+        self.king_squares[color.index()]
+    }
+
+    /// Returns true if `color`'s king is attacked. Always false if `color` doesn't have exactly
+    /// one king.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose king to check.
+    pub fn is_in_check(&self, color: PlayerColor) -> bool
+    {
+        self.king_squares[color.index()].is_some_and(|king_square| self.is_attacked(king_square, !color))
+    }
+}
+
+impl Board
+{
+    /// Works out which squares each side attacks and which pieces are pinned. See [AttackInfo].
+    pub fn attack_info(&self) -> AttackInfo
+    {
+        AttackInfo::new(self)
+    }
+
+    /// Returns `color`'s pieces that are pinned to their own king, i.e that can't leave the line
+    /// between the king and an enemy rook, bishop or queen without exposing the king. Empty if
+    /// `color` doesn't have exactly one king.
+    ///
+    /// This is the same as [AttackInfo::pinned], without working out everything else.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose pinned pieces to find.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor, Square};
+    /// // The knight on e2 is pinned by the rook on e8, and the bishop on c3 by the queen on a5.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4r1k1/8/8/q7/8/2B5/4N3/4K3 w - - 0 1").unwrap());
+    /// let pinned = board.pinned_pieces(PlayerColor::White);
+    /// assert_eq!(pinned, Bitboard::from(Square::from_str("e2").unwrap()) | Bitboard::from(Square::from_str("c3").unwrap()));
+    /// assert!(board.pinned_pieces(PlayerColor::Black).is_empty());
+    /// ```
+    pub fn pinned_pieces(&self, color: PlayerColor) -> Bitboard
+    {
+        only_king_square(self, color).map_or(Bitboard::default(), |king_square| pinned_pieces(self, color, king_square))
+    }
+
+    /// Returns the enemy pieces giving check to `color`'s king. Empty if the king isn't in check
+    /// or `color` doesn't have exactly one king, and two pieces in a double check.
+    ///
+    /// This is the same as [AttackInfo::checkers], without working out everything else.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose king to look at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::bitboard::Bitboard;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor, Square};
+    /// // The knight on f3 gives check.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4r1k1/8/8/8/8/5n2/4N3/4K3 w - - 0 1").unwrap());
+    /// assert_eq!(board.checkers(PlayerColor::White), Bitboard::from(Square::from_str("f3").unwrap()));
+    /// assert!(board.checkers(PlayerColor::Black).is_empty());
+    /// ```
+    pub fn checkers(&self, color: PlayerColor) -> Bitboard
+    {
+        only_king_square(self, color).map_or(Bitboard::default(), |king_square| checkers(self, color, king_square))
+    }
+}
+
+/// Returns the square `color`'s king is on, or [None] if `color` doesn't have exactly one king.
+fn only_king_square(board: &Board, color: PlayerColor) -> Option<Square>
+{
+    let kings = board.pieces_of_color(color) & board.pieces_of_type(PieceType::King);
+    match kings.count()
+    {
+        1 => kings.lsb(),
+        _ => None,
+    }
+}
+
+/// Returns every square attacked by any of `color`'s pieces. This is the same as
+/// [AttackInfo::attacked_by], without counting attackers or looking for pins.
+pub(super) fn attacked_squares(board: &Board, color: PlayerColor) -> Bitboard
+{
+    let mut attacked = Bitboard::default();
+    for piece_type in PIECE_TYPES
+    {
+        let pieces = board.pieces_of_color(color) & board.pieces_of_type(piece_type);
+        for square in pieces.squares()
+        {
+            attacked |= piece_attacks(board, color, piece_type, square);
+        }
+    }
+    attacked
+}
+
+/// Returns the squares a piece attacks, including squares occupied by its own side.
+fn piece_attacks(board: &Board, color: PlayerColor, piece_type: PieceType, square: Square) -> Bitboard
+{
+    // The move functions leave out squares holding the mover's own pieces. Asking again as if the
+    // piece were the other color adds those back. Sliders are looked up directly instead, since
+    // their attack tables already include the first piece they run into.
+    let occupied = board.query().result();
+    let moves: fn(&Board, PlayerColor, Square) -> Bitboard = match piece_type
+    {
+        PieceType::Pawn => return board.pawn_theoretical_attacks(color, square),
+        PieceType::Bishop => return bishop_attack_mask(square, occupied),
+        PieceType::Rook => return rook_attack_mask(square, occupied),
+        PieceType::Queen => return bishop_attack_mask(square, occupied) | rook_attack_mask(square, occupied),
+        PieceType::Knight => Board::knight_moves,
+        PieceType::King => Board::king_moves,
+    };
+    moves(board, color, square) | moves(board, !color, square)
+}
+
+/// Returns the enemy pieces attacking the king of the given color. Attacks go both ways, so
+/// these are the enemy pieces a piece of the same kind standing on the king's square would
+/// attack (looking from the king's side for pawns, since they only attack forwards).
+fn checkers(board: &Board, color: PlayerColor, king_square: Square) -> Bitboard
+{
+    let occupied = board.query().result();
+    let queens = board.pieces_of_type(PieceType::Queen);
+    let attackers =
+        (board.pawn_theoretical_attacks(color, king_square) & board.pieces_of_type(PieceType::Pawn)) |
+        (board.knight_moves(color, king_square) & board.pieces_of_type(PieceType::Knight)) |
+        (bishop_attack_mask(king_square, occupied) & (board.pieces_of_type(PieceType::Bishop) | queens)) |
+        (rook_attack_mask(king_square, occupied) & (board.pieces_of_type(PieceType::Rook) | queens));
+    attackers & board.pieces_of_color(!color)
+}
+
+/// Looks from the king through its own pieces, as a rook and as a bishop. An enemy slider that
+/// moves that way and only shows up when looking through a piece pins the piece between it and
+/// the king.
+fn pinned_pieces(board: &Board, color: PlayerColor, king_square: Square) -> Bitboard
+{
+    let own = board.pieces_of_color(color);
+    let queens = board.pieces_of_type(PieceType::Queen);
+    let pinners = board.pieces_of_color(!color) & (
+        (board.x_ray_attacks(king_square, PieceType::Rook, own) & (board.pieces_of_type(PieceType::Rook) | queens)) |
+        (board.x_ray_attacks(king_square, PieceType::Bishop, own) & (board.pieces_of_type(PieceType::Bishop) | queens)));
+    pinners.squares().fold(Bitboard::default(), |pinned, pinner| pinned | (between_mask(king_square, pinner) & own))
+}
+
+fn piece_type_index(piece_type: PieceType) -> usize
+{
+    match piece_type
+    {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::test_support::board_from_fen;
+
+    use super::*;
+
+    #[test]
+    fn defended_pieces_count_as_attacked()
+    {
+        let attack_info = Board::new_default_starting_board().attack_info();
+        // The e2 pawn is defended by the king, queen, bishop and knight.
+        assert_eq!(attack_info.attacker_count(Square::from_str("e2").unwrap(), PlayerColor::White), 4);
+    }
+
+    #[test]
+    fn sliders_stop_at_the_first_piece()
+    {
+        let attack_info = board_from_fen("4k3/8/8/8/R2p3r/8/8/4K3 w - - 0 1").attack_info();
+        let rook_attacks = attack_info.attacks(PlayerColor::White, PieceType::Rook);
+        assert!(rook_attacks.squares().any(|square| square == Square::from_str("d4").unwrap()));
+        assert!(!rook_attacks.squares().any(|square| square == Square::from_str("e4").unwrap()));
+    }
+
+    #[test]
+    fn pieces_blocked_twice_are_not_pinned()
+    {
+        let attack_info = board_from_fen("4r1k1/8/8/8/4B3/8/4N3/4K3 w - - 0 1").attack_info();
+        assert!(attack_info.pinned(PlayerColor::White).is_empty());
+    }
+
+    #[test]
+    fn check_is_detected()
+    {
+        let attack_info = board_from_fen("4r1k1/8/8/8/8/8/8/4K3 w - - 0 1").attack_info();
+        assert!(attack_info.is_in_check(PlayerColor::White));
+        assert!(!attack_info.is_in_check(PlayerColor::Black));
+    }
+
+    #[test]
+    fn board_pins_and_checkers_match_attack_info()
+    {
+        // White has a pinned knight and is in check from the queen, and black has no king.
+        for board in [board_from_fen("4r3/8/8/8/1q6/8/4N3/4K3 w - - 0 1"), board_from_fen("4r1k1/8/8/q7/8/2B5/4N3/4K3 w - - 0 1")]
+        {
+            let attack_info = board.attack_info();
+            for color in [PlayerColor::White, PlayerColor::Black]
+            {
+                assert_eq!(board.pinned_pieces(color), attack_info.pinned(color));
+                assert_eq!(board.checkers(color), attack_info.checkers(color));
+            }
+        }
+    }
+}
@@ -1,11 +1,12 @@
-use std::{collections::HashMap, fmt::Display, hash::Hash};
+use std::{borrow::Borrow, collections::HashMap, fmt::Display, hash::Hash, str::FromStr};
 
 use colored::{Color, Colorize, Style, Styles};
 use getset::CopyGetters;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{bitboard::Bitboard, board::{DrawReason, PieceType, PlayerColor}, parse::MoveCommand};
+use crate::{bitboard::Bitboard, board::{DrawReason, PieceType, PlayerColor}, parse::{MoveCommand, ParseMode}};
 
-use super::{board_config::BoardConfigurationBuilder, error::MoveError, r#move::{CastlingDirection, Move}, BoardConfiguration, BoardResult, CastlingAvailability, Piece, Square};
+use super::{zobrist, bishop_attack_mask, pawn_attack_mask, rook_attack_mask, board_config::BoardConfigurationBuilder, error::{ApplyMovesError, MoveError, SanMoveError}, piece_type::PROMOTION_PIECE_TYPES, r#move::{CastlingDirection, Move, MoveData}, BoardConfiguration, BoardResult, CastlingAvailability, Piece, Square, UndoInfo, DrawRules};
 mod board_move;
 mod board_query;
 mod board_move_logic;
@@ -13,8 +14,15 @@ mod board_move_checks;
 mod board_move_generation;
 mod board_evaluation;
 mod mut_get_bitboards;
+mod board_perft;
+mod board_dead_position;
+mod board_san;
+mod board_encoding;
+mod board_see;
+mod board_x_ray;
 
-pub use board_evaluation::{Evaluation, EvaluationWeights};
+pub use board_evaluation::{Evaluation, EvaluationWeights, PieceSquareTables};
+pub use board_encoding::ENCODED_POSITION_LENGTH;
 
 const BOARD_COLOR_1: Color = Color::Cyan;
 const BOARD_COLOR_2: Color = Color::Magenta;
@@ -25,13 +33,17 @@ const BOARD_COLOR_2: Color = Color::Magenta;
 /// The board is expected to begin and stay in a valid configuration, all exposed functions should
 /// maintain the internal state and not violate the rules of chess or anything. Boards are
 /// immutable: making moves on a board does not modify the existing board but instead returns a new
-/// one.
+/// one. The exception is [Self::make_move_in_place] and [Self::unmake_move], which searches use to
+/// avoid copying the board for every move they look at.
 #[derive(Clone, Debug, CopyGetters)]
 pub struct Board 
 {
-    /// Tracks what piece is on a given square.
-    /// If a key does not exist, that square is considered empty.
-    piece_mailbox: HashMap<Square, Piece>,
+    /// Tracks what piece is on a given square, indexed by [Square::index].
+    /// A [None] means that square is empty.
+    ///
+    /// This is a plain array rather than a map so looking a square up is just an index, and
+    /// cloning a board (which search does a lot of) is a single copy with no allocation.
+    piece_mailbox: [Option<Piece>; 64],
     /// All white pieces
     white_pieces: Bitboard,
     /// All black pieces
@@ -51,10 +63,45 @@ pub struct Board
     /// Gets the color active/waiting player.
     #[getset(get_copy="pub")]
     active_color: PlayerColor,
+    /// Gets which castles each player still has the right to.
+    #[getset(get_copy="pub")]
     castling_availability: CastlingAvailability,
     en_passant_target_square: Option<Square>,
-    halfmove_clock: u8,
-    fullmove_number: u8,
+    /// Gets the number of ply since the last capture or pawn move.
+    #[getset(get_copy="pub")]
+    halfmove_clock: u16,
+    /// Gets the number of full moves. Starts at 1, increments after Black's move.
+    #[getset(get_copy="pub")]
+    fullmove_number: u16,
+    /// Gets the [DrawRules] the board's [game_result](Self::game_result) goes by. They're carried
+    /// over to the boards after each move, and don't count towards two boards being equal.
+    #[getset(get_copy="pub")]
+    draw_rules: DrawRules,
+    /// Gets the [Zobrist key](https://www.chessprogramming.org/Zobrist_Hashing) of the position,
+    /// the one hash used to identify positions everywhere in the crate.
+    ///
+    /// The key only depends on the pieces, the active color, castling availability and the en
+    /// passant square, not on the move counters or how the position was reached. Each move
+    /// updates it by XORing out the keys of what changed and XORing in the new ones, so it never
+    /// has to be computed from scratch. The random keys come from a fixed seed, so a position has
+    /// the same key on every run and platform, and keys can be saved to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// let board = Board::new_default_starting_board();
+    /// // The same position reached by two different move orders.
+    /// let kingside_first = board.attempt_move_san("Nf3").unwrap().attempt_move_san("Nc6").unwrap().attempt_move_san("Nc3").unwrap();
+    /// let queenside_first = board.attempt_move_san("Nc3").unwrap().attempt_move_san("Nc6").unwrap().attempt_move_san("Nf3").unwrap();
+    /// assert_eq!(kingside_first.zobrist_key(), queenside_first.zobrist_key());
+    ///
+    /// // Setting the position up from scratch gives the key that was updated move by move.
+    /// let set_up = Board::new_board_with_configuration(&kingside_first.board_configuration());
+    /// assert_eq!(set_up.zobrist_key(), kingside_first.zobrist_key());
+    /// ```
+    #[getset(get_copy="pub")]
+    zobrist_key: u64,
 }
 
 impl Board
@@ -94,8 +141,9 @@ impl Board
             en_passant_target_square: board_configuration.en_passant_target_square(),
             halfmove_clock: board_configuration.halfmove_clock(),
             fullmove_number: board_configuration.fullmove_number(),
+            draw_rules: DrawRules::default(),
             // Set remaining defaults. These will be set programatically below.
-            piece_mailbox: HashMap::new(),
+            piece_mailbox: [None; 64],
             white_pieces: Bitboard::default(),
             black_pieces: Bitboard::default(),
             king_pieces: Bitboard::default(),
@@ -103,11 +151,15 @@ impl Board
             rook_pieces: Bitboard::default(),
             knight_pieces: Bitboard::default(),
             bishop_pieces: Bitboard::default(),
-            pawn_pieces: Bitboard::default()
+            pawn_pieces: Bitboard::default(),
+            zobrist_key: zobrist::side_to_move_key(board_configuration.active_color())
+                ^ zobrist::castling_key(&board_configuration.castling_availability())
+                ^ zobrist::en_passant_key(board_configuration.en_passant_target_square()),
         };
 
-        // Add all pieces to the board, setting the state of all the bitboards accordingly
-        for (square, piece) in board_configuration.get_pieces().iter()
+        // Add all pieces to the board, setting the state of all the bitboards (and the zobrist
+        // key) accordingly
+        for (square, piece) in board_configuration.pieces().iter()
         {
             new_board.add_piece(*piece, square);
         }
@@ -115,11 +167,15 @@ impl Board
         new_board
     }
 
-    /// Gets the board configuration associated with the current board state.
+    /// Gets the board configuration associated with the current board state. This is the same as
+    /// `BoardConfiguration::from(&board)`.
     pub fn board_configuration(&self) -> BoardConfiguration
     {
+        let pieces: HashMap<Square, Piece> = self.piece_mailbox.iter().enumerate()
+            .filter_map(|(index, piece)| piece.map(|piece| (Square::from_index(index as u8).expect("The mailbox has 64 squares"), piece)))
+            .collect();
         BoardConfiguration::new(
-            self.piece_mailbox.clone(),
+            pieces,
             self.active_color,
             self.castling_availability,
             self.en_passant_target_square,
@@ -134,34 +190,68 @@ impl Board
     /// in progress.
     pub fn game_result(&self) -> BoardResult
     {
-        // Draw after 50 moves without a pawn push or capture.
-        if self.halfmove_clock >= 50
-        {
-            return BoardResult::Draw(DrawReason::FiftyMoveRule);
-        }
+        self.game_result_with_legal_moves(&self.generate_moves_for_side(self.active_color))
+    }
 
-        // Other things we should check:
-        // - Threefold repitition. This is probably beyond the scope of a board,
-        //   since a board doesn't have any information about past moves.
-        // - Checkmate impossible. A game is drawn when neither player has sufficient material
-        //   to checkmate the other king. We shouild check that.
-        // - Draw by agreement? Can agents agree to a draw?
-        //
-        // For now we're only covering:
-        // - FiftyMoveRule. A draw is automatic when it has been 50 moves without a pawn push
-        //   or capture.
-        // - Stalemate. 
-        // - Checkmate.
+    /// Sets the [DrawRules] the board (and every board after it) goes by.
+    ///
+    /// # Arguments
+    ///
+    /// * `draw_rules` - The draw rules to use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, BoardResult, DrawReason, DrawRules};
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap());
+    /// // Fifty moves only lets the players claim a draw.
+    /// assert_eq!(board.game_result(), BoardResult::InProgress);
+    /// let board = board.with_draw_rules(DrawRules::default().with_halfmoves(100, 100));
+    /// assert_eq!(board.game_result(), BoardResult::Draw(DrawReason::SeventyFiveMoveRule));
+    /// ```
+    pub fn with_draw_rules(mut self, draw_rules: DrawRules) -> Self
+    {
+        self.draw_rules = draw_rules;
+        self
+    }
 
-        match (self.is_king_in_check(self.active_color), self.generate_moves_for_side(self.active_color).is_empty())
+    /// Same as [Self::game_result], but takes the already generated legal moves for the active
+    /// player instead of generating them again.
+    pub(crate) fn game_result_with_legal_moves(&self, legal_moves: &[Move]) -> BoardResult
+    {
+        // Things we don't check here:
+        // - Repetitions. This is beyond the scope of a board, since a board doesn't
+        //   have any information about past moves, so GameState::game_result handles it.
+        // - Draw by agreement, claimed draws, and resignations. These are up to the players, so
+        //   GameState::apply_action handles them.
+        //
+        // What we do cover:
+        // - Checkmate. This comes first, a move that checkmates wins even if it also reaches the
+        //   seventy-five-move limit.
+        // - Stalemate.
+        // - SeventyFiveMoveRule. A draw is automatic when it has been 75 moves by each side
+        //   without a pawn push or capture (see DrawRules).
+        // - Dead positions, either because neither player has enough material left to
+        //   checkmate or because the pawns are locked and only the kings can move.
+
+        match (self.is_king_in_check(self.active_color), legal_moves.is_empty())
         {
             // King is in check *and* the player has no valid moves
             // Then the *other* player wins.
             (true, true) => BoardResult::Win(!self.active_color),
             // King is NOT in check, but the player has no valid moves.
             (false, true) => BoardResult::Draw(DrawReason::Stalemate),
-            // The player can still move, the game is not over yet.
-            (_, false) => BoardResult::InProgress,
+            (_, false) => match self.draw_rules.automatic_draw(self.halfmove_clock, 1)
+            {
+                // Too long without a pawn push or capture. The board has only seen its own
+                // position once.
+                Some(reason) => BoardResult::Draw(reason),
+                // The player can still move, but nothing they do can ever lead to checkmate.
+                None if self.is_dead_position() => BoardResult::Draw(DrawReason::CheckmateImpossible),
+                // The player can still move, the game is not over yet.
+                None => BoardResult::InProgress,
+            },
         }
     }
 
@@ -175,6 +265,118 @@ impl Board
         self.attempt_move(&r#move)
     }
 
+    /// Parses a move in algebraic notation and makes it, in one go. Same as
+    /// [Self::attempt_move_from_command], but without having to build the [MoveCommand] first.
+    ///
+    /// # Arguments
+    ///
+    /// * `san` - The move, i.e `"e4"`, `"Nxf7"` or `"O-O"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [SanMoveError::Parse] if `san` isn't valid algebraic notation, or
+    /// [SanMoveError::Move] if the move is impossible or illegal on this board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, SanMoveError, Square, PieceType};
+    /// let board = Board::new_default_starting_board().attempt_move_san("Nf3").unwrap();
+    /// assert_eq!(board.piece_at(&Square::new(2, 5)).unwrap().piece_type(), PieceType::Knight);
+    /// assert!(matches!(board.attempt_move_san("Nf3"), Err(SanMoveError::Move(_))));
+    /// assert!(matches!(board.attempt_move_san("not a move"), Err(SanMoveError::Parse(_))));
+    /// ```
+    pub fn attempt_move_san(&self, san: &str) -> Result<Self, SanMoveError>
+    {
+        let move_command = MoveCommand::from_str(san)?;
+        Ok(self.attempt_move_from_command(&move_command)?)
+    }
+
+    /// Like [Self::attempt_move_san], but the move has to be written in
+    /// [ParseMode::Strict](crate::parse::ParseMode::Strict) and its check or checkmate suffix has
+    /// to be right: a move that checkmates has to end in `#`, one that only checks has to end in
+    /// `+`, and any other move mustn't have a suffix at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `san` - The move, i.e `"e4"`, `"Qxf7#"` or `"O-O+"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [Self::attempt_move_san], or
+    /// [SanMoveError::WrongCheckAnnotation] if the move was made but its suffix doesn't match the
+    /// resulting board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, SanMoveError};
+    /// // Fool's mate.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2").unwrap());
+    /// assert!(board.attempt_move_san_strict("Qh4#").is_ok());
+    /// assert!(matches!(board.attempt_move_san_strict("Qh4+"), Err(SanMoveError::WrongCheckAnnotation { .. })));
+    /// assert!(matches!(board.attempt_move_san_strict("Qh4"), Err(SanMoveError::WrongCheckAnnotation { .. })));
+    /// // The lenient version doesn't mind.
+    /// assert!(board.attempt_move_san("Qh4").is_ok());
+    /// ```
+    pub fn attempt_move_san_strict(&self, san: &str) -> Result<Self, SanMoveError>
+    {
+        let (move_command, found) = MoveCommand::parse_with_check_annotation(san, ParseMode::Strict)?;
+        let next_board = self.attempt_move_from_command(&move_command)?;
+        let expected = next_board.check_annotation();
+        match expected == found
+        {
+            true => Ok(next_board),
+            false => Err(SanMoveError::WrongCheckAnnotation { expected, found }),
+        }
+    }
+
+    /// Makes each move in `move_commands` one after the other, returning the final board.
+    ///
+    /// This is handy for setting up test positions or replaying a game from its movetext. The
+    /// moves can come from anything that iterates over [MoveCommand]s, owned or borrowed, so they
+    /// can be parsed on the fly without collecting them first.
+    ///
+    /// # Arguments
+    ///
+    /// * `move_commands` - The moves to make, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ApplyMovesError] containing the index of the first move that couldn't be made
+    /// and the [MoveError] explaining why. No further moves are attempted after that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, Square, PieceType};
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let moves = ["e4", "e5", "Nf3", "Nc6"].iter()
+    ///     .map(|m| MoveCommand::from_str(m).unwrap());
+    /// let board = Board::new_default_starting_board().apply_moves(moves).unwrap();
+    /// assert_eq!(board.piece_at(&Square::new(2, 5)).unwrap().piece_type(), PieceType::Knight);
+    ///
+    /// // The second move here is illegal.
+    /// let moves: Vec<MoveCommand> = ["e4", "e4"].iter()
+    ///     .map(|m| MoveCommand::from_str(m).unwrap())
+    ///     .collect();
+    /// let error = Board::new_default_starting_board().apply_moves(&moves).unwrap_err();
+    /// assert_eq!(error.index, 1);
+    /// ```
+    pub fn apply_moves<I>(&self, move_commands: I) -> Result<Self, ApplyMovesError>
+    where I: IntoIterator, I::Item: Borrow<MoveCommand>
+    {
+        let mut board = self.clone();
+        for (index, move_command) in move_commands.into_iter().enumerate()
+        {
+            board = board.attempt_move_from_command(move_command.borrow())
+                .map_err(|error| ApplyMovesError { index, error })?;
+        }
+        Ok(board)
+    }
+
     /// Attempts to make a move on the board. Same as [Self::attempt_move_from_command], but
     /// instead of taking a [MoveCommand], it takes a pre-built [Move] which is evaluated for
     /// correctness.
@@ -189,15 +391,25 @@ impl Board
     /// sense.
     pub fn attempt_move(&self, attempted_move: &Move) -> Result<Self, MoveError>
     {
+        // Only variants let pawns promote to anything else, see Self::make_move_in_place.
+        if let Move::NormalMove(move_data) = attempted_move
+        {
+            if move_data.promotion().is_some_and(|promotion| !PROMOTION_PIECE_TYPES.contains(&promotion))
+            {
+                return Err(MoveError::InvalidPromotion);
+            }
+        }
+        // Make the move first, so that a move which doesn't match the board at all is reported
+        // as such rather than as a generic illegal move.
+        let new_board = self.make_move(attempted_move)?;
+
         // If the move is illegal, we abort.
-        if !self.check_move(&attempted_move)
+        if !self.check_move(attempted_move)
         {
             return Err(MoveError::IllegalMove);
         }
 
-        // Otherwise, we make the move!
-        Ok(self.make_move(&attempted_move))
-
+        Ok(new_board)
     }
 
     /// Gets the piece located on a given square.
@@ -206,7 +418,7 @@ impl Board
     /// If the square is occupied, this function returns a reference to the [Piece].
     pub fn piece_at(&self, square: &Square) -> Option<&Piece>
     {
-        self.piece_mailbox.get(square)
+        self.piece_mailbox[square.index() as usize].as_ref()
     }
 
     /// Converts a [MoveCommand] into a [Move] that may or may not be legal.
@@ -343,53 +555,121 @@ impl Board
     /// let move_command = MoveCommand::from_str("e4").unwrap();
     /// let r#move = board.get_move(move_command).unwrap();
     /// let r#move = board.check_move(r#move).unwrap();
-    /// let new_board = board.make_move(r#move);
+    /// let new_board = board.make_move(r#move).unwrap();
     /// ```
-    fn make_move(&self, r#move: &Move) -> Self
+    ///
+    /// # Errors
+    ///
+    /// Returns [MoveError::MissingPiece] if the pieces the move expects to move aren't where it
+    /// expects them to be, or [MoveError::InvalidPromotion] if the move's promotion doesn't fit the
+    /// moving piece.
+    fn make_move(&self, r#move: &Move) -> Result<Self, MoveError>
     {
         let mut new_board = self.clone();
-        new_board.make_move_in_place(r#move);
-        return new_board;
+        new_board.make_move_in_place(r#move)?;
+        Ok(new_board)
     }
 
-    /// The in-place version of [Board::make_move]. 
+    /// Makes a move on this board instead of returning a new one, and returns what's needed to
+    /// take it back with [Self::unmake_move].
+    ///
+    /// This is meant for searches, which make and take back millions of moves and shouldn't
+    /// have to copy the board for each of them. Unlike [Self::attempt_move], this doesn't check
+    /// that the move is legal, so it should only be given legal moves, i.e ones from
+    /// [Self::generate_moves_for_side] or checked with [Self::is_legal]. That also means it
+    /// doesn't stop a pawn promoting to a king, which some variants allow.
+    ///
+    /// # Arguments
+    ///
+    /// * `r#move` - The move to make.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MoveError::MissingPiece] if the pieces the move expects to move aren't where it
+    /// expects them to be, or [MoveError::InvalidPromotion] if the move's promotion doesn't fit the
+    /// moving piece. The board is left untouched in either case.
+    ///
+    /// # Examples
     ///
-    /// Instead of returning a new board, this function modifies it in place, altering the board
-    /// state as necessary to represent a valid configuration.
-    fn make_move_in_place(&mut self, r#move: &Move)
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut board = Board::new_default_starting_board();
+    /// let r#move = board.get_move(&MoveCommand::from_str("e4").unwrap()).unwrap();
+    /// let undo_info = board.make_move_in_place(&r#move).unwrap();
+    /// assert_ne!(board, Board::new_default_starting_board());
+    /// board.unmake_move(&undo_info);
+    /// assert_eq!(board, Board::new_default_starting_board());
+    /// ```
+    pub fn make_move_in_place(&mut self, r#move: &Move) -> Result<UndoInfo, MoveError>
     {
+        let mut undo_info = UndoInfo
+        {
+            r#move: *r#move,
+            captured_piece: None,
+            castling_availability: self.castling_availability,
+            en_passant_target_square: self.en_passant_target_square,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            zobrist_key: self.zobrist_key,
+        };
+        // Only a double pawn push leaves an en passant square behind.
+        let mut new_en_passant_target_square = None;
         match r#move
         {
             Move::Castle(direction) => {
                 // We hard code the castling squares. At some point we may want to not do this, but
                 // idk what that looks like at allll.
-                let rank = match self.active_color
-                {
-                    PlayerColor::White => 0,
-                    PlayerColor::Black => 7,
-                };
+                let rank = self.active_color.home_rank();
                 let (king_to_file, rook_from_file, rook_to_file) = match direction
                 {
                     CastlingDirection::Kingside => (6, 7, 5),
                     CastlingDirection::Queenside => (2, 0, 3),
                 };
-                let king = self.remove_piece(&Square::new(rank, 4)).expect("Expected king to be on starting square for castling.");
-                let rook = self.remove_piece(&Square::new(rank, rook_from_file)).expect("Expected rook to be on starting square for castling.");
+                let king_square = Square::new(rank, 4);
+                let rook_square = Square::new(rank, rook_from_file);
+                // Make sure both pieces are actually there before we start moving anything, so we
+                // don't leave the board half-castled.
+                if self.piece_at(&king_square) != Some(&Piece::new(self.active_color, PieceType::King))
+                {
+                    return Err(MoveError::MissingPiece(king_square));
+                }
+                if self.piece_at(&rook_square) != Some(&Piece::new(self.active_color, PieceType::Rook))
+                {
+                    return Err(MoveError::MissingPiece(rook_square));
+                }
+                // Neither of these can fail now.
+                let king = self.remove_piece(&king_square).ok_or(MoveError::MissingPiece(king_square))?;
+                let rook = self.remove_piece(&rook_square).ok_or(MoveError::MissingPiece(rook_square))?;
                 self.add_piece(king, &Square::new(rank, king_to_file));
                 self.add_piece(rook, &Square::new(rank, rook_to_file));
 
                 // Castles count as non-captures/pawn moves, so we increment the halfmove clock
-                self.halfmove_clock += 1;
+                self.halfmove_clock = self.halfmove_clock.saturating_add(1);
 
             }
             Move::NormalMove(move_data) => {
-                let piece = self.remove_piece(&move_data.starting_square());
-                let piece = piece.expect("There was no piece at the starting square!");
+                let piece = *self.piece_at(&move_data.starting_square())
+                    .ok_or(MoveError::MissingPiece(move_data.starting_square()))?;
+                // A pawn reaching the last rank has to promote, and nothing else can.
+                let must_promote = piece.piece_type() == PieceType::Pawn && move_data.target_square().rank == piece.color().promotion_rank();
+                let placed_piece = match (must_promote, move_data.promotion())
+                {
+                    // Promoting to a king is only legal in variants, but it's up to the caller to
+                    // check that.
+                    (true, Some(promotion)) if promotion != PieceType::Pawn => Piece::new(piece.color(), promotion),
+                    (false, None) => piece,
+                    _ => return Err(MoveError::InvalidPromotion),
+                };
+                self.remove_piece(&move_data.starting_square());
                 if move_data.capture()
                 {
-                    self.remove_piece(&move_data.target_square());
+                    let captured_square = self.captured_square(piece, move_data);
+                    undo_info.captured_piece = self.remove_piece(&captured_square);
                 }
-                self.add_piece(piece, &move_data.target_square());
+                self.add_piece(placed_piece, &move_data.target_square());
+                new_en_passant_target_square = self.en_passant_target_after(piece, move_data);
 
                 // Check to see if we increment the halfmove_clock.
                 match (piece.piece_type(), move_data.capture())
@@ -399,20 +679,117 @@ impl Board
                     // If we are capturing we reset the halfmove clock
                     (_, true) => { self.halfmove_clock = 0 },
                     // Otherwise we increment the halfmove clock.
-                    _ => { self.halfmove_clock += 1 },
+                    _ => { self.halfmove_clock = self.halfmove_clock.saturating_add(1) },
                 }
             },
         };
-        // Increment fullmove number
+        // Increment fullmove number. The counters saturate instead of wrapping around, a game
+        // that somehow lasts 65535 moves is already well past any draw rule.
         match self.active_color
         {
-            PlayerColor::Black => { self.fullmove_number += 1; }
+            PlayerColor::Black => { self.fullmove_number = self.fullmove_number.saturating_add(1); }
             PlayerColor::White => (),
         };
         // We also want to disable future castling for that player.
+        self.zobrist_key ^= zobrist::castling_key(&self.castling_availability);
         self.castling_availability.update_with_move(self.active_color, r#move);
+        self.zobrist_key ^= zobrist::castling_key(&self.castling_availability);
+        self.zobrist_key ^= zobrist::en_passant_key(self.en_passant_target_square) ^ zobrist::en_passant_key(new_en_passant_target_square);
+        self.en_passant_target_square = new_en_passant_target_square;
         // Switch to next player
+        self.zobrist_key ^= zobrist::side_to_move_key(self.active_color) ^ zobrist::side_to_move_key(!self.active_color);
         self.active_color = !self.active_color;
+        Ok(undo_info)
+    }
+
+    /// Takes back a move made with [Self::make_move_in_place], putting the board back exactly how
+    /// it was before the move.
+    ///
+    /// Moves have to be taken back in the reverse order they were made in, since each
+    /// [UndoInfo] only knows how to undo its own move on the board right after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `undo_info` - What [Self::make_move_in_place] returned for the last move made.
+    pub fn unmake_move(&mut self, undo_info: &UndoInfo)
+    {
+        let mover = !self.active_color;
+        match undo_info.r#move
+        {
+            Move::Castle(direction) => {
+                let rank = mover.home_rank();
+                let (king_to_file, rook_from_file, rook_to_file) = match direction
+                {
+                    CastlingDirection::Kingside => (6, 7, 5),
+                    CastlingDirection::Queenside => (2, 0, 3),
+                };
+                self.remove_piece(&Square::new(rank, king_to_file));
+                self.remove_piece(&Square::new(rank, rook_to_file));
+                self.add_piece(Piece::new(mover, PieceType::King), &Square::new(rank, 4));
+                self.add_piece(Piece::new(mover, PieceType::Rook), &Square::new(rank, rook_from_file));
+            },
+            Move::NormalMove(move_data) => {
+                let moved_piece = self.remove_piece(&move_data.target_square())
+                    .expect("The piece that moved should be on its target square");
+                // A promoted piece goes back to being a pawn.
+                let moved_piece = match move_data.promotion()
+                {
+                    Some(_) => Piece::new(mover, PieceType::Pawn),
+                    None => moved_piece,
+                };
+                self.add_piece(moved_piece, &move_data.starting_square());
+                // Put the en passant square back first, so we know where an en passant capture
+                // took its pawn from.
+                self.en_passant_target_square = undo_info.en_passant_target_square;
+                if let Some(captured_piece) = undo_info.captured_piece
+                {
+                    let captured_square = self.captured_square(moved_piece, &move_data);
+                    self.add_piece(captured_piece, &captured_square);
+                }
+            },
+        }
+        self.active_color = mover;
+        self.castling_availability = undo_info.castling_availability;
+        self.en_passant_target_square = undo_info.en_passant_target_square;
+        self.halfmove_clock = undo_info.halfmove_clock;
+        self.fullmove_number = undo_info.fullmove_number;
+        // Moving the pieces back already undid their part of the key, but restoring the key
+        // covers everything else too.
+        self.zobrist_key = undo_info.zobrist_key;
+    }
+
+    /// Returns the square a capture takes its piece from. This is the target square, except for
+    /// an en passant capture, where the captured pawn is next to the square the pawn moves to.
+    fn captured_square(&self, moving_piece: Piece, move_data: &MoveData) -> Square
+    {
+        let target_square = move_data.target_square();
+        match moving_piece.piece_type() == PieceType::Pawn && Some(target_square) == self.en_passant_target_square
+        {
+            true => Square::new(move_data.starting_square().rank, target_square.file),
+            false => target_square,
+        }
+    }
+
+    /// Returns the en passant target square left behind by a move that was just made, which is the
+    /// square a pawn skipped over with a double push. The square is only set when an enemy pawn
+    /// is standing next to the pushed pawn, since otherwise nothing could capture en passant and
+    /// the position should count as the same one as without the double push.
+    fn en_passant_target_after(&self, moved_piece: Piece, move_data: &MoveData) -> Option<Square>
+    {
+        let (from, to) = (move_data.starting_square(), move_data.target_square());
+        if moved_piece.piece_type() != PieceType::Pawn || from.rank.abs_diff(to.rank) != 2
+        {
+            return None;
+        }
+        let enemy_pawns = self.query().color(!moved_piece.color()).piece_type(PieceType::Pawn).result();
+        let skipped_square = Square::new((from.rank + to.rank) / 2, to.file);
+        // The squares an enemy pawn would capture the skipped square from are exactly the ones
+        // next to the pushed pawn.
+        match (pawn_attack_mask(moved_piece.color(), skipped_square) & enemy_pawns).is_empty()
+        {
+            true => None,
+            false => Some(skipped_square),
+        }
     }
 
     /// Adds a piece onto the board in the set position.
@@ -427,7 +804,8 @@ impl Board
     /// valid (i.e not more than one piece in a square.)
     fn add_piece(&mut self, piece: Piece, position: &Square)
     {
-        self.piece_mailbox.insert(*position, piece);
+        self.piece_mailbox[position.index() as usize] = Some(piece);
+        self.zobrist_key ^= zobrist::piece_key(piece, *position);
         let add_bitmask = Bitboard::from(*position);
         *self.pieces_of_color_as_mut(piece.color()) |= add_bitmask;
         *self.pieces_of_type_as_mut(piece.piece_type()) |= add_bitmask;
@@ -446,7 +824,11 @@ impl Board
     /// it simply returns [None]
     fn remove_piece(&mut self, position: &Square) -> Option<Piece>
     {
-        let piece = self.piece_mailbox.remove(position);
+        let piece = self.piece_mailbox[position.index() as usize].take();
+        if let Some(piece) = piece
+        {
+            self.zobrist_key ^= zobrist::piece_key(piece, *position);
+        }
         let remove_bitmask = !Bitboard::from(*position);
         self.white_pieces &= remove_bitmask;
         self.black_pieces &= remove_bitmask;
@@ -475,45 +857,28 @@ impl Board
     fn is_king_in_check(&self, king_color: PlayerColor) -> bool
     {
         let king = self.query().color(king_color).piece_type(PieceType::King).result();
-        let king_square: Vec<Square> = king.squares().collect();
-        // This should always be true?
-        assert!(king_square.len() == 1);
-        let king_square = king_square[0];
-        // Check each piece type to see if any pieces are attacking the king's square.
-        let pawn_attacks = self.squares_of_type_that_can_capture_square(!king_color, PieceType::Pawn, king_square);
-        if pawn_attacks.len() > 0
-        {
-            return true;
-        }
-        let knight_attacks = self.squares_of_type_that_can_capture_square(!king_color, PieceType::Knight, king_square);
-        if knight_attacks.len() > 0
-        {
-            return true;
-        }
-
-        let bishop_attacks = self.squares_of_type_that_can_capture_square(!king_color, PieceType::Bishop, king_square);
-        if bishop_attacks.len() > 0
-        {
-            return true;
-        }
-        let rook_attacks = self.squares_of_type_that_can_capture_square(!king_color, PieceType::Rook, king_square);
-        if rook_attacks.len() > 0
-        {
-            return true;
-        }
-        let queen_attacks = self.squares_of_type_that_can_capture_square(!king_color, PieceType::Queen, king_square);
-        if queen_attacks.len() > 0
-        {
-            return true;
-        }
-        let king_attacks = self.squares_of_type_that_can_capture_square(!king_color, PieceType::King, king_square);
-        if king_attacks.len() > 0
-        {
-            return true;
-        }
+        // A side without a king, i.e in antichess, can't be in check.
+        king.squares().next().is_some_and(|king_square| self.is_square_attacked(king_square, !king_color))
+    }
 
-        // We shouldn't have to check for king attacks???
-        return false;
+    /// Checks whether any piece of `attacking_color` attacks `square`, whether or not anything is
+    /// standing on it.
+    pub(super) fn is_square_attacked(&self, square: Square, attacking_color: PlayerColor) -> bool
+    {
+        // Every attack is symmetric (except for pawns, which is why we look from the defending
+        // side), so a piece attacks the square exactly when the same kind of piece on the square
+        // would attack it.
+        let defending_color = !attacking_color;
+        let enemy = self.pieces_of_color(attacking_color);
+        let occupied = self.query().result();
+        let queens = self.pieces_of_type(PieceType::Queen);
+        let attackers =
+            (pawn_attack_mask(defending_color, square) & self.pieces_of_type(PieceType::Pawn)) |
+            (self.knight_moves(defending_color, square) & self.pieces_of_type(PieceType::Knight)) |
+            (bishop_attack_mask(square, occupied) & (self.pieces_of_type(PieceType::Bishop) | queens)) |
+            (rook_attack_mask(square, occupied) & (self.pieces_of_type(PieceType::Rook) | queens)) |
+            (self.king_moves(defending_color, square) & self.pieces_of_type(PieceType::King));
+        !(attackers & enemy).is_empty()
     }
 }
 
@@ -554,7 +919,11 @@ impl PartialEq for Board
     /// ```
     fn eq(&self, other: &Self) -> bool
     {
-        return (self.white_pieces == other.white_pieces) &&
+        // Different Zobrist keys always mean different positions, which is by far the most
+        // common case and much cheaper to rule out. Equal keys could still be a collision, so we
+        // compare everything else too.
+        return (self.zobrist_key == other.zobrist_key) &&
+               (self.white_pieces == other.white_pieces) &&
                (self.black_pieces == other.black_pieces) &&
                (self.king_pieces == other.king_pieces) &&
                (self.queen_pieces == other.queen_pieces) &&
@@ -572,25 +941,74 @@ impl Eq for Board {}
 
 impl Hash for Board
 {
+    /// Hashes the board's [Zobrist key](Self::zobrist_key). The key covers exactly the fields
+    /// that [PartialEq] compares, so equal boards always hash the same, and since it's already
+    /// kept up to date there's nothing to recompute. This makes boards cheap to use as keys in
+    /// a [HashMap], i.e as a transposition table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashSet;
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut seen = HashSet::new();
+    /// let mut board = Board::new_default_starting_board();
+    /// seen.insert(board.clone());
+    /// for r#move in ["Nf3", "Nf6", "Ng1", "Ng8"]
+    /// {
+    ///     board = board.attempt_move_from_command(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+    ///     seen.insert(board.clone());
+    /// }
+    /// // We ended up back where we started.
+    /// assert_eq!(seen.len(), 4);
+    /// ```
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.white_pieces.hash(state);
-        self.black_pieces.hash(state);
-        self.king_pieces.hash(state);
-        self.queen_pieces.hash(state);
-        self.rook_pieces.hash(state);
-        self.knight_pieces.hash(state);
-        self.bishop_pieces.hash(state);
-        self.pawn_pieces.hash(state);
-        self.active_color.hash(state);
-        self.castling_availability.hash(state);
-        self.en_passant_target_square.hash(state);
+        state.write_u64(self.zobrist_key);
     }
 }
 
-impl Display for Board
+impl Board
+{
+    /// Returns a [BoardView] of this board as seen by the given player, i.e with their pieces at
+    /// the bottom. Displaying a [Board] directly always shows it from White's side.
+    ///
+    /// # Arguments
+    ///
+    /// * `perspective` - The player sitting at the bottom of the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, PlayerColor};
+    /// let board = Board::new_default_starting_board();
+    /// let black_view = board.view_from(PlayerColor::Black).to_string();
+    /// // Black sees the files from h to a.
+    /// assert!(black_view.lines().next().unwrap().trim().starts_with('h'));
+    /// ```
+    pub fn view_from(&self, perspective: PlayerColor) -> BoardView<'_>
+    {
+        BoardView
+        {
+            board: self,
+            perspective,
+        }
+    }
+}
+
+/// A [Board] as seen from one player's side of the table, see [Board::view_from].
+#[derive(Debug, Clone, Copy)]
+pub struct BoardView<'a>
+{
+    board: &'a Board,
+    perspective: PlayerColor,
+}
+
+impl Display for BoardView<'_>
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Formats the board to look as follows:
+        // Formats the board to look as follows (from White's perspective, Black's is rotated):
         /*    a  b  c  d  e  f  g  h 
          * 8 [r][n][b][q][k][b][n][r]
          * 7 [p][p][p][p][p][p][p][p]
@@ -601,35 +1019,47 @@ impl Display for Board
          * 2 [P][P][P][P][P][P][P][P]
          * 1 [R][N][B][Q][K][B][N][R]
          */
+        // White reads files left to right and ranks top to bottom from 8, Black the opposite.
+        let (files, ranks): (Vec<u8>, Vec<u8>) = match self.perspective
+        {
+            PlayerColor::White => ((0..8).collect(), (0..8).rev().collect()),
+            PlayerColor::Black => ((0..8).rev().collect(), (0..8).collect()),
+        };
         // Initial spacing for top left corner
         // Two spaces.
         write!(f, "  ")?;
         let spacing = 3;
         // We map 0-8 to a-h for writing the files.
-        for char in (0..8).map(|x| char::from_u32(x + 'a' as u32).expect(&format!("{} was not a valid character", x + 'a' as u32)))
+        for char in files.iter().map(|&x| char::from(x + b'a'))
         {
             // Writes the file with a spacing of 3, with the file letter centered.
             write!(f, "{:^spacing$}", char, spacing=spacing)?;
         }
         write!(f, "\n")?;
         // Now we can write the ranks, yay!!!
-        for rank in (0..8).rev()
+        for &rank in &ranks
         {
             // Prefix the line with the rank coordinates
             write!(f, "{:<2}", rank + 1)?;
-            for file in 0..8
+            for &file in &files
             {
                 // Visit each square and print what piece is on that square, if any.
                 let square = Square::new(rank, file);
-                let piece = self.piece_mailbox.get(&square);
+                let piece = self.board.piece_at(&square);
                 let is_even = (rank + file) % 2 == 0;
-                match (piece, is_even)
+                // The alternate flag ({:#}) draws the pieces as figurines instead of letters.
+                let piece = match (piece, f.alternate())
+                {
+                    (Some(x), false) => x.letter(),
+                    (Some(x), true) => x.figurine(),
+                    (None, _) => ' ',
+                };
+                let square_color = match is_even
                 {
-                    (Some(x), false) => write!(f, "{}{}{}", "[".color(BOARD_COLOR_1), x, "]".color(BOARD_COLOR_1))?,
-                    (None, false) => write!(f, "{} {}", "[".color(BOARD_COLOR_1), "]".color(BOARD_COLOR_1))?,
-                    (Some(x), true) => write!(f, "{}{}{}", "[".color(BOARD_COLOR_2), x, "]".color(BOARD_COLOR_2))?,
-                    (None, true) => write!(f, "{} {}", "[".color(BOARD_COLOR_2), "]".color(BOARD_COLOR_2))?,
+                    false => BOARD_COLOR_1,
+                    true => BOARD_COLOR_2,
                 };
+                write!(f, "{}{}{}", "[".color(square_color), piece, "]".color(square_color))?;
             }
             write!(f, "\n")?;
         }
@@ -637,10 +1067,38 @@ impl Display for Board
     }
 }
 
+impl Display for Board
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.view_from(PlayerColor::White).fmt(f)
+    }
+}
+
+impl Serialize for Board
+{
+    /// Serializes the board as its FEN record, which is everything a [Board] is made from. See
+    /// [Self::board_configuration].
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.collect_str(&self.board_configuration())
+    }
+}
+
+impl<'de> Deserialize<'de> for Board
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let fen = String::deserialize(deserializer)?;
+        let board_configuration = BoardConfiguration::from_str(&fen).map_err(de::Error::custom)?;
+        Ok(Self::new_board_with_configuration(&board_configuration))
+    }
+}
+
 #[cfg(test)]
 mod tests
 {
-    use crate::hashmap_diff::print_hashmap_differences;
+    use crate::test_support::print_hashmap_differences;
+    use crate::board::MoveData;
     use std::str::FromStr;
 
     use colored::Colorize;
@@ -895,6 +1353,28 @@ mod tests
         assert_eq!(new_board.piece_at(&Square::new(7, 2)).unwrap().piece_type(), PieceType::King);
     }
 
+    #[test]
+    fn castle_without_pieces_on_home_squares_is_an_error()
+    {
+        // The king has wandered off to f1, so castling doesn't make any sense on this board.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/R4K1R w KQ - 0 1").unwrap());
+        let result = board.attempt_move(&Move::Castle(CastlingDirection::Kingside));
+        assert!(matches!(result, Err(MoveError::MissingPiece(square)) if square == Square::new(0, 4)));
+
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/4K2R w KQ - 0 1").unwrap());
+        let result = board.attempt_move(&Move::Castle(CastlingDirection::Queenside));
+        assert!(matches!(result, Err(MoveError::MissingPiece(square)) if square == Square::new(0, 0)));
+    }
+
+    #[test]
+    fn normal_move_from_empty_square_is_an_error()
+    {
+        let board = Board::new_default_starting_board();
+        let r#move = Move::NormalMove(MoveData::new(Square::new(3, 4), Square::new(4, 4), false));
+        let result = board.attempt_move(&r#move);
+        assert!(matches!(result, Err(MoveError::MissingPiece(square)) if square == Square::new(3, 4)));
+    }
+
     #[test]
     fn white_move_increments_halfmove_clock_but_not_fullmove_number()
     {
@@ -915,6 +1395,26 @@ mod tests
         assert_eq!(2, new_board.board_configuration().fullmove_number());
     }
 
+    #[test]
+    fn fullmove_number_above_u8_range_increments()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("7k/8/8/8/8/8/8/K7 b - - 12 300").unwrap());
+        let r#move = MoveCommand::from_str("Kg8").unwrap();
+        let new_board = board.attempt_move_from_command(&r#move).unwrap();
+        assert_eq!(13, new_board.board_configuration().halfmove_clock());
+        assert_eq!(301, new_board.board_configuration().fullmove_number());
+    }
+
+    #[test]
+    fn move_counters_saturate_instead_of_overflowing()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("7k/8/8/8/8/8/8/K7 b - - 65535 65535").unwrap());
+        let r#move = MoveCommand::from_str("Kg8").unwrap();
+        let new_board = board.attempt_move_from_command(&r#move).unwrap();
+        assert_eq!(u16::MAX, new_board.board_configuration().halfmove_clock());
+        assert_eq!(u16::MAX, new_board.board_configuration().fullmove_number());
+    }
+
     #[test]
     fn pawn_move_resets_halfmove_clock()
     {
@@ -932,4 +1432,167 @@ mod tests
         let new_board = board.attempt_move_from_command(&r#move).unwrap();
         assert_eq!(0, new_board.board_configuration().halfmove_clock());
     }
+
+    #[test]
+    fn promotion_replaces_the_pawn()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+        let new_board = board.attempt_move_from_command(&MoveCommand::from_str("axb8=N").unwrap()).unwrap();
+        assert_eq!(new_board.board_configuration(), BoardConfiguration::from_str("1N2k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap());
+    }
+
+    #[test]
+    fn promotion_must_be_given_and_valid()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+        let result = board.attempt_move_from_command(&MoveCommand::from_str("a8").unwrap());
+        assert!(matches!(result, Err(MoveError::InvalidPromotion)));
+        let r#move = Move::NormalMove(MoveData::new(Square::new(6, 0), Square::new(7, 0), false).with_promotion(PieceType::King));
+        assert!(matches!(board.attempt_move(&r#move), Err(MoveError::InvalidPromotion)));
+        let r#move = Move::NormalMove(MoveData::new(Square::new(0, 4), Square::new(1, 4), false).with_promotion(PieceType::Queen));
+        assert!(matches!(board.attempt_move(&r#move), Err(MoveError::InvalidPromotion)));
+    }
+
+    #[test]
+    fn move_generation_emits_every_promotion()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+        let pawn_moves = board.generate_legal_moves_for_piece(PlayerColor::White, Square::new(6, 0));
+        // a8 and axb8, each with four promotions.
+        assert_eq!(pawn_moves.len(), 8);
+        for promotion in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight]
+        {
+            assert!(pawn_moves.contains(&Move::NormalMove(MoveData::new(Square::new(6, 0), Square::new(7, 1), true).with_promotion(promotion))));
+        }
+    }
+
+    #[test]
+    fn zobrist_key_is_kept_up_to_date()
+    {
+        let mut board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/1P6/8/8/8/8/6p1/R3K2R w KQkq - 0 1").unwrap());
+        for r#move in ["O-O-O", "gxh1=Q", "Rxh1", "O-O", "Kb2", "Kg7", "bxa8=N", "Rxa8"]
+        {
+            board = board.attempt_move_from_command(&MoveCommand::from_str(r#move).unwrap()).expect(r#move);
+            let fresh_board = Board::new_board_with_configuration(&board.board_configuration());
+            assert_eq!(board.zobrist_key(), fresh_board.zobrist_key(), "after {move}");
+        }
+        assert_ne!(board.zobrist_key(), Board::new_default_starting_board().zobrist_key());
+    }
+
+    fn hash_of(board: &Board) -> u64
+    {
+        use std::hash::{DefaultHasher, Hasher};
+        let mut hasher = DefaultHasher::new();
+        board.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn transpositions_are_equal_and_hash_the_same()
+    {
+        let play = |moves: [&str; 4]| moves.iter().fold(Board::new_default_starting_board(), |board, r#move|
+            board.attempt_move_from_command(&MoveCommand::from_str(r#move).unwrap()).unwrap());
+        let board = play(["e4", "e5", "Nf3", "Nc6"]);
+        let transposed = play(["Nf3", "Nc6", "e4", "e5"]);
+        assert_eq!(board, transposed);
+        assert_eq!(hash_of(&board), hash_of(&transposed));
+    }
+
+    #[test]
+    fn castling_rights_make_boards_different()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap());
+        let no_castling = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap());
+        assert_ne!(board, no_castling);
+        assert_ne!(hash_of(&board), hash_of(&no_castling));
+    }
+
+    #[test]
+    fn capturing_a_rook_on_its_corner_takes_away_castling()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap());
+        let board = board.attempt_move_from_command(&MoveCommand::from_str("Rxa8+").unwrap()).unwrap();
+        // White's a-rook moved and Black's a-rook was taken.
+        assert_eq!(board.castling_availability(), CastlingAvailability::new(true, false, true, false));
+        assert!(board.attempt_move_from_command(&MoveCommand::from_str("O-O-O").unwrap()).is_err());
+        let board = board.attempt_move_from_command(&MoveCommand::from_str("Kd7").unwrap()).unwrap();
+        // The rook on h1 never moved, so White can still castle kingside.
+        let board = board.attempt_move_from_command(&MoveCommand::from_str("R8xh8").unwrap()).unwrap();
+        assert_eq!(board.castling_availability(), CastlingAvailability::new(true, false, false, false));
+    }
+
+    #[test]
+    fn mailbox_agrees_with_the_bitboards()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/1P6/8/8/8/8/6p1/R3K2R w KQkq - 0 1").unwrap());
+        let board = board.attempt_move_from_command(&MoveCommand::from_str("bxa8=Q").unwrap()).unwrap();
+        for index in 0..64
+        {
+            let square = Square::from_index(index).unwrap();
+            let occupied = !(board.query().result() & Bitboard::from(square)).is_empty();
+            assert_eq!(board.piece_at(&square).is_some(), occupied, "on {:?}", square);
+        }
+        assert_eq!(board.piece_at(&Square::from_str("a8").unwrap()), Some(&Piece::new(PlayerColor::White, PieceType::Queen)));
+        assert_eq!(board.piece_at(&Square::from_str("b7").unwrap()), None);
+    }
+
+    #[test]
+    fn unmake_move_restores_the_board()
+    {
+        // Castling both ways, captures, promotions with and without a capture, and en passant.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/1P6/8/3p4/4P3/8/6p1/R3K2R w KQkq - 3 17").unwrap());
+        let en_passant_board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 2").unwrap());
+        for player_board in [board.clone(), board.attempt_move_from_command(&MoveCommand::from_str("Kd2").unwrap()).unwrap(), en_passant_board]
+        {
+            let mut made_board = player_board.clone();
+            for r#move in player_board.generate_moves_for_side(player_board.active_color())
+            {
+                let undo_info = made_board.make_move_in_place(&r#move).unwrap();
+                assert_eq!(made_board, player_board.attempt_move(&r#move).unwrap(), "after {:?}", r#move);
+                made_board.unmake_move(&undo_info);
+                assert_eq!(made_board, player_board, "after undoing {:?}", r#move);
+                assert_eq!(made_board.zobrist_key(), player_board.zobrist_key());
+                assert_eq!(made_board.board_configuration(), player_board.board_configuration());
+            }
+        }
+    }
+
+    #[test]
+    fn double_pushes_leave_an_en_passant_square_only_when_it_can_be_used()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/3p4/8/P3P3/4K3 w - - 0 1").unwrap());
+        // Nothing is next to a4, so there's nothing to capture en passant.
+        let after_a4 = board.attempt_move_from_command(&MoveCommand::from_str("a4").unwrap()).unwrap();
+        assert_eq!(after_a4.board_configuration().en_passant_target_square(), None);
+        let after_e4 = board.attempt_move_from_command(&MoveCommand::from_str("e4").unwrap()).unwrap();
+        assert_eq!(after_e4.board_configuration().en_passant_target_square(), Some(Square::from_str("e3").unwrap()));
+        let after_e3 = board.attempt_move_from_command(&MoveCommand::from_str("e3").unwrap()).unwrap();
+        assert_eq!(after_e3.board_configuration().en_passant_target_square(), None);
+        // The square goes away again after any other move.
+        let after_kd7 = after_e4.attempt_move_from_command(&MoveCommand::from_str("Kd7").unwrap()).unwrap();
+        assert_eq!(after_kd7.board_configuration().en_passant_target_square(), None);
+        assert_eq!(after_kd7.zobrist_key(), Board::new_board_with_configuration(&after_kd7.board_configuration()).zobrist_key());
+
+        let capture = after_e4.attempt_move_from_command(&MoveCommand::from_str("dxe3").unwrap()).unwrap();
+        assert_eq!(capture.piece_at(&Square::from_str("e4").unwrap()), None);
+        assert_eq!(capture.piece_at(&Square::from_str("e3").unwrap()), Some(&Piece::new(PlayerColor::Black, PieceType::Pawn)));
+    }
+
+    #[test]
+    fn move_generation_castles_and_captures_en_passant()
+    {
+        // The pawn on g2 covers f1, so only queenside castling is allowed.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/8/8/8/8/8/6p1/R3K2R w KQkq - 0 1").unwrap());
+        let moves = board.generate_moves_for_side(PlayerColor::White);
+        assert!(moves.contains(&Move::Castle(CastlingDirection::Queenside)));
+        assert!(!moves.contains(&Move::Castle(CastlingDirection::Kingside)));
+
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 2").unwrap());
+        let en_passant = Move::NormalMove(MoveData::new(Square::from_str("d5").unwrap(), Square::from_str("c6").unwrap(), true));
+        assert!(board.generate_moves_for_side(PlayerColor::White).contains(&en_passant));
+        assert!(board.generate_captures_for_side(PlayerColor::White).contains(&en_passant));
+        // Taking en passant here would clear the rank between the rook and the king.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/K1pP3r/8/8/8/8 w - c6 0 2").unwrap());
+        assert!(!board.generate_moves_for_side(PlayerColor::White).contains(&en_passant));
+    }
 }
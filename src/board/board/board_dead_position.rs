@@ -0,0 +1,222 @@
+//! This submodule detects "dead" positions, where no sequence of legal moves can ever lead to
+//! checkmate, so the game is a draw even though both players can still move.
+
+use crate::{bitboard::Bitboard, board::{PieceType, PlayerColor}};
+
+use super::Board;
+
+/// Every light square (b1, a2, ...), so bishops on the same color can be told apart.
+const LIGHT_SQUARES: Bitboard = Bitboard::new(0x55AA55AA55AA55AA);
+
+impl Board
+{
+    /// Returns true if neither player can ever checkmate the other, no matter what moves are
+    /// played.
+    ///
+    /// This recognizes positions with insufficient material (see
+    /// [Self::has_insufficient_material]) and fully locked pawn structures: only kings and pawns
+    /// are left,
+    /// every pawn is blocked and has nothing to capture, and neither king can get to an
+    /// undefended enemy pawn. In that case only the kings can ever move, and two lone kings can't
+    /// checkmate each other. The check is conservative, so a position it doesn't recognize as
+    /// dead may still be one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration};
+    /// // A locked pawn wall that neither king can get through.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/4k3/8/1p1p1p1p/pPpPpPpP/P1P1P1P1/8/4K3 w - - 0 1").unwrap());
+    /// assert!(board.is_dead_position());
+    /// assert!(!Board::new_default_starting_board().is_dead_position());
+    /// ```
+    pub fn is_dead_position(&self) -> bool
+    {
+        if self.has_insufficient_material()
+        {
+            return true;
+        }
+
+        let pawns = self.pieces_of_type(PieceType::Pawn);
+        let kings = self.pieces_of_type(PieceType::King);
+        // Any other piece could eventually help deliver mate, and en passant could unlock the
+        // pawns.
+        if (pawns | kings) != self.query().result() || self.en_passant_target_square.is_some()
+        {
+            return false;
+        }
+
+        for color in [PlayerColor::White, PlayerColor::Black]
+        {
+            for pawn_square in (pawns & self.pieces_of_color(color)).squares()
+            {
+                // A pawn with nothing in front of it (or on the last rank) can still move.
+                let square_in_front = match color
+                {
+                    PlayerColor::White if pawn_square.rank < 7 => Bitboard::from(pawn_square) << 8,
+                    PlayerColor::Black if pawn_square.rank > 0 => Bitboard::from(pawn_square) >> 8,
+                    _ => return false,
+                };
+                if (square_in_front & pawns).is_empty()
+                {
+                    return false;
+                }
+                if !self.pawn_attacks(color, pawn_square).is_empty()
+                {
+                    return false;
+                }
+            }
+            if self.king_can_reach_enemy_pawn(color)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns true if neither side has enough material left to ever deliver checkmate.
+    ///
+    /// That's the case with only the kings left, a king and one minor piece against a lone king,
+    /// or when every remaining piece besides the kings is a bishop and they all stand on squares
+    /// of the same color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration};
+    /// // King and knight can't mate a lone king.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/3NK3 w - - 0 1").unwrap());
+    /// assert!(board.has_insufficient_material());
+    /// // King and rook can.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap());
+    /// assert!(!board.has_insufficient_material());
+    /// ```
+    pub fn has_insufficient_material(&self) -> bool
+    {
+        let pawns_rooks_queens = self.pieces_of_type(PieceType::Pawn) | self.pieces_of_type(PieceType::Rook) | self.pieces_of_type(PieceType::Queen);
+        if !pawns_rooks_queens.is_empty()
+        {
+            return false;
+        }
+
+        let knights = self.pieces_of_type(PieceType::Knight);
+        let bishops = self.pieces_of_type(PieceType::Bishop);
+        if (knights | bishops).count() <= 1
+        {
+            return true;
+        }
+        // Bishops that all stay on one color can never cover the squares a king escapes to.
+        knights.is_empty() && ((bishops & LIGHT_SQUARES).is_empty() || (bishops & !LIGHT_SQUARES).is_empty())
+    }
+
+    /// Returns true if `color` has enough material left to checkmate with, on their own. That
+    /// takes a pawn, a rook, a queen or at least two minor pieces. This is what decides whether
+    /// running out of time loses the game, or only draws it (see
+    /// [DrawReason::TimeoutVsInsufficientMaterial](crate::board::DrawReason::TimeoutVsInsufficientMaterial)).
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The player who would be giving checkmate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor};
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/2BNK3 w - - 0 1").unwrap());
+    /// assert!(board.has_mating_material(PlayerColor::White));
+    /// assert!(!board.has_mating_material(PlayerColor::Black));
+    /// ```
+    pub fn has_mating_material(&self, color: PlayerColor) -> bool
+    {
+        let own_pieces = self.pieces_of_color(color);
+        let pawns_rooks_queens = self.pieces_of_type(PieceType::Pawn) | self.pieces_of_type(PieceType::Rook) | self.pieces_of_type(PieceType::Queen);
+        let minor_pieces = self.pieces_of_type(PieceType::Knight) | self.pieces_of_type(PieceType::Bishop);
+        !(pawns_rooks_queens & own_pieces).is_empty() || (minor_pieces & own_pieces).count() >= 2
+    }
+
+    /// Flood fills every square `color`'s king could ever walk to if the pawns never move, and
+    /// returns true if that includes an enemy pawn it could take.
+    fn king_can_reach_enemy_pawn(&self, color: PlayerColor) -> bool
+    {
+        let Some(king_square) = (self.pieces_of_type(PieceType::King) & self.pieces_of_color(color)).squares().next() else
+        {
+            return false;
+        };
+        let enemy_pawns = self.pieces_of_type(PieceType::Pawn) & self.pieces_of_color(!color);
+        // The king can never step onto a square an enemy pawn guards, which includes defended
+        // enemy pawns.
+        let guarded = enemy_pawns.squares()
+            .fold(Bitboard::default(), |guarded, square| guarded | self.pawn_theoretical_attacks(!color, square));
+
+        let mut reachable = Bitboard::from(king_square);
+        let mut frontier = vec![king_square];
+        while let Some(square) = frontier.pop()
+        {
+            let next = self.king_moves(color, square) & !guarded & !reachable;
+            if !(next & enemy_pawns).is_empty()
+            {
+                return true;
+            }
+            reachable |= next;
+            frontier.extend(next.squares());
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{board::{BoardResult, DrawReason}, test_support::board_from_fen};
+
+    #[test]
+    fn locked_position_is_a_draw()
+    {
+        let board = board_from_fen("8/4k3/8/1p1p1p1p/pPpPpPpP/P1P1P1P1/8/4K3 w - - 0 1");
+        assert!(matches!(board.game_result(), BoardResult::Draw(DrawReason::CheckmateImpossible)));
+    }
+
+    #[test]
+    fn king_that_can_reach_a_pawn_keeps_the_game_alive()
+    {
+        // The g and h-pawns are missing, so the white king can walk around to the black pawns.
+        let board = board_from_fen("8/4k3/8/1p1p1p2/pPpPpP2/P1P1P3/8/4K3 w - - 0 1");
+        assert!(!board.is_dead_position());
+    }
+
+    #[test]
+    fn pieces_other_than_pawns_and_kings_keep_the_game_alive()
+    {
+        let board = board_from_fen("8/4k3/8/1p1p1p1p/pPpPpPpP/P1P1P1P1/8/3NK3 w - - 0 1");
+        assert!(!board.is_dead_position());
+    }
+
+    #[test]
+    fn possible_pawn_captures_keep_the_game_alive()
+    {
+        let board = board_from_fen("8/4k3/8/1p1p1p1p/pPpPpPpP/P1P1P1PP/8/4K3 w - - 0 1");
+        assert!(!board.is_dead_position());
+    }
+
+    #[test]
+    fn insufficient_material_is_a_draw()
+    {
+        assert!(matches!(board_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").game_result(), BoardResult::Draw(DrawReason::CheckmateImpossible)));
+        assert!(board_from_fen("4k3/8/8/8/8/8/8/2B1K3 b - - 0 1").has_insufficient_material());
+        // Bishops on c1 and f8 are both on dark squares.
+        assert!(board_from_fen("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1").has_insufficient_material());
+    }
+
+    #[test]
+    fn enough_material_keeps_the_game_alive()
+    {
+        // Bishops on opposite colors.
+        assert!(!board_from_fen("4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1").has_insufficient_material());
+        // Two knights.
+        assert!(!board_from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").has_insufficient_material());
+        assert!(!board_from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").has_insufficient_material());
+    }
+}
@@ -0,0 +1,199 @@
+//! Helper module for the [board](super) module that packs a position into a compact binary form.
+//!
+//! FEN is easy to read but wordy and slow to parse. The encoding here uses a nibble per square
+//! (32 bytes for the whole board) followed by the rest of the state, which makes it a good fit
+//! for on-disk books and tables, or for sending a whole position over the network.
+
+use std::collections::HashMap;
+
+use crate::board::{BoardConfigurationBuilder, CastlingAvailability, DecodePositionError, Piece, PieceType, PlayerColor, Square};
+
+use super::Board;
+
+/// The length in bytes of an encoded position: 32 bytes of pieces, a flags byte, the en passant
+/// square, and the halfmove clock and fullmove number as little endian `u16`s.
+pub const ENCODED_POSITION_LENGTH: usize = 38;
+
+/// Marks a missing en passant target square.
+const NO_EN_PASSANT: u8 = 0xFF;
+/// Set in a piece's nibble if it's black.
+const BLACK_PIECE: u8 = 0b1000;
+
+impl Board
+{
+    /// Packs this position into [ENCODED_POSITION_LENGTH] bytes.
+    ///
+    /// Each square (a1, b1, ... h8) gets a nibble, low nibble first: 0 for an empty square, 1 to 6
+    /// for a white pawn, knight, bishop, rook, queen or king, and the same plus 8 for black
+    /// pieces. After the pieces come a flags byte (bit 0 is set if Black is to move, bits 1 to 4
+    /// are the castling rights in `KQkq` order), the en passant target square's index (or `0xFF`
+    /// if there isn't one), then the halfmove clock and fullmove number.
+    ///
+    /// Use [Self::decode] to get the position back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, ENCODED_POSITION_LENGTH};
+    /// let board = Board::new_default_starting_board();
+    /// let encoded = board.encode();
+    /// assert_eq!(encoded.len(), ENCODED_POSITION_LENGTH);
+    /// assert_eq!(Board::decode(&encoded).unwrap(), board);
+    /// ```
+    pub fn encode(&self) -> [u8; ENCODED_POSITION_LENGTH]
+    {
+        let mut bytes = [0; ENCODED_POSITION_LENGTH];
+        for (index, piece) in self.piece_mailbox.iter().enumerate()
+        {
+            let Some(piece) = piece else { continue };
+            bytes[index / 2] |= encode_piece(piece) << (4 * (index % 2));
+        }
+
+        let castling = &self.castling_availability;
+        let flags = [
+            self.active_color == PlayerColor::Black,
+            *castling.white_castle_kingside(),
+            *castling.white_castle_queenside(),
+            *castling.black_castle_kingside(),
+            *castling.black_castle_queenside(),
+        ];
+        bytes[32] = flags.iter().enumerate().fold(0, |byte, (bit, set)| byte | ((*set as u8) << bit));
+        bytes[33] = self.en_passant_target_square.map_or(NO_EN_PASSANT, |square| square.index());
+        bytes[34..36].copy_from_slice(&self.halfmove_clock.to_le_bytes());
+        bytes[36..38].copy_from_slice(&self.fullmove_number.to_le_bytes());
+        bytes
+    }
+
+    /// Unpacks a position written by [Self::encode].
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded position.
+    ///
+    /// # Errors
+    ///
+    /// - [DecodePositionError::WrongLength] if `bytes` isn't [ENCODED_POSITION_LENGTH] bytes
+    ///   long.
+    /// - [DecodePositionError::InvalidPiece] if a square holds a nibble that isn't a piece.
+    /// - [DecodePositionError::InvalidEnPassantSquare] if the en passant square isn't on the
+    ///   board.
+    /// - [DecodePositionError::InvalidConfiguration] if the decoded position isn't a valid board,
+    ///   see [BoardConfigurationBuilder::try_build].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, DecodePositionError};
+    /// assert!(matches!(Board::decode(&[0; 4]), Err(DecodePositionError::WrongLength(4))));
+    /// ```
+    pub fn decode(bytes: &[u8]) -> Result<Board, DecodePositionError>
+    {
+        if bytes.len() != ENCODED_POSITION_LENGTH
+        {
+            return Err(DecodePositionError::WrongLength(bytes.len()));
+        }
+
+        let mut pieces = HashMap::new();
+        for index in 0..64_u8
+        {
+            let nibble = (bytes[index as usize / 2] >> (4 * (index % 2))) & 0x0F;
+            let square = Square::from_index_unchecked(index);
+            if let Some(piece) = decode_piece(nibble).ok_or(DecodePositionError::InvalidPiece(nibble, square))?
+            {
+                pieces.insert(square, piece);
+            }
+        }
+
+        let flags = bytes[32];
+        let flag = |bit: u8| flags & (1 << bit) != 0;
+        let active_color = match flag(0)
+        {
+            true => PlayerColor::Black,
+            false => PlayerColor::White,
+        };
+        let castling_availability = CastlingAvailability::new(flag(1), flag(2), flag(3), flag(4));
+
+        let mut builder = BoardConfigurationBuilder::default()
+            .set_pieces(pieces)
+            .set_active_color(active_color)
+            .set_castling_availability(castling_availability)
+            .set_halfmove_clock(u16::from_le_bytes([bytes[34], bytes[35]]))
+            .set_fullmove_number(u16::from_le_bytes([bytes[36], bytes[37]]));
+        if bytes[33] != NO_EN_PASSANT
+        {
+            let square = Square::from_index(bytes[33]).map_err(|_| DecodePositionError::InvalidEnPassantSquare(bytes[33]))?;
+            builder = builder.set_en_passant_target_square(square);
+        }
+        Ok(Board::new_board_with_configuration(&builder.try_build()?))
+    }
+}
+
+fn encode_piece(piece: &Piece) -> u8
+{
+    let piece_type = match piece.piece_type()
+    {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    match piece.color()
+    {
+        PlayerColor::White => piece_type,
+        PlayerColor::Black => piece_type | BLACK_PIECE,
+    }
+}
+
+/// Returns [None] if the nibble isn't valid, and `Some(None)` for an empty square.
+fn decode_piece(nibble: u8) -> Option<Option<Piece>>
+{
+    let color = match nibble & BLACK_PIECE
+    {
+        0 => PlayerColor::White,
+        _ => PlayerColor::Black,
+    };
+    let piece_type = match nibble & !BLACK_PIECE
+    {
+        0 if nibble == 0 => return Some(None),
+        1 => PieceType::Pawn,
+        2 => PieceType::Knight,
+        3 => PieceType::Bishop,
+        4 => PieceType::Rook,
+        5 => PieceType::Queen,
+        6 => PieceType::King,
+        _ => return None,
+    };
+    Some(Some(Piece::new(color, piece_type)))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::BoardConfiguration;
+
+    use super::*;
+
+    #[test]
+    fn positions_round_trip()
+    {
+        for fen in ["r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 0 23", "8/8/4k3/8/8/3K4/8/8 b - - 41 300"]
+        {
+            let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            let decoded = Board::decode(&board.encode()).unwrap();
+            assert_eq!(decoded.board_configuration(), BoardConfiguration::from_str(fen).unwrap());
+        }
+    }
+
+    #[test]
+    fn invalid_nibbles_are_rejected()
+    {
+        let mut encoded = Board::new_default_starting_board().encode();
+        // Put an 8 (a black "nothing") on e4.
+        encoded[14] = 0x08;
+        assert!(matches!(Board::decode(&encoded), Err(DecodePositionError::InvalidPiece(8, _))));
+    }
+}
@@ -3,10 +3,22 @@
 use std::cmp::Ordering;
 use derive_more::From;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
-use crate::board::{BoardResult, Move, PieceType, PlayerColor, Square};
+use serde::{Deserialize, Serialize};
+use crate::{bitboard::Bitboard, board::{AttackInfo, BoardResult, Move, PieceType, PlayerColor, Square}};
 use super::Board;
 
+mod piece_square_tables;
+
+pub use piece_square_tables::PieceSquareTables;
+
+/// How much each piece counts towards the game phase, see [Board::middlegame_phase]. Pawns and
+/// kings don't count, ordered knight, bishop, rook, queen.
+const PHASE_WEIGHTS: [(PieceType, u32); 4] = [(PieceType::Knight, 1), (PieceType::Bishop, 1), (PieceType::Rook, 2), (PieceType::Queen, 4)];
+/// The game phase with all the pieces on the board: 4 minor pieces, 4 rooks and 2 queens.
+const STARTING_PHASE: u32 = 24;
+
 /// How highly to evaluate certain aspects of the position.
+#[derive(Clone)]
 pub struct EvaluationWeights
 {
     overall_material_weight: f64,
@@ -18,6 +30,23 @@ pub struct EvaluationWeights
 
     center_control_weight: f64,
     mobility_weight: f64,
+
+    king_tropism_weight: f64,
+    queen_tropism_weight: f64,
+    rook_tropism_weight: f64,
+    bishop_tropism_weight: f64,
+    knight_tropism_weight: f64,
+
+    pawn_race_weight: f64,
+    passed_pawn_king_proximity_weight: f64,
+
+    piece_square_weight: f64,
+    piece_square_tables: PieceSquareTables,
+
+    king_safety_weight: f64,
+    pawn_shield_weight: f64,
+    open_file_weight: f64,
+    king_zone_attack_weight: f64,
 }
 
 impl Default for EvaluationWeights
@@ -34,13 +63,198 @@ impl Default for EvaluationWeights
             pawn_material_weight: 1.0,
             center_control_weight: 0.4,
             mobility_weight: 0.4,
+            king_tropism_weight: 0.05,
+            queen_tropism_weight: 1.0,
+            rook_tropism_weight: 0.5,
+            bishop_tropism_weight: 0.3,
+            knight_tropism_weight: 0.5,
+            pawn_race_weight: 8.0,
+            passed_pawn_king_proximity_weight: 0.1,
+            piece_square_weight: 1.0,
+            piece_square_tables: PieceSquareTables::default(),
+            king_safety_weight: 1.0,
+            pawn_shield_weight: 0.15,
+            open_file_weight: 0.1,
+            king_zone_attack_weight: 0.02,
         }
     }
 }
 
+impl EvaluationWeights
+{
+    /// Sets how much to reward pieces for being close to the enemy king, see
+    /// [Self::with_king_tropism_weights]. Setting this to 0 turns king tropism off.
+    ///
+    /// # Arguments
+    ///
+    /// * `king_tropism_weight` - How much king tropism counts towards the evaluation overall.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// let evaluation_weights = EvaluationWeights::default().with_king_tropism_weight(0.1);
+    /// ```
+    pub fn with_king_tropism_weight(mut self, king_tropism_weight: f64) -> Self
+    {
+        self.king_tropism_weight = king_tropism_weight;
+        self
+    }
+
+    /// Sets how much each piece type is rewarded for being close to the enemy king.
+    ///
+    /// Every queen, rook, bishop and knight scores its weight for each step closer than the
+    /// furthest possible distance (7 squares) it is to the enemy king, and the difference between
+    /// the two sides is multiplied by the overall king tropism weight. Pawns and kings aren't
+    /// counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `queen_tropism_weight` - The weight for queens.
+    /// * `rook_tropism_weight` - The weight for rooks.
+    /// * `bishop_tropism_weight` - The weight for bishops.
+    /// * `knight_tropism_weight` - The weight for knights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// // Only care about getting the queen close to the enemy king.
+    /// let evaluation_weights = EvaluationWeights::default().with_king_tropism_weights(1.0, 0.0, 0.0, 0.0);
+    /// ```
+    pub fn with_king_tropism_weights(mut self, queen_tropism_weight: f64, rook_tropism_weight: f64, bishop_tropism_weight: f64, knight_tropism_weight: f64) -> Self
+    {
+        self.queen_tropism_weight = queen_tropism_weight;
+        self.rook_tropism_weight = rook_tropism_weight;
+        self.bishop_tropism_weight = bishop_tropism_weight;
+        self.knight_tropism_weight = knight_tropism_weight;
+        self
+    }
+
+    /// Sets how pawn endgames are scored.
+    ///
+    /// When only kings and pawns are left, a passed pawn that the enemy king can't catch (by the
+    /// rule of the square) is as good as a new queen, which a shallow search often can't see. The
+    /// side whose unstoppable passer promotes first gets `pawn_race_weight`. On top of that,
+    /// every passed pawn scores `passed_pawn_king_proximity_weight` for each step its own king is
+    /// closer to it than the enemy king.
+    ///
+    /// # Arguments
+    ///
+    /// * `pawn_race_weight` - The bonus for winning the race to promote.
+    /// * `passed_pawn_king_proximity_weight` - The weight for kings supporting or stopping passed
+    ///   pawns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// let evaluation_weights = EvaluationWeights::default().with_pawn_race_weights(9.0, 0.2);
+    /// ```
+    pub fn with_pawn_race_weights(mut self, pawn_race_weight: f64, passed_pawn_king_proximity_weight: f64) -> Self
+    {
+        self.pawn_race_weight = pawn_race_weight;
+        self.passed_pawn_king_proximity_weight = passed_pawn_king_proximity_weight;
+        self
+    }
+
+    /// Sets where pieces like to stand, see [PieceSquareTables].
+    ///
+    /// # Arguments
+    ///
+    /// * `piece_square_tables` - The tables to use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{EvaluationWeights, PieceSquareTables};
+    /// // Pieces don't care where they stand in the endgame.
+    /// let evaluation_weights = EvaluationWeights::default()
+    ///     .with_piece_square_tables(PieceSquareTables::new([[10; 64]; 6], [[0; 64]; 6]));
+    /// ```
+    pub fn with_piece_square_tables(mut self, piece_square_tables: PieceSquareTables) -> Self
+    {
+        self.piece_square_tables = piece_square_tables;
+        self
+    }
+
+    /// Sets how much the piece-square tables count. At 1, a table entry of 100 is worth a pawn.
+    /// Setting this to 0 turns the tables off.
+    ///
+    /// # Arguments
+    ///
+    /// * `piece_square_weight` - How much the piece-square tables count towards the evaluation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// let evaluation_weights = EvaluationWeights::default().with_piece_square_weight(0.5);
+    /// ```
+    pub fn with_piece_square_weight(mut self, piece_square_weight: f64) -> Self
+    {
+        self.piece_square_weight = piece_square_weight;
+        self
+    }
+
+    /// Sets how much king safety counts overall, see [Self::with_king_safety_weights]. Setting
+    /// this to 0 turns king safety off.
+    ///
+    /// # Arguments
+    ///
+    /// * `king_safety_weight` - How much king safety counts towards the evaluation overall.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// let evaluation_weights = EvaluationWeights::default().with_king_safety_weight(1.5);
+    /// ```
+    pub fn with_king_safety_weight(mut self, king_safety_weight: f64) -> Self
+    {
+        self.king_safety_weight = king_safety_weight;
+        self
+    }
+
+    /// Sets how each side is penalized for an exposed king.
+    ///
+    /// Three things are looked at on the king's file and the files next to it:
+    /// - The pawn shield: each file without a pawn of its own right in front of the king costs
+    ///   `pawn_shield_weight`, or half that if the pawn has moved up one more square.
+    /// - Open files: each file without a pawn of its own costs `open_file_weight`, or double if
+    ///   it has no pawns at all, since the enemy's rooks and queens can use it.
+    /// - Attackers: every square around the king that an enemy knight or bishop attacks costs
+    ///   `king_zone_attack_weight` twice, a rook three times and a queen five times.
+    ///
+    /// King safety only matters while there are pieces around to attack the king, so it fades
+    /// out as material comes off the board, the same way the piece-square tables move to the
+    /// endgame.
+    ///
+    /// # Arguments
+    ///
+    /// * `pawn_shield_weight` - The penalty for each missing shield pawn.
+    /// * `open_file_weight` - The penalty for each (half) open file near the king.
+    /// * `king_zone_attack_weight` - The penalty for each attack on the squares around the king.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// // Only worry about pieces attacking the king.
+    /// let evaluation_weights = EvaluationWeights::default().with_king_safety_weights(0.0, 0.0, 0.05);
+    /// ```
+    pub fn with_king_safety_weights(mut self, pawn_shield_weight: f64, open_file_weight: f64, king_zone_attack_weight: f64) -> Self
+    {
+        self.pawn_shield_weight = pawn_shield_weight;
+        self.open_file_weight = open_file_weight;
+        self.king_zone_attack_weight = king_zone_attack_weight;
+        self
+    }
+}
+
 
 /// The evaluated score of a given position.
-#[derive(From, Copy, Clone, Debug)]
+#[derive(From, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Evaluation
 {
     /// This position is over, white has won.
@@ -48,8 +262,14 @@ pub enum Evaluation
     /// The position is guaranteed to be a win for white in X turns with perfect play
     #[from(skip)]
     WhiteCheckmateIn(usize),
+    /// An endgame tablebase says White wins with perfect play, but the search hasn't found the
+    /// mate yet. This is better for White than any score, but worse than a mate the search has
+    /// actually found.
+    WhiteTablebaseWin,
     /// The position is over with a stalemate or draw for both players. 
     Draw,
+    /// An endgame tablebase says Black wins with perfect play. See [Self::WhiteTablebaseWin].
+    BlackTablebaseWin,
     /// The position is guaranteed to be a win for black in X turns with perfect play.
     #[from(skip)]
     BlackCheckmateIn(usize),
@@ -124,6 +344,8 @@ impl Ord for Evaluation
             (Self::Draw, Self::Draw) => Ordering::Equal,
             (Self::BlackCheckmateIn(x), Self::BlackCheckmateIn(y)) => y.cmp(x),
             (Self::BlackWin, Self::BlackWin) => Ordering::Equal,
+            (Self::WhiteTablebaseWin, Self::WhiteTablebaseWin) => Ordering::Equal,
+            (Self::BlackTablebaseWin, Self::BlackTablebaseWin) => Ordering::Equal,
             // Here we DO want a higher score for white.
             (Self::Score(x), Self::Score(y)) => x.partial_cmp(y).expect("Unable to compare floats"),
             // White win is greater than any other evaluation
@@ -133,6 +355,9 @@ impl Ord for Evaluation
             // White checkmate is greater than remaining branches.
             (Self::WhiteCheckmateIn(_), _) => Ordering::Greater,
             (_, Self::WhiteCheckmateIn(_)) => Ordering::Less,
+            // A tablebase win is better than anything but a mate.
+            (Self::WhiteTablebaseWin, _) => Ordering::Greater,
+            (_, Self::WhiteTablebaseWin) => Ordering::Less,
             // Draw can be better than or worse than the evaluation.
             // We treat Draw as a score of 0.
             (Self::Score(x), Self::Draw) => x.partial_cmp(&0_f64).expect("Unable to compare floats"),
@@ -141,6 +366,8 @@ impl Ord for Evaluation
             (_, Self::Draw) => Ordering::Less,
             (Self::Score(_), _) => Ordering::Greater,
             (_, Self::Score(_)) => Ordering::Less,
+            (Self::BlackTablebaseWin, _) => Ordering::Greater,
+            (_, Self::BlackTablebaseWin) => Ordering::Less,
             (Self::BlackCheckmateIn(_), Self::BlackWin) => Ordering::Greater,
             (Self::BlackWin, Self::BlackCheckmateIn(_)) => Ordering::Less,
         }
@@ -156,15 +383,20 @@ impl Board
     {
         match self.game_result()
         {
-            BoardResult::Win(PlayerColor::White) => Evaluation::WhiteWin,
-            BoardResult::Win(PlayerColor::Black) => Evaluation::BlackWin,
+            BoardResult::Win(PlayerColor::White) | BoardResult::LossOnTime(PlayerColor::Black) => Evaluation::WhiteWin,
+            BoardResult::Win(PlayerColor::Black) | BoardResult::LossOnTime(PlayerColor::White) => Evaluation::BlackWin,
             BoardResult::Draw(_) => Evaluation::Draw,
             BoardResult::InProgress => {
 
+                let attack_info = self.attack_info();
                 let material_score = self.evaluate_material_score(evaluation_weights);
-                let center_control_score = self.evaluate_center_control(evaluation_weights);
+                let center_control_score = self.evaluate_center_control(evaluation_weights, &attack_info);
                 let mobility_score = self.evaluate_material_score(evaluation_weights);
-                (material_score + center_control_score + mobility_score).into()
+                let king_tropism_score = self.evaluate_king_tropism(evaluation_weights);
+                let pawn_race_score = self.evaluate_pawn_race(evaluation_weights);
+                let piece_square_score = self.evaluate_piece_squares(evaluation_weights);
+                let king_safety_score = self.evaluate_king_safety(evaluation_weights, &attack_info);
+                (material_score + center_control_score + mobility_score + king_tropism_score + pawn_race_score + piece_square_score + king_safety_score).into()
             }
         }
     }
@@ -173,45 +405,210 @@ impl Board
     fn evaluate_material_score(&self, evaluation_weights: &EvaluationWeights) -> f64
     {
         // Evalute differences in the number of queens
-        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Queen).result().squares().count() as isize;
-        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Queen).result().squares().count() as isize;
+        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Queen).result().count() as isize;
+        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Queen).result().count() as isize;
         let queen_score = (white_piece_count - black_piece_count) as f64 * evaluation_weights.queen_material_weight;
 
         // Evaluate differences in the number of rooks
-        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Rook).result().squares().count() as isize;
-        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Rook).result().squares().count() as isize;
+        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Rook).result().count() as isize;
+        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Rook).result().count() as isize;
         let rook_score = (white_piece_count - black_piece_count) as f64 * evaluation_weights.rook_material_weight;
 
         // Evaluate differences in the number of bishops
-        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Bishop).result().squares().count() as isize;
-        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Bishop).result().squares().count() as isize;
+        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Bishop).result().count() as isize;
+        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Bishop).result().count() as isize;
         let bishop_score = (white_piece_count - black_piece_count) as f64 * evaluation_weights.bishop_material_weight;
 
         // Evaluate differences in the number of knights
-        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Knight).result().squares().count() as isize;
-        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Knight).result().squares().count() as isize;
+        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Knight).result().count() as isize;
+        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Knight).result().count() as isize;
         let knight_score = (white_piece_count - black_piece_count) as f64 * evaluation_weights.knight_material_weight;
 
         // Evaluate differences in the number of pawns
-        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Pawn).result().squares().count() as isize;
-        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Pawn).result().squares().count() as isize;
+        let white_piece_count = self.query().color(PlayerColor::White).piece_type(PieceType::Pawn).result().count() as isize;
+        let black_piece_count = self.query().color(PlayerColor::Black).piece_type(PieceType::Pawn).result().count() as isize;
         let pawn_score = (white_piece_count - black_piece_count) as f64 * evaluation_weights.pawn_material_weight;
 
         return (queen_score + rook_score + bishop_score + knight_score + pawn_score) * evaluation_weights.overall_material_weight;
     }
 
-    fn evaluate_center_control(&self, evaluation_weights: &EvaluationWeights) -> f64
+    fn evaluate_center_control(&self, evaluation_weights: &EvaluationWeights, attack_info: &AttackInfo) -> f64
+    {
+        let center = [Square::new(3, 3), Square::new(3, 4), Square::new(4, 4), Square::new(4, 3)];
+        let control: isize = center.iter()
+            .map(|square| attack_info.attacker_count(*square, PlayerColor::White) as isize - attack_info.attacker_count(*square, PlayerColor::Black) as isize)
+            .sum();
+
+        control as f64 * evaluation_weights.center_control_weight
+    }
+
+    // Rewards pieces for being close to the enemy king, so the engine has some reason to go
+    // after it.
+    fn evaluate_king_tropism(&self, evaluation_weights: &EvaluationWeights) -> f64
+    {
+        let tropism = |color: PlayerColor| -> f64
+        {
+            let Some(enemy_king) = self.query().color(!color).piece_type(PieceType::King).result().squares().next() else { return 0.0 };
+            [
+                (PieceType::Queen, evaluation_weights.queen_tropism_weight),
+                (PieceType::Rook, evaluation_weights.rook_tropism_weight),
+                (PieceType::Bishop, evaluation_weights.bishop_tropism_weight),
+                (PieceType::Knight, evaluation_weights.knight_tropism_weight),
+            ].iter()
+                .map(|(piece_type, weight)|
+                    self.query().color(color).piece_type(*piece_type).result().squares()
+                        .map(|square| (7 - distance(square, enemy_king)) as f64 * weight)
+                        .sum::<f64>()
+                )
+                .sum()
+        };
+        (tropism(PlayerColor::White) - tropism(PlayerColor::Black)) * evaluation_weights.king_tropism_weight
+    }
+
+    // In pawn endgames, works out whether either side has a passed pawn the enemy king can't
+    // catch, and how well placed the kings are to support or stop the passed pawns.
+    fn evaluate_pawn_race(&self, evaluation_weights: &EvaluationWeights) -> f64
+    {
+        let pieces = self.query().result();
+        let kings_and_pawns = self.query().piece_type(PieceType::King).result() | self.query().piece_type(PieceType::Pawn).result();
+        if pieces != kings_and_pawns
+        {
+            return 0.0;
+        }
+        let (Some(white_king), Some(black_king)) = (
+            self.query().color(PlayerColor::White).piece_type(PieceType::King).result().squares().next(),
+            self.query().color(PlayerColor::Black).piece_type(PieceType::King).result().squares().next(),
+        ) else { return 0.0 };
+
+        let mut proximity = 0.0;
+        // How many plies until each side's fastest unstoppable passer promotes.
+        let mut fastest_promotion = [None, None];
+        for (index, color) in [PlayerColor::White, PlayerColor::Black].into_iter().enumerate()
+        {
+            let (own_king, enemy_king, sign) = match color
+            {
+                PlayerColor::White => (white_king, black_king, 1.0),
+                PlayerColor::Black => (black_king, white_king, -1.0),
+            };
+            for pawn in self.query().color(color).piece_type(PieceType::Pawn).result().squares()
+            {
+                if !self.is_passed_pawn(color, pawn)
+                {
+                    continue;
+                }
+                proximity += sign * (distance(enemy_king, pawn) as f64 - distance(own_king, pawn) as f64);
+
+                let promotion_rank = color.promotion_rank();
+                let promotion_square = Square::new(promotion_rank, pawn.file);
+                let mut pawn_moves = pawn.rank.abs_diff(promotion_rank);
+                // A pawn still on its starting rank can push two squares at once.
+                if color.relative_rank(pawn) == 1
+                {
+                    pawn_moves -= 1;
+                }
+                // Our own king in front of the pawn has to get out of the way first.
+                if own_king.file == pawn.file && own_king.rank.abs_diff(promotion_rank) < pawn.rank.abs_diff(promotion_rank)
+                {
+                    pawn_moves += 1;
+                }
+                // The rule of the square: the enemy king can catch the pawn if it can reach the
+                // promotion square in time. It gets a head start if it's the enemy's move.
+                let enemy_to_move = (self.active_color != color) as u8;
+                if distance(enemy_king, promotion_square) > pawn_moves + enemy_to_move
+                {
+                    let plies = (2 * pawn_moves + enemy_to_move).saturating_sub(1);
+                    fastest_promotion[index] = Some(fastest_promotion[index].map_or(plies, |fastest: u8| fastest.min(plies)));
+                }
+            }
+        }
+
+        let race = match fastest_promotion
+        {
+            [Some(_), None] => 1.0,
+            [None, Some(_)] => -1.0,
+            // Both sides queen, whoever gets there first is usually winning.
+            [Some(white), Some(black)] if white < black => 1.0,
+            [Some(white), Some(black)] if black < white => -1.0,
+            _ => 0.0,
+        };
+        race * evaluation_weights.pawn_race_weight + proximity * evaluation_weights.passed_pawn_king_proximity_weight
+    }
+
+    // Adds up the piece-square table values of every piece, blended between the middlegame and
+    // endgame tables by how much material is left.
+    fn evaluate_piece_squares(&self, evaluation_weights: &EvaluationWeights) -> f64
+    {
+        let middlegame_phase = self.middlegame_phase();
+        let score: f64 = self.query().result().squares()
+            .filter_map(|square| self.piece_at(&square).map(|piece| (square, *piece)))
+            .map(|(square, piece)|
+            {
+                let value = evaluation_weights.piece_square_tables.value(piece, square, middlegame_phase);
+                match piece.color()
+                {
+                    PlayerColor::White => value,
+                    PlayerColor::Black => -value,
+                }
+            })
+            .sum();
+        score / 100.0 * evaluation_weights.piece_square_weight
+    }
+
+    // Penalizes each side for missing shield pawns, open files and enemy attacks around its
+    // king, fading out as material comes off the board.
+    fn evaluate_king_safety(&self, evaluation_weights: &EvaluationWeights, attack_info: &AttackInfo) -> f64
+    {
+        let danger = |color: PlayerColor| -> f64
+        {
+            let Some(king) = attack_info.king_square(color) else { return 0.0 };
+            let own_pawns = self.pieces_of_type(PieceType::Pawn) & self.pieces_of_color(color);
+            let all_pawns = self.pieces_of_type(PieceType::Pawn);
+            let forward = |steps: i8| king.offset(steps * color.pawn_direction(), 0).map(|square| square.rank);
+            let has_own_pawn = |rank: Option<u8>, file: u8| rank.is_some_and(|rank| own_pawns.is_bit_set(Square::new(rank, file).index()));
+
+            let mut missing_shield = 0.0;
+            let mut open_files = 0.0;
+            for file in king.file.saturating_sub(1)..=(king.file + 1).min(7)
+            {
+                if !has_own_pawn(forward(1), file)
+                {
+                    missing_shield += if has_own_pawn(forward(2), file) { 0.5 } else { 1.0 };
+                }
+                if (own_pawns & Bitboard::file_mask(file)).is_empty()
+                {
+                    open_files += if (all_pawns & Bitboard::file_mask(file)).is_empty() { 2.0 } else { 1.0 };
+                }
+            }
+
+            let king_zone = attack_info.attacks(color, PieceType::King) | Bitboard::from(king);
+            let attacks: f64 = [(PieceType::Knight, 2.0), (PieceType::Bishop, 2.0), (PieceType::Rook, 3.0), (PieceType::Queen, 5.0)].iter()
+                .map(|(piece_type, weight)| (attack_info.attacks(!color, *piece_type) & king_zone).count() as f64 * weight)
+                .sum();
+
+            missing_shield * evaluation_weights.pawn_shield_weight
+                + open_files * evaluation_weights.open_file_weight
+                + attacks * evaluation_weights.king_zone_attack_weight
+        };
+        (danger(PlayerColor::Black) - danger(PlayerColor::White)) * self.middlegame_phase() * evaluation_weights.king_safety_weight
+    }
+
+    // How far from the endgame the position is, from 1 with every piece on the board to 0 with
+    // only kings and pawns. Extra pieces from promotions don't push it past 1.
+    fn middlegame_phase(&self) -> f64
     {
-        let square_1_count = self.all_squares_that_can_capture_square_theoretical(PlayerColor::White, Square::new(3, 3)).len() as isize -
-                             self.all_squares_that_can_capture_square_theoretical(PlayerColor::Black, Square::new(3, 3)).len() as isize;
-        let square_2_count = self.all_squares_that_can_capture_square_theoretical(PlayerColor::White, Square::new(3, 4)).len() as isize -
-                             self.all_squares_that_can_capture_square_theoretical(PlayerColor::Black, Square::new(3, 4)).len() as isize;
-        let square_3_count = self.all_squares_that_can_capture_square_theoretical(PlayerColor::White, Square::new(4, 4)).len() as isize -
-                             self.all_squares_that_can_capture_square_theoretical(PlayerColor::Black, Square::new(4, 4)).len() as isize;
-        let square_4_count = self.all_squares_that_can_capture_square_theoretical(PlayerColor::White, Square::new(4, 3)).len() as isize -
-                             self.all_squares_that_can_capture_square_theoretical(PlayerColor::Black, Square::new(4, 3)).len() as isize;
+        let phase: u32 = PHASE_WEIGHTS.iter()
+            .map(|(piece_type, weight)| self.query().piece_type(*piece_type).result().count() * weight)
+            .sum();
+        phase.min(STARTING_PHASE) as f64 / STARTING_PHASE as f64
+    }
 
-        return (square_1_count + square_2_count + square_3_count + square_4_count) as f64 * evaluation_weights.center_control_weight;
+    // A passed pawn has no enemy pawns in front of it on its own or either neighbouring file.
+    fn is_passed_pawn(&self, color: PlayerColor, pawn: Square) -> bool
+    {
+        !self.query().color(!color).piece_type(PieceType::Pawn).result().squares().any(|enemy_pawn|
+            enemy_pawn.file.abs_diff(pawn.file) <= 1
+            && color.relative_rank(enemy_pawn) > color.relative_rank(pawn)
+        )
     }
 
     fn evaluate_mobility(&self, evaluation_weights: &EvaluationWeights) -> f64
@@ -222,6 +619,12 @@ impl Board
     }
 }
 
+/// The number of king moves it takes to get from one square to the other.
+fn distance(from: Square, to: Square) -> u8
+{
+    from.rank.abs_diff(to.rank).max(from.file.abs_diff(to.file))
+}
+
 #[cfg(test)]
 mod tests
 {
@@ -278,6 +681,59 @@ mod tests
         assert!(Evaluation::Score(-1.0) < Evaluation::Draw);
     }
 
+    #[test]
+    fn piece_square_tables_reward_development_and_taper_to_the_endgame()
+    {
+        let board = |fen: &str| Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+        let evaluation_weights = EvaluationWeights::default();
+        // Everything is mirrored, so the starting position is even.
+        let starting_board = Board::new_default_starting_board();
+        assert_eq!(starting_board.middlegame_phase(), 1.0);
+        assert_eq!(starting_board.evaluate_piece_squares(&evaluation_weights), 0.0);
+        // Developing a knight is good for White.
+        let developed = board("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1");
+        assert!(developed.evaluate_piece_squares(&evaluation_weights) > 0.0);
+        // With only kings and pawns left, the king belongs in the center and pawns should run.
+        let endgame = board("8/1P6/8/8/3K4/8/8/k7 w - - 0 1");
+        assert_eq!(endgame.middlegame_phase(), 0.0);
+        // The pawn on the 7th (80), the king in the center (40) and the black king in the corner (50).
+        assert!((endgame.evaluate_piece_squares(&evaluation_weights) - 1.7).abs() < 1e-9);
+        let turned_off = EvaluationWeights::default().with_piece_square_weight(0.0);
+        assert_eq!(developed.evaluate_piece_squares(&turned_off), 0.0);
+    }
+
+    #[test]
+    fn exposed_kings_are_penalized()
+    {
+        let king_safety = |fen: &str|
+        {
+            let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            board.evaluate_king_safety(&EvaluationWeights::default(), &board.attack_info())
+        };
+        // Both kings are castled behind three pawns.
+        assert_eq!(king_safety("r2q2k1/5ppp/8/8/8/8/5PPP/R2Q2K1 w - - 0 1"), 0.0);
+        // Pushing a shield pawn one square only costs a little...
+        let pushed = king_safety("r2q2k1/5ppp/8/8/8/6P1/5P1P/R2Q2K1 w - - 0 1");
+        assert!(pushed < 0.0);
+        // ...but losing it opens a file as well.
+        let missing = king_safety("r2q2k1/5ppp/8/8/8/8/5P1P/R2Q2K1 w - - 0 1");
+        assert!(missing < pushed);
+        // The black queen bearing down on the king makes it worse again.
+        assert!(king_safety("r5k1/5ppp/8/8/8/8/5PqP/R2Q2K1 w - - 0 1") < missing);
+        // With only pawns left there's nothing to attack the king with.
+        assert_eq!(king_safety("6k1/8/8/8/8/8/8/6K1 w - - 0 1"), 0.0);
+    }
+
+    #[test]
+    fn tablebase_wins_rank_between_mates_and_scores()
+    {
+        assert!(Evaluation::WhiteCheckmateIn(30) > Evaluation::WhiteTablebaseWin);
+        assert!(Evaluation::WhiteTablebaseWin > Evaluation::Score(50.0));
+        assert!(Evaluation::Score(-50.0) > Evaluation::BlackTablebaseWin);
+        assert!(Evaluation::BlackTablebaseWin > Evaluation::BlackCheckmateIn(30));
+        assert!(Evaluation::BlackTablebaseWin == Evaluation::BlackTablebaseWin);
+    }
+
     #[test]
     fn evaluate_center_control()
     {
@@ -288,6 +744,39 @@ mod tests
         };
 
         // It should be 1 white squares and 2 black squares
-        assert_eq!(-1.0, board.evaluate_center_control(&evaluation_weights));
+        assert_eq!(-1.0, board.evaluate_center_control(&evaluation_weights, &board.attack_info()));
+    }
+
+    #[test]
+    fn evaluate_king_tropism()
+    {
+        // White's knight is next to Black's king, Black's knight is as far away as it gets.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("n6k/8/6N1/8/8/8/8/K7 w - - 0 1").unwrap());
+        let evaluation_weights = EvaluationWeights::default()
+            .with_king_tropism_weight(1.0)
+            .with_king_tropism_weights(0.0, 0.0, 0.0, 1.0);
+
+        // 7 - 2 for White, 7 - 7 for Black.
+        assert_eq!(5.0, board.evaluate_king_tropism(&evaluation_weights));
+    }
+
+    #[test]
+    fn pawn_outside_the_square_wins_the_race()
+    {
+        let evaluation_weights = EvaluationWeights::default().with_pawn_race_weights(1.0, 0.0);
+        // The black king is one step too far from a8 to catch the pawn.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/8/8/P7/4k3/8/8/7K w - - 0 1").unwrap());
+        assert_eq!(1.0, board.evaluate_pawn_race(&evaluation_weights));
+        // With Black to move, the king steps into the square in time.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/8/8/P7/4k3/8/8/7K b - - 0 1").unwrap());
+        assert_eq!(0.0, board.evaluate_pawn_race(&evaluation_weights));
+    }
+
+    #[test]
+    fn pawn_race_only_applies_to_pawn_endgames()
+    {
+        let evaluation_weights = EvaluationWeights::default().with_pawn_race_weights(1.0, 1.0);
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/8/8/P7/5k2/8/8/6NK w - - 0 1").unwrap());
+        assert_eq!(0.0, board.evaluate_pawn_race(&evaluation_weights));
     }
 }
@@ -0,0 +1,193 @@
+//! The [PieceSquareTables] the evaluation uses to score where pieces stand.
+
+use crate::board::{Piece, PieceType, PlayerColor, Square};
+
+/// A table per piece type, in centipawns, ordered pawn, knight, bishop, rook, queen, king.
+type Tables = [[i16; 64]; 6];
+
+/// Bonuses (or penalties) for each piece type standing on each square, with one set of tables for
+/// the middlegame and one for the endgame.
+///
+/// Tables are written from White's point of view with the 8th rank first, the way a board is
+/// usually printed, and mirrored for Black. Values are in centipawns. The evaluation blends the
+/// middlegame and endgame value of each piece by how much material is left, so the king goes from
+/// hiding behind its pawns to marching up the board as pieces come off.
+///
+/// The default tables are the well known "simplified evaluation function" tables, which make the
+/// engine develop its knights and bishops towards the center, castle, put rooks on the 7th rank
+/// and, in the endgame, push its pawns and bring its king to the center.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Piece, PieceSquareTables, PieceType, PlayerColor, Square};
+/// let tables = PieceSquareTables::default();
+/// let knight = Piece::new(PlayerColor::White, PieceType::Knight);
+/// let e4 = Square::from_str("e4").unwrap();
+/// let a1 = Square::from_str("a1").unwrap();
+/// assert!(tables.value(knight, e4, 1.0) > tables.value(knight, a1, 1.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceSquareTables
+{
+    middlegame: Tables,
+    endgame: Tables,
+}
+
+impl PieceSquareTables
+{
+    /// Creates new [PieceSquareTables].
+    ///
+    /// # Arguments
+    ///
+    /// * `middlegame` - The tables used while there's plenty of material on the board, ordered
+    ///   pawn, knight, bishop, rook, queen, king. Each table is in centipawns from White's point
+    ///   of view, starting from a8 and going across then down to h1.
+    /// * `endgame` - The tables used once most of the pieces are gone, laid out the same way.
+    pub fn new(middlegame: Tables, endgame: Tables) -> Self
+    {
+        Self
+        {
+            middlegame,
+            endgame,
+        }
+    }
+
+    /// Returns how much the square is worth for the piece, in centipawns from the piece's own
+    /// point of view.
+    ///
+    /// # Arguments
+    ///
+    /// * `piece` - The piece.
+    /// * `square` - The square the piece stands on.
+    /// * `middlegame_phase` - How far from the endgame the position is, from 1 (all the pieces
+    ///   are still on the board) to 0 (only kings and pawns are left).
+    pub fn value(&self, piece: Piece, square: Square, middlegame_phase: f64) -> f64
+    {
+        let index = match piece.color()
+        {
+            // The tables start at a8, so White's squares are upside down.
+            PlayerColor::White => (square.index() ^ 56) as usize,
+            PlayerColor::Black => square.index() as usize,
+        };
+        let table = table_index(piece.piece_type());
+        let middlegame = self.middlegame[table][index] as f64;
+        let endgame = self.endgame[table][index] as f64;
+        middlegame * middlegame_phase + endgame * (1.0 - middlegame_phase)
+    }
+}
+
+impl Default for PieceSquareTables
+{
+    fn default() -> Self {
+        Self::new(
+            [PAWN_MIDDLEGAME, KNIGHT, BISHOP, ROOK, QUEEN, KING_MIDDLEGAME],
+            [PAWN_ENDGAME, KNIGHT, BISHOP, ROOK, QUEEN, KING_ENDGAME],
+        )
+    }
+}
+
+fn table_index(piece_type: PieceType) -> usize
+{
+    match piece_type
+    {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+const PAWN_MIDDLEGAME: [i16; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+// In the endgame every step closer to promoting counts, wherever the pawn is.
+const PAWN_ENDGAME: [i16; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    15,  15,  15,  15,  15,  15,  15,  15,
+     5,   5,   5,   5,   5,   5,   5,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const KNIGHT: [i16; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+const BISHOP: [i16; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+const ROOK: [i16; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+const QUEEN: [i16; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+// The king should stay tucked away behind its pawns while there are pieces around to attack it...
+const KING_MIDDLEGAME: [i16; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+// ...and become a fighting piece in the center once they're gone.
+const KING_ENDGAME: [i16; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
@@ -39,21 +39,10 @@
 //! another piece of the same color. The only exception to that COULD be considered to be checking,
 //! but checking is weird and is handled in its own edge case anyways.
 
-use crate::{bitboard::Bitboard, board::{PlayerColor, Square}};
+use crate::{bitboard::Bitboard, board::{bishop_attack_mask, pawn_attack_mask, pawn_double_push_mask, pawn_push_mask, rook_attack_mask, PlayerColor, Square}};
 
 use super::Board;
 
-// All possible directions, orthogonal and directional, specified at compile time.
-// This is used to calculate moves for queens, bishops, and rooks.
-const DIRECTION_UP: Direction = Direction(4);
-const DIRECTION_UP_RIGHT: Direction = Direction(7);
-const DIRECTION_RIGHT: Direction = Direction(6);
-const DIRECTION_DOWN_RIGHT: Direction = Direction(2);
-const DIRECTION_DOWN: Direction = Direction(1);
-const DIRECTION_DOWN_LEFT: Direction = Direction(0);
-const DIRECTION_LEFT: Direction = Direction(3);
-const DIRECTION_UP_LEFT: Direction = Direction(5);
-
 impl Board {
     /// Generates a bitboard where all valid squares that a knight on a given square can move to are set to 1.
     /// The square that the knight is currently on is not included in this set.
@@ -211,10 +200,7 @@ impl Board {
     /// ```
     pub fn bishop_moves(&self, active_color: PlayerColor, from: Square) -> Bitboard
     {
-        self.moves_in_direction(active_color, from, DIRECTION_UP_LEFT) |
-        self.moves_in_direction(active_color, from, DIRECTION_UP_RIGHT) |
-        self.moves_in_direction(active_color, from, DIRECTION_DOWN_RIGHT) |
-        self.moves_in_direction(active_color, from, DIRECTION_DOWN_LEFT)
+        bishop_attack_mask(from, self.query().result()) & !self.query().color(active_color).result()
     }
 
     /// Generates a bitmask of all valid squares that a rook can move to, taking into account
@@ -237,10 +223,7 @@ impl Board {
     /// ```
     pub fn rook_moves(&self, active_color: PlayerColor, from: Square) -> Bitboard
     {
-        self.moves_in_direction(active_color, from, DIRECTION_UP) |
-        self.moves_in_direction(active_color, from, DIRECTION_RIGHT) | 
-        self.moves_in_direction(active_color, from, DIRECTION_DOWN) | 
-        self.moves_in_direction(active_color, from, DIRECTION_LEFT)
+        rook_attack_mask(from, self.query().result()) & !self.query().color(active_color).result()
     }
 
     /// Returns all valid squares that a pawn can move to. This is normally just the square
@@ -269,37 +252,23 @@ impl Board {
     /// ```
     pub fn pawn_moves(&self, active_color: PlayerColor, from: Square) -> Bitboard
     {
-        let result = match (active_color, from.rank)
-        {
-            // Pawn on the last or first rank can't actually move at all. But I actually don't know
-            // if that's even possible since those pawns should promote???? 
-            (PlayerColor::White, 7) => Bitboard::new(0),
-            (PlayerColor::Black, 0) => Bitboard::new(0),
-            // Move to the square one rank in front of the current square
-            // We consider occupancy here bc if we can't move one space we also can't move two
-            // spaces.
-            (PlayerColor::White, _) => Bitboard::from(from) << 8,
-            (PlayerColor::Black, _) => Bitboard::from(from) >> 8
-        };
-        // If the square we want to move to is occupied, we can't move at all
-        let result = result & !self.query().result();
-        // Now we can try to enable moving two ranks at a time if this is the first move
-        let result = match (active_color, from.rank)
+        // Pawns can't capture in front of themselves, so ANY piece, black or white, blocks them.
+        let empty = !self.query().result();
+        let single_push = pawn_push_mask(active_color, from) & empty;
+        // If we can't move one space we also can't move two spaces.
+        match single_push.is_empty()
         {
-            (PlayerColor::White, 1) => result | (result << 8),
-            (PlayerColor::Black, 6) => result | (result >> 8),
-            _ => result,
-        };
-        // We exclude ANY pieces, black or white, 
-        // that are in our way, bc pawns can't capture in front of themselves.
-        result & !self.query().result()
+            true => single_push,
+            false => single_push | pawn_double_push_mask(active_color, from) & empty,
+        }
     }
 
 
     /// Returns all the squares that a given pawn can attack. This is the two squares immediately
     /// in front of and to either side of the pawn. This function *does* take into account where
     /// enemy pieces are, so will return a [Bitboard] with all bits set to 0 if the given square
-    /// does not attack any enemy pieces.
+    /// does not attack any enemy pieces. If `active_color` is the side to move, the en passant
+    /// target square counts as something to capture too.
     ///
     /// # Arguments
     ///
@@ -320,12 +289,13 @@ impl Board {
     /// ```
     pub fn pawn_attacks(&self, active_color: PlayerColor, from: Square) -> Bitboard
     {
-        let up_right = Bitboard::from(from) << 9;
-        let up_left = Bitboard::from(from) << 7;
-        let down_right = Bitboard::from(from) >> 7;
-        let down_left = Bitboard::from(from) >> 9;
-
-        self.query().color(!active_color).result() & self.pawn_theoretical_attacks(active_color, from)
+        // The en passant square is empty, but the side to move can still capture on it.
+        let en_passant = match self.en_passant_target_square
+        {
+            Some(square) if active_color == self.active_color => Bitboard::from(square),
+            _ => Bitboard::default(),
+        };
+        (self.query().color(!active_color).result() | en_passant) & self.pawn_theoretical_attacks(active_color, from)
     }
 
     /// Like [Self::pawn_attacks] but does not mask out unoccupied squares.
@@ -333,219 +303,7 @@ impl Board {
     /// squares, even if there's no piece there.
     pub fn pawn_theoretical_attacks(&self, active_color: PlayerColor, from: Square) -> Bitboard
     {
-        let up_right = Bitboard::from(from) << 9;
-        let up_left = Bitboard::from(from) << 7;
-        let down_right = Bitboard::from(from) >> 7;
-        let down_left = Bitboard::from(from) >> 9;
-
-        match active_color
-        {
-            PlayerColor::White => up_right & !Bitboard::file_mask(0) |
-                                  up_left & !Bitboard::file_mask(7),
-            PlayerColor::Black => down_right & !Bitboard::file_mask(0) |
-                                  down_left & !Bitboard::file_mask(7)
-        }
-    }
-
-    /// Calculates available moves in a certain direction from a square,
-    /// while taking into account occupancy (i.e squares that are blocked by other pieces)
-    ///
-    /// "Occupancy" considers any squares occupied by a same-colored piece (or past one) occupied.
-    /// Any squares occupied by one opposite-color piece are considered movable (since we can
-    /// capture pieces), but any squares beyond that are occupied.
-    ///
-    /// This is a private helper method but forms all of the core logic for [Self::queen_moves], 
-    /// [Self::bishop_moves], and [Self::rook_moves], all of which move in the exact same way.
-    /// # Arguments
-    ///
-    /// * `active_color` - What color is currently moving. We need this to determine which occupied
-    /// squares are still fair game to move into.
-    /// * `from` - The starting squares of the piece.
-    /// * `direction` - The direction to check in.
-    fn moves_in_direction(&self, active_color: PlayerColor, from: Square, direction: Direction) -> Bitboard
-    {
-        let mut bitboard = Bitboard::new(0);
-        // We set this to true after we hit another piece or the edge of the board.
-        let mut checked_square = increment_square_in_direction(&from, direction);
-        loop
-        {
-            // We hit an edge, break out.
-            if checked_square.is_none()
-            {
-                break;
-            }
-            // If there is a same colored piece on the target square, we break out.
-            if self.query().color(active_color).piece_at_unchecked(checked_square.unwrap()).result()
-            {
-                break;
-            }
-            // if there is an opposite colored piece on the target square, we include that square
-            // in the final bitboard, but we still break out bc we can't move past it.
-            if self.query().color(!active_color).piece_at_unchecked(checked_square.unwrap()).result()
-            {
-                // Enable the bit for the current square.
-                bitboard |= Bitboard::from(checked_square.unwrap());
-                break;
-            }
-            // Finally if NONE of the following was true, we can add the current square and
-            // continue onto the next square in the given direction
-            bitboard |= Bitboard::from(checked_square.unwrap());
-            checked_square = increment_square_in_direction(&checked_square.unwrap(), direction);
-        }
-        bitboard
-    }
-}
-
-// Returns a new square with rank and file incremented or decremented depending on the Direction
-// provided.
-fn increment_square_in_direction(square: &Square, direction: Direction) -> Option<Square>
-{
-    let new_rank: i8 = square.rank as i8 + direction.vertical_component();
-    let new_file: i8 = square.file as i8 + direction.horizontal_component();
-    if new_rank < 0 || new_rank >= 8
-    {
-        None
-    }
-    else if new_file < 0 || new_file >= 8
-    {
-        None
-    }
-    else
-    {
-        Some(Square {
-            rank: new_rank as u8,
-            file: new_file as u8,
-        })
-    }
-}
-
-/// Represents one of 8 directions that rooks/queens/bishops can move in. Used for
-/// occupancy/movement checks. 
-///
-/// We use a u8 internally bc Rust isn't actually smart enough to know that there are only 8
-/// possible directions when using a tuple, so we map those 8 possible directions to a u8.
-#[derive(Copy, Clone)]
-struct Direction(u8);
-
-impl Direction
-{
-    /// Creates a new direction from the two components/basis of the direction space.
-    ///
-    /// Note that the magnitude of the direction vector cannot be 0, so `Direction::new(0, 0)` is
-    /// invalid, as is a direction component where `abs(component) > 1`.
-    ///
-    /// # Arguments
-    ///
-    /// * `vertical_direction` - The vertical component. Can be -1, 0 (unless horizontal_direction is also 0), or 1 
-    /// * `horizontal_direction` - The horizontal component. Can be -1, 0 (unless vertical_direction is also 0), or 1.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// let direction_up = Direction::new(1, 0);
-    /// let direction_down_left = Direction::new(-1, -1);
-    /// ```
-    pub const fn new(vertical_direction: i8, horizontal_direction: i8) -> Option<Self>
-    {
-        match (vertical_direction, horizontal_direction)
-        {
-            (-1, -1) => Some(Self(0)),
-            (-1, 0) => Some(Self(1)),
-            (-1, 1) => Some(Self(2)),
-            (0, -1) => Some(Self(3)),
-            // We skip the direction (0, 0).
-            (0, 1) => Some(Self(4)),
-            (1, -1) => Some(Self(5)),
-            (1, 0) => Some(Self(6)),
-            (1, 1) => Some(Self(7)),
-            _ => None
-        }
-    }
-
-    /// Same as [Self::new] but panics if either of `vertical_direction`
-    /// or `horizontal_direction` are outside of the allowed values.
-    ///
-    /// # Arguments
-    ///
-    /// * `vertical_direction` - The vertical component. Can be -1, 1, or 0 (unless
-    /// `horizontal_direction` is also 0).
-    /// * `horizontal_direction` - The horizontal component. Can be -1, 1, or 0 (unless 
-    /// `vertical_direction` is also 0).
-    pub const fn new_unchecked(vertical_direction: i8, horizontal_direction: i8) -> Self
-    {
-        match Self::new(vertical_direction, horizontal_direction)
-        {
-            Some(x) => x,
-            None => panic!("Failed to create new Direction!"),
-        }
-    }
-
-    /// Returns the magnitude of the vertical component.
-    ///
-    /// This can be either -1, 0, or 1.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// let direction = Direction::new(1, 0);
-    /// assert_eq!(direction.vertical_component(), 1);
-    /// let direction = Direction::new(0, 1);
-    /// assert_eq!(direction.vertical_component(), 0);
-    /// let direction = Direction::new(-1, -1);
-    /// assert_eq!(direction.vertical_component(), -1);
-    /// ```
-    pub const fn vertical_component(&self) -> i8
-    {
-        match self.0
-        {
-            0|1|2 => -1,
-            3|4 => 0,
-            5|6|7 => 1,
-            _ => unreachable!(),
-        }
-    }
-
-    /// Returns the magnitude of the horizontal component.
-    ///
-    /// This can be either -1, 0, or 1.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// let direction = Direction::new(1, -1);
-    /// assert_eq!(direction.horizontal_component(), -1);
-    /// let direction = Direction::new(-1, 0);
-    /// assert_eq!(direction.horizontal_component(), 0);
-    /// let direction = Direction::new(0, 1);
-    /// assert_eq!(direction.horizontal_component(), 1);
-    /// ```
-    pub const fn horizontal_component(&self) -> i8
-    {
-        match self.0
-        {
-            0|3|5 => -1,
-            1|6   => 0,
-            2|4|7 => 1,
-            _ => unreachable!(),
-        }
-    }
-
-    /// Returns the internal state represented by the Direction as a tuple,
-    /// where the first element is the vertical component and the second is the horizontal
-    /// component.
-    ///
-    /// Note that this is basically flipped from standard notation but matches better with the
-    /// representation used by [Square] and such.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// let direction = Direction::new(1, 0);
-    /// assert_eq!(direction.as_tuple(), (1,0));
-    /// ```
-    pub const fn as_tuple(&self) -> (i8, i8)
-    {
-        (self.vertical_component(), self.horizontal_component())
+        pawn_attack_mask(active_color, from)
     }
 }
 
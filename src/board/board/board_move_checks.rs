@@ -1,17 +1,94 @@
 //! This is a helper module for [board](super) that handles checking whether a given [Move]
 //! is legal or not.
 
-use crate::board::{r#move::CastlingDirection, Move, PlayerColor, Square};
+use crate::{bitboard::Bitboard, board::{r#move::CastlingDirection, error::MoveError, Move, PieceType, PlayerColor, Square}};
 
 use super::Board;
 
 impl Board
 {
+    /// Checks whether an already built [Move] can legally be played by the active player right
+    /// now, without having to go through a [MoveCommand](crate::parse::MoveCommand). This is
+    /// useful for moves that come from somewhere we don't trust, like a remote peer or a GUI.
+    ///
+    /// Unlike [Self::attempt_move], this also checks that the piece can actually get to its target
+    /// square, so a rook can't jump over pieces or move diagonally.
+    ///
+    /// # Arguments
+    ///
+    /// * `r#move` - The move to check.
+    ///
+    /// # Errors
+    ///
+    /// - [MoveError::MissingPiece] if the active player doesn't have a piece on the starting
+    ///   square (or, when castling, a king or rook on its starting square).
+    /// - [MoveError::NoPossibleMove] if the piece can't move to the target square at all.
+    /// - [MoveError::IllegalMove] if the move's capture flag doesn't match the board, it would
+    ///   leave the king in check, or it's a castle that isn't allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, Move, MoveData, MoveError, Square};
+    /// let board = Board::new_default_starting_board();
+    /// // 1. e4 is fine.
+    /// assert!(board.is_legal(&Move::NormalMove(MoveData::new(Square::new(1, 4), Square::new(3, 4), false))).is_ok());
+    /// // The rook on a1 can't jump over its own pawn.
+    /// let error = board.is_legal(&Move::NormalMove(MoveData::new(Square::new(0, 0), Square::new(3, 0), false))).unwrap_err();
+    /// assert!(matches!(error, MoveError::NoPossibleMove));
+    /// ```
+    pub fn is_legal(&self, r#move: &Move) -> Result<(), MoveError>
+    {
+        match r#move
+        {
+            Move::NormalMove(move_data) =>
+            {
+                let starting_square = move_data.starting_square();
+                if !self.piece_at(&starting_square).is_some_and(|piece| piece.color() == self.active_color)
+                {
+                    return Err(MoveError::MissingPiece(starting_square));
+                }
+                let possible_moves = self.generate_possible_moves_for_piece(self.active_color, starting_square).unwrap_or_default();
+                if (possible_moves & Bitboard::from(move_data.target_square())).is_empty()
+                {
+                    return Err(MoveError::NoPossibleMove);
+                }
+                let is_en_passant = self.piece_at(&starting_square).is_some_and(|piece| piece.piece_type() == PieceType::Pawn)
+                    && Some(move_data.target_square()) == self.en_passant_target_square;
+                if move_data.capture() != (self.piece_at(&move_data.target_square()).is_some() || is_en_passant)
+                {
+                    return Err(MoveError::IllegalMove);
+                }
+            },
+            Move::Castle(direction) =>
+            {
+                if self.has_castled_already(self.active_color, *direction)
+                {
+                    return Err(MoveError::IllegalMove);
+                }
+                let rank = self.active_color.home_rank();
+                let between_files = match direction
+                {
+                    CastlingDirection::Kingside => 5..7,
+                    CastlingDirection::Queenside => 1..4,
+                };
+                if between_files.into_iter().any(|file| self.piece_at(&Square::new(rank, file)).is_some())
+                {
+                    return Err(MoveError::IllegalMove);
+                }
+            },
+        }
+        // This catches castling without a king or rook, and any move that leaves the king in
+        // check.
+        self.attempt_move(r#move).map(|_| ())
+    }
+
     /// Checks if a given [Move] would leave the current player's king in check after it.
     /// 
     /// This covers both leaving a king in check (bad) and putting a king into check (bad).
     ///
     /// Returns true if the move is illegal/ends up putting the king in check, false otherwise.
+    /// Moves that can't be made on this board at all (i.e a piece is missing) also return true.
     ///
     /// # Arguments
     ///
@@ -29,7 +106,9 @@ impl Board
     {
         // Attempt to make the move on the board and see if the king would be in check.
         // If so, returns true. Otherwise returns false.
-        let theoretical_next_board = self.make_move(r#move);
+        // A move that can't be made on this board at all is never legal, so we treat it the same
+        // as one that leaves the king in check.
+        let Ok(theoretical_next_board) = self.make_move(r#move) else { return true };
         // The active color here is the player on the CURRENT board, not the next board.
         // If white moves, we check to see if their king is still in check on the next board.
         theoretical_next_board.is_king_in_check(self.active_color)
@@ -48,11 +127,7 @@ impl Board
     /// ```
     pub fn kingside_castle_moves_through_check(&self, moving_color: PlayerColor) -> bool
     {
-        let rank = match moving_color 
-        {
-            PlayerColor::White => 0,
-            PlayerColor::Black => 7,
-        };
+        let rank = moving_color.home_rank();
         let castling_squares = vec![Square::new(rank, 4), Square::new(rank, 5), Square::new(rank, 6)];
         self.check_squares_for_attack(&castling_squares)
     }
@@ -70,11 +145,7 @@ impl Board
     /// ```
     pub fn queenside_castle_moves_through_check(&self, moving_color: PlayerColor) -> bool
     {
-        let rank = match moving_color
-        {
-            PlayerColor::White => 0,
-            PlayerColor::Black => 7,
-        };
+        let rank = moving_color.home_rank();
         let castling_squares = vec![Square::new(rank, 4), Square::new(rank, 3), Square::new(rank, 2)];
         self.check_squares_for_attack(&castling_squares)
     }
@@ -104,14 +175,50 @@ impl Board
 
     fn check_squares_for_attack(&self, check_squares: &[Square]) -> bool
     {
-        for square in check_squares
-        {
-            // We check the OPPOSITE color from the side currently moving 
-            if !self.all_squares_that_can_capture_square(!self.active_color, *square).is_empty()
-            {
-                return true;
-            }
-        }
-        return false;
+        // We check the OPPOSITE color from the side currently moving. The squares the king passes
+        // over are empty, so pawns count as attacking them even with nothing there to capture.
+        check_squares.iter().any(|square| self.is_square_attacked(*square, !self.active_color))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{board::MoveData, test_support::board_from_fen};
+
+    use super::*;
+
+    #[test]
+    fn is_legal_rejects_moving_the_opponents_pieces()
+    {
+        let board = Board::new_default_starting_board();
+        let r#move = Move::NormalMove(MoveData::new(Square::new(6, 4), Square::new(4, 4), false));
+        assert!(matches!(board.is_legal(&r#move), Err(MoveError::MissingPiece(_))));
+    }
+
+    #[test]
+    fn is_legal_rejects_a_wrong_capture_flag()
+    {
+        let board = Board::new_default_starting_board();
+        let r#move = Move::NormalMove(MoveData::new(Square::new(1, 4), Square::new(3, 4), true));
+        assert!(matches!(board.is_legal(&r#move), Err(MoveError::IllegalMove)));
+    }
+
+    #[test]
+    fn is_legal_rejects_exposing_the_king()
+    {
+        // The knight is pinned.
+        let board = board_from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1");
+        let r#move = Move::NormalMove(MoveData::new(Square::new(1, 4), Square::new(3, 3), false));
+        assert!(matches!(board.is_legal(&r#move), Err(MoveError::IllegalMove)));
+    }
+
+    #[test]
+    fn is_legal_checks_castling()
+    {
+        let board = board_from_fen("r3k2r/8/8/8/8/8/8/R3K1NR w KQkq - 0 1");
+        assert!(board.is_legal(&Move::Castle(CastlingDirection::Queenside)).is_ok());
+        // The knight on g1 is in the way.
+        assert!(matches!(board.is_legal(&Move::Castle(CastlingDirection::Kingside)), Err(MoveError::IllegalMove)));
     }
 }
@@ -2,15 +2,18 @@
 //! agents for evaluation and to check for end-game positions, since checkmate or stalemate
 //! both occur when there are no possible moves.
 
-use crate::{bitboard::Bitboard, board::{r#move::MoveData, Move, PieceType, PlayerColor, Square}};
+use crate::{bitboard::Bitboard, board::{between_mask, line_mask, piece_type::PROMOTION_PIECE_TYPES, r#move::{CastlingDirection, MoveData}, AttackInfo, Move, PieceType, PlayerColor, Square}};
 
 use super::Board;
 
+/// Every square on the board, for when we don't want to filter the target squares at all.
+const ALL_SQUARES: Bitboard = Bitboard::new(u64::MAX);
+
 impl Board
 {
     /// Generates a bitboard for all possible moves for a piece on a square.
     /// If there is no piece at that square, this function returns [None].
-    fn generate_possible_moves_for_piece(&self, player_color: PlayerColor, square: Square) -> Option<Bitboard>
+    pub(super) fn generate_possible_moves_for_piece(&self, player_color: PlayerColor, square: Square) -> Option<Bitboard>
     {
         let piece = self.piece_at(&square)?;
         Some(match piece.piece_type()
@@ -45,23 +48,69 @@ impl Board
     /// ```
     pub fn generate_legal_moves_for_piece(&self, player_color: PlayerColor, starting_square: Square) -> Vec<Move>
     {
-        self.generate_possible_moves_for_piece(player_color, starting_square)
-            .into_iter()
-            .flat_map(|bb| bb.squares().collect::<Vec<_>>())
-            .map(|square| 
-                Move::NormalMove(
-                    MoveData::new(
-                        starting_square, square, 
-                        // We are basically assuming that since the bitboard
-                        // filtered out moves where we collide with our own pieces,
-                        // if there exists any piece at the target square,
-                        // that must be a capture, otherwise it's just a move.
-                        self.piece_at(&square).is_some()
-                    )
-                )
-            )
-            .filter(|m| self.check_move(m))
-            .collect()
+        let mut moves = Vec::new();
+        self.push_legal_moves_for_piece(player_color, starting_square, None, ALL_SQUARES, &mut moves);
+        moves
+    }
+
+    /// Same as [Self::generate_legal_moves_for_piece], but pushes the moves onto `moves` instead
+    /// of allocating a new [Vec], and uses the position's [AttackInfo] (if given) to skip the full
+    /// legality check for every move that isn't a king move. Only moves that end on one of the
+    /// `targets` squares are pushed, or for en passant, that capture a pawn on one of them.
+    fn push_legal_moves_for_piece(&self, player_color: PlayerColor, starting_square: Square, attack_info: Option<&AttackInfo>, targets: Bitboard, moves: &mut Vec<Move>)
+    {
+        let is_pawn = self.piece_at(&starting_square).is_some_and(|piece| piece.piece_type() == PieceType::Pawn);
+        // An en passant capture takes a pawn that isn't on the target square, and can uncover a
+        // check along the rank both pawns leave, so it doesn't go through the masks below and
+        // always gets the full check instead.
+        let en_passant = match self.en_passant_target_square
+        {
+            Some(square) if is_pawn && player_color == self.active_color
+                && !(targets & Bitboard::from(Square::new(starting_square.rank, square.file))).is_empty() => Bitboard::from(square),
+            _ => Bitboard::default(),
+        };
+        // Knowing where the king is, what's checking it and what's pinned to it is enough to
+        // tell exactly which squares anything but the king can legally move to. King moves still
+        // need the full check, since the king can't step back along the line it's checked on.
+        let legal_targets = attack_info
+            .filter(|_| player_color == self.active_color)
+            .filter(|_| self.piece_at(&starting_square).is_some_and(|piece| piece.piece_type() != PieceType::King))
+            .and_then(|attack_info| legal_target_mask(attack_info, player_color, starting_square));
+        let always_legal = legal_targets.is_some();
+        let targets = targets & legal_targets.unwrap_or(ALL_SQUARES) | en_passant;
+        let Some(possible_moves) = self.generate_possible_moves_for_piece(player_color, starting_square) else { return };
+        // A pawn that reaches the last rank has to promote, so each of those squares turns into
+        // one move per promotion piece.
+        let promotion_rank = player_color.promotion_rank();
+        for square in (possible_moves & targets).squares()
+        {
+            let is_en_passant = !(en_passant & Bitboard::from(square)).is_empty();
+            let move_data = MoveData::new(
+                starting_square, square,
+                // We are basically assuming that since the bitboard
+                // filtered out moves where we collide with our own pieces,
+                // if there exists any piece at the target square,
+                // that must be a capture, otherwise it's just a move.
+                self.piece_at(&square).is_some() || is_en_passant
+            );
+            let is_promotion = is_pawn && square.rank == promotion_rank;
+            // Whether a promotion is legal doesn't depend on the piece we promote to, so we only
+            // check the first one.
+            let checked_move = match is_promotion
+            {
+                true => move_data.with_promotion(PROMOTION_PIECE_TYPES[0]),
+                false => move_data,
+            };
+            if (!always_legal || is_en_passant) && !self.check_move(&Move::NormalMove(checked_move))
+            {
+                continue;
+            }
+            match is_promotion
+            {
+                true => moves.extend(PROMOTION_PIECE_TYPES.iter().map(|promotion| Move::NormalMove(move_data.with_promotion(*promotion)))),
+                false => moves.push(Move::NormalMove(move_data)),
+            }
+        }
     }
 
     /// Returns a [Vec] containing all possible, *legal* moves a player can make
@@ -83,11 +132,300 @@ impl Board
     pub fn generate_moves_for_side(&self, player_color: PlayerColor) -> Vec<Move>
     {
         let mut moves = Vec::new();
+        self.generate_moves_for_side_into(player_color, &mut moves);
+        moves
+    }
+
+    /// Same as [Self::generate_moves_for_side], but writes the moves into an existing [Vec]
+    /// instead of allocating a new one. `moves` is cleared first, but keeps its capacity, so a
+    /// search can reuse the same buffers at every node. See
+    /// [MoveStack](crate::search::MoveStack).
+    ///
+    /// # Arguments
+    ///
+    /// * `player_color` - The side moving.
+    /// * `moves` - Where to put the moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, PlayerColor};
+    /// let board = Board::new_default_starting_board();
+    /// let mut moves = Vec::with_capacity(64);
+    /// board.generate_moves_for_side_into(PlayerColor::White, &mut moves);
+    /// assert_eq!(moves.len(), 20);
+    /// board.generate_moves_for_side_into(PlayerColor::Black, &mut moves);
+    /// assert_eq!(moves.len(), 20);
+    /// ```
+    pub fn generate_moves_for_side_into(&self, player_color: PlayerColor, moves: &mut Vec<Move>)
+    {
+        moves.clear();
+        let attack_info = self.attack_info();
+        if player_color == self.active_color && attack_info.is_in_check(player_color)
+        {
+            self.push_check_evasions(&attack_info, moves);
+            return;
+        }
         for square in self.query().color(player_color).result().squares()
         {
-            moves.extend(self.generate_legal_moves_for_piece(player_color, square))
+            self.push_legal_moves_for_piece(player_color, square, Some(&attack_info), ALL_SQUARES, moves);
         }
+        if player_color == self.active_color
+        {
+            self.push_castles(moves);
+        }
+    }
 
+    /// Pushes the castles the side to move can legally make. Castling is never a way out of
+    /// check, so this isn't needed for check evasions.
+    fn push_castles(&self, moves: &mut Vec<Move>)
+    {
+        for direction in [CastlingDirection::Kingside, CastlingDirection::Queenside]
+        {
+            // Most of the time the right to castle is already gone, which is cheap to check before
+            // looking at the squares the king passes through.
+            if self.has_castled_already(self.active_color, direction)
+            {
+                continue;
+            }
+            let castle = Move::Castle(direction);
+            if self.is_legal(&castle).is_ok()
+            {
+                moves.push(castle);
+            }
+        }
+    }
+
+    /// Returns every move the side to move could make if it didn't have to look after its king,
+    /// so moves that leave the king in check are included. Castling isn't, since the king's
+    /// safety is what it's all about. A pawn reaching the last rank gets one move for each of
+    /// `promotion_piece_types`.
+    ///
+    /// This is the move generator for variants where the king is just another piece, like
+    /// [Antichess](crate::rules::Antichess). It works on boards without kings too.
+    ///
+    /// # Arguments
+    ///
+    /// * `promotion_piece_types` - The pieces a pawn can promote to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PROMOTION_PIECE_TYPES};
+    /// // The knight on d2 is pinned, but it can still move.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("3rk3/8/8/8/8/8/3N4/3K4 w - - 0 1").unwrap());
+    /// assert_eq!(board.generate_moves_for_side(board.active_color()).len(), 4);
+    /// assert_eq!(board.generate_pseudo_legal_moves(&PROMOTION_PIECE_TYPES).len(), 4 + 6);
+    /// ```
+    pub fn generate_pseudo_legal_moves(&self, promotion_piece_types: &[PieceType]) -> Vec<Move>
+    {
+        let mut moves = Vec::new();
+        let promotion_rank = self.active_color.promotion_rank();
+        for starting_square in self.query().color(self.active_color).result().squares()
+        {
+            let Some(possible_moves) = self.generate_possible_moves_for_piece(self.active_color, starting_square) else { continue };
+            let is_pawn = self.piece_at(&starting_square).is_some_and(|piece| piece.piece_type() == PieceType::Pawn);
+            for square in possible_moves.squares()
+            {
+                let is_en_passant = is_pawn && Some(square) == self.en_passant_target_square;
+                let move_data = MoveData::new(starting_square, square, self.piece_at(&square).is_some() || is_en_passant);
+                match is_pawn && square.rank == promotion_rank
+                {
+                    true => moves.extend(promotion_piece_types.iter().map(|promotion| Move::NormalMove(move_data.with_promotion(*promotion)))),
+                    false => moves.push(Move::NormalMove(move_data)),
+                }
+            }
+        }
         moves
     }
+
+    /// Returns the legal moves of the side to move when its king is in check, i.e moving the
+    /// king, capturing the checking piece, or blocking the check. Returns no moves if the side to
+    /// move isn't in check.
+    ///
+    /// This is the same as [Self::generate_moves_for_side] in check, but skips straight to the
+    /// king in double check, when no other piece can help.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration};
+    /// // The rook on e8 checks the king, which can step aside to four squares or be covered by Ne5.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4r1k1/8/8/8/8/5N2/8/4K3 w - - 0 1").unwrap());
+    /// let evasions = board.generate_check_evasions();
+    /// assert_eq!(evasions.len(), 4 + 1);
+    /// assert!(Board::new_default_starting_board().generate_check_evasions().is_empty());
+    /// ```
+    pub fn generate_check_evasions(&self) -> Vec<Move>
+    {
+        let mut moves = Vec::new();
+        self.generate_check_evasions_into(&mut moves);
+        moves
+    }
+
+    /// Same as [Self::generate_check_evasions], but writes the moves into an existing [Vec], like
+    /// [Self::generate_moves_for_side_into]. `moves` is cleared first.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - Where to put the moves.
+    pub fn generate_check_evasions_into(&self, moves: &mut Vec<Move>)
+    {
+        moves.clear();
+        let attack_info = self.attack_info();
+        if attack_info.is_in_check(self.active_color)
+        {
+            self.push_check_evasions(&attack_info, moves);
+        }
+    }
+
+    /// Pushes the moves out of check for the side to move, which has to be in check.
+    fn push_check_evasions(&self, attack_info: &AttackInfo, moves: &mut Vec<Move>)
+    {
+        let color = self.active_color;
+        let is_double_check = attack_info.checkers(color).count() > 1;
+        let pieces = match (is_double_check, attack_info.king_square(color))
+        {
+            (true, Some(king_square)) => Bitboard::from(king_square),
+            _ => self.pieces_of_color(color),
+        };
+        for square in pieces.squares()
+        {
+            self.push_legal_moves_for_piece(color, square, Some(attack_info), ALL_SQUARES, moves);
+        }
+    }
+
+    /// Returns a [Vec] containing only the legal moves that capture one of the opponent's
+    /// pieces, i.e for a quiescence search that only wants to play out the exchanges in a
+    /// position.
+    ///
+    /// # Arguments
+    ///
+    /// * `player_color` - The side moving.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor};
+    /// // After 1. e4 d5 the only capture is exd5.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap());
+    /// assert_eq!(board.generate_captures_for_side(PlayerColor::White).len(), 1);
+    /// assert!(Board::new_default_starting_board().generate_captures_for_side(PlayerColor::White).is_empty());
+    /// ```
+    pub fn generate_captures_for_side(&self, player_color: PlayerColor) -> Vec<Move>
+    {
+        let mut moves = Vec::new();
+        self.generate_captures_for_side_into(player_color, &mut moves);
+        moves
+    }
+
+    /// Same as [Self::generate_captures_for_side], but writes the moves into an existing [Vec],
+    /// like [Self::generate_moves_for_side_into]. `moves` is cleared first.
+    ///
+    /// # Arguments
+    ///
+    /// * `player_color` - The side moving.
+    /// * `moves` - Where to put the moves.
+    pub fn generate_captures_for_side_into(&self, player_color: PlayerColor, moves: &mut Vec<Move>)
+    {
+        moves.clear();
+        let attack_info = self.attack_info();
+        let targets = self.pieces_of_color(!player_color);
+        for square in self.query().color(player_color).result().squares()
+        {
+            self.push_legal_moves_for_piece(player_color, square, Some(&attack_info), targets, moves);
+        }
+    }
+}
+
+/// Returns the squares a piece (other than the king) on `square` can move to without leaving
+/// its king in check, or [None] if `color` doesn't have exactly one king.
+///
+/// A pinned piece has to stay on the line through its king and the piece pinning it. When the
+/// king is in check, every other piece has to either capture the checking piece or block it,
+/// and when it's in double check none of them can move at all.
+fn legal_target_mask(attack_info: &AttackInfo, color: PlayerColor, square: Square) -> Option<Bitboard>
+{
+    let king_square = attack_info.king_square(color)?;
+    let checkers = attack_info.checkers(color);
+    let mut checkers = checkers.squares();
+    let check_mask = match (checkers.next(), checkers.next())
+    {
+        (None, _) => ALL_SQUARES,
+        (Some(checker), None) => Bitboard::from(checker) | between_mask(king_square, checker),
+        (Some(_), Some(_)) => Bitboard::default(),
+    };
+    let pin_mask = match (attack_info.pinned(color) & Bitboard::from(square)).is_empty()
+    {
+        true => ALL_SQUARES,
+        false => line_mask(king_square, square),
+    };
+    Some(check_mask & pin_mask)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::BoardConfiguration;
+
+    use super::*;
+
+    /// Generates every legal move the slow way, checking each possible move in full.
+    fn fully_checked_moves(board: &Board) -> Vec<Move>
+    {
+        board.query().color(board.active_color()).result().squares()
+            .flat_map(|square| board.generate_legal_moves_for_piece(board.active_color(), square))
+            .collect()
+    }
+
+    #[test]
+    fn pins_and_checks_give_the_same_moves_as_checking_every_move()
+    {
+        let positions = [
+            // A bishop pinned along a diagonal and a knight pinned along a file.
+            "4r1k1/8/8/b7/8/2B5/4N3/4K3 w - - 0 1",
+            // A rook pinned along the rank it can still slide along.
+            "8/8/8/8/k7/8/8/K2R3r w - - 0 1",
+            // A single check from a rook that can be blocked or captured.
+            "4r1k1/8/8/8/8/2N5/3B4/R3K3 w - - 0 1",
+            // A knight check can only be captured.
+            "4k3/8/8/8/8/3n4/8/R3K1N1 w - - 0 1",
+            // Double check, only the king can move.
+            "4r1k1/8/8/8/8/5n2/8/R3K1N1 w - - 0 1",
+            // A pawn pinned on a diagonal can only capture the pinner.
+            "6k1/8/8/8/8/6b1/5P2/4K3 w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in positions
+        {
+            let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            let mut expected = fully_checked_moves(&board);
+            let mut generated = board.generate_moves_for_side(board.active_color());
+            let key = |r#move: &Move| format!("{:?}", r#move);
+            expected.sort_by_key(key);
+            generated.sort_by_key(key);
+            assert_eq!(generated, expected, "in {}", fen);
+        }
+    }
+
+    #[test]
+    fn evasions_are_every_legal_move_in_check()
+    {
+        for fen in ["4r1k1/8/8/8/8/2N5/3B4/R3K3 w - - 0 1", "4k3/8/8/8/8/3n4/8/R3K1N1 w - - 0 1", "4r1k1/8/8/8/8/5n2/8/R3K1N1 w - - 0 1"]
+        {
+            let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            let mut evasions = board.generate_check_evasions();
+            let mut expected = fully_checked_moves(&board);
+            let key = |r#move: &Move| format!("{:?}", r#move);
+            evasions.sort_by_key(key);
+            expected.sort_by_key(key);
+            assert!(!evasions.is_empty());
+            assert_eq!(evasions, expected, "in {}", fen);
+        }
+    }
 }
@@ -3,7 +3,7 @@
 //!
 //! This is all private to [Board](super::Board), it's just meant to clean up the module a bit.
 
-use crate::{bitboard::Bitboard, board::{r#move::MoveData, piece_type::PIECE_TYPES, Move, MoveError, PieceType, PlayerColor, Square}, parse::MoveCommandData};
+use crate::{bitboard::Bitboard, board::{r#move::MoveData, piece_type::PIECE_TYPES, CastlingDirection, Move, MoveError, PieceType, PlayerColor, Square}, parse::{MoveCommandData, UciMove}};
 
 use super::Board;
 
@@ -71,6 +71,66 @@ impl Board
         }
     }
 
+    /// Converts a move in coordinate notation (see [UciMove]) into a [Move] on this board, working
+    /// out whether it's a capture or a castle. Like [Self::get_move], the move is possible but
+    /// might not be legal.
+    ///
+    /// # Arguments
+    ///
+    /// * `uci_move` - The move to convert.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MoveError::NoPossibleMove] if the active player has no piece on the starting
+    /// square, or that piece can't move to the target square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, CastlingDirection, Move};
+    /// # use rust_chess_engine::parse::UciMove;
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap());
+    /// let r#move = board.get_uci_move(&UciMove::from_str("e1g1").unwrap()).unwrap();
+    /// assert_eq!(r#move, Move::Castle(CastlingDirection::Kingside));
+    /// let Move::NormalMove(move_data) = board.get_uci_move(&UciMove::from_str("a1a8").unwrap()).unwrap() else { unreachable!() };
+    /// assert!(move_data.capture());
+    /// assert!(board.get_uci_move(&UciMove::from_str("a1b2").unwrap()).is_err());
+    /// ```
+    pub fn get_uci_move(&self, uci_move: &UciMove) -> Result<Move, MoveError>
+    {
+        let starting_square = uci_move.starting_square();
+        let target_square = uci_move.target_square();
+        let piece = self.piece_at(&starting_square)
+            .filter(|piece| piece.color() == self.active_color)
+            .ok_or(MoveError::NoPossibleMove)?;
+        // Castling is written as the king moving two squares along its home rank.
+        if piece.piece_type() == PieceType::King
+            && uci_move.promotion().is_none()
+            && starting_square == Square::new(self.active_color.home_rank(), 4)
+            && target_square.rank == starting_square.rank
+        {
+            match target_square.file
+            {
+                6 => return Ok(Move::Castle(CastlingDirection::Kingside)),
+                2 => return Ok(Move::Castle(CastlingDirection::Queenside)),
+                _ => (),
+            }
+        }
+        let possible_moves = self.generate_possible_moves_for_piece(self.active_color, starting_square).unwrap_or_default();
+        if (possible_moves & Bitboard::from(target_square)).is_empty()
+        {
+            return Err(MoveError::NoPossibleMove);
+        }
+        let is_en_passant = piece.piece_type() == PieceType::Pawn && Some(target_square) == self.en_passant_target_square;
+        let move_data = MoveData::new(starting_square, target_square, self.piece_at(&target_square).is_some() || is_en_passant);
+        Ok(Move::NormalMove(match uci_move.promotion()
+        {
+            Some(promotion) => move_data.with_promotion(promotion),
+            None => move_data,
+        }))
+    }
+
     /// Gets all of the pieces of a type that can move to a given square.
     ///
     /// Since we know from a [MoveCommand] what piece is being moved and where it is being moved
@@ -266,6 +326,21 @@ mod tests
 
     use super::*;
 
+    #[test]
+    fn uci_moves_round_trip()
+    {
+        // Castling both ways, promotions, captures and en passant.
+        for fen in ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1", "4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 2"]
+        {
+            let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            for r#move in board.generate_moves_for_side(board.active_color())
+            {
+                let uci_move = UciMove::from_move(&r#move, board.active_color());
+                assert_eq!(board.get_uci_move(&uci_move).unwrap(), r#move, "{uci_move} in {fen}");
+            }
+        }
+    }
+
     #[test]
     fn check_queen_can_reach_king_square()
     {
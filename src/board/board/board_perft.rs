@@ -0,0 +1,221 @@
+//! This submodule contains perft ("performance test"), which counts every position reachable in a
+//! given number of moves. Comparing the counts against known values is the standard way of
+//! checking that move generation is correct, and timing it measures move generation speed.
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::board::Move;
+
+use super::Board;
+
+impl Board
+{
+    /// Counts the number of leaf nodes `depth` moves (ply) away from this board.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - How many moves deep to count. A depth of 0 counts only this board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// let board = Board::new_default_starting_board();
+    /// assert_eq!(board.perft(1), 20);
+    /// assert_eq!(board.perft(2), 400);
+    /// ```
+    pub fn perft(&self, depth: usize) -> u64
+    {
+        if depth == 0
+        {
+            return 1;
+        }
+
+        let moves = self.generate_moves_for_side(self.active_color);
+        if depth == 1
+        {
+            return moves.len() as u64;
+        }
+        moves.iter()
+            .map(|r#move| self.attempt_move(r#move).expect("Generated moves should always be legal").perft(depth - 1))
+            .sum()
+    }
+
+    /// Same as [Self::perft], but splits the work across threads at the root and at the first
+    /// ply, which makes deep counts (depth 6 or 7) finish in reasonable time.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - How many moves deep to count. A depth of 0 counts only this board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// let board = Board::new_default_starting_board();
+    /// assert_eq!(board.perft_parallel(3), board.perft(3));
+    /// ```
+    pub fn perft_parallel(&self, depth: usize) -> u64
+    {
+        if depth <= 2
+        {
+            return self.perft(depth);
+        }
+
+        self.children().into_par_iter()
+            .map(|child| child.children().into_par_iter()
+                .map(|grandchild| grandchild.perft(depth - 2))
+                .sum::<u64>())
+            .sum()
+    }
+
+    /// Same as [Self::perft], but counts the leaf nodes under each of the active player's moves
+    /// separately ("divide"). When a count doesn't match a reference engine, dividing both and
+    /// following the move whose count differs leads straight to the position with the bug.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - How many moves deep to count, including the divided move. A depth of 0 has no
+    ///   moves to divide, so the result is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// let board = Board::new_default_starting_board();
+    /// let divided = board.perft_divide(3);
+    /// assert_eq!(divided.len(), 20);
+    /// assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), board.perft(3));
+    /// for (r#move, count) in divided
+    /// {
+    ///     println!("{}: {}", board.to_san(&r#move), count);
+    /// }
+    /// ```
+    pub fn perft_divide(&self, depth: usize) -> Vec<(Move, u64)>
+    {
+        if depth == 0
+        {
+            return Vec::new();
+        }
+
+        self.generate_moves_for_side(self.active_color).into_par_iter()
+            .map(|r#move|
+            {
+                let child = self.attempt_move(&r#move).expect("Generated moves should always be legal");
+                (r#move, child.perft(depth - 1))
+            })
+            .collect()
+    }
+
+    /// Returns the board after each of the active player's legal moves.
+    fn children(&self) -> Vec<Board>
+    {
+        self.generate_moves_for_side(self.active_color).iter()
+            .map(|r#move| self.attempt_move(r#move).expect("Generated moves should always be legal"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::BoardConfiguration;
+
+    use super::*;
+
+    /// Checks the perft counts of a position against known values, starting at depth 1.
+    fn assert_perft(fen: &str, expected_counts: &[u64])
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+        for (depth, expected) in (1..).zip(expected_counts)
+        {
+            assert_eq!(board.perft_parallel(depth), *expected, "perft({}) of {}", depth, fen);
+        }
+    }
+
+    #[test]
+    fn perft_startpos_depth_3()
+    {
+        let board = Board::new_default_starting_board();
+        assert_eq!(board.perft(3), 8902);
+        assert_eq!(board.perft_parallel(3), 8902);
+    }
+
+    #[test]
+    fn perft_startpos()
+    {
+        assert_perft("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902, 197281]);
+    }
+
+    // The reference counts below are from the Chess Programming Wiki's perft results page.
+
+    #[test]
+    fn perft_rook_endgame_with_en_passant()
+    {
+        assert_perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238]);
+    }
+
+    #[test]
+    fn perft_kiwipete()
+    {
+        assert_perft("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862]);
+    }
+
+    #[test]
+    fn perft_promotions_and_castling()
+    {
+        assert_perft("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467]);
+    }
+
+    #[test]
+    fn perft_discovered_checks()
+    {
+        assert_perft("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379]);
+    }
+
+    // Castling rights edge cases: each side's rooks can take the other side's rooks on their
+    // corners, which has to take away the captured rook's castling right. Counts from the perft
+    // test suite distributed with Marcel van Kervinck's perft tool.
+
+    #[test]
+    fn perft_castling_rights_all_rooks_home()
+    {
+        assert_perft("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &[26, 568, 13744]);
+        assert_perft("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", &[26, 568, 13744]);
+    }
+
+    #[test]
+    fn perft_castling_rights_white_rook_moved()
+    {
+        assert_perft("r3k2r/8/8/8/8/8/8/1R2K2R w Kkq - 0 1", &[25, 567, 14095]);
+        assert_perft("r3k2r/8/8/8/8/8/8/2R1K2R w Kkq - 0 1", &[25, 548, 13502]);
+        assert_perft("r3k2r/8/8/8/8/8/8/R3K1R1 w Qkq - 0 1", &[25, 547, 13579]);
+    }
+
+    #[test]
+    fn perft_castling_rights_black_rook_moved()
+    {
+        assert_perft("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1", &[26, 583, 14252]);
+        assert_perft("2r1k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1", &[25, 560, 13592]);
+        assert_perft("r3k1r1/8/8/8/8/8/8/R3K2R w KQq - 0 1", &[25, 560, 13607]);
+    }
+
+    #[test]
+    fn divide_adds_up_to_perft()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap());
+        let divided = board.perft_divide(2);
+        assert_eq!(divided.len(), 14);
+        assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), 191);
+        assert!(board.perft_divide(0).is_empty());
+    }
+
+    #[test]
+    fn perft_parallel_matches_serial()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap());
+        assert_eq!(board.perft_parallel(3), board.perft(3));
+    }
+}
@@ -6,7 +6,7 @@
 
 use crate::{bitboard::{Bitboard, OutOfBoundsError}, board::{PieceType, PlayerColor, Square}};
 
-use super::Board;
+use super::{super::attack_info::attacked_squares, Board};
 
 pub struct BoardQuery<'a, T>
 {
@@ -96,6 +96,45 @@ impl<'a> BoardQuery<'a, Bitboard>
         }
     }
 
+    /// Replaces the result of the query with every square attacked by `color`, whether anything
+    /// is on it or not. A square counts as attacked if one of `color`'s pieces could capture on
+    /// it, the same as in [AttackInfo](crate::board::AttackInfo), so squares holding `color`'s
+    /// own pieces count if they're defended.
+    ///
+    /// Filters chained after this one narrow the attacked squares down, so
+    /// `.attacked_by(PlayerColor::White).color(PlayerColor::Black)` gives the black pieces that
+    /// white attacks, and [Self::piece_at] tells whether a square is attacked at all. Filters
+    /// before it are thrown away.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The attacking side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PlayerColor, Square};
+    /// let board = Board::new_default_starting_board();
+    /// // Every square on the second and third ranks, and every piece on the first rank but the
+    /// // rook in each corner.
+    /// assert_eq!(board.query().attacked_by(PlayerColor::White).result().count(), 22);
+    /// assert!(board.query().attacked_by(PlayerColor::White).piece_at(Square::new(2, 0)).unwrap().result());
+    ///
+    /// // The white queen attacks the black pawn on f7.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("rnbqkbnr/pppp1ppp/8/4p3/4P3/5Q2/PPPP1PPP/RNB1KBNR b KQkq - 1 2").unwrap());
+    /// let attacked_black_pieces = board.query().attacked_by(PlayerColor::White).color(PlayerColor::Black).result();
+    /// assert_eq!(attacked_black_pieces.squares().collect::<Vec<_>>(), vec![Square::new(6, 5)]);
+    /// ```
+    pub fn attacked_by(self, color: PlayerColor) -> Self
+    {
+        Self
+        {
+            result: attacked_squares(self.board, color),
+            ..self
+        }
+    }
+
     /// Takes a [BoardQuery] and converts it from a [Bitboard] to a [bool],
     /// with that `bool` set to true if there was a piece at that location (i.e a bit set to 1 at
     /// the specified coordinates), and false if there was not a piece at that location (i.e a bit
@@ -178,6 +217,10 @@ impl Board
 #[cfg(test)]
 mod tests
 {
+    use std::str::FromStr;
+
+    use crate::board::BoardConfiguration;
+
     use super::*;
 
     #[test]
@@ -261,5 +304,17 @@ mod tests
         let board = Board::new_blank_board();
         assert!(board.query().piece_at(Square::new(100, 100)).is_err());
     }
-}
 
+    #[test]
+    fn test_bitboard_query_attacked_by_matches_attack_info()
+    {
+        for fen in ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"]
+        {
+            let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            for color in [PlayerColor::White, PlayerColor::Black]
+            {
+                assert_eq!(board.query().attacked_by(color).result(), board.attack_info().attacked_by(color));
+            }
+        }
+    }
+}
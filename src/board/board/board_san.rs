@@ -0,0 +1,209 @@
+//! This submodule turns [Move]s back into standard algebraic notation (SAN), the inverse of
+//! parsing a [MoveCommand](crate::parse::MoveCommand).
+
+use crate::{board::{CastlingDirection, Move, PieceType, Square}, parse::{square_to_algebraic, CheckAnnotation}};
+
+use super::Board;
+
+impl Board
+{
+    /// Writes a legal [Move] in standard algebraic notation, like `Nbd2`, `exd5`, `e8=Q`, `O-O` or
+    /// `Qh5#`.
+    ///
+    /// The piece letter, a discriminant (only when another piece of the same type could also move
+    /// to the target square), `x` for captures and a `+` or `#` suffix for check and checkmate
+    /// are all included, so the result can be read back by any other chess program.
+    ///
+    /// # Arguments
+    ///
+    /// * `r#move` - The move to write. This should be legal on this board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration};
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let board = Board::new_default_starting_board();
+    /// let r#move = board.get_move(&MoveCommand::from_str("Nf3").unwrap()).unwrap();
+    /// assert_eq!(board.to_san(&r#move), "Nf3");
+    ///
+    /// // Fool's mate.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2").unwrap());
+    /// let r#move = board.get_move(&MoveCommand::from_str("Qh4").unwrap()).unwrap();
+    /// assert_eq!(board.to_san(&r#move), "Qh4#");
+    /// ```
+    pub fn to_san(&self, r#move: &Move) -> String
+    {
+        let mut san = match r#move
+        {
+            Move::Castle(CastlingDirection::Kingside) => "O-O".to_string(),
+            Move::Castle(CastlingDirection::Queenside) => "O-O-O".to_string(),
+            Move::NormalMove(move_data) =>
+            {
+                let starting_square = move_data.starting_square();
+                let target_square = move_data.target_square();
+                let piece_type = self.piece_at(&starting_square).map(|piece| piece.piece_type()).unwrap_or(PieceType::Pawn);
+                let mut san = String::new();
+                match piece_type
+                {
+                    // Pawn captures always name the file the pawn came from.
+                    PieceType::Pawn if move_data.capture() => san.push(file_letter(starting_square.file)),
+                    PieceType::Pawn => (),
+                    _ =>
+                    {
+                        san.push(piece_letter(piece_type));
+                        san.push_str(&self.discriminant(piece_type, starting_square, target_square));
+                    },
+                }
+                if move_data.capture()
+                {
+                    san.push('x');
+                }
+                san.push_str(&square_to_algebraic(target_square));
+                if let Some(promotion) = move_data.promotion()
+                {
+                    san.push('=');
+                    san.push(piece_letter(promotion));
+                }
+                san
+            },
+        };
+
+        match self.attempt_move(r#move).ok().and_then(|next_board| next_board.check_annotation())
+        {
+            Some(CheckAnnotation::Check) => san.push('+'),
+            Some(CheckAnnotation::Checkmate) => san.push('#'),
+            None => (),
+        }
+        san
+    }
+
+    /// Returns the suffix the move that led to this board should be written with: checkmate if
+    /// the player to move is checkmated, check if they're only in check, or [None] otherwise.
+    pub(super) fn check_annotation(&self) -> Option<CheckAnnotation>
+    {
+        if !self.is_king_in_check(self.active_color)
+        {
+            None
+        }
+        else if self.generate_moves_for_side(self.active_color).is_empty()
+        {
+            Some(CheckAnnotation::Checkmate)
+        }
+        else
+        {
+            Some(CheckAnnotation::Check)
+        }
+    }
+
+    /// Returns the smallest discriminant (nothing, the file, the rank, or both) that tells the
+    /// piece on `starting_square` apart from every other piece of the same type that could
+    /// also move to `target_square`.
+    fn discriminant(&self, piece_type: PieceType, starting_square: Square, target_square: Square) -> String
+    {
+        let rivals: Vec<Square> = self.generate_moves_for_side(self.active_color).into_iter()
+            .filter_map(|r#move| match r#move
+            {
+                Move::NormalMove(move_data) if move_data.target_square() == target_square => Some(move_data.starting_square()),
+                _ => None,
+            })
+            .filter(|square| *square != starting_square
+                && self.piece_at(square).is_some_and(|piece| piece.piece_type() == piece_type))
+            .collect();
+
+        if rivals.is_empty()
+        {
+            String::new()
+        }
+        else if rivals.iter().all(|square| square.file != starting_square.file)
+        {
+            file_letter(starting_square.file).to_string()
+        }
+        else if rivals.iter().all(|square| square.rank != starting_square.rank)
+        {
+            (starting_square.rank + 1).to_string()
+        }
+        else
+        {
+            square_to_algebraic(starting_square)
+        }
+    }
+}
+
+fn file_letter(file: u8) -> char
+{
+    char::from(b'a' + file)
+}
+
+fn piece_letter(piece_type: PieceType) -> char
+{
+    match piece_type
+    {
+        PieceType::Pawn => 'P',
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Rook => 'R',
+        PieceType::Queen => 'Q',
+        PieceType::King => 'K',
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::BoardConfiguration, parse::MoveCommand};
+
+    use super::*;
+
+    fn san(fen: &str, move_command: &str) -> String
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+        let r#move = board.get_move(&MoveCommand::from_str(move_command).unwrap()).unwrap();
+        board.to_san(&r#move)
+    }
+
+    #[test]
+    fn pawn_captures_name_their_file()
+    {
+        assert_eq!(san("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "exd5"), "exd5");
+    }
+
+    #[test]
+    fn ambiguous_moves_get_a_discriminant()
+    {
+        // Knights on b1 and f1 can both reach d2.
+        assert_eq!(san("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "Nbd2"), "Nbd2");
+        // Rooks on a1 and a5 can both reach a3.
+        assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "R1a3"), "R1a3");
+    }
+
+    #[test]
+    fn checks_get_a_suffix()
+    {
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "Ra8"), "Ra8+");
+    }
+
+    #[test]
+    fn promotions_name_the_new_piece()
+    {
+        assert_eq!(san("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q"), "a8=Q+");
+        assert_eq!(san("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8N"), "a8=N");
+    }
+
+    #[test]
+    fn strict_moves_need_the_right_suffix()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap());
+        assert!(board.attempt_move_san_strict("Ra8+").is_ok());
+        assert!(board.attempt_move_san_strict("Ra7").is_ok());
+        assert!(board.attempt_move_san_strict("Ra8").is_err());
+        assert!(board.attempt_move_san_strict("Ra7+").is_err());
+        // The castled rook gives check along the d file.
+        assert!(board.attempt_move_san_strict("O-O-O+").is_ok());
+        assert!(board.attempt_move_san_strict("O-O-O").is_err());
+        assert!(board.attempt_move_san("Ra8#").is_ok());
+    }
+}
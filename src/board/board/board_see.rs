@@ -0,0 +1,197 @@
+//! This submodule works out static exchange evaluation (SEE): what a capture wins or loses once
+//! both sides have taken back on the target square as many times as it pays to.
+
+use crate::{bitboard::Bitboard, board::{bishop_attack_mask, pawn_attack_mask, rook_attack_mask, Move, PieceType, PlayerColor, Square}};
+
+use super::Board;
+
+/// Attackers are tried from least to most valuable, since recapturing with the cheapest piece
+/// risks the least.
+const ATTACKER_ORDER: [PieceType; 6] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King];
+
+impl Board
+{
+    /// Returns how much material the side to move wins, in centipawns, by playing `r#move` and
+    /// then letting both sides take back on `square` for as long as it's worth it.
+    ///
+    /// Each side always recaptures with its least valuable piece, and can stop taking back
+    /// whenever that's better than carrying on. Sliders lined up behind other pieces (x-rays)
+    /// join in once the pieces in front of them have captured. Pins, checks and anything that
+    /// happens off `square` are ignored, so this is only an estimate, but a cheap one, which
+    /// makes it useful for ordering and pruning captures.
+    ///
+    /// Quiet moves start from nothing, so a negative result means the piece just gets taken.
+    /// Castling always returns 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square the exchange happens on. This is normally the target square of
+    ///   `r#move`.
+    /// * `r#move` - The move that starts the exchange.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, Square};
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// // The e5 pawn is defended by the d6 pawn.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/3p4/4p3/8/5N2/8/4K3 w - - 0 1").unwrap());
+    /// let e5 = Square::from_str("e5").unwrap();
+    /// // The knight wins a pawn, but loses itself.
+    /// let knight_takes = board.get_move(&MoveCommand::from_str("Nxe5").unwrap()).unwrap();
+    /// assert_eq!(board.see(e5, &knight_takes), -200);
+    /// ```
+    pub fn see(&self, square: Square, r#move: &Move) -> i32
+    {
+        let Move::NormalMove(move_data) = r#move else { return 0 };
+        let Some(attacker) = self.piece_at(&move_data.starting_square()) else { return 0 };
+
+        let mut occupied = self.query().result() & !Bitboard::from(move_data.starting_square());
+        let victim_value = match self.piece_at(&square)
+        {
+            Some(victim) => see_value(victim.piece_type()),
+            // En passant takes a pawn that isn't on the target square.
+            None if move_data.capture() =>
+            {
+                let captured_square = Square::new(move_data.starting_square().rank, square.file);
+                occupied &= !Bitboard::from(captured_square);
+                see_value(PieceType::Pawn)
+            },
+            None => 0,
+        };
+
+        // gains[n] is what the side making the nth capture has won so far, if the exchange
+        // stopped right after it.
+        let mut gains = vec![victim_value];
+        let mut piece_on_square = match move_data.promotion()
+        {
+            Some(promotion) =>
+            {
+                gains[0] += see_value(promotion) - see_value(PieceType::Pawn);
+                promotion
+            },
+            None => attacker.piece_type(),
+        };
+        let mut side = !attacker.color();
+        loop
+        {
+            let attackers = self.attackers_to(square, occupied) & occupied;
+            let Some((attacker_square, attacker_type)) = self.least_valuable_attacker(attackers, side) else { break };
+            // The king can only take back if nothing is left to take it.
+            if attacker_type == PieceType::King && !(attackers & self.pieces_of_color(!side)).is_empty()
+            {
+                break;
+            }
+            gains.push(see_value(piece_on_square) - gains[gains.len() - 1]);
+            piece_on_square = attacker_type;
+            occupied &= !Bitboard::from(attacker_square);
+            side = !side;
+        }
+
+        // Walk back through the exchange: each side only makes its capture if that's better than
+        // stopping before it.
+        while gains.len() > 1
+        {
+            let last = gains.pop().expect("There's more than one gain");
+            let previous = gains.last_mut().expect("There's at least one gain left");
+            *previous = -Ord::max(-*previous, last);
+        }
+        gains[0]
+    }
+
+    /// Returns every piece of either color on `occupied` that attacks `square`.
+    fn attackers_to(&self, square: Square, occupied: Bitboard) -> Bitboard
+    {
+        let queens = self.pieces_of_type(PieceType::Queen);
+        // Pawns attack diagonally forwards, so a white pawn attacks the square if a black pawn on
+        // the square would attack it back.
+        (pawn_attack_mask(PlayerColor::Black, square) & self.query().color(PlayerColor::White).piece_type(PieceType::Pawn).result()) |
+        (pawn_attack_mask(PlayerColor::White, square) & self.query().color(PlayerColor::Black).piece_type(PieceType::Pawn).result()) |
+        ((self.knight_moves(PlayerColor::White, square) | self.knight_moves(PlayerColor::Black, square)) & self.pieces_of_type(PieceType::Knight)) |
+        ((self.king_moves(PlayerColor::White, square) | self.king_moves(PlayerColor::Black, square)) & self.pieces_of_type(PieceType::King)) |
+        (bishop_attack_mask(square, occupied) & (self.pieces_of_type(PieceType::Bishop) | queens)) |
+        (rook_attack_mask(square, occupied) & (self.pieces_of_type(PieceType::Rook) | queens))
+    }
+
+    fn least_valuable_attacker(&self, attackers: Bitboard, color: PlayerColor) -> Option<(Square, PieceType)>
+    {
+        let attackers = attackers & self.pieces_of_color(color);
+        ATTACKER_ORDER.into_iter().find_map(|piece_type|
+            (attackers & self.pieces_of_type(piece_type)).squares().next().map(|square| (square, piece_type)))
+    }
+}
+
+/// What each piece is worth in an exchange, in centipawns. The king can't be traded, so it's
+/// worth more than everything else put together.
+fn see_value(piece_type: PieceType) -> i32
+{
+    match piece_type
+    {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 300,
+        PieceType::Bishop => 300,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        PieceType::King => 10000,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::BoardConfiguration, parse::MoveCommand};
+
+    use super::*;
+
+    fn see(fen: &str, r#move: &str) -> i32
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+        let r#move = board.get_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+        let Move::NormalMove(move_data) = r#move else { panic!("Expected a normal move") };
+        board.see(move_data.target_square(), &r#move)
+    }
+
+    #[test]
+    fn undefended_pieces_are_won_outright()
+    {
+        assert_eq!(see("4k3/8/8/3r4/8/8/3R4/4K3 w - - 0 1", "Rxd5"), 500);
+    }
+
+    #[test]
+    fn defended_pieces_cost_the_attacker()
+    {
+        // The queen takes a pawn and the c6 pawn takes the queen.
+        assert_eq!(see("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", "Qxd5"), -800);
+        // Pawn takes pawn, pawn takes back: even.
+        assert_eq!(see("4k3/8/2p5/3p4/4P3/8/8/4K3 w - - 0 1", "exd5"), 0);
+    }
+
+    #[test]
+    fn sliders_behind_other_pieces_join_in()
+    {
+        // The d1 rook backs up the d2 rook through it, so the exchange on d5 wins a rook for a
+        // rook and a pawn.
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5"), 100);
+        // Without the second rook, white just loses a rook for a pawn.
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "Rxd5"), -400);
+    }
+
+    #[test]
+    fn king_only_recaptures_when_safe()
+    {
+        // The king can't take the rook on d7, since the d1 rook covers it through the d2 rook.
+        assert_eq!(see("4k3/8/8/8/8/8/3R4/3R2K1 w - - 0 1", "Rd7"), 0);
+        assert_eq!(see("4k3/8/8/8/8/8/3R4/6K1 w - - 0 1", "Rd7"), -500);
+    }
+
+    #[test]
+    fn en_passant_and_promotions_are_counted()
+    {
+        assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6"), 100);
+        assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q"), 800);
+        assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q"), -100);
+    }
+}
@@ -0,0 +1,76 @@
+//! This submodule works out x-ray attacks: the squares a slider would attack if the pieces in
+//! front of it moved out of the way.
+
+use crate::{bitboard::Bitboard, board::{bishop_attack_mask, rook_attack_mask, PieceType, Square}};
+
+use super::Board;
+
+impl Board
+{
+    /// Returns the squares a rook, bishop or queen on `square` attacks *through* the first piece
+    /// in each direction, when that piece is one of `blockers`. Only the extra squares are
+    /// returned, up to and including the next occupied square behind each blocker; the squares
+    /// the piece already attacks aren't.
+    ///
+    /// Passing the pieces of the slider's own color as `blockers` shows what a discovered attack
+    /// would hit, and passing the pieces of the other color shows what's behind the pieces the
+    /// slider attacks, i.e a king behind a pinned piece. Other piece types can't x-ray, so they
+    /// always get an empty mask.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square the slider is on. It doesn't have to actually be there.
+    /// * `piece_type` - The type of the slider.
+    /// * `blockers` - The pieces the slider may look through.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardConfiguration, PieceType, PlayerColor, Square};
+    /// // The white rook on a1 looks through the knight on a4 at the black king on a8.
+    /// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("k7/8/8/8/N7/8/4K3/R7 w - - 0 1").unwrap());
+    /// let a1 = Square::from_str("a1").unwrap();
+    /// let x_ray = board.x_ray_attacks(a1, PieceType::Rook, board.pieces_of_color(PlayerColor::White));
+    /// // a5, a6, a7 and a8.
+    /// assert_eq!(x_ray.count(), 4);
+    /// assert!(x_ray.squares().any(|square| square == Square::from_str("a8").unwrap()));
+    /// ```
+    pub fn x_ray_attacks(&self, square: Square, piece_type: PieceType, blockers: Bitboard) -> Bitboard
+    {
+        let attacks: fn(Square, Bitboard) -> Bitboard = match piece_type
+        {
+            PieceType::Rook => rook_attack_mask,
+            PieceType::Bishop => bishop_attack_mask,
+            PieceType::Queen => |square, occupied| rook_attack_mask(square, occupied) | bishop_attack_mask(square, occupied),
+            _ => return Bitboard::default(),
+        };
+        let occupied = self.query().result();
+        let direct = attacks(square, occupied);
+        // Take away the blockers the slider runs into, and see what it runs into next.
+        attacks(square, occupied ^ (direct & blockers)) & !direct
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::{between_mask, BoardConfiguration, PlayerColor};
+
+    use super::*;
+
+    #[test]
+    fn x_rays_through_enemy_pieces_find_pins()
+    {
+        // The black bishop on b4 attacks the knight on c3, with the king on e1 behind it.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/1b6/2N5/8/4K3 w - - 0 1").unwrap());
+        let (b4, e1) = (Square::from_str("b4").unwrap(), Square::from_str("e1").unwrap());
+        let x_ray = board.x_ray_attacks(b4, PieceType::Bishop, board.pieces_of_color(PlayerColor::White));
+        assert!(x_ray.squares().any(|square| square == e1));
+        // The pinned piece is the only one between the bishop and the king.
+        assert_eq!(between_mask(b4, e1) & board.query().result(), board.pinned_pieces(PlayerColor::White));
+        assert!(board.x_ray_attacks(b4, PieceType::Knight, board.query().result()).is_empty());
+    }
+}
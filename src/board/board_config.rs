@@ -1,10 +1,13 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{borrow::Borrow, collections::HashMap, fmt::Display};
 use std::str::FromStr;
 
 use getset::{CopyGetters, Getters};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
-use super::{Move, Piece, PieceType, PlayerColor, Square};
+use crate::parse::{square_to_algebraic, MoveCommand};
+
+use super::{ApplyMovesError, Board, Move, Piece, PieceType, PlayerColor, Square};
 
 /// A specified arrangement of pieces.
 ///
@@ -35,10 +38,10 @@ pub struct BoardConfiguration
     en_passant_target_square: Option<Square>,
     /// Gets the number of moves since the last capture or pawn push.
     #[getset(get_copy="pub")]
-    halfmove_clock: u8,
+    halfmove_clock: u16,
     /// Gets the total number of turns.
     #[getset(get_copy="pub")]
-    fullmove_number: u8,
+    fullmove_number: u16,
 }
 
 /// This struct records information on who can castle and where.
@@ -54,7 +57,7 @@ pub struct BoardConfiguration
 /// Note that this doesn't include information on *temporary* scenarios in which castling are
 /// prevented. If castling would put the king in check, the option is still available to the king
 /// later.
-#[derive(Debug, PartialEq, Clone, Copy, Getters, Eq, Hash)]
+#[derive(Debug, PartialEq, Clone, Copy, Getters, Eq, Hash, Serialize, Deserialize)]
 pub struct CastlingAvailability
 {
     /// Returns true if white is allowed to castle kingside.
@@ -118,20 +121,19 @@ impl CastlingAvailability
     ///
     /// What this entails is essentially the following:
     ///
-    /// - After the piece at 0,0 moves (for white) or 7,0 (for black), castling kingside is disabled
-    /// for that side.
-    /// - After the piece at 0,7 moves (for white) or 7,0 (for black), castling queenside is
-    /// disabled for that side.
-    /// - After the piece at 0,4 moves (for white) or 7,4 (for black), castling at all is disabled
-    /// on that side.
-    /// - Also, if a player castles, any further castling is disabled for that side.
+    /// - If a player castles, any further castling is disabled for that side.
+    /// - Any other move disables every castling right whose king or rook starts on the move's
+    ///   starting square or its target square. Moving the king off e1 (for white) or e8 (for
+    ///   black) disables castling at all for that side, moving a rook off its corner disables
+    ///   castling on that side of the board, and so does capturing a rook on its corner,
+    ///   whichever side made the capture.
     ///
     /// # Limitations
     ///
-    /// This function doesn't actually check what piece is being moved! Once castling has been
-    /// disabled (which *must* involve moving any of the involved pieces off their starting
-    /// square), we don't actually care what piece ends up there. You can't ever re-enable castling
-    /// after it's been disabled.
+    /// This function doesn't actually check what piece is being moved or captured! Once castling
+    /// has been disabled (which *must* involve moving any of the involved pieces off their
+    /// starting square), we don't actually care what piece ends up there. You can't ever
+    /// re-enable castling after it's been disabled.
     ///
     /// # Arguments
     ///
@@ -141,7 +143,8 @@ impl CastlingAvailability
     /// # Examples
     ///
     /// ```
-    /// # use rust_chess_engine::board::{CastlingAvailability, PlayerColor, Move, CastlingDirection};
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{CastlingAvailability, PlayerColor, Move, MoveData, CastlingDirection, Square};
     /// let mut castling_availability = CastlingAvailability::new(true, true, true, true);
     /// let r#move = Move::Castle(CastlingDirection::Kingside);
     /// castling_availability.update_with_move(PlayerColor::White, &r#move);
@@ -149,59 +152,67 @@ impl CastlingAvailability
     /// assert!(!castling_availability.white_castle_queenside());
     /// assert!(castling_availability.black_castle_kingside());
     /// assert!(castling_availability.black_castle_queenside());
+    ///
+    /// // Taking the rook on a8 means Black can't castle queenside any more.
+    /// let capture = Move::NormalMove(MoveData::new(Square::from_str("g2").unwrap(), Square::from_str("a8").unwrap(), true));
+    /// castling_availability.update_with_move(PlayerColor::White, &capture);
+    /// assert!(castling_availability.black_castle_kingside());
+    /// assert!(!castling_availability.black_castle_queenside());
     /// ```
     pub fn update_with_move(&mut self, moving_color: PlayerColor, r#move: &Move)
     {
-        match (moving_color, r#move)
+        match r#move
         {
-            (PlayerColor::White, Move::Castle(_)) => 
+            Move::Castle(_) => match moving_color
             {
-                self.white_castle_kingside = false;
-                self.white_castle_queenside = false;
+                PlayerColor::White =>
+                {
+                    self.white_castle_kingside = false;
+                    self.white_castle_queenside = false;
+                },
+                PlayerColor::Black =>
+                {
+                    self.black_castle_kingside = false;
+                    self.black_castle_queenside = false;
+                },
             },
-            (PlayerColor::Black, Move::Castle(_)) =>
+            Move::NormalMove(move_data) =>
             {
-                self.black_castle_kingside = false;
-                self.black_castle_queenside = false;
+                // Whatever leaves a square or lands on it, the piece that started there for
+                // castling isn't there any more.
+                self.remove_rights_touching(move_data.starting_square());
+                self.remove_rights_touching(move_data.target_square());
             },
-            (_, Move::NormalMove(move_data)) =>
+        }
+    }
+
+    // Disables every castling right that needs a king or rook still on its starting `square`.
+    fn remove_rights_touching(&mut self, square: Square)
+    {
+        for color in PlayerColor::both()
+        {
+            if square.rank != color.home_rank()
+            {
+                continue;
+            }
+            let (kingside, queenside) = match color
+            {
+                PlayerColor::White => (&mut self.white_castle_kingside, &mut self.white_castle_queenside),
+                PlayerColor::Black => (&mut self.black_castle_kingside, &mut self.black_castle_queenside),
+            };
+            match square.file
             {
-                match (moving_color, move_data.starting_square())
+                // The a-file rook castles queenside.
+                0 => *queenside = false,
+                // The h-file rook castles kingside.
+                7 => *kingside = false,
+                // The king castles both ways.
+                4 =>
                 {
-                    // If we move off of the starting rook square on the a-file at any point, we disable castling
-                    // queenside.
-                    (PlayerColor::White, Square { rank: 0, file: 0 }) => 
-                    {
-                        self.white_castle_queenside = false;
-                    },
-                    // If we move off of the starting rook square on the h-file at any point, we
-                    // disable castling kingside.
-                    (PlayerColor::White, Square { rank: 0, file: 7 }) =>
-                    {
-                        self.white_castle_kingside = false;
-                    },
-                    // If we ever move the king (even if we don't castle) we disable castling in
-                    // both directions.
-                    (PlayerColor::White, Square { rank: 0, file: 4 }) =>
-                    {
-                        self.white_castle_kingside = false;
-                        self.white_castle_queenside = false;
-                    },
-                    (PlayerColor::Black, Square { rank: 7, file: 0 }) => 
-                    {
-                        self.black_castle_queenside = false;
-                    },
-                    (PlayerColor::Black, Square { rank: 7, file: 7 }) =>
-                    {
-                        self.black_castle_kingside = false;
-                    },
-                    (PlayerColor::Black, Square { rank: 7, file: 4 }) =>
-                    {
-                        self.black_castle_kingside = false;
-                        self.black_castle_queenside = false;
-                    },
-                    (_, _) => (),
-                }
+                    *kingside = false;
+                    *queenside = false;
+                },
+                _ => (),
             }
         }
     }
@@ -226,9 +237,48 @@ impl Default for CastlingAvailability
 impl BoardConfiguration
 {
     /// Gets the pieces of the board as a hashmap.
+    #[deprecated(note = "use `pieces()` instead, which matches the other getters")]
     pub fn get_pieces(&self) -> &HashMap<Square, Piece>
     {
-        return &self.pieces;
+        &self.pieces
+    }
+
+    /// Returns the piece on the given square, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to look at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{BoardConfiguration, Piece, PieceType, PlayerColor, Square};
+    /// let board_config = BoardConfiguration::default();
+    /// assert_eq!(board_config.piece_at(&Square::from_str("e1").unwrap()), Some(Piece::new(PlayerColor::White, PieceType::King)));
+    /// assert_eq!(board_config.piece_at(&Square::from_str("e4").unwrap()), None);
+    /// ```
+    pub fn piece_at(&self, square: &Square) -> Option<Piece>
+    {
+        self.pieces.get(square).copied()
+    }
+
+    /// Returns an iterator over every piece on the board along with the square it's on, in no
+    /// particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{BoardConfiguration, PieceType, PlayerColor};
+    /// let board_config = BoardConfiguration::default();
+    /// let white_pawns = board_config.iter_pieces()
+    ///     .filter(|(_, piece)| piece.piece_type() == PieceType::Pawn && piece.color() == PlayerColor::White)
+    ///     .count();
+    /// assert_eq!(white_pawns, 8);
+    /// ```
+    pub fn iter_pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_
+    {
+        self.pieces.iter().map(|(square, piece)| (*square, *piece))
     }
 
     /// Creates a new instance of the [BoardConfiguration].
@@ -265,7 +315,7 @@ impl BoardConfiguration
     ///     CastlingAvailability::default(),
     ///     None, 0, 1);
     /// ```
-    pub fn new(pieces: HashMap<Square, Piece>, active_color: PlayerColor, castling_availability: CastlingAvailability, en_passant_target_square: Option<Square>, halfmove_clock: u8, fullmove_number: u8) -> Self
+    pub fn new(pieces: HashMap<Square, Piece>, active_color: PlayerColor, castling_availability: CastlingAvailability, en_passant_target_square: Option<Square>, halfmove_clock: u16, fullmove_number: u16) -> Self
     {
         Self
         {
@@ -277,13 +327,206 @@ impl BoardConfiguration
             fullmove_number,
         }
     }
+
+    /// Returns the configuration reached by making each of `move_commands` in order starting from
+    /// this configuration. See [Board::apply_moves].
+    ///
+    /// # Arguments
+    ///
+    /// * `move_commands` - The moves to make, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ApplyMovesError] with the index of the first move that couldn't be made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::BoardConfiguration;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let moves = [MoveCommand::from_str("e4").unwrap(), MoveCommand::from_str("c5").unwrap()];
+    /// let sicilian = BoardConfiguration::default().after_moves(&moves).unwrap();
+    /// assert_eq!(sicilian, BoardConfiguration::from_str("rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap());
+    /// ```
+    pub fn after_moves<I>(&self, move_commands: I) -> Result<BoardConfiguration, ApplyMovesError>
+    where I: IntoIterator, I::Item: Borrow<MoveCommand>
+    {
+        Ok(Board::new_board_with_configuration(self).apply_moves(move_commands)?.board_configuration())
+    }
+
+    /// Checks that the configuration could come up in a game of chess, and returns everything
+    /// that's wrong with it if it couldn't. Positions from users or over the network should be
+    /// validated before they're played on, since the rest of the crate assumes they make sense.
+    ///
+    /// # Errors
+    ///
+    /// Returns every [InvalidBoardConfigurationError] that applies, out of:
+    /// - A piece is placed off the board.
+    /// - Either player doesn't have exactly one king.
+    /// - A pawn is on the first or last rank.
+    /// - The en passant target square isn't one a pawn of the waiting player could have just
+    ///   skipped over: it has to be on that player's third rank, empty, with the pawn right in
+    ///   front of it and nothing on the square the pawn came from.
+    /// - A player is allowed to castle even though their king or rook isn't on its starting
+    ///   square.
+    /// - The waiting player is in check, which would mean they made a move that left their own
+    ///   king in check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{BoardConfiguration, InvalidBoardConfigurationError, PlayerColor};
+    /// assert_eq!(BoardConfiguration::default().validate(), Ok(()));
+    ///
+    /// // White can't castle queenside without a rook on a1, and with Black to move White can't be
+    /// // in check from the rook that's there instead.
+    /// let board_configuration = BoardConfiguration::from_str("4k3/8/8/8/8/8/8/r3K3 b Q - 0 1").unwrap();
+    /// assert_eq!(board_configuration.validate(), Err(vec![
+    ///     InvalidBoardConfigurationError::InvalidCastlingAvailability(PlayerColor::White),
+    ///     InvalidBoardConfigurationError::WaitingPlayerInCheck(PlayerColor::White),
+    /// ]));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<InvalidBoardConfigurationError>>
+    {
+        let pieces = &self.pieces;
+        let mut errors = Vec::new();
+
+        let out_of_bounds: Vec<Square> = pieces.keys().copied().filter(|square| !is_on_board(*square)).collect();
+        errors.extend(out_of_bounds.iter().map(|square| InvalidBoardConfigurationError::SquareOutOfBounds(*square)));
+        let mut wrong_number_of_kings = false;
+        for color in PlayerColor::both()
+        {
+            let kings = pieces.values().filter(|piece| **piece == Piece::new(color, PieceType::King)).count();
+            if kings != 1
+            {
+                wrong_number_of_kings = true;
+                errors.push(InvalidBoardConfigurationError::WrongNumberOfKings(color, kings));
+            }
+        }
+        errors.extend(pieces.iter()
+            .filter(|(square, piece)| piece.piece_type() == PieceType::Pawn && (square.rank == 0 || square.rank == 7))
+            .map(|(square, _)| InvalidBoardConfigurationError::PawnOnBackRank(*square)));
+        if let Some(square) = self.en_passant_target_square
+        {
+            if !self.is_plausible_en_passant_target_square(square)
+            {
+                errors.push(InvalidBoardConfigurationError::InvalidEnPassantTargetSquare(square));
+            }
+        }
+        let castling = self.castling_availability;
+        for (color, kingside, queenside) in [
+            (PlayerColor::White, castling.white_castle_kingside, castling.white_castle_queenside),
+            (PlayerColor::Black, castling.black_castle_kingside, castling.black_castle_queenside),
+        ]
+        {
+            let has = |piece_type, file| pieces.get(&Square::new(color.home_rank(), file)) == Some(&Piece::new(color, piece_type));
+            if (kingside && !(has(PieceType::King, 4) && has(PieceType::Rook, 7)))
+                || (queenside && !(has(PieceType::King, 4) && has(PieceType::Rook, 0)))
+            {
+                errors.push(InvalidBoardConfigurationError::InvalidCastlingAvailability(color));
+            }
+        }
+        // Looking for check needs a board, which only makes sense with the pieces on it and one
+        // king each.
+        let waiting_color = !self.active_color;
+        if out_of_bounds.is_empty() && !wrong_number_of_kings && !Board::new_board_with_configuration(self).checkers(waiting_color).is_empty()
+        {
+            errors.push(InvalidBoardConfigurationError::WaitingPlayerInCheck(waiting_color));
+        }
+
+        match errors.is_empty()
+        {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    // The en passant target square is the square a pawn of the waiting player just skipped over
+    // with a double push.
+    fn is_plausible_en_passant_target_square(&self, square: Square) -> bool
+    {
+        let mover = !self.active_color;
+        if !is_on_board(square) || mover.relative_rank(square) != 2 || self.pieces.contains_key(&square)
+        {
+            return false;
+        }
+        let pawn_square = square.offset(mover.pawn_direction(), 0);
+        let starting_square = square.offset(-mover.pawn_direction(), 0);
+        pawn_square.is_some_and(|pawn_square| self.pieces.get(&pawn_square) == Some(&Piece::new(mover, PieceType::Pawn)))
+            && starting_square.is_some_and(|starting_square| !self.pieces.contains_key(&starting_square))
+    }
+}
+
+impl From<&Board> for BoardConfiguration
+{
+    fn from(board: &Board) -> Self {
+        board.board_configuration()
+    }
 }
 
 impl Display for BoardConfiguration
 {
-    // This will print out the FEN notation of a board configuration.
+    /// Writes the configuration as a
+    /// [FEN](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation) record, with all six
+    /// fields.
+    ///
+    /// The output can always be read back with [BoardConfiguration::from_str], giving an equal
+    /// configuration, so FEN strings are a safe way to send positions to another program or
+    /// over the network.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::BoardConfiguration;
+    /// let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+    /// let board_configuration = BoardConfiguration::from_str(fen).unwrap();
+    /// assert_eq!(board_configuration.to_string(), fen);
+    /// assert_eq!(BoardConfiguration::from_str(&board_configuration.to_string()).unwrap(), board_configuration);
+    /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+        for rank in (0..8).rev()
+        {
+            let mut empty_squares = 0;
+            for file in 0..8
+            {
+                match self.pieces.get(&Square::new(rank, file))
+                {
+                    Some(piece) =>
+                    {
+                        if empty_squares > 0
+                        {
+                            write!(f, "{}", empty_squares)?;
+                            empty_squares = 0;
+                        }
+                        write!(f, "{}", piece)?;
+                    },
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0
+            {
+                write!(f, "{}", empty_squares)?;
+            }
+            if rank > 0
+            {
+                write!(f, "/")?;
+            }
+        }
+
+        let active_color = match self.active_color
+        {
+            PlayerColor::White => "w",
+            PlayerColor::Black => "b",
+        };
+        let en_passant = match self.en_passant_target_square
+        {
+            Some(square) => square_to_algebraic(square),
+            None => "-".to_string(),
+        };
+        write!(f, " {} {} {} {} {}", active_color, self.castling_availability, en_passant, self.halfmove_clock, self.fullmove_number)
     }
 }
 
@@ -402,8 +645,8 @@ impl FromStr for BoardConfiguration
             x => Some(x.parse().map_err(|_| InvalidFENError::InvalidEnPassantTargetSquare(x.to_string(), s.to_string()))?)
         };
 
-        let half_move_clock: u8 = half_move_clock.parse().map_err(|_| InvalidFENError::InvalidHalfMoveClock(half_move_clock.to_string()))?;
-        let full_move_number: u8 = full_move_number.parse().map_err(|_| InvalidFENError::InvalidFullMoveNumber(full_move_number.to_string()))?;
+        let half_move_clock: u16 = half_move_clock.parse().map_err(|_| InvalidFENError::InvalidHalfMoveClock(half_move_clock.to_string()))?;
+        let full_move_number: u16 = full_move_number.parse().map_err(|_| InvalidFENError::InvalidFullMoveNumber(full_move_number.to_string()))?;
 
         Ok(Self
         {
@@ -417,6 +660,25 @@ impl FromStr for BoardConfiguration
     }
 }
 
+impl Serialize for BoardConfiguration
+{
+    /// Serializes the configuration as its FEN record, the same way as a
+    /// [Board](super::Board).
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoardConfiguration
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let fen = String::deserialize(deserializer)?;
+        Self::from_str(&fen).map_err(de::Error::custom)
+    }
+}
+
 
 /// Parses the section of the FEN responsible for describing the piece positions on the board
 /// and returns a HashMap<Square, Piece>, where the key is the square on which a piece rests,
@@ -455,22 +717,20 @@ fn parse_pieces(s: &str) -> Result<HashMap<Square, Piece>, InvalidFENError>
             {
                 let num_blank_spaces: u8 = char.to_digit(10).expect("to_digit() failed after asserting that char.is_ascii_digit()").try_into().expect("Somehow converting a single digit u32 to a u8 failed???");
                 current_file += num_blank_spaces;
+                // Something like "9" or "44P" runs off the end of the rank.
+                if current_file > 8
+                {
+                    return Err(InvalidFENError::TooManyFiles(rank, current_file, s.to_string()));
+                }
                 continue;
             }
 
-            // Otherwise we assume it is a piece type.
+            // Otherwise we assume it is a piece.
             // Upper case pieces are white, lowercase is black
-            let piece_color = match char.is_uppercase()
-            {
-                true => PlayerColor::White,
-                false => PlayerColor::Black,
-            };
-
-            let piece_type: PieceType = char.to_string().parse().map_err(|_| InvalidFENError::InvalidPieceCharacter(char.to_string(), s.to_string()))?;
+            let piece: Piece = char.to_string().parse().map_err(|_| InvalidFENError::InvalidPieceCharacter(char.to_string(), s.to_string()))?;
 
-            // Create the new piece and add it to the board.
+            // Add the new piece to the board.
             let square = Square::new(rank, current_file);
-            let piece = Piece::new(piece_color, piece_type);
             map.insert(square, piece);
             // Move to next square
             current_file += 1;
@@ -493,6 +753,26 @@ fn parse_pieces(s: &str) -> Result<HashMap<Square, Piece>, InvalidFENError>
     Ok(map)
 }
 
+impl Display for CastlingAvailability
+{
+    /// Writes the castling availability as it appears in FEN, i.e "KQkq", or "-" if nobody can
+    /// castle.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flags = [
+            (self.white_castle_kingside, 'K'),
+            (self.white_castle_queenside, 'Q'),
+            (self.black_castle_kingside, 'k'),
+            (self.black_castle_queenside, 'q'),
+        ];
+        let castling: String = flags.iter().filter(|(allowed, _)| *allowed).map(|(_, flag)| flag).collect();
+        match castling.is_empty()
+        {
+            true => write!(f, "-"),
+            false => write!(f, "{}", castling),
+        }
+    }
+}
+
 impl FromStr for CastlingAvailability
 {
     type Err = InvalidFENError;
@@ -582,12 +862,42 @@ pub struct BoardConfigurationBuilder
     active_color: Option<PlayerColor>,
     castling_availability: Option<CastlingAvailability>,
     en_passant_target_square: Option<Square>,
-    halfmove_clock: Option<u8>,
-    fullmove_number: Option<u8>,
+    halfmove_clock: Option<u16>,
+    fullmove_number: Option<u16>,
 }
 
 impl BoardConfigurationBuilder
 {
+    /// Creates a builder that starts out with every field taken from the given
+    /// [FEN](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation) record, so that
+    /// individual fields can be tweaked before building.
+    ///
+    /// # Arguments
+    ///
+    /// * `fen` - A valid FEN string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [InvalidFENError] if the FEN record is invalid, see [BoardConfiguration::from_str].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{BoardConfigurationBuilder, PlayerColor};
+    /// // The starting position, but with Black to move.
+    /// let board_configuration = BoardConfigurationBuilder::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+    ///     .unwrap()
+    ///     .set_active_color(PlayerColor::Black)
+    ///     .try_build()
+    ///     .unwrap();
+    /// assert_eq!(board_configuration.active_color(), PlayerColor::Black);
+    /// assert_eq!(board_configuration.pieces().len(), 32);
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidFENError>
+    {
+        Ok(BoardConfiguration::from_str(fen)?.into())
+    }
+
     /// Sets the list of pieces included with the board.
     ///
     /// This will replace any previously added pieces, creating an entirely new map of pieces.
@@ -652,7 +962,7 @@ impl BoardConfigurationBuilder
     /// advance.
     ///
     /// If this method is not called, it will default to 0.
-    pub fn set_halfmove_clock(mut self, halfmove_clock: u8) -> Self
+    pub fn set_halfmove_clock(mut self, halfmove_clock: u16) -> Self
     {
         self.halfmove_clock = Some(halfmove_clock);
         self
@@ -661,7 +971,7 @@ impl BoardConfigurationBuilder
     /// Sets how many total moves (1 for each 2 turns).
     ///
     /// Defaults to 1.
-    pub fn set_fullmove_number(mut self, fullmove_number: u8) -> Self
+    pub fn set_fullmove_number(mut self, fullmove_number: u16) -> Self
     {
         self.fullmove_number = Some(fullmove_number);
         self
@@ -682,6 +992,81 @@ impl BoardConfigurationBuilder
             fullmove_number: self.fullmove_number.unwrap_or(1)
         }
     }
+
+    /// Like [Self::build] but checks that the resulting [BoardConfiguration] makes sense before
+    /// returning it, the same way a position set up on a real board would have to. See
+    /// [BoardConfiguration::validate] for everything that's checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [InvalidBoardConfigurationError] [BoardConfiguration::validate] finds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{BoardConfigurationBuilder, Piece, PieceType, PlayerColor, Square};
+    /// // No kings at all!
+    /// let result = BoardConfigurationBuilder::default()
+    ///     .add_piece(Piece::new(PlayerColor::White, PieceType::Queen), Square::new(3, 3))
+    ///     .try_build();
+    /// assert!(result.is_err());
+    /// ```
+    pub fn try_build(self) -> Result<BoardConfiguration, InvalidBoardConfigurationError>
+    {
+        let board_configuration = self.build();
+        match board_configuration.validate()
+        {
+            Ok(()) => Ok(board_configuration),
+            Err(mut errors) => Err(errors.swap_remove(0)),
+        }
+    }
+}
+
+impl From<BoardConfiguration> for BoardConfigurationBuilder
+{
+    fn from(board_configuration: BoardConfiguration) -> Self {
+        Self
+        {
+            pieces: Some(board_configuration.pieces),
+            active_color: Some(board_configuration.active_color),
+            castling_availability: Some(board_configuration.castling_availability),
+            en_passant_target_square: board_configuration.en_passant_target_square,
+            halfmove_clock: Some(board_configuration.halfmove_clock),
+            fullmove_number: Some(board_configuration.fullmove_number),
+        }
+    }
+}
+
+/// Represents an error returned by [BoardConfiguration::validate] and
+/// [BoardConfigurationBuilder::try_build] when a configuration couldn't happen in a game of chess.
+#[derive(Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum InvalidBoardConfigurationError
+{
+    /// A piece was placed on a square that isn't on the board.
+    #[error("{0:?} is not on the board")]
+    SquareOutOfBounds(Square),
+    /// A player has no king, or more than one.
+    #[error("{0:?} has {1} kings instead of exactly one")]
+    WrongNumberOfKings(PlayerColor, usize),
+    /// A pawn is on the first or last rank, where pawns can never be.
+    #[error("There is a pawn on the back rank at {0:?}")]
+    PawnOnBackRank(Square),
+    /// The en passant target square isn't one a pawn could have just skipped over.
+    #[error("{0:?} can't be the en passant target square with this player to move")]
+    InvalidEnPassantTargetSquare(Square),
+    /// A player can castle even though their king or rook has left its starting square.
+    #[error("{0:?} is allowed to castle but their king or rook isn't on its starting square")]
+    InvalidCastlingAvailability(PlayerColor),
+    /// The player who just moved is in check, so their last move must have been illegal.
+    #[error("{0:?} is in check even though it isn't their move")]
+    WaitingPlayerInCheck(PlayerColor),
+}
+
+// Returns true if `square` is on the 8x8 board.
+fn is_on_board(square: Square) -> bool
+{
+    square.rank < 8 && square.file < 8
 }
 
 
@@ -748,7 +1133,7 @@ mod tests
         let board_config = BoardConfiguration::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
         let mut hashmap: HashMap<Square, Piece> = HashMap::new();
         add_all_pieces_to_map(&mut hashmap);
-        assert_eq!(*board_config.get_pieces(), hashmap);
+        assert_eq!(*board_config.pieces(), hashmap);
     }
 
     #[test]
@@ -756,7 +1141,7 @@ mod tests
     {
         let board_config = BoardConfiguration::from_str("8/8/8/8/8/8/8/8 w KQkq - 0 1").unwrap();
         let hashmap: HashMap<Square, Piece> = HashMap::new();
-        assert_eq!(*board_config.get_pieces(), hashmap);
+        assert_eq!(*board_config.pieces(), hashmap);
     }
 
     #[test]
@@ -772,6 +1157,17 @@ mod tests
         assert!(board_config.is_err());
     }
 
+    #[test]
+    fn test_large_move_counters()
+    {
+        let board_config = BoardConfiguration::from_str("8/8/8/8/8/8/8/8 w - - 150 300").unwrap();
+        assert_eq!(board_config.halfmove_clock(), 150);
+        assert_eq!(board_config.fullmove_number(), 300);
+        // Counters that don't fit in a u16 are rejected rather than wrapped.
+        let board_config = BoardConfiguration::from_str("8/8/8/8/8/8/8/8 w - - 0 70000");
+        assert!(matches!(board_config, Err(InvalidFENError::InvalidFullMoveNumber(_))));
+    }
+
     #[test]
     fn castling_availability_parse_1()
     {
@@ -820,4 +1216,122 @@ mod tests
         let castling_availability = CastlingAvailability::from_str(":");
         assert!(castling_availability.is_err());
     }
+
+    #[test]
+    fn try_build_accepts_the_starting_position()
+    {
+        let board_config = BoardConfigurationBuilder::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
+            .try_build()
+            .unwrap();
+        assert_eq!(board_config, BoardConfiguration::default());
+    }
+
+    #[test]
+    fn try_build_rejects_pawns_on_the_back_rank()
+    {
+        let result = BoardConfigurationBuilder::from_fen("4k3/8/8/8/8/8/8/P3K3 w - - 0 1").unwrap().try_build();
+        assert_eq!(result.unwrap_err(), InvalidBoardConfigurationError::PawnOnBackRank(Square::new(0, 0)));
+    }
+
+    #[test]
+    fn try_build_rejects_castling_without_a_rook()
+    {
+        let result = BoardConfigurationBuilder::from_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1").unwrap().try_build();
+        assert_eq!(result.unwrap_err(), InvalidBoardConfigurationError::InvalidCastlingAvailability(PlayerColor::White));
+    }
+
+    #[test]
+    fn try_build_checks_en_passant_rank()
+    {
+        let builder = BoardConfigurationBuilder::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+        assert!(builder.clone().set_en_passant_target_square(Square::new(2, 4)).try_build().is_ok());
+        assert_eq!(builder.set_en_passant_target_square(Square::new(5, 4)).try_build().unwrap_err(),
+            InvalidBoardConfigurationError::InvalidEnPassantTargetSquare(Square::new(5, 4)));
+    }
+
+    #[test]
+    fn validate_reports_every_problem()
+    {
+        let board_config = BoardConfiguration::from_str("P3k3/8/8/8/8/8/8/8 w KQkq - 0 1").unwrap();
+        let errors = board_config.validate().unwrap_err();
+        assert_eq!(errors, vec![
+            InvalidBoardConfigurationError::WrongNumberOfKings(PlayerColor::White, 0),
+            InvalidBoardConfigurationError::PawnOnBackRank(Square::new(7, 0)),
+            InvalidBoardConfigurationError::InvalidCastlingAvailability(PlayerColor::White),
+            InvalidBoardConfigurationError::InvalidCastlingAvailability(PlayerColor::Black),
+        ]);
+    }
+
+    #[test]
+    fn validate_needs_a_pawn_in_front_of_the_en_passant_square()
+    {
+        assert!(BoardConfiguration::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap().validate().is_ok());
+        // No black pawn on d5.
+        assert_eq!(BoardConfiguration::from_str("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 2").unwrap().validate(),
+            Err(vec![InvalidBoardConfigurationError::InvalidEnPassantTargetSquare(Square::new(5, 3))]));
+        // Something is on d7, so the pawn couldn't have come from there.
+        assert_eq!(BoardConfiguration::from_str("4k3/3n4/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap().validate(),
+            Err(vec![InvalidBoardConfigurationError::InvalidEnPassantTargetSquare(Square::new(5, 3))]));
+    }
+
+    #[test]
+    fn validate_rejects_the_waiting_player_in_check()
+    {
+        assert!(BoardConfiguration::from_str("4k3/8/8/8/8/8/8/4K2r w - - 0 1").unwrap().validate().is_ok());
+        assert_eq!(BoardConfiguration::from_str("4k3/8/8/8/8/8/8/4K2r b - - 0 1").unwrap().validate(),
+            Err(vec![InvalidBoardConfigurationError::WaitingPlayerInCheck(PlayerColor::White)]));
+    }
+
+    #[test]
+    fn display_writes_fen()
+    {
+        assert_eq!(BoardConfiguration::default().to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let fen = "r3k3/8/8/3pP3/8/8/8/4K2R w Kq d6 0 23";
+        assert_eq!(BoardConfiguration::from_str(fen).unwrap().to_string(), fen);
+    }
+
+    #[test]
+    fn fen_round_trips_through_games()
+    {
+        // Play the first few legal moves from a handful of positions and make sure every
+        // position along the way survives being written out and read back in.
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/1P6/8/8/8/8/6p1/4K3 b - - 12 80",
+        ];
+        for fen in fens
+        {
+            let mut board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+            for ply in 0..6
+            {
+                let board_configuration = board.board_configuration();
+                let written = board_configuration.to_string();
+                assert_eq!(BoardConfiguration::from_str(&written).unwrap(), board_configuration, "{written}");
+                let moves = board.generate_moves_for_side(board.active_color());
+                let Some(r#move) = moves.get(ply % moves.len().max(1)) else { break };
+                board = board.attempt_move(r#move).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn ranks_that_run_off_the_board_are_rejected()
+    {
+        assert!(matches!(BoardConfiguration::from_str("9/8/8/8/8/8/8/8 w - - 0 1"), Err(InvalidFENError::TooManyFiles(7, 9, _))));
+        assert!(matches!(BoardConfiguration::from_str("4k3/8/8/8/8/8/8/44K w - - 0 1"), Err(InvalidFENError::TooManyFiles(0, _, _))));
+    }
+
+    #[test]
+    fn test_serde_round_trip()
+    {
+        let configuration = BoardConfiguration::from_str("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let bytes = postcard::to_allocvec(&configuration).unwrap();
+        assert_eq!(postcard::from_bytes::<BoardConfiguration>(&bytes).unwrap(), configuration);
+
+        let piece = Piece::new(PlayerColor::Black, PieceType::Knight);
+        let bytes = postcard::to_allocvec(&(piece, configuration.castling_availability())).unwrap();
+        assert_eq!(postcard::from_bytes::<(Piece, CastlingAvailability)>(&bytes).unwrap(), (piece, configuration.castling_availability()));
+    }
 }
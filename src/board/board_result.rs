@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::PlayerColor;
@@ -17,7 +18,7 @@ pub enum GetWinnerError
 /// The current game outcome. A game still in progress is [BoardResult::InProgress],
 /// while a game that has ended will have one of various enum values recording the game outcome,
 /// whether it was a victory for one of the players or one of various draw conditions.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum BoardResult
 {
     /// A game is still in progress.
@@ -26,22 +27,33 @@ pub enum BoardResult
     Win(PlayerColor),
     /// The game was a draw, the reason is recorded in [DrawReason]
     Draw(DrawReason),
+    /// The specified color ran out of time, so the other color wins.
+    LossOnTime(PlayerColor),
 }
 
 /// If the game is a draw, this enum records the reason for the draw.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum DrawReason
 {
     /// A draw where the same board position was reached three times.
     ThreefoldRepitition,
     /// A draw where 50 moves have been made without a pawn move or capture.
     FiftyMoveRule,
+    /// A draw where the same board position was reached five times, which ends the game without
+    /// either player claiming it.
+    FivefoldRepetition,
+    /// A draw where 75 moves have been made without a pawn move or capture, which ends the game
+    /// without either player claiming it.
+    SeventyFiveMoveRule,
     /// A dead position where neither player can possibly give checkmate.
     CheckmateImpossible,
     /// A stalemate, where one player has no legal moves but is not currently in check.
     Stalemate,
     /// A draw where both players have agreed to draw.
-    Agreement
+    Agreement,
+    /// A draw where one player ran out of time, but the other player doesn't have enough
+    /// material left to checkmate with.
+    TimeoutVsInsufficientMaterial,
 }
 
 impl BoardResult
@@ -73,6 +85,7 @@ impl BoardResult
         match self
         {
             Self::Win(_) => true,
+            Self::LossOnTime(_) => true,
             Self::InProgress => false,
             Self::Draw(_) => false,
         }
@@ -85,6 +98,7 @@ impl BoardResult
         match self
         {
             Self::Win(color) => Ok(*color),
+            Self::LossOnTime(color) => Ok(!*color),
             Self::InProgress => Err(GetWinnerError::StillInProgress),
             Self::Draw(x) => Err(GetWinnerError::Draw(*x)),
         }
@@ -109,6 +123,7 @@ impl BoardResult
         {
             Self::InProgress => None,
             Self::Win(_) => None,
+            Self::LossOnTime(_) => None,
             // Trivial copy here.
             Self::Draw(x) => Some(*x),
         }
@@ -145,6 +160,7 @@ mod tests
         assert!(BoardResult::Draw(DrawReason::Agreement).get_winner().is_err());
         assert_eq!(Ok(PlayerColor::White), BoardResult::Win(PlayerColor::White).get_winner());
         assert_eq!(Ok(PlayerColor::Black), BoardResult::Win(PlayerColor::Black).get_winner());
+        assert_eq!(Ok(PlayerColor::Black), BoardResult::LossOnTime(PlayerColor::White).get_winner());
     }
 
     #[test]
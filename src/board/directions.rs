@@ -0,0 +1,23 @@
+//! The directions the sliding pieces move in, as (rank, file) steps, for everything that walks
+//! along ranks, files and diagonals.
+
+/// The directions a rook slides in.
+pub(crate) const ORTHOGONAL_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+/// The directions a bishop slides in.
+pub(crate) const DIAGONAL_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+/// The directions a queen slides in: the [rook's](ORTHOGONAL_DIRECTIONS), then the
+/// [bishop's](DIAGONAL_DIRECTIONS).
+pub(crate) const QUEEN_DIRECTIONS: [(i8, i8); 8] = queen_directions();
+
+const fn queen_directions() -> [(i8, i8); 8]
+{
+    let mut directions = [(0, 0); 8];
+    let mut i = 0;
+    while i < 4
+    {
+        directions[i] = ORTHOGONAL_DIRECTIONS[i];
+        directions[i + 4] = DIAGONAL_DIRECTIONS[i];
+        i += 1;
+    }
+    directions
+}
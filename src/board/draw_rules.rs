@@ -0,0 +1,153 @@
+use getset::CopyGetters;
+use serde::{Deserialize, Serialize};
+
+use super::DrawReason;
+
+/// When a game is drawn because it isn't going anywhere: after a long stretch without a pawn move
+/// or capture, or after the same position keeps coming up.
+///
+/// Each rule has two thresholds. Once the first is reached either player can
+/// [claim](crate::game::GameState::claimable_draw) a draw, and once the second is reached the game
+/// is drawn whether anyone claims it or not. The [default](Self::default) follows the FIDE laws:
+/// a draw can be claimed after fifty moves by each side (100 ply) or a threefold repetition, and
+/// happens automatically after seventy-five moves by each side (150 ply) or a fivefold
+/// repetition.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::{DrawReason, DrawRules};
+/// let draw_rules = DrawRules::default();
+/// assert_eq!(draw_rules.claimable_draw(100, 1), Some(DrawReason::FiftyMoveRule));
+/// assert_eq!(draw_rules.automatic_draw(100, 1), None);
+/// assert_eq!(draw_rules.automatic_draw(150, 1), Some(DrawReason::SeventyFiveMoveRule));
+/// assert_eq!(draw_rules.automatic_draw(0, 5), Some(DrawReason::FivefoldRepetition));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CopyGetters, Serialize, Deserialize)]
+#[getset(get_copy="pub")]
+pub struct DrawRules
+{
+    /// Gets how many ply without a pawn move or capture it takes before either player can claim
+    /// a draw.
+    claimable_halfmoves: u16,
+    /// Gets how many ply without a pawn move or capture it takes before the game is drawn
+    /// automatically.
+    automatic_halfmoves: u16,
+    /// Gets how many times the same position has to come up before either player can claim a
+    /// draw.
+    claimable_repetitions: usize,
+    /// Gets how many times the same position has to come up before the game is drawn
+    /// automatically.
+    automatic_repetitions: usize,
+}
+
+impl Default for DrawRules
+{
+    /// The FIDE draw rules: fifty moves or a threefold repetition to claim a draw, seventy-five
+    /// moves or a fivefold repetition for an automatic draw.
+    fn default() -> Self {
+        Self
+        {
+            claimable_halfmoves: 100,
+            automatic_halfmoves: 150,
+            claimable_repetitions: 3,
+            automatic_repetitions: 5,
+        }
+    }
+}
+
+impl DrawRules
+{
+    /// Sets how many ply (half-moves) without a pawn move or capture it takes before either
+    /// player can claim a draw, and before the game is drawn automatically. Note that a *move*
+    /// in the fifty-move rule is one by each side, so it's 100 ply.
+    ///
+    /// # Arguments
+    ///
+    /// * `claimable_halfmoves` - How many ply before a draw can be claimed.
+    /// * `automatic_halfmoves` - How many ply before the game is drawn automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::DrawRules;
+    /// // Draw automatically after fifty moves, without waiting for a claim.
+    /// let draw_rules = DrawRules::default().with_halfmoves(100, 100);
+    /// assert!(draw_rules.automatic_draw(100, 1).is_some());
+    /// ```
+    pub fn with_halfmoves(mut self, claimable_halfmoves: u16, automatic_halfmoves: u16) -> Self
+    {
+        self.claimable_halfmoves = claimable_halfmoves;
+        self.automatic_halfmoves = automatic_halfmoves;
+        self
+    }
+
+    /// Sets how many times the same position has to come up before either player can claim a
+    /// draw, and before the game is drawn automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `claimable_repetitions` - How many repetitions before a draw can be claimed.
+    /// * `automatic_repetitions` - How many repetitions before the game is drawn automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::DrawRules;
+    /// // End the game on the first threefold repetition, like engine matches often do.
+    /// let draw_rules = DrawRules::default().with_repetitions(3, 3);
+    /// assert!(draw_rules.automatic_draw(0, 3).is_some());
+    /// ```
+    pub fn with_repetitions(mut self, claimable_repetitions: usize, automatic_repetitions: usize) -> Self
+    {
+        self.claimable_repetitions = claimable_repetitions;
+        self.automatic_repetitions = automatic_repetitions;
+        self
+    }
+
+    /// Returns why the game is drawn automatically, or [None] if it isn't.
+    ///
+    /// # Arguments
+    ///
+    /// * `halfmove_clock` - The number of ply since the last pawn move or capture, see
+    ///   [Board::halfmove_clock](crate::board::Board::halfmove_clock).
+    /// * `repetitions` - How many times the current position has come up, counting this time.
+    pub fn automatic_draw(&self, halfmove_clock: u16, repetitions: usize) -> Option<DrawReason>
+    {
+        if repetitions >= self.automatic_repetitions
+        {
+            Some(DrawReason::FivefoldRepetition)
+        }
+        else if halfmove_clock >= self.automatic_halfmoves
+        {
+            Some(DrawReason::SeventyFiveMoveRule)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Returns the reason either player could claim a draw, or [None] if they can't.
+    ///
+    /// # Arguments
+    ///
+    /// * `halfmove_clock` - The number of ply since the last pawn move or capture, see
+    ///   [Board::halfmove_clock](crate::board::Board::halfmove_clock).
+    /// * `repetitions` - How many times the current position has come up, counting this time.
+    pub fn claimable_draw(&self, halfmove_clock: u16, repetitions: usize) -> Option<DrawReason>
+    {
+        if repetitions >= self.claimable_repetitions
+        {
+            Some(DrawReason::ThreefoldRepitition)
+        }
+        else if halfmove_clock >= self.claimable_halfmoves
+        {
+            Some(DrawReason::FiftyMoveRule)
+        }
+        else
+        {
+            None
+        }
+    }
+}
@@ -2,6 +2,10 @@
 
 use thiserror::Error;
 
+use crate::parse::{CheckAnnotation, NotationParseError};
+
+use super::{InvalidBoardConfigurationError, Square};
+
 /// Errors that are returned by the board when attempting to make an invalid move.
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -17,4 +21,74 @@ pub enum MoveError
     /// The error returned when we found more than one possible move.
     #[error("Too many moves found that matched the command given! Do you need to add a discriminant?")]
     TooManyMoves,
+    /// The error returned when a [Move](super::Move) expects a piece to be on a square but the
+    /// square is empty or holds the wrong piece, i.e the move doesn't fit the board it was made
+    /// on at all. This can happen with moves received from a buggy or malicious remote peer.
+    #[error("The move expected a different piece on {0:?}")]
+    MissingPiece(Square),
+    /// The error returned when a pawn reaches the last rank without saying what to promote to,
+    /// when it tries to promote to a pawn or king, or when a move that isn't a pawn reaching the
+    /// last rank asks for a promotion.
+    #[error("Invalid promotion: a pawn reaching the last rank must promote to a knight, bishop, rook or queen")]
+    InvalidPromotion,
+    /// The error returned when a move is legal in standard chess, but not in the variant being
+    /// played, see [Rules::allows_move](crate::rules::Rules::allows_move).
+    #[error("The move isn't allowed by the rules of this variant")]
+    NotAllowedByRules,
+}
+
+/// The error returned when a move given in algebraic notation can't be made, see
+/// [Board::attempt_move_san](super::Board::attempt_move_san).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SanMoveError
+{
+    /// The move isn't valid algebraic notation.
+    #[error("Couldn't parse the move: {0}")]
+    Parse(#[from] NotationParseError),
+    /// The move was parsed, but is impossible or illegal on the board.
+    #[error("Couldn't make the move: {0}")]
+    Move(#[from] MoveError),
+    /// The move was written with the wrong check or checkmate suffix (or without one it needed),
+    /// see [Board::attempt_move_san_strict](super::Board::attempt_move_san_strict).
+    #[error("The move should be annotated with {expected:?}, but was annotated with {found:?}")]
+    WrongCheckAnnotation
+    {
+        /// The suffix the move should have had.
+        expected: Option<CheckAnnotation>,
+        /// The suffix the move was written with.
+        found: Option<CheckAnnotation>,
+    },
+}
+
+/// The error returned when applying a sequence of moves fails partway through.
+#[derive(Debug, Error)]
+#[error("Move {index} could not be made: {error}")]
+pub struct ApplyMovesError
+{
+    /// The index (starting at 0) of the first move that failed.
+    pub index: usize,
+    /// Why that move failed.
+    pub error: MoveError,
+}
+
+/// The error returned when a position encoded with [Board::encode](super::Board::encode) can't be
+/// decoded.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DecodePositionError
+{
+    /// The encoded position isn't [ENCODED_POSITION_LENGTH](super::ENCODED_POSITION_LENGTH) bytes
+    /// long.
+    #[error("Expected an encoded position to be {expected} bytes long, got {0}", expected = super::ENCODED_POSITION_LENGTH)]
+    WrongLength(usize),
+    /// A square holds a nibble that isn't a piece.
+    #[error("Invalid piece {0} on {1:?}")]
+    InvalidPiece(u8, Square),
+    /// The en passant target square isn't on the board.
+    #[error("Invalid en passant target square index {0}")]
+    InvalidEnPassantSquare(u8),
+    /// The decoded position isn't a valid board.
+    #[error("The decoded position is invalid: {0}")]
+    InvalidConfiguration(#[from] InvalidBoardConfigurationError),
 }
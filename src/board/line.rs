@@ -3,6 +3,7 @@
 use std::str::FromStr;
 
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 use crate::{parse::{alphabetic_file_to_numeric, rank_to_numeric, NotationParseError}, UInt};
 
@@ -15,7 +16,7 @@ use super::Square;
 /// In chess, when this happens, we use either the rank or file to distinguish between them.
 /// In exceedingly rare situations we may require both the rank and file to fully distinguish
 /// between them.
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum Line {
     /// This line represents a rank of squares of the given index.
     Rank(UInt),
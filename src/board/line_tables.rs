@@ -0,0 +1,159 @@
+//! Precomputed masks of the squares between and along pairs of squares.
+//!
+//! Pin detection, check evasion and x-ray attacks all come down to asking which squares lie
+//! between two squares, or which line runs through them. There are only 64×64 pairs, so the
+//! answers are worked out at compile time and looked up by [between_mask] and [line_mask].
+
+use crate::bitboard::Bitboard;
+
+use super::{directions::QUEEN_DIRECTIONS, Square};
+
+/// The squares strictly between two squares, indexed by the two square indices.
+static BETWEEN: [[u64; 64]; 64] = build_tables().0;
+/// The whole rank, file or diagonal through two squares, indexed by the two square indices.
+static LINE: [[u64; 64]; 64] = build_tables().1;
+
+/// Returns the squares strictly between `from` and `to` if they're on the same rank, file or
+/// diagonal, or an empty mask if they aren't (or are the same square).
+///
+/// These are the squares a piece has to go to to block a check from a slider on `from` to a
+/// king on `to`.
+///
+/// # Arguments
+///
+/// * `from` - One of the squares.
+/// * `to` - The other square.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{between_mask, Square};
+/// let between = between_mask(Square::from_str("a1").unwrap(), Square::from_str("d4").unwrap());
+/// assert_eq!(between.squares().collect::<Vec<_>>(), vec![Square::from_str("b2").unwrap(), Square::from_str("c3").unwrap()]);
+/// // A knight's move away isn't on a line.
+/// assert!(between_mask(Square::from_str("a1").unwrap(), Square::from_str("b3").unwrap()).is_empty());
+/// ```
+pub fn between_mask(from: Square, to: Square) -> Bitboard
+{
+    Bitboard::new(BETWEEN[from.index() as usize][to.index() as usize])
+}
+
+/// Returns every square on the rank, file or diagonal running through `from` and `to`, edge to
+/// edge and including both of them, or an empty mask if they aren't on a line (or are the same
+/// square).
+///
+/// A pinned piece can only move along the line through itself and its king.
+///
+/// # Arguments
+///
+/// * `from` - One of the squares.
+/// * `to` - The other square.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::bitboard::Bitboard;
+/// # use rust_chess_engine::board::{line_mask, Square};
+/// let line = line_mask(Square::from_str("c3").unwrap(), Square::from_str("e5").unwrap());
+/// // The long diagonal.
+/// assert_eq!(line, Bitboard::new(0x8040_2010_0804_0201));
+/// ```
+pub fn line_mask(from: Square, to: Square) -> Bitboard
+{
+    Bitboard::new(LINE[from.index() as usize][to.index() as usize])
+}
+
+const fn build_tables() -> ([[u64; 64]; 64], [[u64; 64]; 64])
+{
+    let mut between = [[0; 64]; 64];
+    let mut line = [[0; 64]; 64];
+    let mut from = 0;
+    while from < 64
+    {
+        let mut direction = 0;
+        while direction < QUEEN_DIRECTIONS.len()
+        {
+            let (rank_step, file_step) = QUEEN_DIRECTIONS[direction];
+            let full_line = (1 << from) | ray(from, rank_step, file_step) | ray(from, -rank_step, -file_step);
+            let mut passed = 0;
+            let mut rank = (from / 8) as i8 + rank_step;
+            let mut file = (from % 8) as i8 + file_step;
+            while is_on_board(rank, file)
+            {
+                let to = (rank * 8 + file) as usize;
+                between[from][to] = passed;
+                line[from][to] = full_line;
+                passed |= 1 << to;
+                rank += rank_step;
+                file += file_step;
+            }
+            direction += 1;
+        }
+        from += 1;
+    }
+    (between, line)
+}
+
+/// Returns every square from `from` to the edge of the board in the given direction, not
+/// including `from`.
+const fn ray(from: usize, rank_step: i8, file_step: i8) -> u64
+{
+    let mut ray = 0;
+    let mut rank = (from / 8) as i8 + rank_step;
+    let mut file = (from % 8) as i8 + file_step;
+    while is_on_board(rank, file)
+    {
+        ray |= 1 << (rank * 8 + file);
+        rank += rank_step;
+        file += file_step;
+    }
+    ray
+}
+
+const fn is_on_board(rank: i8, file: i8) -> bool
+{
+    rank >= 0 && rank < 8 && file >= 0 && file < 8
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn masks_are_symmetric()
+    {
+        for from in 0..64
+        {
+            for to in 0..64
+            {
+                let (from, to) = (Bitboard::index_to_coords_unchecked(from), Bitboard::index_to_coords_unchecked(to));
+                assert_eq!(between_mask(from, to), between_mask(to, from));
+                assert_eq!(line_mask(from, to), line_mask(to, from));
+            }
+        }
+    }
+
+    #[test]
+    fn adjacent_squares_have_nothing_between_them()
+    {
+        let (e4, e5) = (Square::from_str("e4").unwrap(), Square::from_str("e5").unwrap());
+        assert!(between_mask(e4, e5).is_empty());
+        assert_eq!(line_mask(e4, e5), Bitboard::file_mask(4));
+        assert!(line_mask(e4, e4).is_empty());
+    }
+
+    #[test]
+    fn lines_and_gaps_between_squares()
+    {
+        let square = |name| Square::from_str(name).unwrap();
+        assert_eq!(between_mask(square("a1"), square("a4")), Bitboard::from(square("a2")) | Bitboard::from(square("a3")));
+        assert_eq!(between_mask(square("c1"), square("e3")), Bitboard::from(square("d2")));
+        assert_eq!(line_mask(square("b2"), square("c3")).count(), 8);
+        assert_eq!(line_mask(square("h1"), square("b7")), Bitboard::anti_diagonal_mask(square("e4")));
+    }
+}
@@ -0,0 +1,279 @@
+//! Precomputed rook and bishop attacks using magic bitboards.
+//!
+//! A slider's attacks depend on which squares along its lines are occupied, so they can't be
+//! looked up by square alone like a knight's. They only depend on the squares that could block
+//! it though, which is at most 12 squares for a rook and 9 for a bishop. Multiplying those
+//! squares by a "magic" number and keeping the top bits maps every arrangement of blockers to
+//! a small index without collisions (or at least, without two arrangements that need different
+//! attacks sharing an index), so every slider attack is one multiply and one lookup away.
+//!
+//! The magic numbers were found with a random search ahead of time. The blocker masks are built
+//! at compile time and the attack tables the first time they're needed.
+//!
+//! See the [Chess Programming Wiki](https://www.chessprogramming.org/Magic_Bitboards) for more.
+
+use std::sync::LazyLock;
+
+use crate::bitboard::Bitboard;
+
+use super::{directions::{DIAGONAL_DIRECTIONS, ORTHOGONAL_DIRECTIONS}, Square};
+
+const ROOK_MAGIC_NUMBERS: [u64; 64] = [
+    0x0080068051E04000, 0x0040001000402000, 0x0080100020008008, 0x4E000A0010208440,
+    0x4200040802002010, 0x0100010008020400, 0x9080608019000600, 0x8100020080204100,
+    0x4103800480400020, 0x8015004004802100, 0x000200108A002040, 0x0801000821001000,
+    0x0015000500080070, 0x0120800400800200, 0x0109000432001100, 0x020080055B000080,
+    0x0080004000402002, 0x5260848020004008, 0x2402020014402080, 0x3000808010000802,
+    0x0304018004810800, 0x0000808004000200, 0x0002040001500248, 0x0012020000408401,
+    0x8440008080004020, 0x0804200840100040, 0x0820008080201000, 0x2080100100082100,
+    0x0001000500100800, 0x00A1000900028400, 0x0100100400C80102, 0x000001120000A044,
+    0x800080C004800620, 0x4040081000202000, 0x0D08802008801000, 0x1000800800801004,
+    0x1004000801010010, 0x0402800400800200, 0x0004080204008110, 0x0000404082000401,
+    0x00C0118861408000, 0x1100220081020048, 0x09A0430420050010, 0x0000082200420010,
+    0x2110080004008080, 0x2004201040680104, 0x1106001451820008, 0x0002224104820014,
+    0x00800C8044210500, 0x02A0200040100040, 0x040100A0001E4100, 0x00204023108A0200,
+    0x2400080080040080, 0x1289008400020900, 0x0002088250010400, 0x0001006084010200,
+    0x0001023480002141, 0x0006400021810015, 0x8400100840200101, 0x40003000A1000825,
+    0x1002011008200402, 0x100D000400080201, 0x0020048806102904, 0x8401000020804201,
+];
+
+const BISHOP_MAGIC_NUMBERS: [u64; 64] = [
+    0x4C40240122060016, 0x8048110404004A80, 0x8004440410414020, 0x021C410060405000,
+    0x80CD1040D0480812, 0x0002021104000082, 0x08440082A8200001, 0x00202A0800841002,
+    0x0200C40810842088, 0x60C0081000C08901, 0x00A3D0040042510C, 0x1C00110400808541,
+    0x0400820211084005, 0x0000008860080800, 0x002002020202C000, 0x0400344E08040A81,
+    0x812800102098A080, 0x00202010823A2040, 0x4086400800830201, 0x5008012A22004000,
+    0x0004801C00A00000, 0x0000400200505400, 0x0480408401080820, 0x8000400029082824,
+    0x0008880804501000, 0x0001600048084100, 0x0108220624040400, 0x0008080000820002,
+    0xC804040010410041, 0x01080A0040208400, 0x2018030480A88800, 0x4040410020410810,
+    0x1108044010100210, 0x084A100400029800, 0x0801080100820C00, 0x8010400808108200,
+    0x0084008400020500, 0x0002004200290481, 0x0010150200032090, 0x8404042220404102,
+    0x0302080308004008, 0x1200420820000408, 0x0802002024200800, 0x4020824208000084,
+    0x000002020C008200, 0x2C40208081000882, 0x2082223441000401, 0x8804080081101020,
+    0x4401011002220808, 0x81020C4202100000, 0x4005004404040308, 0x0820400C42020001,
+    0x0020206421820010, 0x0150401001424008, 0x02A20242020C0608, 0x5020110109011200,
+    0x2050840108410401, 0x0100090880842108, 0x220008960142187A, 0x1111028880208820,
+    0x4400200042028200, 0x4400010802084206, 0x0000400242040100, 0x0002201104010944,
+];
+
+/// Where to find the attacks of a slider on one square.
+#[derive(Clone, Copy)]
+struct Magic
+{
+    /// The squares that could block the slider. The edge of the board is left out, since a piece
+    /// there doesn't block anything further along.
+    mask: u64,
+    magic: u64,
+    /// How far to shift the product down, 64 minus the number of squares in the mask.
+    shift: u32,
+    /// Where this square's attacks start in [SLIDER_ATTACKS].
+    offset: usize,
+}
+
+const ROOK_MAGICS: [Magic; 64] = build_magics(&ROOK_MAGIC_NUMBERS, &ORTHOGONAL_DIRECTIONS, 0);
+const BISHOP_MAGICS: [Magic; 64] = build_magics(&BISHOP_MAGIC_NUMBERS, &DIAGONAL_DIRECTIONS, table_size(&ROOK_MAGICS));
+
+/// The attacks of every rook and bishop, for every arrangement of blockers, one after another.
+static SLIDER_ATTACKS: LazyLock<Vec<u64>> = LazyLock::new(||
+{
+    let mut attacks = vec![0; table_size(&BISHOP_MAGICS)];
+    fill_attacks(&mut attacks, &ROOK_MAGICS, &ORTHOGONAL_DIRECTIONS);
+    fill_attacks(&mut attacks, &BISHOP_MAGICS, &DIAGONAL_DIRECTIONS);
+    attacks
+});
+
+/// Returns the squares a rook on `square` attacks, stopping at (and including) the first
+/// occupied square in each direction.
+///
+/// # Arguments
+///
+/// * `square` - The square the rook is on.
+/// * `occupied` - Every occupied square on the board, of either color.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::bitboard::Bitboard;
+/// # use rust_chess_engine::board::{rook_attack_mask, Square};
+/// let blocker = Bitboard::from(Square::from_str("a3").unwrap());
+/// let attacks = rook_attack_mask(Square::from_str("a1").unwrap(), blocker);
+/// // a2, a3, and b1 through h1.
+/// assert_eq!(attacks.squares().count(), 9);
+/// ```
+pub fn rook_attack_mask(square: Square, occupied: Bitboard) -> Bitboard
+{
+    attack_mask(&ROOK_MAGICS[square.index() as usize], occupied)
+}
+
+/// Returns the squares a bishop on `square` attacks, stopping at (and including) the first
+/// occupied square in each direction.
+///
+/// # Arguments
+///
+/// * `square` - The square the bishop is on.
+/// * `occupied` - Every occupied square on the board, of either color.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::bitboard::Bitboard;
+/// # use rust_chess_engine::board::{bishop_attack_mask, Square};
+/// let attacks = bishop_attack_mask(Square::from_str("d4").unwrap(), Bitboard::default());
+/// assert_eq!(attacks.squares().count(), 13);
+/// ```
+pub fn bishop_attack_mask(square: Square, occupied: Bitboard) -> Bitboard
+{
+    attack_mask(&BISHOP_MAGICS[square.index() as usize], occupied)
+}
+
+fn attack_mask(magic: &Magic, occupied: Bitboard) -> Bitboard
+{
+    Bitboard::new(SLIDER_ATTACKS[magic.offset + magic_index(magic, u64::from(occupied))])
+}
+
+const fn magic_index(magic: &Magic, occupied: u64) -> usize
+{
+    ((occupied & magic.mask).wrapping_mul(magic.magic) >> magic.shift) as usize
+}
+
+const fn build_magics(magic_numbers: &[u64; 64], directions: &[(i8, i8); 4], first_offset: usize) -> [Magic; 64]
+{
+    let mut magics = [Magic { mask: 0, magic: 0, shift: 0, offset: 0 }; 64];
+    let mut offset = first_offset;
+    let mut square = 0;
+    while square < 64
+    {
+        let mask = blocker_mask(square, directions);
+        magics[square] = Magic
+        {
+            mask,
+            magic: magic_numbers[square],
+            shift: 64 - mask.count_ones(),
+            offset,
+        };
+        offset += 1 << mask.count_ones();
+        square += 1;
+    }
+    magics
+}
+
+/// Returns where the table ends, i.e the offset after the last square's attacks.
+const fn table_size(magics: &[Magic; 64]) -> usize
+{
+    magics[63].offset + (1 << (64 - magics[63].shift))
+}
+
+/// Returns the squares along each direction that could block a slider on `square`, leaving out
+/// the last square before the edge of the board.
+const fn blocker_mask(square: usize, directions: &[(i8, i8); 4]) -> u64
+{
+    let mut mask = 0;
+    let mut direction = 0;
+    while direction < 4
+    {
+        let (rank_step, file_step) = directions[direction];
+        let mut rank = (square / 8) as i8 + rank_step;
+        let mut file = (square % 8) as i8 + file_step;
+        while is_on_board(rank + rank_step, file + file_step) && is_on_board(rank, file)
+        {
+            mask |= 1 << (rank * 8 + file);
+            rank += rank_step;
+            file += file_step;
+        }
+        direction += 1;
+    }
+    mask
+}
+
+/// Works out the attacks of a slider the slow way, by walking along each direction until it
+/// hits a piece or the edge of the board.
+fn slow_attacks(square: usize, occupied: u64, directions: &[(i8, i8); 4]) -> u64
+{
+    let mut attacks = 0;
+    for (rank_step, file_step) in directions
+    {
+        let mut rank = (square / 8) as i8 + rank_step;
+        let mut file = (square % 8) as i8 + file_step;
+        while is_on_board(rank, file)
+        {
+            let bit = 1 << (rank * 8 + file);
+            attacks |= bit;
+            if occupied & bit != 0
+            {
+                break;
+            }
+            rank += rank_step;
+            file += file_step;
+        }
+    }
+    attacks
+}
+
+fn fill_attacks(attacks: &mut [u64], magics: &[Magic; 64], directions: &[(i8, i8); 4])
+{
+    for (square, magic) in magics.iter().enumerate()
+    {
+        // Walks through every subset of the mask (the Carry-Rippler trick), starting and ending
+        // with the empty set.
+        let mut blockers: u64 = 0;
+        loop
+        {
+            attacks[magic.offset + magic_index(magic, blockers)] = slow_attacks(square, blockers, directions);
+            blockers = blockers.wrapping_sub(magic.mask) & magic.mask;
+            if blockers == 0
+            {
+                break;
+            }
+        }
+    }
+}
+
+const fn is_on_board(rank: i8, file: i8) -> bool
+{
+    rank >= 0 && rank < 8 && file >= 0 && file < 8
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// A small xorshift generator, so the test positions are the same on every run.
+    fn random_occupancy(state: &mut u64) -> u64
+    {
+        let mut next = ||
+        {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        };
+        // Sparse boards look more like real games than half-full ones.
+        next() & next()
+    }
+
+    #[test]
+    fn lookups_match_walking_the_board()
+    {
+        let mut state = 0x2545F4914F6CDD1D;
+        for _ in 0..2000
+        {
+            let occupied = random_occupancy(&mut state);
+            for index in 0..64
+            {
+                let square = Square::from_index(index).unwrap();
+                assert_eq!(u64::from(rook_attack_mask(square, Bitboard::new(occupied))), slow_attacks(index as usize, occupied, &ORTHOGONAL_DIRECTIONS));
+                assert_eq!(u64::from(bishop_attack_mask(square, Bitboard::new(occupied))), slow_attacks(index as usize, occupied, &DIAGONAL_DIRECTIONS));
+            }
+        }
+    }
+
+    #[test]
+    fn tables_are_the_expected_size()
+    {
+        assert_eq!(table_size(&ROOK_MAGICS), 102400);
+        assert_eq!(table_size(&BISHOP_MAGICS), 102400 + 5248);
+    }
+}
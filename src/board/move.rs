@@ -3,10 +3,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::parse::MoveCommandData;
 
-use super::Square;
+use super::{PieceType, Square};
 
 /// The direction the player is castling.
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CastlingDirection
 {
     /// Castling kingside (i.e castling "short" or "O-O" notation)
@@ -35,19 +35,18 @@ pub enum CastlingDirection
 /// But doesn't check things like:
 /// - Occupancy in line of sight
 /// - Whether the king is in check
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Move
 {
-    /// Basically any move that is not a castle.
-    ///
-    /// (Also probably won't include pawn promotions, which are currently unhandled)
+    /// Basically any move that is not a castle. This includes pawn promotions, see
+    /// [MoveData::promotion].
     NormalMove(MoveData),
     /// A castle.
     Castle(CastlingDirection),
 }
 
 /// Contains information about the move relevant to the [crate::board::Board]
-#[derive(Debug, CopyGetters, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, CopyGetters, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[getset(get_copy="pub")]
 pub struct MoveData
 {
@@ -57,6 +56,9 @@ pub struct MoveData
     capture: bool,
     /// The square that this move ends on
     target_square: Square,
+    /// The piece a pawn turns into when it reaches the last rank, or [None] if this move isn't a
+    /// promotion.
+    promotion: Option<PieceType>,
 }
 
 impl MoveData
@@ -83,6 +85,30 @@ impl MoveData
             starting_square,
             capture,
             target_square,
+            promotion: None,
+        }
+    }
+
+    /// Returns the same move, but promoting the moving pawn to `piece_type`.
+    ///
+    /// # Arguments
+    ///
+    /// * `piece_type` - The piece the pawn promotes to. Must be a knight, bishop, rook or queen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{MoveData, PieceType, Square};
+    /// // e7-e8=Q
+    /// let move_data = MoveData::new(Square::new(6,4), Square::new(7,4), false).with_promotion(PieceType::Queen);
+    /// assert_eq!(move_data.promotion(), Some(PieceType::Queen));
+    /// ```
+    pub const fn with_promotion(self, piece_type: PieceType) -> Self
+    {
+        Self
+        {
+            promotion: Some(piece_type),
+            ..self
         }
     }
 
@@ -115,6 +141,7 @@ impl MoveData
             starting_square,
             capture: move_command_data.capture(),
             target_square: move_command_data.target_square(),
+            promotion: move_command_data.promotion(),
         }
     }
 }
@@ -0,0 +1,173 @@
+//! Precomputed pawn attack, push and double push masks for every square and color.
+//!
+//! Pawns are the only pieces whose moves depend on their color, and working their moves out with
+//! shifts and file masks every time they're needed is wasteful when there are only 64 squares.
+//! The tables here are built at compile time and looked up by [Board::pawn_moves](super::Board::pawn_moves),
+//! [Board::pawn_attacks](super::Board::pawn_attacks) and friends.
+
+use crate::bitboard::Bitboard;
+
+use super::{PlayerColor, Square};
+
+/// The squares a pawn attacks, indexed by color, then square index.
+const PAWN_ATTACKS: [[u64; 64]; 2] = build_table(TableKind::Attack);
+/// The square a pawn pushes to, indexed by color, then square index.
+const PAWN_PUSHES: [[u64; 64]; 2] = build_table(TableKind::Push);
+/// The square a pawn double pushes to, indexed by color, then square index. Only set on the
+/// starting rank.
+const PAWN_DOUBLE_PUSHES: [[u64; 64]; 2] = build_table(TableKind::DoublePush);
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+
+/// Returns the squares a pawn of the given color on `square` attacks, whether or not anything is
+/// on them.
+///
+/// # Arguments
+///
+/// * `color` - The color of the pawn.
+/// * `square` - The square the pawn is on.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::bitboard::Bitboard;
+/// # use rust_chess_engine::board::{pawn_attack_mask, PlayerColor, Square};
+/// let attacks = pawn_attack_mask(PlayerColor::White, Square::from_str("a2").unwrap());
+/// assert_eq!(attacks, Bitboard::from(Square::from_str("b3").unwrap()));
+/// ```
+pub fn pawn_attack_mask(color: PlayerColor, square: Square) -> Bitboard
+{
+    Bitboard::new(PAWN_ATTACKS[color.index()][square.index() as usize])
+}
+
+/// Returns the square a pawn of the given color on `square` pushes to, ignoring whether it's
+/// blocked. Empty for pawns on their last rank.
+///
+/// # Arguments
+///
+/// * `color` - The color of the pawn.
+/// * `square` - The square the pawn is on.
+pub fn pawn_push_mask(color: PlayerColor, square: Square) -> Bitboard
+{
+    Bitboard::new(PAWN_PUSHES[color.index()][square.index() as usize])
+}
+
+/// Returns the square a pawn of the given color on `square` double pushes to, ignoring whether
+/// it's blocked. Empty unless the pawn is on its starting rank.
+///
+/// # Arguments
+///
+/// * `color` - The color of the pawn.
+/// * `square` - The square the pawn is on.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::bitboard::Bitboard;
+/// # use rust_chess_engine::board::{pawn_double_push_mask, PlayerColor, Square};
+/// let double_push = pawn_double_push_mask(PlayerColor::Black, Square::from_str("e7").unwrap());
+/// assert_eq!(double_push, Bitboard::from(Square::from_str("e5").unwrap()));
+/// assert!(pawn_double_push_mask(PlayerColor::Black, Square::from_str("e6").unwrap()).is_empty());
+/// ```
+pub fn pawn_double_push_mask(color: PlayerColor, square: Square) -> Bitboard
+{
+    Bitboard::new(PAWN_DOUBLE_PUSHES[color.index()][square.index() as usize])
+}
+
+/// Which of the tables to build. Function pointers can't be called in a const context, so
+/// [build_table] matches on this instead.
+#[derive(Clone, Copy)]
+enum TableKind
+{
+    Attack,
+    Push,
+    DoublePush,
+}
+
+const fn build_table(kind: TableKind) -> [[u64; 64]; 2]
+{
+    let mut table = [[0; 64]; 2];
+    let mut index = 0;
+    while index < 64
+    {
+        let pawn = 1 << index;
+        table[0][index] = mask(kind, true, pawn);
+        table[1][index] = mask(kind, false, pawn);
+        index += 1;
+    }
+    table
+}
+
+const fn mask(kind: TableKind, is_white: bool, pawn: u64) -> u64
+{
+    match kind
+    {
+        TableKind::Attack => attack_mask(is_white, pawn),
+        TableKind::Push => push_mask(is_white, pawn),
+        TableKind::DoublePush => double_push_mask(is_white, pawn),
+    }
+}
+
+const fn attack_mask(is_white: bool, pawn: u64) -> u64
+{
+    match is_white
+    {
+        true => (pawn << 9) & !FILE_A | (pawn << 7) & !FILE_H,
+        false => (pawn >> 7) & !FILE_A | (pawn >> 9) & !FILE_H,
+    }
+}
+
+const fn push_mask(is_white: bool, pawn: u64) -> u64
+{
+    // Shifting off the end of the board leaves an empty mask, which is what we want for pawns on
+    // their last rank.
+    match is_white
+    {
+        true => pawn << 8,
+        false => pawn >> 8,
+    }
+}
+
+const fn double_push_mask(is_white: bool, pawn: u64) -> u64
+{
+    const RANK_2: u64 = 0xFF << 8;
+    const RANK_7: u64 = 0xFF << 48;
+    match is_white
+    {
+        true => (pawn & RANK_2) << 16,
+        false => (pawn & RANK_7) >> 16,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn attacks_dont_wrap_around_the_board()
+    {
+        for rank in 0..8
+        {
+            for file in [0, 7]
+            {
+                let square = Square::new(rank, file);
+                for color in [PlayerColor::White, PlayerColor::Black]
+                {
+                    assert!(pawn_attack_mask(color, square).squares().all(|attacked| attacked.file.abs_diff(file) == 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pawns_on_their_last_rank_cant_push()
+    {
+        assert!(pawn_push_mask(PlayerColor::White, Square::new(7, 3)).is_empty());
+        assert!(pawn_push_mask(PlayerColor::Black, Square::new(0, 3)).is_empty());
+        assert_eq!(pawn_push_mask(PlayerColor::Black, Square::new(1, 3)), Bitboard::from(Square::new(0, 3)));
+    }
+}
@@ -1,11 +1,15 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use super::{PieceType, PlayerColor};
+use serde::{Deserialize, Serialize};
+
+use crate::parse::NotationParseError;
+
+use super::{piece_type::PIECE_TYPES, PieceType, PlayerColor};
 
 /// Represents one of the player's pieces.
 /// 
 /// Stores the piece type and the player's color.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub struct Piece
 {
     piece_type: PieceType,
@@ -63,29 +67,142 @@ impl Piece
     {
         self.color
     }
+
+    /// Returns the letter for the piece used in FEN: uppercase for White, lowercase for Black.
+    /// This is what [Display] prints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{PieceType, PlayerColor, Piece};
+    /// assert_eq!(Piece::new(PlayerColor::White, PieceType::Knight).letter(), 'N');
+    /// assert_eq!(Piece::new(PlayerColor::Black, PieceType::Queen).letter(), 'q');
+    /// ```
+    pub fn letter(&self) -> char
+    {
+        let letter = match self.piece_type
+        {
+            PieceType::Pawn => 'P',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Rook => 'R',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        };
+        // White pieces show up as uppercase letters,
+        // Black pieces show up as lowercase letters,
+        // as in FEN.
+        match self.color
+        {
+            PlayerColor::White => letter,
+            PlayerColor::Black => letter.to_ascii_lowercase(),
+        }
+    }
+
+    /// Returns the Unicode chess figurine for the piece, i.e '♔' for a white king or '♟' for a
+    /// black pawn. This is what [Display] prints with the alternate flag (`{:#}`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{PieceType, PlayerColor, Piece};
+    /// let white_king = Piece::new(PlayerColor::White, PieceType::King);
+    /// assert_eq!(white_king.figurine(), '♔');
+    /// assert_eq!(format!("{:#}", white_king), "♔");
+    /// ```
+    pub fn figurine(&self) -> char
+    {
+        match (self.color, self.piece_type)
+        {
+            (PlayerColor::White, PieceType::King) => '♔',
+            (PlayerColor::White, PieceType::Queen) => '♕',
+            (PlayerColor::White, PieceType::Rook) => '♖',
+            (PlayerColor::White, PieceType::Bishop) => '♗',
+            (PlayerColor::White, PieceType::Knight) => '♘',
+            (PlayerColor::White, PieceType::Pawn) => '♙',
+            (PlayerColor::Black, PieceType::King) => '♚',
+            (PlayerColor::Black, PieceType::Queen) => '♛',
+            (PlayerColor::Black, PieceType::Rook) => '♜',
+            (PlayerColor::Black, PieceType::Bishop) => '♝',
+            (PlayerColor::Black, PieceType::Knight) => '♞',
+            (PlayerColor::Black, PieceType::Pawn) => '♟',
+        }
+    }
 }
 
 impl Display for Piece
 {
+    /// Writes the piece's FEN [letter](Piece::letter), or its Unicode
+    /// [figurine](Piece::figurine) with the alternate flag (`{:#}`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{PieceType, PlayerColor, Piece};
+    /// let black_rook = Piece::new(PlayerColor::Black, PieceType::Rook);
+    /// assert_eq!(black_rook.to_string(), "r");
+    /// assert_eq!(format!("{:#}", black_rook), "♜");
+    /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // White pieces show up as uppercase letters,
-        // Black pieces show up as lowercase letters,
-        // as in FEN.
-        let piece_symbol = match (self.color, self.piece_type)
+        match f.alternate()
+        {
+            true => write!(f, "{}", self.figurine()),
+            false => write!(f, "{}", self.letter()),
+        }
+    }
+}
+
+impl FromStr for Piece
+{
+    type Err = NotationParseError;
+
+    /// Reads a piece from a single FEN letter (uppercase for White, lowercase for Black) or
+    /// Unicode figurine, the reverse of [Display].
+    ///
+    /// # Errors
+    ///
+    /// Returns [NotationParseError::InvalidPieceCharacter] if `s` isn't exactly one piece
+    /// letter or figurine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{PieceType, PlayerColor, Piece};
+    /// assert_eq!(Piece::from_str("n").unwrap(), Piece::new(PlayerColor::Black, PieceType::Knight));
+    /// assert_eq!(Piece::from_str("♕").unwrap(), Piece::new(PlayerColor::White, PieceType::Queen));
+    /// assert!(Piece::from_str("x").is_err());
+    /// assert!(Piece::from_str("NN").is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let (Some(char), None) = (chars.next(), chars.next()) else
         {
-            (PlayerColor::White, PieceType::Pawn) => "P",
-            (PlayerColor::White, PieceType::Bishop) => "B",
-            (PlayerColor::White, PieceType::Knight) => "N",
-            (PlayerColor::White, PieceType::Rook) => "R",
-            (PlayerColor::White, PieceType::Queen) => "Q",
-            (PlayerColor::White, PieceType::King) => "K",
-            (PlayerColor::Black, PieceType::Pawn) => "p",
-            (PlayerColor::Black, PieceType::Bishop) => "b",
-            (PlayerColor::Black, PieceType::Knight) => "n",
-            (PlayerColor::Black, PieceType::Rook) => "r",
-            (PlayerColor::Black, PieceType::Queen) => "q",
-            (PlayerColor::Black, PieceType::King) => "k",
+            return Err(NotationParseError::InvalidPieceCharacter(s.to_string()));
         };
-        write!(f, "{}", piece_symbol)
+        [PlayerColor::White, PlayerColor::Black].into_iter()
+            .flat_map(|color| PIECE_TYPES.map(|piece_type| Piece::new(color, piece_type)))
+            .find(|piece| piece.letter() == char || piece.figurine() == char)
+            .ok_or_else(|| NotationParseError::InvalidPieceCharacter(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn letters_and_figurines_round_trip()
+    {
+        for color in [PlayerColor::White, PlayerColor::Black]
+        {
+            for piece_type in PIECE_TYPES
+            {
+                let piece = Piece::new(color, piece_type);
+                assert_eq!(Piece::from_str(&piece.to_string()).unwrap(), piece);
+                assert_eq!(Piece::from_str(&format!("{:#}", piece)).unwrap(), piece);
+            }
+        }
     }
 }
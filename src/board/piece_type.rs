@@ -2,6 +2,8 @@
 
 use std::str::FromStr;
 
+use serde::{Deserialize, Serialize};
+
 use crate::parse::NotationParseError;
 
 pub const PIECE_TYPES: [PieceType; 6] = [
@@ -12,6 +14,14 @@ pub const PIECE_TYPES: [PieceType; 6] = [
     PieceType::Queen,
     PieceType::King];
 
+/// The piece types a pawn can promote to, best first. Move generation emits promotions in this
+/// order.
+pub const PROMOTION_PIECE_TYPES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight];
+
 /// One of the six valid chess piece types.
 /// Can be:
 /// - Pawn
@@ -20,7 +30,7 @@ pub const PIECE_TYPES: [PieceType; 6] = [
 /// - Rook
 /// - Queen
 /// - King
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum PieceType {
     /// A pawn piece
     Pawn,
@@ -1,7 +1,13 @@
-use std::ops::Not;
+use std::{fmt::Display, ops::Not};
+
+use serde::{Deserialize, Serialize};
+
+use crate::UInt;
+
+use super::Square;
 
 /// The two side colors, white and black.
-#[derive(Debug, PartialEq, Copy, Clone, Eq, Hash)]
+#[derive(Debug, PartialEq, Copy, Clone, Eq, Hash, Serialize, Deserialize)]
 pub enum PlayerColor
 {
     /// The white player color
@@ -10,6 +16,149 @@ pub enum PlayerColor
     Black,
 }
 
+impl PlayerColor
+{
+    /// Returns an iterator over both player colors, White first.
+    ///
+    /// This is handy for any calculation that needs to be done once per side, like summing up
+    /// material or generating attack maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// let colors: Vec<PlayerColor> = PlayerColor::both().collect();
+    /// assert_eq!(colors, vec![PlayerColor::White, PlayerColor::Black]);
+    /// ```
+    pub fn both() -> std::array::IntoIter<PlayerColor, 2>
+    {
+        [PlayerColor::White, PlayerColor::Black].into_iter()
+    }
+
+    /// Returns 0 for White and 1 for Black, for looking up per-side entries in arrays and
+    /// tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// let material = [39, 30];
+    /// assert_eq!(material[PlayerColor::Black.index()], 30);
+    /// ```
+    pub const fn index(&self) -> usize
+    {
+        match self
+        {
+            PlayerColor::White => 0,
+            PlayerColor::Black => 1,
+        }
+    }
+
+    /// Returns the sign of this color from White's point of view.
+    ///
+    /// Evaluations are always given from White's perspective (positive is good for White,
+    /// negative is good for Black), so multiplying a per-side score by its color's sign gives the
+    /// contribution to the overall score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// assert_eq!(PlayerColor::White.sign(), 1);
+    /// assert_eq!(PlayerColor::Black.sign(), -1);
+    /// ```
+    pub const fn sign(&self) -> i8
+    {
+        match self
+        {
+            PlayerColor::White => 1,
+            PlayerColor::Black => -1,
+        }
+    }
+
+    /// Returns the direction (as a change in rank) that this color's pawns move in.
+    ///
+    /// White pawns move up the board (towards rank 8), Black pawns move down the board (towards
+    /// rank 1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{PlayerColor, Square};
+    /// let square = Square::new(1, 4);
+    /// let rank = square.rank as i8 + PlayerColor::White.pawn_direction();
+    /// assert_eq!(rank, 2);
+    /// assert_eq!(PlayerColor::Black.pawn_direction(), -1);
+    /// ```
+    pub const fn pawn_direction(&self) -> i8
+    {
+        self.sign()
+    }
+
+    /// Returns the rank this color's king and rooks start on, which is also the rank it castles
+    /// on: the first rank for White and the eighth for Black.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// assert_eq!(PlayerColor::White.home_rank(), 0);
+    /// assert_eq!(PlayerColor::Black.home_rank(), 7);
+    /// ```
+    pub const fn home_rank(&self) -> UInt
+    {
+        match self
+        {
+            PlayerColor::White => 0,
+            PlayerColor::Black => 7,
+        }
+    }
+
+    /// Returns the rank this color's pawns promote on, which is the other side's
+    /// [home rank](Self::home_rank).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// assert_eq!(PlayerColor::White.promotion_rank(), 7);
+    /// assert_eq!(PlayerColor::Black.promotion_rank(), 0);
+    /// ```
+    pub const fn promotion_rank(&self) -> UInt
+    {
+        match self
+        {
+            PlayerColor::White => 7,
+            PlayerColor::Black => 0,
+        }
+    }
+
+    /// Returns the rank of `square` counted from this color's side of the board, so that 0 is
+    /// always the [home rank](Self::home_rank) and 7 the [promotion rank](Self::promotion_rank).
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to get the rank of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{PlayerColor, Square};
+    /// // Both sides' pawns start on their second rank.
+    /// assert_eq!(PlayerColor::White.relative_rank(Square::from_str("e2").unwrap()), 1);
+    /// assert_eq!(PlayerColor::Black.relative_rank(Square::from_str("e7").unwrap()), 1);
+    /// ```
+    pub const fn relative_rank(&self, square: Square) -> UInt
+    {
+        match self
+        {
+            PlayerColor::White => square.rank,
+            PlayerColor::Black => 7 - square.rank,
+        }
+    }
+}
+
 impl Default for PlayerColor
 {
     /// The default starting color is white.
@@ -39,3 +188,23 @@ impl Not for PlayerColor
         }
     }
 }
+
+impl Display for PlayerColor
+{
+    /// Writes the name of the color, i.e "White" or "Black".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// assert_eq!(PlayerColor::White.to_string(), "White");
+    /// assert_eq!(PlayerColor::Black.to_string(), "Black");
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Self::White => write!(f, "White"),
+            Self::Black => write!(f, "Black"),
+        }
+    }
+}
@@ -1,11 +1,11 @@
 //! Specifies the [Square] type.
 
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::{parse::{alphabetic_file_to_numeric, rank_to_numeric, NotationParseError}, UInt};
+use crate::{bitboard::{Bitboard, OutOfBoundsError}, parse::{alphabetic_file_to_numeric, rank_to_numeric, square_to_algebraic, NotationParseError}, UInt};
 
 /// Represents a space on the board, specified by its coordinates in both
 /// rank and file.
@@ -46,6 +46,156 @@ impl Square {
             file
         }
     }
+
+    /// Creates a new square like [Self::new], but checks that it's on the board first.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank` - The rank (or row) of the square, from 0 to 7.
+    /// * `file` - The file (or column) of the square, from 0 to 7.
+    ///
+    /// # Errors
+    ///
+    /// An [OutOfBoundsError] is returned if the rank or file is 8 or higher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Square::try_new(3, 4).unwrap(), Square::new(3, 4));
+    /// assert!(Square::try_new(8, 0).is_err());
+    /// ```
+    pub fn try_new(rank: UInt, file: UInt) -> Result<Self, OutOfBoundsError>
+    {
+        let square = Self::new(rank, file);
+        Bitboard::coords_to_index(square)?;
+        Ok(square)
+    }
+
+    /// Returns the rank of the square, from 0 to 7 for ranks 1 to 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Square::from_str("e4").unwrap().rank(), 3);
+    /// ```
+    pub const fn rank(&self) -> UInt
+    {
+        self.rank
+    }
+
+    /// Returns the file of the square, from 0 to 7 for files a to h.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Square::from_str("e4").unwrap().file(), 4);
+    /// ```
+    pub const fn file(&self) -> UInt
+    {
+        self.file
+    }
+
+    /// Returns the square `rank_offset` ranks up and `file_offset` files to the right of this one,
+    /// or [None] if that's off the board. Negative offsets go down and to the left.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank_offset` - How many ranks to move up.
+    /// * `file_offset` - How many files to move right.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Square;
+    /// let e4 = Square::from_str("e4").unwrap();
+    /// assert_eq!(e4.offset(2, -1), Some(Square::from_str("d6").unwrap()));
+    /// assert_eq!(e4.offset(-4, 0), None);
+    /// ```
+    pub fn offset(&self, rank_offset: i8, file_offset: i8) -> Option<Self>
+    {
+        let rank = self.rank as i16 + rank_offset as i16;
+        let file = self.file as i16 + file_offset as i16;
+        match (0..8).contains(&rank) && (0..8).contains(&file)
+        {
+            true => Some(Self::new(rank as UInt, file as UInt)),
+            false => None,
+        }
+    }
+
+    /// Returns the index of this square from 0 to 63, counting a1, b1, ... h1, a2, ... h8.
+    ///
+    /// This is the same index used for bits in a [Bitboard], which makes it a natural key for
+    /// array-based lookup tables.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rank or file is 8 or higher. See [Bitboard::coords_to_index] for a fallible
+    /// alternative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Square::from_str("a1").unwrap().index(), 0);
+    /// assert_eq!(Square::from_str("c2").unwrap().index(), 10);
+    /// assert_eq!(Square::from_str("h8").unwrap().index(), 63);
+    /// ```
+    pub fn index(&self) -> u8
+    {
+        Bitboard::coords_to_index_unchecked(*self)
+    }
+
+    /// Creates the square with the given index, the inverse of [Self::index].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the square, from 0 (a1) to 63 (h8).
+    ///
+    /// # Errors
+    ///
+    /// An [OutOfBoundsError] is returned if `index` is 64 or higher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Square::from_index(10).unwrap(), Square::from_str("c2").unwrap());
+    /// assert!(Square::from_index(64).is_err());
+    /// ```
+    pub fn from_index(index: u8) -> Result<Self, OutOfBoundsError>
+    {
+        Bitboard::index_to_coords(index)
+    }
+
+    /// Like [Self::from_index] but panics instead of returning an [Err] if the index is out of
+    /// bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the square, from 0 (a1) to 63 (h8). Panics if this is 64 or
+    ///   higher.
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// # use rust_chess_engine::board::Square;
+    /// // This should work
+    /// let square = Square::from_index_unchecked(63);
+    /// // This will panic at runtime
+    /// let square = Square::from_index_unchecked(64);
+    /// ```
+    pub fn from_index_unchecked(index: u8) -> Self
+    {
+        Bitboard::index_to_coords_unchecked(index)
+    }
 }
 
 impl FromStr for Square
@@ -70,6 +220,21 @@ impl FromStr for Square
     }
 }
 
+impl Display for Square
+{
+    /// Writes the square in algebraic notation, i.e "e4". See [square_to_algebraic].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Square;
+    /// assert_eq!(Square::new(3, 4).to_string(), "e4");
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", square_to_algebraic(*self))
+    }
+}
+
 #[cfg(test)]
 mod tests
 {
@@ -127,4 +292,33 @@ mod tests
         let square = Square::from_str("!a3");
         assert!(square.is_err());
     }
+
+    #[test]
+    pub fn test_index_round_trip()
+    {
+        for index in 0..64
+        {
+            assert_eq!(Square::from_index(index).unwrap().index(), index);
+        }
+    }
+
+    #[test]
+    pub fn test_display_round_trip()
+    {
+        for index in 0..64
+        {
+            let square = Square::from_index(index).unwrap();
+            assert_eq!(Square::from_str(&square.to_string()).unwrap(), square);
+        }
+    }
+
+    #[test]
+    pub fn test_offset_stays_on_the_board()
+    {
+        let h8 = Square::from_str("h8").unwrap();
+        assert_eq!(h8.offset(0, 1), None);
+        assert_eq!(h8.offset(1, 0), None);
+        assert_eq!(h8.offset(-7, -7), Some(Square::new(0, 0)));
+        assert_eq!(h8.offset(0, 0), Some(h8));
+    }
 }
@@ -0,0 +1,37 @@
+//! Specifies the [UndoInfo] type, which remembers what a move changed so it can be taken back.
+
+use super::{CastlingAvailability, Move, Piece, Square};
+
+/// Everything [Board::make_move_in_place](super::Board::make_move_in_place) needs to remember so
+/// [Board::unmake_move](super::Board::unmake_move) can put the board back exactly how it was.
+///
+/// A move can't always be undone from the move alone: the piece it captured, the castling rights
+/// it took away, and the clocks it reset are gone from the board afterwards. Those are kept here
+/// instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UndoInfo
+{
+    pub(super) r#move: Move,
+    /// The piece that was captured, if any.
+    pub(super) captured_piece: Option<Piece>,
+    pub(super) castling_availability: CastlingAvailability,
+    pub(super) en_passant_target_square: Option<Square>,
+    pub(super) halfmove_clock: u16,
+    pub(super) fullmove_number: u16,
+    pub(super) zobrist_key: u64,
+}
+
+impl UndoInfo
+{
+    /// Returns the move that was made.
+    pub fn r#move(&self) -> Move
+    {
+        self.r#move
+    }
+
+    /// Returns the piece the move captured, or [None] if it wasn't a capture.
+    pub fn captured_piece(&self) -> Option<Piece>
+    {
+        self.captured_piece
+    }
+}
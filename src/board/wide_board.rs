@@ -0,0 +1,454 @@
+//! Implements [WideBoard], an arrangement of pieces on a board bigger than 8×8, like the 10×8
+//! [CapablancaBoard], and the [WidePieceType]s that can stand on it.
+
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{bitboard::WideBitboard, parse::NotationParseError, UInt};
+
+use super::{PieceType, PlayerColor, Square};
+
+/// Every [WidePieceType], in the order their bitboards are stored in a [WideBoard].
+pub const WIDE_PIECE_TYPES: [WidePieceType; 8] = [
+    WidePieceType::Pawn,
+    WidePieceType::Knight,
+    WidePieceType::Bishop,
+    WidePieceType::Rook,
+    WidePieceType::Queen,
+    WidePieceType::King,
+    WidePieceType::Archbishop,
+    WidePieceType::Chancellor];
+
+/// The pieces that can stand on a [WideBoard]: the six standard [PieceType]s, plus the two
+/// compound pieces of Capablanca chess.
+///
+/// In FEN, the archbishop is written `A` and the chancellor `C`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum WidePieceType
+{
+    /// A pawn piece
+    Pawn,
+    /// A knight piece
+    Knight,
+    /// A bishop piece
+    Bishop,
+    /// A rook piece
+    Rook,
+    /// A queen piece
+    Queen,
+    /// A king piece
+    King,
+    /// A piece that moves like a bishop or a knight.
+    Archbishop,
+    /// A piece that moves like a rook or a knight.
+    Chancellor,
+}
+
+impl WidePieceType
+{
+    /// Returns the piece's index in [WIDE_PIECE_TYPES].
+    const fn index(self) -> usize
+    {
+        self as usize
+    }
+
+    /// Returns the lowercase letter the piece is written with in FEN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::WidePieceType;
+    /// assert_eq!(WidePieceType::Chancellor.fen_char(), 'c');
+    /// ```
+    pub const fn fen_char(self) -> char
+    {
+        match self
+        {
+            Self::Pawn => 'p',
+            Self::Knight => 'n',
+            Self::Bishop => 'b',
+            Self::Rook => 'r',
+            Self::Queen => 'q',
+            Self::King => 'k',
+            Self::Archbishop => 'a',
+            Self::Chancellor => 'c',
+        }
+    }
+}
+
+impl From<PieceType> for WidePieceType
+{
+    fn from(piece_type: PieceType) -> Self {
+        match piece_type
+        {
+            PieceType::Pawn => Self::Pawn,
+            PieceType::Knight => Self::Knight,
+            PieceType::Bishop => Self::Bishop,
+            PieceType::Rook => Self::Rook,
+            PieceType::Queen => Self::Queen,
+            PieceType::King => Self::King,
+        }
+    }
+}
+
+impl FromStr for WidePieceType
+{
+    type Err = NotationParseError;
+
+    /// Parses the piece from its FEN letter, in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let piece_type = match (chars.next(), chars.next())
+        {
+            (Some('a' | 'A'), None) => Self::Archbishop,
+            (Some('c' | 'C'), None) => Self::Chancellor,
+            (Some(_), None) => PieceType::from_str(s)?.into(),
+            _ => return Err(NotationParseError::InvalidPieceCharacter(s.to_string())),
+        };
+        Ok(piece_type)
+    }
+}
+
+/// The 10×8 board of Capablanca chess.
+pub type CapablancaBoard = WideBoard<10, 8>;
+
+/// Represents an error returned by [WideBoard::from_str].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum InvalidWideFENError
+{
+    /// The piece placement doesn't have one entry per rank.
+    #[error("Expected {expected} ranks in the piece placement, found {found}")]
+    WrongRankCount
+    {
+        /// The number of ranks on the board.
+        expected: UInt,
+        /// The number of ranks in the piece placement.
+        found: usize,
+    },
+    /// A rank of the piece placement doesn't cover every file.
+    #[error("Expected {expected} files on rank {rank}, found {found}")]
+    WrongFileCount
+    {
+        /// The rank, starting from 1.
+        rank: UInt,
+        /// The number of files on the board.
+        expected: UInt,
+        /// The number of files the rank covered.
+        found: usize,
+    },
+    /// The piece placement has a character that isn't a piece or a number of empty squares.
+    #[error("Invalid piece placement: {0}")]
+    InvalidPiece(#[from] NotationParseError),
+    /// The active color isn't `w` or `b`.
+    #[error("{0} was not a valid character for the active color")]
+    InvalidTurnCharacter(String),
+}
+
+/// An arrangement of pieces on a board with `FILES` files and `RANKS` ranks, stored as one
+/// [WideBitboard] per color and [WidePieceType], along with the side to move.
+///
+/// This is what holds a position that doesn't fit a [Board](super::Board), like a game of
+/// Capablanca chess. It can be set up from a FEN, with ranks as wide as the board (so `10` is one
+/// empty rank of a [CapablancaBoard]) and `A` and `C` for archbishops and chancellors, and it
+/// knows which squares every piece attacks, so it can tell when a king is in check. It doesn't
+/// generate or make moves.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::{CapablancaBoard, PlayerColor, Square, WidePieceType};
+/// let board = CapablancaBoard::new_capablanca_starting_board();
+/// assert_eq!(board.piece_at(Square::new(0, 2)), Some((PlayerColor::White, WidePieceType::Archbishop)));
+/// assert_eq!(board.piece_at(Square::new(7, 7)), Some((PlayerColor::Black, WidePieceType::Chancellor)));
+/// assert_eq!(board.occupied().count(), 40);
+/// assert!(!board.is_in_check(PlayerColor::White));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WideBoard<const FILES: UInt, const RANKS: UInt>
+{
+    pieces: [[WideBitboard<FILES, RANKS>; 8]; 2],
+    active_color: PlayerColor,
+}
+
+impl CapablancaBoard
+{
+    /// Returns the starting position of Capablanca chess: the standard setup, with an
+    /// archbishop between the queenside knight and bishop and a chancellor between the kingside
+    /// bishop and knight.
+    pub fn new_capablanca_starting_board() -> Self
+    {
+        Self::from_str("rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w").unwrap()
+    }
+}
+
+impl<const FILES: UInt, const RANKS: UInt> WideBoard<FILES, RANKS>
+{
+    /// Returns a board with no pieces on it and white to move.
+    pub fn new_blank_board() -> Self
+    {
+        Self
+        {
+            pieces: [[WideBitboard::default(); 8]; 2],
+            active_color: PlayerColor::White,
+        }
+    }
+
+    /// Returns the side to move.
+    pub fn active_color(&self) -> PlayerColor
+    {
+        self.active_color
+    }
+
+    /// Puts a piece on `square`, replacing whatever was there. Squares off the board are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the piece.
+    /// * `piece_type` - The type of the piece.
+    /// * `square` - Where to put it.
+    pub fn add_piece(mut self, color: PlayerColor, piece_type: WidePieceType, square: Square) -> Self
+    {
+        let mask = WideBitboard::from_square(square);
+        self.pieces.iter_mut().flatten().for_each(|bitboard| *bitboard &= !mask);
+        self.pieces[color.index()][piece_type.index()] |= mask;
+        self
+    }
+
+    /// Sets the side to move.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side to move.
+    pub fn set_active_color(mut self, color: PlayerColor) -> Self
+    {
+        self.active_color = color;
+        self
+    }
+
+    /// Returns the squares holding pieces of the given color and type.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the pieces.
+    /// * `piece_type` - The type of the pieces.
+    pub fn pieces(&self, color: PlayerColor, piece_type: WidePieceType) -> WideBitboard<FILES, RANKS>
+    {
+        self.pieces[color.index()][piece_type.index()]
+    }
+
+    /// Returns every square holding a piece of the given color.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the pieces.
+    pub fn pieces_of_color(&self, color: PlayerColor) -> WideBitboard<FILES, RANKS>
+    {
+        self.pieces[color.index()].iter().fold(WideBitboard::default(), |all, bitboard| all | *bitboard)
+    }
+
+    /// Returns every occupied square.
+    pub fn occupied(&self) -> WideBitboard<FILES, RANKS>
+    {
+        self.pieces_of_color(PlayerColor::White) | self.pieces_of_color(PlayerColor::Black)
+    }
+
+    /// Returns the color and type of the piece on `square`, or [None] if it's empty or off the
+    /// board.
+    ///
+    /// # Arguments
+    ///
+    /// * `square` - The square to look at.
+    pub fn piece_at(&self, square: Square) -> Option<(PlayerColor, WidePieceType)>
+    {
+        PlayerColor::both()
+            .flat_map(|color| WIDE_PIECE_TYPES.map(|piece_type| (color, piece_type)))
+            .find(|(color, piece_type)| self.pieces(*color, *piece_type).contains(square))
+    }
+
+    /// Returns the squares a piece of the given color and type on `square` attacks, with the
+    /// pieces currently on the board in the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the piece, which decides which way pawns capture.
+    /// * `piece_type` - The type of the piece.
+    /// * `square` - The piece's square.
+    pub fn piece_attacks(&self, color: PlayerColor, piece_type: WidePieceType, square: Square) -> WideBitboard<FILES, RANKS>
+    {
+        let occupied = self.occupied();
+        match piece_type
+        {
+            WidePieceType::Pawn =>
+            {
+                let forward = match color
+                {
+                    PlayerColor::White => 1,
+                    PlayerColor::Black => -1,
+                };
+                let pawn = WideBitboard::from_square(square);
+                pawn.shift(forward, -1) | pawn.shift(forward, 1)
+            },
+            WidePieceType::Knight => WideBitboard::knight_attacks(square),
+            WidePieceType::Bishop => WideBitboard::bishop_attacks(square, occupied),
+            WidePieceType::Rook => WideBitboard::rook_attacks(square, occupied),
+            WidePieceType::Queen => WideBitboard::queen_attacks(square, occupied),
+            WidePieceType::King => WideBitboard::king_attacks(square),
+            WidePieceType::Archbishop => WideBitboard::archbishop_attacks(square, occupied),
+            WidePieceType::Chancellor => WideBitboard::chancellor_attacks(square, occupied),
+        }
+    }
+
+    /// Returns every square attacked by a piece of the given color.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The attacking side.
+    pub fn attacked_squares(&self, color: PlayerColor) -> WideBitboard<FILES, RANKS>
+    {
+        WIDE_PIECE_TYPES.into_iter()
+            .flat_map(|piece_type| self.pieces(color, piece_type).squares().map(move |square| (piece_type, square)))
+            .fold(WideBitboard::default(), |attacked, (piece_type, square)| attacked | self.piece_attacks(color, piece_type, square))
+    }
+
+    /// Returns true if the king of the given color is attacked.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side whose king to look at.
+    pub fn is_in_check(&self, color: PlayerColor) -> bool
+    {
+        !(self.pieces(color, WidePieceType::King) & self.attacked_squares(!color)).is_empty()
+    }
+}
+
+impl<const FILES: UInt, const RANKS: UInt> FromStr for WideBoard<FILES, RANKS>
+{
+    type Err = InvalidWideFENError;
+
+    /// Reads the piece placement and, if it's there, the active color of a FEN. The remaining
+    /// fields are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let placement = fields.next().unwrap_or_default();
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != RANKS as usize
+        {
+            return Err(InvalidWideFENError::WrongRankCount { expected: RANKS, found: ranks.len() });
+        }
+
+        let mut board = Self::new_blank_board();
+        // The first rank in a FEN is the last rank of the board.
+        for (rank, rank_placement) in (0..RANKS).rev().zip(ranks)
+        {
+            let mut file = 0;
+            let mut empty = 0;
+            for c in rank_placement.chars()
+            {
+                if let Some(digit) = c.to_digit(10)
+                {
+                    // Empty squares can take more than one digit on boards wider than 9 files.
+                    empty = empty * 10 + digit as usize;
+                    continue;
+                }
+                file += std::mem::take(&mut empty);
+                let piece_type = WidePieceType::from_str(&c.to_string())?;
+                let color = if c.is_ascii_uppercase() { PlayerColor::White } else { PlayerColor::Black };
+                if file < FILES as usize
+                {
+                    board = board.add_piece(color, piece_type, Square::new(rank, file as UInt));
+                }
+                file += 1;
+            }
+            file += empty;
+            if file != FILES as usize
+            {
+                return Err(InvalidWideFENError::WrongFileCount { rank: rank + 1, expected: FILES, found: file });
+            }
+        }
+
+        match fields.next()
+        {
+            None | Some("w") => Ok(board),
+            Some("b") => Ok(board.set_active_color(PlayerColor::Black)),
+            Some(other) => Err(InvalidWideFENError::InvalidTurnCharacter(other.to_string())),
+        }
+    }
+}
+
+impl<const FILES: UInt, const RANKS: UInt> Display for WideBoard<FILES, RANKS>
+{
+    /// Writes the piece placement and active color, in the format [WideBoard::from_str] reads.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rank in (0..RANKS).rev()
+        {
+            let mut empty = 0;
+            for file in 0..FILES
+            {
+                match self.piece_at(Square::new(rank, file))
+                {
+                    None => empty += 1,
+                    Some((color, piece_type)) =>
+                    {
+                        if empty > 0
+                        {
+                            write!(f, "{}", std::mem::take(&mut empty))?;
+                        }
+                        let c = piece_type.fen_char();
+                        write!(f, "{}", if color == PlayerColor::White { c.to_ascii_uppercase() } else { c })?;
+                    },
+                }
+            }
+            if empty > 0
+            {
+                write!(f, "{}", empty)?;
+            }
+            if rank > 0
+            {
+                write!(f, "/")?;
+            }
+        }
+        write!(f, " {}", if self.active_color == PlayerColor::White { "w" } else { "b" })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn fen_round_trips()
+    {
+        let fen = "rnabqkbcnr/pppppppppp/10/10/4P5/10/PPPP1PPPPP/RNABQKBCNR b";
+        let board = CapablancaBoard::from_str(fen).unwrap();
+        assert_eq!(board.active_color(), PlayerColor::Black);
+        assert_eq!(board.piece_at(Square::new(3, 4)), Some((PlayerColor::White, WidePieceType::Pawn)));
+        assert_eq!(board.to_string(), fen);
+        assert_eq!(CapablancaBoard::new_capablanca_starting_board().to_string(), "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w");
+    }
+
+    #[test]
+    fn bad_fens_are_rejected()
+    {
+        assert!(matches!(CapablancaBoard::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w"), Err(InvalidWideFENError::WrongFileCount { rank: 8, expected: 10, found: 8 })));
+        assert!(matches!(CapablancaBoard::from_str("10/10/10"), Err(InvalidWideFENError::WrongRankCount { expected: 8, found: 3 })));
+        assert!(matches!(CapablancaBoard::from_str("10/10/10/10/10/10/10/9x w"), Err(InvalidWideFENError::InvalidPiece(_))));
+    }
+
+    #[test]
+    fn compound_pieces_give_check()
+    {
+        // The chancellor on h6 checks the king on i8 with a knight jump.
+        let board = CapablancaBoard::from_str("8k1/8p1/7C2/10/10/10/10/K9 b").unwrap();
+        assert!(board.is_in_check(PlayerColor::Black));
+        assert!(!board.is_in_check(PlayerColor::White));
+
+        // The archbishop on j1 checks the king on c8 along the diagonal, which only exists
+        // because the board is wider than it is tall.
+        let board = CapablancaBoard::from_str("2K7/10/10/10/10/10/10/k8a w").unwrap();
+        assert!(board.is_in_check(PlayerColor::White));
+        assert!(!board.is_in_check(PlayerColor::Black));
+    }
+}
@@ -0,0 +1,145 @@
+//! Implements [Zobrist hashing](https://www.chessprogramming.org/Zobrist_Hashing) for boards.
+//!
+//! Every piece on every square, the side to move, each castling right and each en passant file
+//! get a random 64 bit key. A position's key is all of the keys that apply to it XORed together,
+//! so making a move only has to XOR out the keys that stop applying and XOR in the new ones
+//! instead of hashing the whole board again. See [Board::zobrist_key](super::Board::zobrist_key).
+
+use super::{CastlingAvailability, Piece, PieceType, PlayerColor, Square};
+
+/// All of the random keys. They're generated at compile time from a fixed seed, so a position's
+/// key is the same on every run.
+struct ZobristKeys
+{
+    /// Indexed by [piece_index], then square index.
+    pieces: [[u64; 64]; 12],
+    /// XORed in when black is to move.
+    black_to_move: u64,
+    /// White kingside, white queenside, black kingside, black queenside.
+    castling: [u64; 4],
+    /// Indexed by the file of the en passant square.
+    en_passant_files: [u64; 8],
+}
+
+const KEYS: ZobristKeys = generate_keys(0x9E3779B97F4A7C15);
+
+/// Fills in all of the keys using [SplitMix64](https://prng.di.unimi.it/splitmix64.c), which is
+/// simple enough to run in a const fn.
+const fn generate_keys(seed: u64) -> ZobristKeys
+{
+    let mut state = seed;
+    let mut keys = ZobristKeys
+    {
+        pieces: [[0; 64]; 12],
+        black_to_move: 0,
+        castling: [0; 4],
+        en_passant_files: [0; 8],
+    };
+
+    let mut piece = 0;
+    while piece < 12
+    {
+        let mut square = 0;
+        while square < 64
+        {
+            (state, keys.pieces[piece][square]) = splitmix64(state);
+            square += 1;
+        }
+        piece += 1;
+    }
+    (state, keys.black_to_move) = splitmix64(state);
+    let mut i = 0;
+    while i < 4
+    {
+        (state, keys.castling[i]) = splitmix64(state);
+        i += 1;
+    }
+    let mut file = 0;
+    while file < 8
+    {
+        (state, keys.en_passant_files[file]) = splitmix64(state);
+        file += 1;
+    }
+    keys
+}
+
+/// Advances the generator, returning the new state and the next random number.
+const fn splitmix64(state: u64) -> (u64, u64)
+{
+    let state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (state, z ^ (z >> 31))
+}
+
+fn piece_index(piece: Piece) -> usize
+{
+    piece.color().index() * 6 + match piece.piece_type()
+    {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+/// The key for `piece` standing on `square`.
+pub(super) fn piece_key(piece: Piece, square: Square) -> u64
+{
+    KEYS.pieces[piece_index(piece)][square.index() as usize]
+}
+
+/// The key for `color` being the side to move. White to move doesn't change the key.
+pub(super) fn side_to_move_key(color: PlayerColor) -> u64
+{
+    match color
+    {
+        PlayerColor::White => 0,
+        PlayerColor::Black => KEYS.black_to_move,
+    }
+}
+
+/// The combined key for every castling right that's still available.
+pub(super) fn castling_key(castling_availability: &CastlingAvailability) -> u64
+{
+    let rights = [
+        *castling_availability.white_castle_kingside(),
+        *castling_availability.white_castle_queenside(),
+        *castling_availability.black_castle_kingside(),
+        *castling_availability.black_castle_queenside(),
+    ];
+    rights.iter().zip(KEYS.castling)
+        .filter(|(available, _)| **available)
+        .fold(0, |key, (_, castling_key)| key ^ castling_key)
+}
+
+/// The key for the en passant square, if there is one. Only the file matters, since the rank
+/// always follows from the side to move.
+pub(super) fn en_passant_key(en_passant_target_square: Option<Square>) -> u64
+{
+    en_passant_target_square.map_or(0, |square| KEYS.en_passant_files[square.file as usize])
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn keys_are_distinct()
+    {
+        let mut all_keys: Vec<u64> = KEYS.pieces.iter().flatten().copied()
+            .chain([KEYS.black_to_move])
+            .chain(KEYS.castling)
+            .chain(KEYS.en_passant_files)
+            .collect();
+        let key_count = all_keys.len();
+        all_keys.sort_unstable();
+        all_keys.dedup();
+        assert_eq!(all_keys.len(), key_count);
+        assert!(!all_keys.contains(&0));
+    }
+}
@@ -0,0 +1,316 @@
+//! This module implements [ExperienceBook], a record of how the engine's own games went.
+//!
+//! An opening book only knows what strong players like to play, not whether the engine itself
+//! actually does well in those lines. The experience book remembers the result of every move the
+//! engine played from every opening position, so lines that keep losing can be avoided next time.
+//! The book is saved to disk so it keeps learning between runs.
+
+use std::{collections::HashMap, fs, io, path::Path};
+
+use getset::CopyGetters;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{agent::MoveSource, board::{Board, Move, MoveError, PlayerColor}, game::GameState, opening_explorer::OpeningExplorer};
+
+/// Only the first 20 moves (40 ply) of each game are recorded, past that positions rarely repeat.
+const MAX_PLY: usize = 40;
+
+/// A move needs to have been played at least this many times before we trust its results enough
+/// to avoid it.
+const MINIMUM_GAMES_TO_AVOID: u32 = 3;
+
+/// Moves that score less than this for the side playing them are avoided.
+const AVOID_SCORE_THRESHOLD: f64 = 0.25;
+
+/// The errors that can happen when loading or saving an [ExperienceBook].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ExperienceBookError
+{
+    /// Reading or writing the file failed.
+    #[error("Couldn't read or write the experience book: {0}")]
+    Io(#[from] io::Error),
+    /// The file doesn't contain a valid experience book.
+    #[error("The experience book is corrupt: {0}")]
+    Serialization(#[from] postcard::Error),
+}
+
+/// The results of a move, from the point of view of the player who played it.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, CopyGetters, Serialize, Deserialize)]
+#[getset(get_copy="pub")]
+pub struct Experience
+{
+    /// How many games the move was played in.
+    games: u32,
+    /// How many of those games the player won.
+    wins: u32,
+    /// How many of those games were drawn.
+    draws: u32,
+    /// How many of those games the player lost.
+    losses: u32,
+}
+
+impl Experience
+{
+    /// Returns the average score of the move (1 for a win, 0.5 for a draw, 0 for a loss), or
+    /// [None] if it was never played.
+    pub fn score(&self) -> Option<f64>
+    {
+        match self.games
+        {
+            0 => None,
+            games => Some((self.wins as f64 + self.draws as f64 / 2.0) / games as f64),
+        }
+    }
+}
+
+/// A record of the results of every move played from every opening position in the games it was
+/// shown, which can be saved to and loaded from disk.
+///
+/// Positions are keyed by [Board::zobrist_key], so the book stays valid between runs.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Board, PlayerColor};
+/// # use rust_chess_engine::experience_book::ExperienceBook;
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::MoveCommand;
+/// let game_state = GameState::new(Board::new_default_starting_board());
+/// let f3 = game_state.legal_move(&MoveCommand::from_str("f3").unwrap()).unwrap();
+///
+/// let mut experience_book = ExperienceBook::new();
+/// for _ in 0..3
+/// {
+///     // We keep losing after 1. f3...
+///     experience_book.record_game(&[f3], Some(PlayerColor::Black)).unwrap();
+/// }
+/// assert!(experience_book.avoids(game_state.current_board(), &f3));
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExperienceBook
+{
+    positions: HashMap<u64, HashMap<Move, Experience>>,
+}
+
+impl ExperienceBook
+{
+    /// Creates a new, empty [ExperienceBook].
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Loads an [ExperienceBook] from a file previously written by [Self::save]. If the file
+    /// doesn't exist yet, an empty book is returned so the engine can start learning.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ExperienceBookError] if the file couldn't be read or is corrupt.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ExperienceBookError>
+    {
+        match fs::read(path)
+        {
+            Ok(bytes) => Ok(postcard::from_bytes(&bytes)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Saves the [ExperienceBook] to a file, overwriting it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ExperienceBookError] if the file couldn't be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ExperienceBookError>
+    {
+        fs::write(path, postcard::to_stdvec(self)?)?;
+        Ok(())
+    }
+
+    /// Records the result of a game played from the default starting position.
+    ///
+    /// # Arguments
+    ///
+    /// * `moves` - The moves of the game, in order.
+    /// * `winner` - The color that won the game, or [None] if it was a draw.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [MoveError] if one of the moves couldn't be made. The moves before it are still
+    /// recorded.
+    pub fn record_game(&mut self, moves: &[Move], winner: Option<PlayerColor>) -> Result<(), MoveError>
+    {
+        let mut board = Board::new_default_starting_board();
+        for r#move in moves.iter().take(MAX_PLY)
+        {
+            let next_board = board.attempt_move(r#move)?;
+            let experience = self.positions.entry(board.zobrist_key()).or_default()
+                .entry(*r#move).or_default();
+            experience.games += 1;
+            match winner
+            {
+                None => experience.draws += 1,
+                Some(winner) if winner == board.active_color() => experience.wins += 1,
+                Some(_) => experience.losses += 1,
+            }
+            board = next_board;
+        }
+        Ok(())
+    }
+
+    /// Returns the recorded results of playing `r#move` on `board`, if it was ever played.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position the move was played from.
+    /// * `r#move` - The move.
+    pub fn experience(&self, board: &Board, r#move: &Move) -> Option<Experience>
+    {
+        self.positions.get(&board.zobrist_key())
+            .and_then(|moves| moves.get(r#move))
+            .copied()
+    }
+
+    /// Returns true if `r#move` has been played from `board` enough times, and done badly enough,
+    /// that it should be avoided.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position the move would be played from.
+    /// * `r#move` - The move.
+    pub fn avoids(&self, board: &Board, r#move: &Move) -> bool
+    {
+        self.experience(board, r#move).is_some_and(|experience|
+            experience.games >= MINIMUM_GAMES_TO_AVOID
+            && experience.score().is_some_and(|score| score < AVOID_SCORE_THRESHOLD)
+        )
+    }
+}
+
+/// An opening book that steers clear of the lines an [ExperienceBook] says keep losing.
+///
+/// As a [MoveSource] it plays the most popular book move that isn't avoided, so it can be used as
+/// a stage of a [PipelineAgent](crate::agent::PipelineAgent) in place of a plain
+/// [OpeningExplorer].
+pub struct ExperiencedBook
+{
+    book: OpeningExplorer,
+    experience: ExperienceBook,
+}
+
+impl ExperiencedBook
+{
+    /// Combines an opening book with the engine's experience.
+    ///
+    /// # Arguments
+    ///
+    /// * `book` - The opening book to pick moves from.
+    /// * `experience` - The engine's experience, used to skip losing book moves.
+    pub fn new(book: OpeningExplorer, experience: ExperienceBook) -> Self
+    {
+        Self
+        {
+            book,
+            experience,
+        }
+    }
+}
+
+impl MoveSource for ExperiencedBook
+{
+    fn probe(&mut self, game_state: &GameState) -> Option<Move> {
+        let board = game_state.current_board();
+        self.book.moves(board).into_iter()
+            .map(|(r#move, _)| r#move)
+            .filter(|r#move| !self.experience.avoids(board, r#move))
+            .find(|r#move| board.attempt_move(r#move).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{env, str::FromStr};
+
+    use crate::parse::MoveCommand;
+
+    use super::*;
+
+    fn starting_move(move_command: &str) -> Move
+    {
+        GameState::new(Board::new_default_starting_board())
+            .legal_move(&MoveCommand::from_str(move_command).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn results_are_from_the_movers_point_of_view()
+    {
+        let board = Board::new_default_starting_board();
+        let e4 = starting_move("e4");
+        let e5 = board.attempt_move(&e4).unwrap().get_move(&MoveCommand::from_str("e5").unwrap()).unwrap();
+        let mut experience_book = ExperienceBook::new();
+        experience_book.record_game(&[e4, e5], Some(PlayerColor::White)).unwrap();
+
+        let white_experience = experience_book.experience(&board, &e4).unwrap();
+        assert_eq!(white_experience.wins(), 1);
+        let black_experience = experience_book.experience(&board.attempt_move(&e4).unwrap(), &e5).unwrap();
+        assert_eq!(black_experience.losses(), 1);
+    }
+
+    #[test]
+    fn needs_several_games_before_avoiding_a_move()
+    {
+        let f3 = starting_move("f3");
+        let mut experience_book = ExperienceBook::new();
+        experience_book.record_game(&[f3], Some(PlayerColor::Black)).unwrap();
+        assert!(!experience_book.avoids(&Board::new_default_starting_board(), &f3));
+    }
+
+    #[test]
+    fn save_and_load_round_trip()
+    {
+        let path = env::temp_dir().join(format!("experience_book_test_{}.bin", std::process::id()));
+        let d4 = starting_move("d4");
+        let mut experience_book = ExperienceBook::new();
+        experience_book.record_game(&[d4], None).unwrap();
+        experience_book.save(&path).unwrap();
+
+        let loaded = ExperienceBook::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.experience(&Board::new_default_starting_board(), &d4).unwrap().draws(), 1);
+    }
+
+    #[test]
+    fn missing_file_loads_an_empty_book()
+    {
+        let path = env::temp_dir().join("this_experience_book_does_not_exist.bin");
+        assert!(ExperienceBook::load(path).unwrap().positions.is_empty());
+    }
+
+    #[test]
+    fn experienced_book_skips_losing_lines()
+    {
+        let mut book = OpeningExplorer::new();
+        book.add_pgn("1. f3 *\n\n1. f3 *\n\n1. e4 *").unwrap();
+        let mut experience = ExperienceBook::new();
+        for _ in 0..3
+        {
+            experience.record_game(&[starting_move("f3")], Some(PlayerColor::Black)).unwrap();
+        }
+        let mut experienced_book = ExperiencedBook::new(book, experience);
+        let game_state = GameState::new(Board::new_default_starting_board());
+        assert_eq!(experienced_book.probe(&game_state), Some(starting_move("e4")));
+    }
+}
@@ -1,31 +1,365 @@
 //! This module implements the main game loop
 
+use std::{fs::{self, File}, io::{self, BufWriter, Write}, path::Path, str::FromStr, sync::{Arc, OnceLock}, time::{Duration, Instant}};
+
 use getset::Getters;
+use log::{debug, info, log, warn, Level};
+use serde::{Deserialize, Serialize};
+
+use crate::{agent::{Agent, AgentAction}, board::{ApplyMovesError, Board, BoardConfiguration, BoardResult, DrawReason, DrawRules, Move, MoveError, PlayerColor, SanMoveError}, parse::{lichess_analysis_url, parse_pgn, write_pgn, AnnotatedMove, MoveCommand, PgnGame, PgnResult}, rules::{rules_by_name, Rules, StandardRules}};
+
+mod game_record;
+mod clock;
+mod error;
+mod saved_game_state;
+mod game_observer;
+
+pub use game_record::{GameRecord, Termination};
+pub use clock::{Clock, TimeControl};
+pub use error::{ActionError, LoadPgnError, SavedGameError};
+pub use game_observer::GameObserver;
 
-use crate::{agent::Agent, board::{Board, Move, MoveError}};
+use saved_game_state::SavedGameState;
+
+/// The [log](mod@log) target for game loop events, like moves being played and games ending. Use it to
+/// turn game logging up or down on its own, i.e `RUST_LOG=rust_chess_engine::game=debug` with
+/// [env_logger](https://docs.rs/env_logger).
+pub const LOG_TARGET: &str = "rust_chess_engine::game";
 
 /// A game of chess!!!
-pub struct Game<A1, A2>
+///
+/// A game is generic over the types of its two [Agent]s, which lets the agents be used directly.
+/// When the agents are only known at runtime, i.e picked from a config file or a list of
+/// tournament players, both sides can be boxed instead. That's what a plain `Game` without type
+/// parameters is, see [Game::new_boxed].
+pub struct Game<A1 = Box<dyn Agent>, A2 = Box<dyn Agent>>
 where A1: Agent, A2: Agent
 {
     game_state: GameState,
     agent_white: A1,
     agent_black: A2,
+    trace: Option<BufWriter<File>>,
+    /// How long each move of the game took. Moves from before the game was resumed count as
+    /// taking no time.
+    move_times: Vec<Duration>,
+    /// Moves taken back with [Self::undo] and how long they took, most recently undone last, so
+    /// [Self::redo] can play them again.
+    undone_moves: Vec<(Move, Duration)>,
+    /// Everything following the game, see [Self::add_observer].
+    observers: Vec<Box<dyn GameObserver>>,
 }
 
-/// Holds the current board.
-/// Will also probably hold things like the board history 
-/// and the last made move so specific agents can use them when needed.
-#[derive(Getters)]
+/// Holds the current board, the moves that led up to it, and the positions since the last pawn
+/// move or capture, so repetitions can be detected.
+///
+/// A [GameState] can be serialized, i.e to send it to a remote player or to adjourn a game to
+/// disk (see [Game::save]). Only the starting board and the moves are stored, and deserializing
+/// replays the moves, so it fails if any of them are illegal.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::MoveCommand;
+/// let game_state = GameState::new(Board::new_default_starting_board());
+/// let e4 = game_state.legal_move(&MoveCommand::from_str("e4").unwrap()).unwrap();
+/// let game_state = game_state.update(&e4).unwrap();
+///
+/// let bytes = postcard::to_stdvec(&game_state).unwrap();
+/// let loaded: GameState = postcard::from_bytes(&bytes).unwrap();
+/// assert_eq!(loaded.current_board(), game_state.current_board());
+/// assert_eq!(loaded.moves(), game_state.moves());
+/// ```
+#[derive(Getters, Clone, Serialize, Deserialize)]
+#[serde(into = "SavedGameState", try_from = "SavedGameState")]
 #[getset(get="pub")]
 pub struct GameState
 {
     /// Gets the current [Board].
     current_board: Board,
+    /// Gets the [Board] the game started from.
+    starting_board: Board,
+    /// Gets every move made since the starting board, oldest first.
+    moves: Vec<Move>,
+    /// The [Zobrist keys](Board::zobrist_key) of every position since the last pawn move or
+    /// capture, oldest first and ending with the current board. Positions from before a pawn
+    /// move or capture can never come back, so they aren't kept.
+    #[getset(skip)]
+    position_history: Vec<u64>,
+    /// The legal moves on the current board, generated the first time they're asked for.
+    #[getset(skip)]
+    legal_moves: OnceLock<Vec<Move>>,
+    /// The player who offered a draw with the last move, if they did.
+    #[getset(skip)]
+    draw_offer: Option<PlayerColor>,
+    /// How the game ended, if it ended off the board, by resignation or a draw the players
+    /// agreed on or claimed.
+    #[getset(skip)]
+    outcome: Option<BoardResult>,
+    /// The clock, if the game is played with one.
+    #[getset(skip)]
+    clock: Option<Clock>,
+    /// The rules the game is played by.
+    #[getset(skip)]
+    rules: Arc<dyn Rules>,
+    /// How many times White has given check.
+    #[getset(skip)]
+    white_checks: u32,
+    /// How many times Black has given check.
+    #[getset(skip)]
+    black_checks: u32,
 }
 
 impl GameState
 {
+    /// Creates a new [GameState] starting from the given [Board].
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The current board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// assert_eq!(game_state.current_board(), &Board::new_default_starting_board());
+    /// ```
+    pub fn new(board: Board) -> Self
+    {
+        Self::with_rules(board, StandardRules)
+    }
+
+    /// Creates a new [GameState] starting from the given [Board], played by the rules of a
+    /// variant instead of standard chess.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The current board.
+    /// * `rules` - The rules to play by.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::rules::KingOfTheHill;
+    /// let game_state = GameState::with_rules(Board::new_default_starting_board(), KingOfTheHill);
+    /// assert_eq!(game_state.rules().name(), "King of the Hill");
+    /// ```
+    pub fn with_rules<R: Rules + 'static>(board: Board, rules: R) -> Self
+    {
+        Self::with_shared_rules(board, Arc::new(rules))
+    }
+
+    /// Like [Self::with_rules], but with rules that might be shared with other games.
+    fn with_shared_rules(board: Board, rules: Arc<dyn Rules>) -> Self
+    {
+        Self
+        {
+            position_history: vec![board.zobrist_key()],
+            starting_board: board.clone(),
+            moves: Vec::new(),
+            current_board: board,
+            legal_moves: OnceLock::new(),
+            draw_offer: None,
+            outcome: None,
+            clock: None,
+            rules,
+            white_checks: 0,
+            black_checks: 0,
+        }
+    }
+
+    /// Replays a game read from PGN, checking that every move is legal along the way. The game
+    /// starts from its `FEN` tag if it has one, or the default starting position otherwise, and
+    /// is played by the rules of its `Variant` tag if it has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to replay.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [LoadPgnError] if the `FEN` tag is invalid, the `Variant` tag isn't a known
+    /// variant, or one of the moves is impossible or illegal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::parse_pgn;
+    /// let games = parse_pgn("1. e4 e5 2. Nf3 Nc6 *").unwrap();
+    /// let game_state = GameState::from_pgn(&games[0]).unwrap();
+    /// assert_eq!(game_state.moves().len(), 4);
+    ///
+    /// let games = parse_pgn("1. e4 e5 2. Ke3 *").unwrap();
+    /// assert!(GameState::from_pgn(&games[0]).is_err());
+    /// ```
+    pub fn from_pgn(game: &PgnGame) -> Result<Self, LoadPgnError>
+    {
+        let starting_board = match game.tag("FEN")
+        {
+            Some(fen) => Board::new_board_with_configuration(&BoardConfiguration::from_str(fen)?),
+            None => Board::new_default_starting_board(),
+        };
+        let rules = match game.tag("Variant")
+        {
+            Some(variant) => rules_by_name(variant).ok_or_else(|| LoadPgnError::UnknownVariant(variant.to_string()))?,
+            None => Arc::new(StandardRules),
+        };
+        let mut game_state = Self::with_shared_rules(starting_board, rules);
+        for (index, move_command) in game.moves().iter().enumerate()
+        {
+            game_state = game_state.legal_move(move_command)
+                .and_then(|r#move| game_state.update(&r#move))
+                .map_err(|error| ApplyMovesError { index, error })?;
+        }
+        Ok(game_state)
+    }
+
+    /// Returns the [Rules] the game is played by.
+    pub fn rules(&self) -> &dyn Rules
+    {
+        self.rules.as_ref()
+    }
+
+    /// Returns how many times `color` has given check so far this game, counting checkmate.
+    /// Checks given before the starting board don't count.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The player whose checks to count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, PlayerColor};
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut game_state = GameState::new(Board::new_default_starting_board());
+    /// for r#move in ["e4", "f5", "Qh5+"]
+    /// {
+    ///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+    ///     game_state = game_state.update(&r#move).unwrap();
+    /// }
+    /// assert_eq!(game_state.checks_given(PlayerColor::White), 1);
+    /// assert_eq!(game_state.checks_given(PlayerColor::Black), 0);
+    /// ```
+    pub fn checks_given(&self, color: PlayerColor) -> u32
+    {
+        match color
+        {
+            PlayerColor::White => self.white_checks,
+            PlayerColor::Black => self.black_checks,
+        }
+    }
+
+    /// Returns all of the legal moves the active player can make on the current board, going by
+    /// the game's [Rules].
+    ///
+    /// Move generation is fairly expensive, so the moves are only generated once per position
+    /// and cached, no matter how many agents, displays, etc. ask for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// assert_eq!(game_state.legal_moves().len(), 20);
+    /// ```
+    pub fn legal_moves(&self) -> &[Move]
+    {
+        self.legal_moves.get_or_init(||
+            self.rules.legal_moves(&self.current_board)
+        )
+    }
+
+    /// Converts a [MoveCommand] into a [Move] and checks that it's legal on the current board.
+    ///
+    /// This is mostly useful for moves that were decided on *before* the current position was
+    /// reached, like premoves, which need to be checked again once the opponent has moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `move_command` - The move to look for.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [MoveError] if the move is impossible or illegal on the current board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// assert!(game_state.legal_move(&MoveCommand::from_str("Nf3").unwrap()).is_ok());
+    /// assert!(game_state.legal_move(&MoveCommand::from_str("Nf4").unwrap()).is_err());
+    /// ```
+    pub fn legal_move(&self, move_command: &MoveCommand) -> Result<Move, MoveError>
+    {
+        let r#move = self.current_board.get_move(move_command)?;
+        self.rules.check_move(&self.current_board, &r#move)?;
+        Ok(r#move)
+    }
+
+    /// Returns how many times the current position has come up in this game, counting the
+    /// current board. Positions are the same if the same pieces are on the same squares, the
+    /// same side is to move, and castling and en passant are the same, see [Board::zobrist_key].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut game_state = GameState::new(Board::new_default_starting_board());
+    /// assert_eq!(game_state.repetition_count(), 1);
+    /// for r#move in ["Nf3", "Nf6", "Ng1", "Ng8"]
+    /// {
+    ///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+    ///     game_state = game_state.update(&r#move).unwrap();
+    /// }
+    /// assert_eq!(game_state.repetition_count(), 2);
+    /// ```
+    pub fn repetition_count(&self) -> usize
+    {
+        let current_key = self.current_board.zobrist_key();
+        self.position_history.iter().filter(|key| **key == current_key).count()
+    }
+
+    /// Returns the result of the game on the current board. Same as [Board::game_result] but
+    /// reuses the cached [Self::legal_moves], and also ends the game in a draw once the same
+    /// position comes up for the fifth time (see [DrawRules]). Games that ended by resignation or a draw the
+    /// players agreed on or claimed (see [Self::apply_action]) return how they ended, and games
+    /// won by a variant's own rules return that, see [Rules::variant_result].
+    pub fn game_result(&self) -> BoardResult
+    {
+        if let Some(outcome) = self.outcome
+        {
+            return outcome;
+        }
+        if let Some(result) = self.rules.variant_result(self)
+        {
+            return result;
+        }
+        match self.current_board.game_result_with_legal_moves(self.legal_moves())
+        {
+            BoardResult::InProgress => self.draw_rules().automatic_draw(self.current_board.halfmove_clock(), self.repetition_count())
+                .map_or(BoardResult::InProgress, BoardResult::Draw),
+            result => result,
+        }
+    }
+
     /// Makes a [Move] on a [Board] 
     /// and updates the state of the game accordingly.
     ///
@@ -35,7 +369,8 @@ impl GameState
     ///
     /// # Errors
     ///
-    /// Returns a [MoveError] if [Board::attempt_move] failed.
+    /// Returns a [MoveError] if the move isn't legal by the game's [Rules], see
+    /// [Rules::check_move].
     ///
     /// # Examples
     ///
@@ -43,92 +378,1419 @@ impl GameState
     /// ```
     pub fn update(&self, r#move: &Move) -> Result<Self, MoveError>
     {
+        // Moves can come from anywhere (i.e a remote peer), so make sure they're actually legal
+        // and not just possible to make.
+        self.rules.check_move(&self.current_board, r#move)?;
+        let next_board = self.rules.make_move(&self.current_board, r#move)?;
+        let mut position_history = match next_board.halfmove_clock()
+        {
+            // A pawn move or capture means none of the earlier positions can come up again.
+            0 => Vec::new(),
+            _ => self.position_history.clone(),
+        };
+        position_history.push(next_board.zobrist_key());
+        let mut moves = self.moves.clone();
+        moves.push(*r#move);
+        let (mut white_checks, mut black_checks) = (self.white_checks, self.black_checks);
+        if next_board.attack_info().is_in_check(next_board.active_color())
+        {
+            match self.current_board.active_color()
+            {
+                PlayerColor::White => white_checks += 1,
+                PlayerColor::Black => black_checks += 1,
+            }
+        }
         Ok(Self
         {
-            current_board: self.current_board.attempt_move(r#move)?,
+            current_board: next_board,
+            starting_board: self.starting_board.clone(),
+            moves,
+            position_history,
+            legal_moves: OnceLock::new(),
+            draw_offer: None,
+            outcome: None,
+            clock: self.clock,
+            rules: self.rules.clone(),
+            white_checks,
+            black_checks,
         })
     }
-}
 
-impl<A1, A2> Game<A1, A2>
-where A1: Agent, A2: Agent
-{
-    /// Creates a new [Game] with the given [Agent]s.
+    /// Parses a move in algebraic notation, checks that it's legal, and makes it, in one go.
+    /// Same as [Self::legal_move] followed by [Self::update].
     ///
     /// # Arguments
     ///
-    /// * `agent_white` - The white player [Agent]
-    /// * `agent_black` - The black player [Agent]
+    /// * `san` - The move, i.e `"e4"`, `"Nxf7"` or `"O-O"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [SanMoveError::Parse] if `san` isn't valid algebraic notation, or
+    /// [SanMoveError::Move] if the move isn't legal by the game's [Rules].
     ///
     /// # Examples
     ///
-    pub fn new(agent_white: A1, agent_black: A2) -> Self
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let game_state = GameState::new(Board::new_default_starting_board())
+    ///     .play("e4").unwrap()
+    ///     .play("e5").unwrap();
+    /// assert_eq!(game_state.moves().len(), 2);
+    /// assert!(game_state.play("e5").is_err());
+    /// ```
+    pub fn play(&self, san: &str) -> Result<Self, SanMoveError>
     {
-        Self
+        let r#move = self.legal_move(&MoveCommand::from_str(san)?)?;
+        Ok(self.update(&r#move)?)
+    }
+
+    /// Returns the last move made, or [None] if no moves have been made since the starting board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// assert_eq!(game_state.last_move(), None);
+    /// let r#move = game_state.legal_moves()[0];
+    /// assert_eq!(game_state.update(&r#move).unwrap().last_move(), Some(&r#move));
+    /// ```
+    pub fn last_move(&self) -> Option<&Move>
+    {
+        self.moves.last()
+    }
+
+    /// Returns every move made since the starting board, oldest first, each with the [Board] it
+    /// was played on. The boards aren't stored, so they're worked out again by replaying the
+    /// moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// let e4 = game_state.legal_move(&MoveCommand::from_str("e4").unwrap()).unwrap();
+    /// let history = game_state.update(&e4).unwrap().history();
+    /// assert_eq!(history, vec![(e4, Board::new_default_starting_board())]);
+    /// ```
+    pub fn history(&self) -> Vec<(Move, Board)>
+    {
+        let mut board = self.starting_board.clone();
+        self.moves.iter().map(|r#move|
         {
-            game_state: GameState
-            {
-                current_board: Board::new_default_starting_board(),
-            },
-            agent_white,
-            agent_black,
+            // Every move was checked by Self::update, so they can all be made again.
+            let next_board = board.attempt_move(r#move).expect("moves in a GameState are always legal");
+            (*r#move, std::mem::replace(&mut board, next_board))
+        }).collect()
+    }
+
+    /// Returns every move made since the starting board in standard algebraic notation, oldest
+    /// first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut game_state = GameState::new(Board::new_default_starting_board());
+    /// for r#move in ["f3", "e5", "g4", "Qh4"]
+    /// {
+    ///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+    ///     game_state = game_state.update(&r#move).unwrap();
+    /// }
+    /// assert_eq!(game_state.san_moves(), vec!["f3", "e5", "g4", "Qh4#"]);
+    /// ```
+    pub fn san_moves(&self) -> Vec<String>
+    {
+        self.history().iter().map(|(r#move, board)| board.to_san(r#move)).collect()
+    }
+
+    /// Returns the state of the game from before the last move, or [None] if no moves have been
+    /// made since the starting board. The game is played through again from the starting board,
+    /// so draw offers and resignations or draws that ended the game are forgotten. The clock
+    /// isn't turned back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// assert!(game_state.undo().is_none());
+    /// let after_move = game_state.update(&game_state.legal_moves()[0]).unwrap();
+    /// assert_eq!(after_move.undo().unwrap().current_board(), game_state.current_board());
+    /// ```
+    pub fn undo(&self) -> Option<Self>
+    {
+        let (_, moves) = self.moves.split_last()?;
+        let mut game_state = moves.iter().fold(Self::with_shared_rules(self.starting_board.clone(), self.rules.clone()), |game_state, r#move|
+        {
+            // Every move was checked by Self::update, so they can all be made again.
+            game_state.update(r#move).expect("moves in a GameState are always legal")
+        });
+        game_state.clock = self.clock;
+        Some(game_state)
+    }
+
+    /// Returns how the game ended, or [None] if it's still in progress.
+    pub fn termination(&self) -> Option<Termination>
+    {
+        match (self.game_result(), self.outcome)
+        {
+            (BoardResult::InProgress, _) => None,
+            (BoardResult::Draw(reason), _) => Some(Termination::Draw(reason)),
+            (BoardResult::LossOnTime(_), _) => Some(Termination::TimeForfeit),
+            // Wins that didn't happen on the board are resignations.
+            (BoardResult::Win(_), Some(_)) => Some(Termination::Resignation),
+            (BoardResult::Win(_), None) if self.rules.variant_result(self).is_some() => Some(Termination::VariantWin),
+            (BoardResult::Win(_), None) => Some(Termination::Checkmate),
         }
     }
 
-    /// Runs the full game until the game is over
-    pub fn run(&mut self) 
+    /// Returns the [Clock], or [None] if the game is played without one. See
+    /// [Game::set_time_control].
+    pub fn clock(&self) -> Option<&Clock>
+    {
+        self.clock.as_ref()
+    }
+
+    /// Returns true if the player who made the last move offered a draw with it, which the side
+    /// to move can accept with [AgentAction::AcceptDraw].
+    pub fn draw_offered(&self) -> bool
+    {
+        self.draw_offer.is_some()
+    }
+
+    /// Returns the reason the side to move could claim a draw, or [None] if they can't. By
+    /// default a draw can be claimed once the same position has come up three times, or after
+    /// fifty moves by each side without a pawn move or capture, see [DrawRules].
+    pub fn claimable_draw(&self) -> Option<DrawReason>
+    {
+        self.draw_rules().claimable_draw(self.current_board.halfmove_clock(), self.repetition_count())
+    }
+
+    /// Returns the [DrawRules] the game is played with.
+    pub fn draw_rules(&self) -> DrawRules
+    {
+        self.current_board.draw_rules()
+    }
+
+    /// Returns the same game played with different [DrawRules], i.e to end engine games on the
+    /// first threefold repetition instead of waiting for a fivefold one.
+    ///
+    /// # Arguments
+    ///
+    /// * `draw_rules` - The draw rules to play with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Board, DrawReason, DrawRules};
+    /// # use rust_chess_engine::game::GameState;
+    /// let mut game_state = GameState::new(Board::new_default_starting_board())
+    ///     .with_draw_rules(DrawRules::default().with_repetitions(3, 3));
+    /// for r#move in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"]
+    /// {
+    ///     game_state = game_state.play(r#move).unwrap();
+    /// }
+    /// assert!(game_state.game_result().is_draw());
+    /// ```
+    pub fn with_draw_rules(mut self, draw_rules: DrawRules) -> Self
+    {
+        self.starting_board = self.starting_board.with_draw_rules(draw_rules);
+        self.current_board = self.current_board.with_draw_rules(draw_rules);
+        self
+    }
+
+    /// Carries out an [AgentAction] by the side to move, and returns the state of the game after
+    /// it. Moves are made with [Self::update], a draw offer stands until the opponent's next
+    /// action, resigning or agreeing on or claiming a draw ends the game, and a takeback
+    /// [undoes](Self::undo) the last two moves so the side to move can play something else.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - What the side to move does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ActionError] if the game is already over, the move is illegal, there's no draw
+    /// offer to accept, there's no draw to claim or the side to move hasn't made a move to take
+    /// back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::AgentAction;
+    /// # use rust_chess_engine::board::{Board, DrawReason, PlayerColor};
+    /// # use rust_chess_engine::game::GameState;
+    /// let game_state = GameState::new(Board::new_default_starting_board());
+    /// let offered = game_state.apply_action(&AgentAction::OfferDraw(game_state.legal_moves()[0])).unwrap();
+    /// assert!(offered.draw_offered());
+    /// let agreed = offered.apply_action(&AgentAction::AcceptDraw).unwrap();
+    /// assert_eq!(agreed.game_result().get_draw_reason(), Some(DrawReason::Agreement));
+    ///
+    /// let resigned = game_state.apply_action(&AgentAction::Resign).unwrap();
+    /// assert_eq!(resigned.game_result().get_winner(), Ok(PlayerColor::Black));
+    /// assert!(game_state.apply_action(&AgentAction::AcceptDraw).is_err());
+    /// ```
+    pub fn apply_action(&self, action: &AgentAction) -> Result<Self, ActionError>
     {
-        while self.game_state.current_board().game_result().is_in_progress()
+        if self.game_result().is_over()
         {
-            self.next_round()
+            return Err(ActionError::GameOver);
         }
+        let active_color = self.current_board.active_color();
+        let outcome = match action
+        {
+            AgentAction::Move(r#move) => return Ok(self.update(r#move)?),
+            AgentAction::OfferDraw(r#move) =>
+            {
+                let mut game_state = self.update(r#move)?;
+                game_state.draw_offer = Some(active_color);
+                return Ok(game_state);
+            },
+            AgentAction::AcceptDraw if self.draw_offered() => BoardResult::Draw(DrawReason::Agreement),
+            AgentAction::AcceptDraw => return Err(ActionError::NoDrawOffered),
+            AgentAction::ClaimDraw => BoardResult::Draw(self.claimable_draw().ok_or(ActionError::NoDrawToClaim)?),
+            AgentAction::Resign => BoardResult::Win(!active_color),
+            AgentAction::Takeback => return self.undo()
+                .and_then(|game_state| game_state.undo())
+                .ok_or(ActionError::NoMoveToTakeBack),
+        };
+        let mut game_state = self.clone();
+        game_state.draw_offer = None;
+        game_state.outcome = Some(outcome);
+        Ok(game_state)
+    }
 
-        // Once the game is over we do something idk
-        println!("Game is over!");
-        println!("Result: {:?}", self.game_state.current_board().game_result());
+    /// Returns the state of the game after the side to move ran out of time on `clock`. They lose,
+    /// unless their opponent couldn't have checkmated them anyways, in which case it's a draw.
+    fn time_out(&self, clock: Clock) -> Self
+    {
+        let color = self.current_board.active_color();
+        let outcome = match self.current_board.has_mating_material(!color)
+        {
+            true => BoardResult::LossOnTime(color),
+            false => BoardResult::Draw(DrawReason::TimeoutVsInsufficientMaterial),
+        };
+        let mut game_state = self.clone();
+        game_state.draw_offer = None;
+        game_state.outcome = Some(outcome);
+        game_state.clock = Some(clock);
+        game_state
     }
 
-    /// Progresses the game by one "round", i.e
-    /// one move by white and one move by black.
+    /// Writes the game so far as PGN, with the given tag pairs followed by a `Result` tag for
+    /// the current [Self::game_result]. Games that don't start from the standard position also
+    /// get `SetUp` and `FEN` tags. See [Game::to_pgn] for a game with the Seven Tag Roster
+    /// filled in.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tag pairs to write before the result, like `("White", "Carlsen")`.
     ///
     /// # Examples
     ///
-    pub fn next_round(&mut self)
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut game_state = GameState::new(Board::new_default_starting_board());
+    /// for r#move in ["f3", "e5", "g4", "Qh4"]
+    /// {
+    ///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+    ///     game_state = game_state.update(&r#move).unwrap();
+    /// }
+    /// let pgn = game_state.to_pgn(&[("Event".to_string(), "Fool's mate".to_string())]);
+    /// assert_eq!(pgn, "[Event \"Fool's mate\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n");
+    /// ```
+    pub fn to_pgn(&self, tags: &[(String, String)]) -> String
     {
-        if self.game_state.current_board().game_result().is_in_progress()
+        let result = PgnResult::from(self.game_result());
+        let mut tags = tags.to_vec();
+        tags.push(("Result".to_string(), result.to_string()));
+        if self.starting_board != Board::new_default_starting_board()
         {
-            self.game_state = Self::agent_turn(&self.game_state, &mut self.agent_white);
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), self.starting_board.board_configuration().to_string()));
         }
-        if self.game_state.current_board().game_result().is_in_progress()
+        if self.rules.name() != StandardRules.name()
         {
-            self.game_state = Self::agent_turn(&self.game_state, &mut self.agent_black);
+            tags.push(("Variant".to_string(), self.rules.name().to_string()));
         }
+        tags.extend(self.rules.pgn_tags(self));
+        let moves: Vec<AnnotatedMove> = self.moves.iter().copied().map(AnnotatedMove::from).collect();
+        // Every move was checked by Self::update, so they can all be made again.
+        write_pgn(&tags, &self.starting_board, &moves, result)
+            .expect("moves in a GameState are always legal")
     }
+}
 
-    /// Progresses the game by one "turn",
-    /// i.e one move by either white or black.
+impl Game
+{
+    /// Creates a new [Game] with the given [Agent]s boxed up, so games between different kinds
+    /// of agents all have the same type.
     ///
     /// # Arguments
     ///
-    /// * `agent` - The agent taking their turn.
+    /// * `agent_white` - The white player [Agent]
+    /// * `agent_black` - The black player [Agent]
+    ///
+    /// # Examples
     ///
-    fn agent_turn<A: Agent>(game_state: &GameState, agent: &mut A) -> GameState
+    /// ```
+    /// # use rust_chess_engine::agent::{GreedyAgent, MinmaxAgent};
+    /// # use rust_chess_engine::game::Game;
+    /// let mut games: Vec<Game> = vec![
+    ///     Game::new_boxed(MinmaxAgent::new(1), GreedyAgent::new()),
+    ///     Game::new_boxed(GreedyAgent::new(), GreedyAgent::new()),
+    /// ];
+    /// for game in &mut games
+    /// {
+    ///     game.next_round();
+    /// }
+    /// ```
+    pub fn new_boxed<A1, A2>(agent_white: A1, agent_black: A2) -> Self
+    where A1: Agent + 'static, A2: Agent + 'static
     {
-        loop
+        Self::new(Box::new(agent_white), Box::new(agent_black))
+    }
+}
+
+impl<A1, A2> Game<A1, A2>
+where A1: Agent, A2: Agent
+{
+    /// Boxes up both [Agent]s, turning this into a plain [Game] whose type doesn't depend on
+    /// the agents'. The game carries on where it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::{GreedyAgent, MinmaxAgent};
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(MinmaxAgent::new(1), GreedyAgent::new());
+    /// game.next_round();
+    /// let game: Game = game.into_boxed();
+    /// assert_eq!(game.game_state().moves().len(), 2);
+    /// ```
+    pub fn into_boxed(self) -> Game
+    where A1: 'static, A2: 'static
+    {
+        Game
         {
-            let move_request = agent.agent_move_request(game_state);
-            let new_game_state = game_state.update(&move_request);
-            match new_game_state
-            {
-                Err(error) => {
-                    println!("Error making move! {}", error);
-                    continue;
-                },
-                Ok(new_game_state) =>
-                {
-                    return new_game_state;
-                }
-            }
+            game_state: self.game_state,
+            agent_white: Box::new(self.agent_white),
+            agent_black: Box::new(self.agent_black),
+            trace: self.trace,
+            move_times: self.move_times,
+            undone_moves: self.undone_moves,
+            observers: self.observers,
+        }
+    }
+
+    /// Loads the first game in a PGN string and picks it up from its final position with the
+    /// given [Agent]s, i.e to finish an adjourned game or play on from a game between humans.
+    /// See [GameState::from_pgn].
+    ///
+    /// # Arguments
+    ///
+    /// * `pgn` - The contents of a PGN file. Only the first game is used.
+    /// * `agent_white` - The white player [Agent]
+    /// * `agent_black` - The black player [Agent]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [LoadPgnError] if the PGN can't be parsed, has no games, or the first game
+    /// can't be replayed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::game::Game;
+    /// let game = Game::from_pgn("[Event \"Casual game\"]\n\n1. e4 e5 2. Nf3 *", MinmaxAgent::new(1), MinmaxAgent::new(1)).unwrap();
+    /// assert_eq!(game.game_state().moves().len(), 3);
+    /// ```
+    pub fn from_pgn(pgn: &str, agent_white: A1, agent_black: A2) -> Result<Self, LoadPgnError>
+    {
+        let game = parse_pgn(pgn)?.into_iter().next().ok_or(LoadPgnError::NoGames)?;
+        Ok(Self::resume(GameState::from_pgn(&game)?, agent_white, agent_black))
+    }
+
+    /// Creates a new [Game] with the given [Agent]s.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent_white` - The white player [Agent]
+    /// * `agent_black` - The black player [Agent]
+    ///
+    /// # Examples
+    ///
+    pub fn new(agent_white: A1, agent_black: A2) -> Self
+    {
+        Self::resume(GameState::new(Board::new_default_starting_board()), agent_white, agent_black)
+    }
+
+    /// Resumes a game from the given [GameState] with the given [Agent]s.
+    ///
+    /// This is the counterpart to [Self::adjourn], and can also be used to start a game from any
+    /// position. Both agents are told which color they're playing through
+    /// [Agent::on_game_start].
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The state to pick the game up from.
+    /// * `agent_white` - The white player [Agent]
+    /// * `agent_black` - The black player [Agent]
+    pub fn resume(game_state: GameState, mut agent_white: A1, mut agent_black: A2) -> Self
+    {
+        agent_white.on_game_start(PlayerColor::White);
+        agent_black.on_game_start(PlayerColor::Black);
+        debug!(target: LOG_TARGET, "Game started from {}", game_state.current_board().board_configuration());
+        Self
+        {
+            move_times: vec![Duration::ZERO; game_state.moves().len()],
+            game_state,
+            agent_white,
+            agent_black,
+            trace: None,
+            undone_moves: Vec::new(),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Writes every event of this game (moves, rejected moves and the result) to a trace file as
+    /// well as to the [log](mod@log), so a single game can be looked at after the fact without turning up
+    /// logging for everything else.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Where to write the trace. The file is created, or truncated if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [io::Error] if the file can't be created.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(MinmaxAgent::new(2), MinmaxAgent::new(2));
+    /// game.set_trace_file("game.trace").unwrap();
+    /// game.run();
+    /// ```
+    pub fn set_trace_file(&mut self, path: impl AsRef<Path>) -> io::Result<()>
+    {
+        self.trace = Some(BufWriter::new(File::create(path)?));
+        Ok(())
+    }
+
+    /// Plays the rest of the game with a chess clock set to `time_control`, starting both players
+    /// on the full base time. Whoever runs out of time first loses (see [BoardResult::LossOnTime]).
+    /// The agents aren't told about the clock, but can look at it through [GameState::clock].
+    ///
+    /// # Arguments
+    ///
+    /// * `time_control` - How much time each player gets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::board::PlayerColor;
+    /// # use rust_chess_engine::game::{Game, TimeControl};
+    /// let mut game = Game::new(MinmaxAgent::new(1), MinmaxAgent::new(1));
+    /// game.set_time_control(TimeControl::new(Duration::from_secs(60), Duration::from_secs(1)));
+    /// game.next_turn();
+    /// let clock = game.game_state().clock().unwrap();
+    /// assert!(clock.remaining(PlayerColor::White) > Duration::from_secs(60) - Duration::from_secs(1));
+    /// assert_eq!(clock.remaining(PlayerColor::Black), Duration::from_secs(60));
+    /// ```
+    pub fn set_time_control(&mut self, time_control: TimeControl)
+    {
+        self.game_state.clock = Some(Clock::new(time_control));
+    }
+
+    /// Sets the [DrawRules] the game is played with, see [GameState::with_draw_rules].
+    ///
+    /// # Arguments
+    ///
+    /// * `draw_rules` - The draw rules to play with.
+    pub fn set_draw_rules(&mut self, draw_rules: DrawRules)
+    {
+        self.game_state = self.game_state.clone().with_draw_rules(draw_rules);
+    }
+
+    /// Adds a [GameObserver] to be told about every move from now on, and about how the game
+    /// ends. Observers are told in the order they were added, after the agents.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to add.
+    pub fn add_observer<O: GameObserver + 'static>(&mut self, observer: O)
+    {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Pauses the game, handing back its state and both [Agent]s so the game can be resumed later
+    /// with [Self::resume], possibly with different agents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(MinmaxAgent::new(1), MinmaxAgent::new(1));
+    /// game.next_round();
+    /// let (game_state, white, black) = game.adjourn();
+    /// // Some time later...
+    /// let mut game = Game::resume(game_state, white, black);
+    /// game.next_round();
+    /// assert_eq!(game.game_state().current_board().board_configuration().fullmove_number(), 3);
+    /// ```
+    pub fn adjourn(self) -> (GameState, A1, A2)
+    {
+        (self.game_state, self.agent_white, self.agent_black)
+    }
+
+    /// Saves the game so far to a file, so it can be picked up again later with [Self::load].
+    /// Only the [GameState] is saved: the agents, the trace file and how long each move took are
+    /// left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write. It's overwritten if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [SavedGameError] if the file couldn't be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_chess_engine::agent::{LocalAgent, MinmaxAgent};
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(LocalAgent::new(), MinmaxAgent::new(4));
+    /// game.next_round();
+    /// game.save("adjourned.game").unwrap();
+    /// // Some time later...
+    /// let mut game = Game::load("adjourned.game", LocalAgent::new(), MinmaxAgent::new(4)).unwrap();
+    /// game.run();
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SavedGameError>
+    {
+        fs::write(path, postcard::to_stdvec(&self.game_state)?)?;
+        Ok(())
+    }
+
+    /// Loads a game written by [Self::save] and resumes it with the given [Agent]s, like
+    /// [Self::resume].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to load.
+    /// * `agent_white` - The white player [Agent]
+    /// * `agent_black` - The black player [Agent]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [SavedGameError] if the file couldn't be read, or doesn't contain a valid game.
+    pub fn load<P: AsRef<Path>>(path: P, agent_white: A1, agent_black: A2) -> Result<Self, SavedGameError>
+    {
+        let game_state = postcard::from_bytes(&fs::read(path)?)?;
+        Ok(Self::resume(game_state, agent_white, agent_black))
+    }
+
+    /// Returns the current [GameState].
+    pub fn game_state(&self) -> &GameState
+    {
+        &self.game_state
+    }
+
+    /// Writes the game so far as PGN, so it can be saved and opened in other chess software.
+    ///
+    /// The tags are the Seven Tag Roster every PGN game has: `Event`, `Site`, `Date`, `Round`,
+    /// `White`, `Black` and `Result`. Agents don't have names, and the game doesn't know where
+    /// or when it's being played, so everything but the result is left as unknown. A game that
+    /// hasn't ended yet gets the result `*`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(MinmaxAgent::new(1), MinmaxAgent::new(1));
+    /// game.next_round();
+    /// let pgn = game.to_pgn();
+    /// assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n"));
+    /// assert!(pgn.contains("[Result \"*\"]"));
+    /// assert!(pgn.contains("1. "));
+    /// ```
+    pub fn to_pgn(&self) -> String
+    {
+        let seven_tag_roster: Vec<(String, String)> = [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+        ].iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        self.game_state.to_pgn(&seven_tag_roster)
+    }
+
+    /// Replaces the white [Agent] mid-game, i.e when a player leaves and an engine takes over.
+    /// Returns the game with the new agent along with the old agent.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent_white` - The new white player [Agent]
+    pub fn replace_white_agent<B: Agent>(self, agent_white: B) -> (Game<B, A2>, A1)
+    {
+        let (trace, move_times, undone_moves, observers) = (self.trace, self.move_times, self.undone_moves, self.observers);
+        let mut game = Game::resume(self.game_state, agent_white, self.agent_black);
+        game.trace = trace;
+        game.move_times = move_times;
+        game.undone_moves = undone_moves;
+        game.observers = observers;
+        (game, self.agent_white)
+    }
+
+    /// Replaces the black [Agent] mid-game. See [Self::replace_white_agent].
+    ///
+    /// # Arguments
+    ///
+    /// * `agent_black` - The new black player [Agent]
+    pub fn replace_black_agent<B: Agent>(self, agent_black: B) -> (Game<A1, B>, A2)
+    {
+        let (trace, move_times, undone_moves, observers) = (self.trace, self.move_times, self.undone_moves, self.observers);
+        let mut game = Game::resume(self.game_state, self.agent_white, agent_black);
+        game.trace = trace;
+        game.move_times = move_times;
+        game.undone_moves = undone_moves;
+        game.observers = observers;
+        (game, self.agent_black)
+    }
+
+    /// Takes back the last move, i.e to step backwards through a game while analysing it.
+    /// Returns the move that was taken back, or [None] if there aren't any moves left to take
+    /// back. See [GameState::undo].
+    ///
+    /// The agents aren't told about it, and the next [Self::next_turn] asks whichever side is to
+    /// move after the takeback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::board::Board;
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(MinmaxAgent::new(1), MinmaxAgent::new(1));
+    /// game.next_round();
+    /// let last_move = *game.game_state().last_move().unwrap();
+    /// assert_eq!(game.undo(), Some(last_move));
+    /// game.undo();
+    /// assert_eq!(game.game_state().current_board(), &Board::new_default_starting_board());
+    /// assert_eq!(game.undo(), None);
+    /// ```
+    pub fn undo(&mut self) -> Option<Move>
+    {
+        let r#move = *self.game_state.last_move()?;
+        self.game_state = self.game_state.undo()?;
+        let move_time = self.move_times.pop().unwrap_or_default();
+        self.undone_moves.push((r#move, move_time));
+        Some(r#move)
+    }
+
+    /// Plays the last move taken back with [Self::undo] again. Returns the move, or [None] if
+    /// there's nothing to redo. Once a new move is played the undone moves are forgotten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::game::Game;
+    /// let mut game = Game::new(MinmaxAgent::new(1), MinmaxAgent::new(1));
+    /// game.next_round();
+    /// let moves = game.game_state().moves().clone();
+    /// game.undo();
+    /// game.undo();
+    /// assert_eq!(game.redo(), Some(moves[0]));
+    /// assert_eq!(game.redo(), Some(moves[1]));
+    /// assert_eq!(game.redo(), None);
+    /// assert_eq!(game.game_state().moves(), &moves);
+    /// ```
+    pub fn redo(&mut self) -> Option<Move>
+    {
+        let (r#move, move_time) = self.undone_moves.pop()?;
+        // Undone moves were played from this position before, so they're still legal.
+        self.game_state = self.game_state.update(&r#move).expect("undone moves can always be played again");
+        self.move_times.push(move_time);
+        Some(r#move)
+    }
+
+    /// Returns a [GameRecord] of the game so far, with how long each move took.
+    pub fn record(&self) -> GameRecord
+    {
+        GameRecord::from_game_state(&self.game_state).with_move_times(self.move_times.clone())
+    }
+
+    /// Runs the full game until the game is over, and returns a [GameRecord] of it. Nothing is
+    /// printed, but the moves and result are [logged](LOG_TARGET), and the record can be
+    /// printed as a summary of the game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::ScriptedAgent;
+    /// # use rust_chess_engine::game::{Game, Termination};
+    /// let (white, black) = ScriptedAgent::pair_from_san(&["f3", "e5", "g4", "Qh4#"]).unwrap();
+    /// let record = Game::new(white, black).run();
+    /// assert_eq!(record.termination(), Some(Termination::Checkmate));
+    /// assert_eq!(record.move_times().len(), 4);
+    /// assert!(record.to_string().starts_with("0-1 (checkmate) after 4 plies"));
+    /// ```
+    pub fn run(&mut self) -> GameRecord
+    {
+        while self.game_state.game_result().is_in_progress()
+        {
+            self.next_round()
+        }
+
+        let result = self.game_state.game_result();
+        record_event(&mut self.trace, Level::Info, format!("Game is over! Result: {:?}", result));
+        info!(target: LOG_TARGET, "Analyse the final position: {}", lichess_analysis_url(self.game_state.current_board()));
+        if let Some(trace) = &mut self.trace
+        {
+            if let Err(error) = trace.flush()
+            {
+                warn!(target: LOG_TARGET, "Couldn't write the trace file: {}", error);
+            }
+        }
+        self.record()
+    }
+
+    /// Progresses the game by one "round", i.e
+    /// one move by white and one move by black.
+    ///
+    /// # Examples
+    ///
+    pub fn next_round(&mut self)
+    {
+        self.next_turn();
+        self.next_turn();
+    }
+
+    /// Lets whichever player is to move take their turn, unless the game is already over. If they
+    /// make a move, the other player is told about it through [Agent::on_opponent_move], and if
+    /// the turn ends the game (by the move, a resignation, a draw or running out of time), both
+    /// are told through [Agent::on_game_end].
+    ///
+    /// If the game has a clock, the turn is timed from when the agent is asked for its move
+    /// until it comes up with one it's allowed to make. A player who runs out of time doesn't
+    /// get to make their move.
+    ///
+    /// Since a game can be resumed from any position, this is the one to use to step through a
+    /// game that might have Black to move.
+    pub fn next_turn(&mut self)
+    {
+        if !self.game_state.game_result().is_in_progress()
+        {
+            return;
+        }
+        let board = self.game_state.current_board().clone();
+        let started = Instant::now();
+        let (action, mut game_state) = match board.active_color()
+        {
+            PlayerColor::White => Self::agent_turn(&self.game_state, &mut self.agent_white, &mut self.trace),
+            PlayerColor::Black => Self::agent_turn(&self.game_state, &mut self.agent_black, &mut self.trace),
+        };
+        let elapsed = started.elapsed();
+        if let Some(mut clock) = self.game_state.clock
+        {
+            if !clock.record_move(board.active_color(), elapsed)
+            {
+                record_event(&mut self.trace, Level::Debug, format!("{:?} ran out of time", board.active_color()));
+                self.game_state = self.game_state.time_out(clock);
+                self.end_game();
+                return;
+            }
+            game_state.clock = Some(clock);
+        }
+        let move_number = match board.active_color()
+        {
+            PlayerColor::White => format!("{}.", board.fullmove_number()),
+            PlayerColor::Black => format!("{}...", board.fullmove_number()),
+        };
+        let description = match action
+        {
+            AgentAction::Move(r#move) => board.to_san(&r#move),
+            // (=) is how a draw offer is written down on a score sheet.
+            AgentAction::OfferDraw(r#move) => format!("{} (=)", board.to_san(&r#move)),
+            AgentAction::AcceptDraw => format!("{:?} accepts the draw", board.active_color()),
+            AgentAction::ClaimDraw => format!("{:?} claims a draw", board.active_color()),
+            AgentAction::Resign => format!("{:?} resigns", board.active_color()),
+            AgentAction::Takeback => format!("{:?} takes back their last move", board.active_color()),
+        };
+        record_event(&mut self.trace, Level::Debug, format!("{} {}", move_number, description));
+        self.undone_moves.clear();
+        // A takeback drops the times of the moves it took back.
+        self.move_times.truncate(game_state.moves().len());
+        if let Some(r#move) = action.played_move()
+        {
+            self.move_times.push(elapsed);
+            match board.active_color()
+            {
+                PlayerColor::White => self.agent_black.on_opponent_move(&game_state, &r#move),
+                PlayerColor::Black => self.agent_white.on_opponent_move(&game_state, &r#move),
+            }
+            notify_move(&mut self.observers, &board, &game_state, &r#move);
+        }
+        self.game_state = game_state;
+        if !self.game_state.game_result().is_in_progress()
+        {
+            self.end_game();
+        }
+    }
+
+    /// Tells both [Agent]s and every [GameObserver] that the game is over.
+    fn end_game(&mut self)
+    {
+        let result = self.game_state.game_result();
+        self.agent_white.on_game_end(&result);
+        self.agent_black.on_game_end(&result);
+        if !self.observers.is_empty()
+        {
+            let record = self.record();
+            for observer in self.observers.iter_mut()
+            {
+                observer.on_game_end(&record);
+            }
+        }
+    }
+
+    /// Progresses the game by one "turn",
+    /// i.e one move by either white or black.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent taking their turn.
+    /// * `trace` - The trace file, if any. Actions the agent isn't allowed to take are recorded
+    ///   here.
+    ///
+    /// Returns the action that was taken along with the new state. Actions that aren't allowed
+    /// (see [GameState::apply_action]) are turned down, and the agent is asked again.
+    fn agent_turn<A: Agent>(game_state: &GameState, agent: &mut A, trace: &mut Option<BufWriter<File>>) -> (AgentAction, GameState)
+    {
+        loop
+        {
+            let move_request = agent.agent_move_request(game_state);
+            let new_game_state = game_state.apply_action(&move_request);
+            match new_game_state
+            {
+                Err(error) => {
+                    record_event(trace, Level::Warn, format!("Error making move! {}", error));
+                    continue;
+                },
+                Ok(new_game_state) =>
+                {
+                    return (move_request, new_game_state);
+                }
+            }
+        }
+    }
+}
+
+/// Tells every [GameObserver] about a move, and about the capture and check that came with it if
+/// there were any.
+///
+/// # Arguments
+///
+/// * `observers` - The observers to tell.
+/// * `board` - The board the move was made on.
+/// * `game_state` - The game after the move.
+/// * `r#move` - The move that was made.
+fn notify_move(observers: &mut [Box<dyn GameObserver>], board: &Board, game_state: &GameState, r#move: &Move)
+{
+    if observers.is_empty()
+    {
+        return;
+    }
+    let captured = board.clone().make_move_in_place(r#move).expect("Moves in a GameState can always be made").captured_piece();
+    let next_board = game_state.current_board();
+    let in_check = next_board.attack_info().is_in_check(next_board.active_color());
+    for observer in observers.iter_mut()
+    {
+        observer.on_move_made(game_state, r#move);
+        if let Some(captured) = captured
+        {
+            observer.on_capture(game_state, r#move, captured);
+        }
+        if in_check
+        {
+            observer.on_check(game_state, next_board.active_color());
+        }
+    }
+}
+
+/// Logs a game event, and writes it to the trace file if there is one.
+fn record_event(trace: &mut Option<BufWriter<File>>, level: Level, message: String)
+{
+    log!(target: LOG_TARGET, level, "{}", message);
+    if let Some(writer) = trace
+    {
+        if let Err(error) = writeln!(writer, "{}", message)
+        {
+            warn!(target: LOG_TARGET, "Couldn't write the trace file, turning tracing off: {}", error);
+            *trace = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use crate::{agent::{MinmaxAgent, ScriptedAgent}, board::Piece};
+
+    use super::*;
+
+    /// Plays the first legal move it finds and counts how many moves it made, how many moves it
+    /// saw its opponent make and how many times it was told the game ended.
+    #[derive(Default)]
+    struct CountingAgent
+    {
+        moves_made: usize,
+        opponent_moves_seen: usize,
+        games_ended: usize,
+    }
+
+    impl Agent for CountingAgent
+    {
+        fn agent_move_request(&mut self, game_state: &GameState) -> AgentAction {
+            self.moves_made += 1;
+            AgentAction::Move(game_state.legal_moves()[0])
+        }
+
+        fn on_opponent_move(&mut self, game_state: &GameState, r#move: &Move) {
+            assert_eq!(game_state.last_move(), Some(r#move));
+            self.opponent_moves_seen += 1;
+        }
+
+        fn on_game_end(&mut self, result: &BoardResult) {
+            assert!(result.is_over());
+            self.games_ended += 1;
+        }
+    }
+
+    #[test]
+    fn agents_hear_about_opponent_moves_and_the_end_of_the_game()
+    {
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.run();
+        // Asking for more moves after the game is over does nothing.
+        game.next_round();
+        let (_, white, black) = game.adjourn();
+        assert_eq!(white.opponent_moves_seen, black.moves_made);
+        assert_eq!(black.opponent_moves_seen, white.moves_made);
+        assert_eq!((white.games_ended, black.games_ended), (1, 1));
+    }
+
+    #[test]
+    fn swapped_agent_takes_over_mid_game()
+    {
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.next_round();
+        let (mut game, old_white) = game.replace_white_agent(MinmaxAgent::new(1));
+        assert_eq!(old_white.moves_made, 1);
+        game.next_round();
+        let (_, _, black) = game.adjourn();
+        assert_eq!(black.moves_made, 2);
+    }
+
+    #[test]
+    fn boxed_games_can_hold_any_agents()
+    {
+        let lineup = ["scripted", "counting"];
+        let make_agent = |name: &str| -> Box<dyn Agent>
+        {
+            match name
+            {
+                "scripted" => Box::new(ScriptedAgent::from_san(&["f3", "g4"]).unwrap()),
+                _ => Box::new(CountingAgent::default()),
+            }
+        };
+        let mut game = Game::new(make_agent(lineup[0]), make_agent(lineup[1]));
+        game.next_round();
+        let (game_state, white, black) = game.adjourn();
+        let mut game: Game = Game::resume(game_state, white, black);
+        game.next_round();
+        assert_eq!(game.game_state().moves().len(), 4);
+    }
+
+    #[test]
+    fn resumed_game_with_black_to_move_asks_black_first()
+    {
+        let starting_state = GameState::new(Board::new_default_starting_board());
+        let game_state = starting_state.update(&starting_state.legal_moves()[0]).unwrap();
+        let mut game = Game::resume(game_state, CountingAgent::default(), CountingAgent::default());
+        game.next_turn();
+        let (game_state, white, black) = game.adjourn();
+        assert_eq!(white.moves_made, 0);
+        assert_eq!(black.moves_made, 1);
+        assert_eq!(game_state.current_board().active_color(), PlayerColor::White);
+    }
+
+    #[test]
+    fn trace_file_records_every_move()
+    {
+        let path = std::env::temp_dir().join(format!("rust-chess-engine-trace-{}.txt", std::process::id()));
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.set_trace_file(&path).unwrap();
+        game.next_round();
+        // The trace is flushed when the game is dropped.
+        let (mut game, _) = game.replace_white_agent(CountingAgent::default());
+        game.next_turn();
+        drop(game);
+
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = trace.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("1. "));
+        assert!(lines[1].starts_with("1... "));
+        assert!(lines[2].starts_with("2. "));
+    }
+
+    /// Writes down everything it's told as a line of text.
+    struct RecordingObserver(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl GameObserver for RecordingObserver
+    {
+        fn on_move_made(&mut self, game_state: &GameState, _move: &Move) {
+            self.0.borrow_mut().push(format!("move {}", game_state.moves().len()));
+        }
+
+        fn on_capture(&mut self, _game_state: &GameState, _move: &Move, captured: Piece) {
+            self.0.borrow_mut().push(format!("capture {:?}", captured.piece_type()));
+        }
+
+        fn on_check(&mut self, _game_state: &GameState, color: PlayerColor) {
+            self.0.borrow_mut().push(format!("check {:?}", color));
+        }
+
+        fn on_game_end(&mut self, record: &GameRecord) {
+            self.0.borrow_mut().push(format!("end {:?}", record.termination()));
+        }
+    }
+
+    #[test]
+    fn observers_hear_about_moves_captures_checks_and_the_end()
+    {
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let (white, black) = ScriptedAgent::pair_from_san(&["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+"]).unwrap();
+        let mut game = Game::new(white, black);
+        game.add_observer(RecordingObserver(events.clone()));
+        for _ in 0..6
+        {
+            game.next_turn();
+        }
+        assert_eq!(*events.borrow(), vec!["move 1", "move 2", "move 3", "capture Pawn", "move 4", "capture Pawn", "move 5", "move 6", "check White"]);
+
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let (white, black) = ScriptedAgent::pair_from_san(&["f3", "e5", "g4", "Qh4#"]).unwrap();
+        let mut game = Game::new(white, black).into_boxed();
+        game.add_observer(RecordingObserver(events.clone()));
+        game.run();
+        assert_eq!(events.borrow()[4..], ["check White", "end Some(Checkmate)"]);
+    }
+
+    #[test]
+    fn saved_games_pick_up_where_they_left_off()
+    {
+        let path = std::env::temp_dir().join(format!("rust-chess-engine-save-{}.bin", std::process::id()));
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.set_time_control(TimeControl::new(Duration::from_secs(60), Duration::ZERO));
+        game.next_round();
+        let r#move = game.game_state().legal_moves()[0];
+        game.game_state = game.game_state().apply_action(&AgentAction::OfferDraw(r#move)).unwrap();
+        game.save(&path).unwrap();
+
+        let loaded = Game::load(&path, CountingAgent::default(), CountingAgent::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let (saved, loaded) = (game.game_state(), loaded.game_state());
+        assert_eq!(loaded.current_board(), saved.current_board());
+        assert_eq!(loaded.moves(), saved.moves());
+        assert_eq!(loaded.repetition_count(), saved.repetition_count());
+        assert!(loaded.draw_offered());
+        assert_eq!(loaded.clock(), saved.clock());
+    }
+
+    #[test]
+    fn variants_survive_pgn_and_saving()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/4K3/8/8 w - - 0 1").unwrap());
+        let game_state = GameState::with_rules(board, crate::rules::KingOfTheHill);
+        let ke4 = game_state.legal_move(&MoveCommand::from_str("Ke4").unwrap()).unwrap();
+        let game_state = game_state.update(&ke4).unwrap();
+        assert_eq!(game_state.termination(), Some(Termination::VariantWin));
+
+        let pgn = game_state.to_pgn(&[]);
+        assert!(pgn.contains("[Variant \"King of the Hill\"]"));
+        let loaded = GameState::from_pgn(&parse_pgn(&pgn).unwrap()[0]).unwrap();
+        assert_eq!(loaded.game_result(), BoardResult::Win(PlayerColor::White));
+        let loaded: GameState = postcard::from_bytes(&postcard::to_stdvec(&game_state).unwrap()).unwrap();
+        assert_eq!(loaded.rules().name(), "King of the Hill");
+
+        let pgn = pgn.replace("King of the Hill", "Calvinball");
+        assert!(matches!(GameState::from_pgn(&parse_pgn(&pgn).unwrap()[0]), Err(LoadPgnError::UnknownVariant(_))));
+    }
+
+    #[test]
+    fn saved_games_with_illegal_moves_do_not_load()
+    {
+        let game_state = GameState::new(Board::new_default_starting_board());
+        let e4 = game_state.legal_move(&MoveCommand::from_str("e4").unwrap()).unwrap();
+        let black_to_move = Board::new_board_with_configuration(&BoardConfiguration::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap());
+        // Saved game states are laid out like this tuple.
+        let saved = (black_to_move, "Standard", vec![e4], None::<PlayerColor>, None::<BoardResult>, None::<Clock>);
+        let bytes = postcard::to_stdvec(&saved).unwrap();
+        assert!(postcard::from_bytes::<GameState>(&bytes).is_err());
+    }
+
+    #[test]
+    fn resigning_hands_the_opponent_the_win()
+    {
+        let white = ScriptedAgent::from_san(&["e4"]).unwrap();
+        let mut game = Game::new(white, ScriptedAgent::new([AgentAction::Resign]));
+        let record = game.run();
+        assert_eq!(game.game_state().game_result(), BoardResult::Win(PlayerColor::White));
+        assert_eq!(record.termination(), Some(Termination::Resignation));
+        assert_eq!(game.game_state().moves().len(), 1);
+    }
+
+    #[test]
+    fn draw_offers_can_be_accepted_or_declined()
+    {
+        let game_state = GameState::new(Board::new_default_starting_board());
+        let e4 = game_state.legal_move(&MoveCommand::from_str("e4").unwrap()).unwrap();
+        let offered = game_state.apply_action(&AgentAction::OfferDraw(e4)).unwrap();
+        // Making a move instead of accepting turns the offer down.
+        let declined = play(offered.clone(), &["e5"]);
+        assert!(!declined.draw_offered());
+        assert!(matches!(declined.apply_action(&AgentAction::AcceptDraw), Err(ActionError::NoDrawOffered)));
+
+        let agreed = offered.apply_action(&AgentAction::AcceptDraw).unwrap();
+        assert_eq!(agreed.game_result(), BoardResult::Draw(DrawReason::Agreement));
+        assert!(matches!(agreed.apply_action(&AgentAction::Resign), Err(ActionError::GameOver)));
+    }
+
+    #[test]
+    fn draws_can_only_be_claimed_when_the_rules_allow()
+    {
+        let game_state = GameState::new(Board::new_default_starting_board());
+        assert_eq!(game_state.claimable_draw(), None);
+        assert!(matches!(game_state.apply_action(&AgentAction::ClaimDraw), Err(ActionError::NoDrawToClaim)));
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap());
+        assert_eq!(GameState::new(board).claimable_draw(), Some(DrawReason::FiftyMoveRule));
+    }
+
+    #[test]
+    fn takebacks_undo_both_sides_last_moves()
+    {
+        let game_state = GameState::new(Board::new_default_starting_board());
+        assert!(matches!(game_state.apply_action(&AgentAction::Takeback), Err(ActionError::NoMoveToTakeBack)));
+        let after_e4 = play(game_state.clone(), &["e4"]);
+        assert!(matches!(after_e4.apply_action(&AgentAction::Takeback), Err(ActionError::NoMoveToTakeBack)));
+
+        let d4 = game_state.legal_move(&MoveCommand::from_str("d4").unwrap()).unwrap();
+        let white = ScriptedAgent::new([AgentAction::Move(after_e4.moves()[0]), AgentAction::Takeback, AgentAction::Move(d4)]);
+        let mut game = Game::new(white, ScriptedAgent::from_san(&["e5", "d5"]).unwrap());
+        for _ in 0..5
+        {
+            game.next_turn();
+        }
+        assert_eq!(game.game_state().san_moves(), vec!["d4", "d5"]);
+        assert_eq!(game.record().move_times().len(), 2);
+    }
+
+    #[test]
+    fn new_moves_forget_undone_moves()
+    {
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.next_round();
+        game.undo();
+        game.next_turn();
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.game_state().moves().len(), 2);
+    }
+
+    #[test]
+    fn running_out_of_time_loses_unless_the_opponent_cant_mate()
+    {
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.set_time_control(TimeControl::new(Duration::ZERO, Duration::from_secs(1)));
+        assert_eq!(game.run().termination(), Some(Termination::TimeForfeit));
+        assert_eq!(game.game_state().game_result(), BoardResult::LossOnTime(PlayerColor::White));
+        assert!(game.game_state().moves().is_empty());
+        assert!(game.to_pgn().contains("[Result \"0-1\"]"));
+
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k2r/8/8/8/8/8/8/N3K3 b - - 0 1").unwrap());
+        let mut game = Game::resume(GameState::new(board), CountingAgent::default(), CountingAgent::default());
+        game.set_time_control(TimeControl::new(Duration::ZERO, Duration::ZERO));
+        game.next_turn();
+        assert_eq!(game.game_state().game_result(), BoardResult::Draw(DrawReason::TimeoutVsInsufficientMaterial));
+        assert_eq!(game.agent_white.games_ended, 1);
+    }
+
+    fn play(game_state: GameState, moves: &[&str]) -> GameState
+    {
+        moves.iter().fold(game_state, |game_state, r#move|
+        {
+            let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+            game_state.update(&r#move).unwrap()
+        })
+    }
+
+    #[test]
+    fn third_repetition_can_be_claimed_and_fifth_is_a_draw()
+    {
+        let knights_out_and_back = ["Nf3", "Nf6", "Ng1", "Ng8"];
+        let game_state = play(GameState::new(Board::new_default_starting_board()), &knights_out_and_back);
+        assert!(game_state.game_result().is_in_progress());
+        assert_eq!(game_state.claimable_draw(), None);
+        let game_state = play(game_state, &knights_out_and_back);
+        assert_eq!(game_state.repetition_count(), 3);
+        assert!(game_state.game_result().is_in_progress());
+        assert_eq!(game_state.claimable_draw(), Some(DrawReason::ThreefoldRepitition));
+        let game_state = play(game_state, &knights_out_and_back);
+        assert!(game_state.game_result().is_in_progress());
+        let game_state = play(game_state, &knights_out_and_back);
+        assert_eq!(game_state.repetition_count(), 5);
+        assert_eq!(game_state.game_result().get_draw_reason(), Some(DrawReason::FivefoldRepetition));
+    }
+
+    #[test]
+    fn seventy_five_moves_is_a_draw_unless_its_checkmate()
+    {
+        let game_state = |fen| GameState::new(Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap()));
+        assert!(game_state("4k3/8/8/8/8/8/8/R3K3 w - - 149 80").game_result().is_in_progress());
+        assert_eq!(game_state("4k3/8/8/8/8/8/8/R3K3 w - - 150 80").game_result(), BoardResult::Draw(DrawReason::SeventyFiveMoveRule));
+        assert_eq!(game_state("R3k3/8/4K3/8/8/8/8/8 b - - 150 80").game_result(), BoardResult::Win(PlayerColor::White));
+    }
+
+    #[test]
+    fn draw_rules_survive_saving()
+    {
+        let draw_rules = DrawRules::default().with_halfmoves(80, 120).with_repetitions(2, 3);
+        let game_state = play(GameState::new(Board::new_default_starting_board()).with_draw_rules(draw_rules), &["e4"]);
+        let loaded: GameState = postcard::from_bytes(&postcard::to_stdvec(&game_state).unwrap()).unwrap();
+        assert_eq!(loaded.draw_rules(), draw_rules);
+    }
+
+    #[test]
+    fn pawn_moves_clear_the_position_history()
+    {
+        let game_state = play(GameState::new(Board::new_default_starting_board()), &["Nf3", "Nf6", "Ng1", "Ng8", "e4"]);
+        assert_eq!(game_state.position_history.len(), 1);
+        assert_eq!(game_state.repetition_count(), 1);
+    }
+
+    #[test]
+    fn moves_are_recorded()
+    {
+        let game_state = play(GameState::new(Board::new_default_starting_board()), &["e4", "e5", "Nf3"]);
+        let expected = game_state.moves().iter()
+            .fold(Board::new_default_starting_board(), |board, r#move| board.attempt_move(r#move).unwrap());
+        assert_eq!(game_state.moves().len(), 3);
+        assert_eq!(game_state.starting_board(), &Board::new_default_starting_board());
+        assert_eq!(&expected, game_state.current_board());
+    }
+
+    #[test]
+    fn history_replays_the_boards_each_move_was_played_on()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap());
+        let game_state = play(GameState::new(board.clone()), &["exd6", "Kd7", "Ke2"]);
+        let history = game_state.history();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].1, board);
+        for window in history.windows(2)
+        {
+            assert_eq!(window[0].1.attempt_move(&window[0].0).unwrap(), window[1].1);
+        }
+        assert_eq!(game_state.san_moves(), vec!["exd6", "Kd7", "Ke2"]);
+        assert_eq!(game_state.last_move(), Some(&history[2].0));
+    }
+
+    #[test]
+    fn finished_games_export_to_pgn()
+    {
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        game.run();
+        let pgn = game.to_pgn();
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games.len(), 1);
+        for tag in ["Event", "Site", "Date", "Round", "White", "Black", "Result"]
+        {
+            assert!(games[0].tag(tag).is_some(), "missing the {} tag", tag);
+        }
+        let result = PgnResult::from(game.game_state().game_result());
+        assert_ne!(result, PgnResult::Unknown);
+        assert_eq!(*games[0].result(), result);
+        assert_eq!(games[0].moves().len(), game.game_state().moves().len());
+    }
+
+    #[test]
+    fn games_from_other_positions_export_their_fen()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap());
+        let game_state = play(GameState::new(board), &["Rh8"]);
+        let pgn = game_state.to_pgn(&[]);
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"k7/8/1K6/8/8/8/8/7R w - - 0 1\"]"));
+        assert!(pgn.ends_with("1. Rh8# 1-0\n"));
+    }
+
+    #[test]
+    fn exported_games_load_back()
+    {
+        let mut game = Game::new(CountingAgent::default(), CountingAgent::default());
+        for _ in 0..5
+        {
+            game.next_round();
+        }
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap());
+        let from_position = play(GameState::new(board), &["Rh7", "Kb8"]);
+        for game_state in [game.game_state().clone(), from_position]
+        {
+            let loaded = Game::from_pgn(&game_state.to_pgn(&[]), CountingAgent::default(), CountingAgent::default()).unwrap();
+            assert_eq!(loaded.game_state().starting_board(), game_state.starting_board());
+            assert_eq!(loaded.game_state().moves(), game_state.moves());
+            assert_eq!(loaded.game_state().current_board(), game_state.current_board());
+        }
+    }
+
+    #[test]
+    fn loading_reports_the_first_illegal_move()
+    {
+        let error = Game::from_pgn("1. e4 e5 2. Nf3 Ke7 3. Ke2 Kd6 4. Kd3 Kxd5 *", CountingAgent::default(), CountingAgent::default());
+        match error
+        {
+            Err(LoadPgnError::IllegalMove(ApplyMovesError { index, .. })) => assert_eq!(index, 7),
+            _ => panic!("expected an illegal move"),
         }
+        assert!(matches!(Game::from_pgn("", CountingAgent::default(), CountingAgent::default()), Err(LoadPgnError::NoGames)));
+        assert!(matches!(Game::from_pgn("[FEN \"8/8 w\"]\n\n*", CountingAgent::default(), CountingAgent::default()), Err(LoadPgnError::InvalidFen(_))));
     }
 }
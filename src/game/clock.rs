@@ -0,0 +1,204 @@
+//! Implements [Clock], the chess clock a [Game](super::Game) can be played with, and the
+//! [TimeControl] it's set to.
+
+use std::time::Duration;
+
+use getset::CopyGetters;
+use serde::{Deserialize, Serialize};
+
+use crate::{board::PlayerColor, search::ClockState};
+
+/// How much time each player gets for the game.
+///
+/// Each player starts with the base time. Every move, the first `delay` of thinking is free (a
+/// "simple" or US delay), and once the move is made the `increment` is added to the player's
+/// clock (a Fischer increment). Most time controls only use one of the two.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rust_chess_engine::game::TimeControl;
+/// // 3 minutes plus 2 seconds per move.
+/// let blitz = TimeControl::new(Duration::from_secs(180), Duration::from_secs(2));
+/// // 90 minutes with a 30 second delay.
+/// let classical = TimeControl::new(Duration::from_secs(90 * 60), Duration::ZERO).with_delay(Duration::from_secs(30));
+/// assert_eq!(classical.delay(), Duration::from_secs(30));
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone, CopyGetters, Serialize, Deserialize)]
+#[getset(get_copy="pub")]
+pub struct TimeControl
+{
+    /// How much time each player starts with.
+    base: Duration,
+    /// How much time is added to a player's clock after each of their moves.
+    increment: Duration,
+    /// How much of each move's thinking time isn't taken off the clock.
+    delay: Duration,
+}
+
+impl TimeControl
+{
+    /// Creates a [TimeControl] with a base time and an increment, and no delay.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - How much time each player starts with.
+    /// * `increment` - How much time is added after every move.
+    pub fn new(base: Duration, increment: Duration) -> Self
+    {
+        Self
+        {
+            base,
+            increment,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Returns the same time control with a delay on every move.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - How much of each move's thinking time is free.
+    pub fn with_delay(self, delay: Duration) -> Self
+    {
+        Self
+        {
+            delay,
+            ..self
+        }
+    }
+}
+
+/// A chess clock, keeping track of how much time both players have left.
+///
+/// A [Game](super::Game) with a clock (see [Game::set_time_control](super::Game::set_time_control))
+/// times every turn, and a player whose time runs out loses on time.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rust_chess_engine::board::PlayerColor;
+/// # use rust_chess_engine::game::{Clock, TimeControl};
+/// let mut clock = Clock::new(TimeControl::new(Duration::from_secs(60), Duration::from_secs(1)));
+/// assert!(clock.record_move(PlayerColor::White, Duration::from_secs(10)));
+/// assert_eq!(clock.remaining(PlayerColor::White), Duration::from_secs(51));
+/// assert!(!clock.record_move(PlayerColor::Black, Duration::from_secs(61)));
+/// assert!(clock.is_flagged(PlayerColor::Black));
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone, CopyGetters, Serialize, Deserialize)]
+pub struct Clock
+{
+    /// Gets the [TimeControl] the clock was set to.
+    #[getset(get_copy="pub")]
+    time_control: TimeControl,
+    white_remaining: Duration,
+    black_remaining: Duration,
+}
+
+impl Clock
+{
+    /// Creates a [Clock] with the base time of `time_control` on both sides.
+    ///
+    /// # Arguments
+    ///
+    /// * `time_control` - The time control to play with.
+    pub fn new(time_control: TimeControl) -> Self
+    {
+        Self
+        {
+            time_control,
+            white_remaining: time_control.base(),
+            black_remaining: time_control.base(),
+        }
+    }
+
+    /// Returns how much time `color` has left.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - Whose time to look at.
+    pub fn remaining(&self, color: PlayerColor) -> Duration
+    {
+        match color
+        {
+            PlayerColor::White => self.white_remaining,
+            PlayerColor::Black => self.black_remaining,
+        }
+    }
+
+    /// Returns true if `color` has run out of time.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - Whose flag to look at.
+    pub fn is_flagged(&self, color: PlayerColor) -> bool
+    {
+        self.remaining(color).is_zero()
+    }
+
+    /// Takes the time `color` spent on a move off their clock, less the delay, and adds the
+    /// increment. Returns false if their time ran out before they moved, in which case no
+    /// increment is added and their clock stays at zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The player who moved.
+    /// * `elapsed` - How long they took.
+    pub fn record_move(&mut self, color: PlayerColor, elapsed: Duration) -> bool
+    {
+        let charged = elapsed.saturating_sub(self.time_control.delay());
+        let remaining = match color
+        {
+            PlayerColor::White => &mut self.white_remaining,
+            PlayerColor::Black => &mut self.black_remaining,
+        };
+        if charged >= *remaining
+        {
+            *remaining = Duration::ZERO;
+            return false;
+        }
+        *remaining = *remaining - charged + self.time_control.increment();
+        true
+    }
+
+    /// Returns `color`'s side of the clock as a [ClockState], which is what a
+    /// [MinmaxAgent](crate::agent::MinmaxAgent) budgets its time from.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - Whose side of the clock to look at.
+    pub fn clock_state(&self, color: PlayerColor) -> ClockState
+    {
+        ClockState::new(self.remaining(color), self.time_control.increment(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn delay_is_free_and_increment_is_added_after()
+    {
+        let time_control = TimeControl::new(Duration::from_secs(10), Duration::from_secs(2)).with_delay(Duration::from_secs(3));
+        let mut clock = Clock::new(time_control);
+        assert!(clock.record_move(PlayerColor::White, Duration::from_secs(2)));
+        assert_eq!(clock.remaining(PlayerColor::White), Duration::from_secs(12));
+        assert!(clock.record_move(PlayerColor::White, Duration::from_secs(8)));
+        assert_eq!(clock.remaining(PlayerColor::White), Duration::from_secs(9));
+        assert_eq!(clock.remaining(PlayerColor::Black), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn flag_falls_when_time_runs_out()
+    {
+        let mut clock = Clock::new(TimeControl::new(Duration::from_secs(5), Duration::from_secs(5)));
+        assert!(!clock.record_move(PlayerColor::Black, Duration::from_secs(5)));
+        assert!(clock.is_flagged(PlayerColor::Black));
+        assert!(!clock.is_flagged(PlayerColor::White));
+        assert_eq!(clock.remaining(PlayerColor::Black), Duration::ZERO);
+    }
+}
@@ -0,0 +1,68 @@
+//! Error types for the [crate::game] module
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::{board::{ApplyMovesError, InvalidFENError, MoveError}, parse::PgnParseError};
+
+/// The error returned when a game can't be loaded from PGN, see
+/// [Game::from_pgn](super::Game::from_pgn).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LoadPgnError
+{
+    /// The PGN couldn't be parsed.
+    #[error("Couldn't parse the PGN: {0}")]
+    Parse(#[from] PgnParseError),
+    /// The PGN didn't contain any games.
+    #[error("The PGN doesn't contain any games")]
+    NoGames,
+    /// The game's `FEN` tag isn't a valid FEN record.
+    #[error("The FEN tag is invalid: {0}")]
+    InvalidFen(#[from] InvalidFENError),
+    /// One of the game's moves is impossible or illegal.
+    #[error("The game has an illegal move: {0}")]
+    IllegalMove(#[from] ApplyMovesError),
+    /// The game's `Variant` tag isn't a variant this crate knows the rules of, see
+    /// [rules_by_name](crate::rules::rules_by_name).
+    #[error("Unknown variant {0}")]
+    UnknownVariant(String),
+}
+
+/// The error returned when a game can't be saved or loaded, see [Game::save](super::Game::save)
+/// and [Game::load](super::Game::load).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SavedGameError
+{
+    /// Reading or writing the file failed.
+    #[error("Couldn't read or write the saved game: {0}")]
+    Io(#[from] io::Error),
+    /// The file doesn't contain a valid game, i.e it's corrupt or one of its moves is illegal.
+    #[error("The saved game is invalid: {0}")]
+    Serialization(#[from] postcard::Error),
+}
+
+/// The error returned when an [AgentAction](crate::agent::AgentAction) isn't allowed, see
+/// [GameState::apply_action](super::GameState::apply_action).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ActionError
+{
+    /// The move that came with the action is impossible or illegal.
+    #[error("Illegal move: {0}")]
+    IllegalMove(#[from] MoveError),
+    /// A draw can only be accepted right after the opponent offered one.
+    #[error("There's no draw offer to accept")]
+    NoDrawOffered,
+    /// A draw can only be claimed by threefold repetition or the fifty-move rule.
+    #[error("There's no draw to claim")]
+    NoDrawToClaim,
+    /// A takeback needs a move by the side to move to take back.
+    #[error("There's no move to take back")]
+    NoMoveToTakeBack,
+    /// Nothing can happen in a game that's already over.
+    #[error("The game is already over")]
+    GameOver,
+}
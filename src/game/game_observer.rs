@@ -0,0 +1,83 @@
+//! Defines the [GameObserver] trait, for following a [Game](super::Game) from the outside.
+
+use crate::board::{Move, Piece, PlayerColor};
+
+use super::{GameRecord, GameState};
+
+/// Something that wants to hear about a [Game](super::Game) as it's played, but doesn't play in
+/// it, like a GUI, a logger or a broadcast to spectators. Observers are added with
+/// [Game::add_observer](super::Game::add_observer).
+///
+/// Every move an [Agent](crate::agent::Agent) makes is reported to every observer with
+/// [Self::on_move_made], followed by [Self::on_capture] if it took a piece and [Self::on_check]
+/// if it gave check. Moves taken back or replayed with [Game::undo](super::Game::undo) and
+/// [Game::redo](super::Game::redo) aren't reported. These hooks all do nothing by default, so
+/// observers only implement the ones they care about.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::ScriptedAgent;
+/// # use rust_chess_engine::board::PlayerColor;
+/// # use rust_chess_engine::game::{Game, GameObserver, GameState};
+/// struct CheckAnnouncer;
+///
+/// impl GameObserver for CheckAnnouncer
+/// {
+///     fn on_check(&mut self, _game_state: &GameState, color: PlayerColor) {
+///         println!("{:?} is in check!", color);
+///     }
+/// }
+///
+/// let (white, black) = ScriptedAgent::pair_from_san(&["f3", "e5", "g4", "Qh4#"]).unwrap();
+/// let mut game = Game::new(white, black);
+/// game.add_observer(CheckAnnouncer);
+/// game.run();
+/// ```
+pub trait GameObserver
+{
+    /// Called after a move is made. Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game after the move.
+    /// * `r#move` - The move that was made.
+    fn on_move_made(&mut self, game_state: &GameState, r#move: &Move)
+    {
+        let _ = (game_state, r#move);
+    }
+
+    /// Called after a move that captured a piece, right after [Self::on_move_made]. Does nothing
+    /// by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game after the capture.
+    /// * `r#move` - The move that made the capture.
+    /// * `captured` - The piece that was captured.
+    fn on_capture(&mut self, game_state: &GameState, r#move: &Move, captured: Piece)
+    {
+        let _ = (game_state, r#move, captured);
+    }
+
+    /// Called after a move that gave check, including checkmate. Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game after the move.
+    /// * `color` - The color whose king is in check.
+    fn on_check(&mut self, game_state: &GameState, color: PlayerColor)
+    {
+        let _ = (game_state, color);
+    }
+
+    /// Called once the game is over. Does nothing by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The finished game.
+    fn on_game_end(&mut self, record: &GameRecord)
+    {
+        let _ = record;
+    }
+}
@@ -0,0 +1,394 @@
+//! Implements [GameRecord], a finished or loaded game that can be stepped through move by move.
+
+use std::{fmt::Display, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{board::{ApplyMovesError, Board, BoardResult, DrawReason, Move}, parse::{PgnGame, PgnResult}};
+
+use super::{GameState, LoadPgnError};
+
+/// How a game ended.
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum Termination
+{
+    /// The loser was checkmated.
+    Checkmate,
+    /// The loser resigned.
+    Resignation,
+    /// The loser ran out of time.
+    TimeForfeit,
+    /// The winner won by one of the variant's own rules, see
+    /// [Rules::variant_result](crate::rules::Rules::variant_result).
+    VariantWin,
+    /// The game was drawn, for the given reason.
+    Draw(DrawReason),
+}
+
+impl Display for Termination
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let termination = match self
+        {
+            Self::Checkmate => "checkmate",
+            Self::Resignation => "resignation",
+            Self::TimeForfeit => "time forfeit",
+            Self::VariantWin => "variant win",
+            Self::Draw(DrawReason::ThreefoldRepitition) => "draw by threefold repetition",
+            Self::Draw(DrawReason::FiftyMoveRule) => "draw by the fifty-move rule",
+            Self::Draw(DrawReason::FivefoldRepetition) => "draw by fivefold repetition",
+            Self::Draw(DrawReason::SeventyFiveMoveRule) => "draw by the seventy-five-move rule",
+            Self::Draw(DrawReason::CheckmateImpossible) => "draw, checkmate is impossible",
+            Self::Draw(DrawReason::Stalemate) => "stalemate",
+            Self::Draw(DrawReason::Agreement) => "draw by agreement",
+            Self::Draw(DrawReason::TimeoutVsInsufficientMaterial) => "draw, time forfeit against insufficient material",
+        };
+        write!(f, "{}", termination)
+    }
+}
+
+/// A game that can be scrubbed through, i.e by a replay viewer or an annotator.
+///
+/// Every position of the game is worked out once when the record is made, so stepping back and
+/// forth or jumping to any ply is just a lookup. A ply is a single move by one side: ply 0 is
+/// the starting position, ply 1 is after White's first move, and so on.
+///
+/// The record keeps a cursor, which [Self::step_forward], [Self::step_back] and [Self::jump_to]
+/// move around.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::game::GameRecord;
+/// # use rust_chess_engine::parse::parse_pgn;
+/// let games = parse_pgn("1. e4 e5 2. Nf3 *").unwrap();
+/// let mut record = GameRecord::from_pgn(&games[0]).unwrap();
+/// assert_eq!(record.len(), 3);
+/// assert_eq!(record.current_board(), &Board::new_default_starting_board());
+///
+/// let after_e5 = record.jump_to(2).unwrap().clone();
+/// let after_nf3 = record.board_at(3).unwrap().clone();
+/// assert_eq!(record.step_forward(), Some(&after_nf3));
+/// assert_eq!(record.step_forward(), None);
+/// assert_eq!(record.step_back(), Some(&after_e5));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord
+{
+    moves: Vec<Move>,
+    /// The board at every ply, starting with the starting position. Always one longer than
+    /// `moves`.
+    boards: Vec<Board>,
+    result: PgnResult,
+    /// The result and how the game ended, for games that were played out by a
+    /// [Game](super::Game). A PGN result doesn't say how the game ended.
+    ending: Option<(BoardResult, Termination)>,
+    /// How long each move took, for games that were played out by a [Game](super::Game).
+    move_times: Vec<Duration>,
+    ply: usize,
+}
+
+impl GameRecord
+{
+    /// Creates a [GameRecord] by playing `moves` from `starting_board`. The cursor starts at the
+    /// starting position.
+    ///
+    /// # Arguments
+    ///
+    /// * `starting_board` - The position the game started from.
+    /// * `moves` - The moves of the game, in order.
+    /// * `result` - The result of the game, or [PgnResult::Unknown] if it isn't over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ApplyMovesError] if one of the moves can't be made.
+    pub fn new(starting_board: Board, moves: Vec<Move>, result: PgnResult) -> Result<Self, ApplyMovesError>
+    {
+        let mut boards = Vec::with_capacity(moves.len() + 1);
+        boards.push(starting_board);
+        for (index, r#move) in moves.iter().enumerate()
+        {
+            let next_board = boards[index].attempt_move(r#move)
+                .map_err(|error| ApplyMovesError { index, error })?;
+            boards.push(next_board);
+        }
+        Ok(Self
+        {
+            moves,
+            boards,
+            result,
+            ending: None,
+            move_times: Vec::new(),
+            ply: 0,
+        })
+    }
+
+    /// Creates a [GameRecord] of a game so far, which also knows how the game ended if it's over.
+    /// See [Game::run](super::Game::run), which also records how long each move took.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game to record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use rust_chess_engine::board::{Board, BoardResult, PlayerColor};
+    /// # use rust_chess_engine::game::{GameRecord, GameState, Termination};
+    /// # use rust_chess_engine::parse::MoveCommand;
+    /// let mut game_state = GameState::new(Board::new_default_starting_board());
+    /// for r#move in ["f3", "e5", "g4", "Qh4"]
+    /// {
+    ///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+    ///     game_state = game_state.update(&r#move).unwrap();
+    /// }
+    /// let record = GameRecord::from_game_state(&game_state);
+    /// assert_eq!(record.len(), 4);
+    /// assert_eq!(record.board_result(), Some(BoardResult::Win(PlayerColor::Black)));
+    /// assert_eq!(record.termination(), Some(Termination::Checkmate));
+    /// ```
+    pub fn from_game_state(game_state: &GameState) -> Self
+    {
+        let boards = game_state.history().into_iter()
+            .map(|(_, board)| board)
+            .chain(std::iter::once(game_state.current_board().clone()))
+            .collect();
+        let board_result = game_state.game_result();
+        Self
+        {
+            moves: game_state.moves().clone(),
+            boards,
+            result: PgnResult::from(board_result),
+            ending: game_state.termination().map(|termination| (board_result, termination)),
+            move_times: Vec::new(),
+            ply: 0,
+        }
+    }
+
+    /// Returns the same record with how long each move took.
+    ///
+    /// # Arguments
+    ///
+    /// * `move_times` - How long each move took, in order.
+    pub fn with_move_times(self, move_times: Vec<Duration>) -> Self
+    {
+        Self
+        {
+            move_times,
+            ..self
+        }
+    }
+
+    /// Creates a [GameRecord] from a game read from PGN, replayed with [GameState::from_pgn]. The
+    /// game starts from its `FEN` tag if it has one, and the result is the one the PGN gives.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [LoadPgnError] if the `FEN` or `Variant` tag is invalid, or one of the game's
+    /// moves is impossible or illegal.
+    pub fn from_pgn(game: &PgnGame) -> Result<Self, LoadPgnError>
+    {
+        let game_state = GameState::from_pgn(game)?;
+        Ok(Self
+        {
+            result: *game.result(),
+            ..Self::from_game_state(&game_state)
+        })
+    }
+
+    /// Returns the moves of the game, in order.
+    pub fn moves(&self) -> &[Move]
+    {
+        &self.moves
+    }
+
+    /// Returns the result of the game.
+    pub fn result(&self) -> PgnResult
+    {
+        self.result
+    }
+
+    /// Returns the final [BoardResult], or [None] if the game isn't over. A record read from PGN
+    /// only has one if the final position ends the game, since a PGN result doesn't say how the
+    /// game ended.
+    pub fn board_result(&self) -> Option<BoardResult>
+    {
+        self.ending.map(|(board_result, _)| board_result)
+    }
+
+    /// Returns how the game ended, or [None] if the game isn't over, or the record was read from
+    /// PGN and the final position doesn't end the game.
+    pub fn termination(&self) -> Option<Termination>
+    {
+        self.ending.map(|(_, termination)| termination)
+    }
+
+    /// Returns how long each move took, in order, or an empty slice if the times weren't
+    /// recorded.
+    pub fn move_times(&self) -> &[Duration]
+    {
+        &self.move_times
+    }
+
+    /// Returns the number of moves (plies) in the game.
+    pub fn len(&self) -> usize
+    {
+        self.moves.len()
+    }
+
+    /// Returns true if the game doesn't have any moves.
+    pub fn is_empty(&self) -> bool
+    {
+        self.moves.is_empty()
+    }
+
+    /// Returns the ply the cursor is at.
+    pub fn ply(&self) -> usize
+    {
+        self.ply
+    }
+
+    /// Returns the board at the cursor.
+    pub fn current_board(&self) -> &Board
+    {
+        &self.boards[self.ply]
+    }
+
+    /// Returns the board at the given ply, or [None] if the game isn't that long. This doesn't
+    /// move the cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `ply` - The ply to look at, from 0 (the starting position) to [Self::len].
+    pub fn board_at(&self, ply: usize) -> Option<&Board>
+    {
+        self.boards.get(ply)
+    }
+
+    /// Returns the move that was played from the cursor's position, or [None] at the end of the
+    /// game.
+    pub fn next_move(&self) -> Option<&Move>
+    {
+        self.moves.get(self.ply)
+    }
+
+    /// Moves the cursor forward one ply and returns the board there, or returns [None] (and leaves
+    /// the cursor alone) if it's already at the end of the game.
+    pub fn step_forward(&mut self) -> Option<&Board>
+    {
+        self.jump_to(self.ply + 1)
+    }
+
+    /// Moves the cursor back one ply and returns the board there, or returns [None] (and leaves
+    /// the cursor alone) if it's already at the starting position.
+    pub fn step_back(&mut self) -> Option<&Board>
+    {
+        self.jump_to(self.ply.checked_sub(1)?)
+    }
+
+    /// Moves the cursor to the given ply and returns the board there, or returns [None] (and
+    /// leaves the cursor alone) if the game isn't that long.
+    ///
+    /// # Arguments
+    ///
+    /// * `ply` - The ply to jump to, from 0 (the starting position) to [Self::len].
+    pub fn jump_to(&mut self, ply: usize) -> Option<&Board>
+    {
+        let board = self.boards.get(ply)?;
+        self.ply = ply;
+        Some(board)
+    }
+}
+
+impl Display for GameRecord
+{
+    /// Writes a one line summary of the game, like `0-1 (checkmate) after 4 plies`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.result)?;
+        if let Some(termination) = self.termination()
+        {
+            write!(f, " ({})", termination)?;
+        }
+        write!(f, " after {} plies", self.len())?;
+        if !self.move_times.is_empty()
+        {
+            let total: Duration = self.move_times.iter().sum();
+            write!(f, " in {:.1?}", total)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{game::GameState, parse::parse_pgn};
+
+    use super::*;
+
+    #[test]
+    fn cursor_stays_put_at_the_ends()
+    {
+        let mut record = GameRecord::from_pgn(&parse_pgn("1. d4 d5 1/2-1/2").unwrap()[0]).unwrap();
+        assert_eq!(record.step_back(), None);
+        assert_eq!(record.ply(), 0);
+        assert!(record.jump_to(3).is_none());
+        assert_eq!(record.jump_to(2).map(|board| board.fullmove_number()), Some(2));
+        assert_eq!(record.step_forward(), None);
+        assert_eq!(record.ply(), 2);
+        assert_eq!(record.result(), PgnResult::Draw);
+    }
+
+    #[test]
+    fn records_of_game_states_replay_every_board()
+    {
+        let game_state = GameState::from_pgn(&parse_pgn("1. e4 e5 2. Nf3 *").unwrap()[0]).unwrap();
+        let record = GameRecord::from_game_state(&game_state);
+        let from_pgn = GameRecord::from_pgn(&parse_pgn("1. e4 e5 2. Nf3 *").unwrap()[0]).unwrap();
+        for ply in 0..=3
+        {
+            assert_eq!(record.board_at(ply), from_pgn.board_at(ply));
+        }
+        assert_eq!(record.board_at(4), None);
+        assert_eq!(record.termination(), None);
+        assert_eq!(record.to_string(), "* after 3 plies");
+    }
+
+    #[test]
+    fn illegal_moves_are_rejected()
+    {
+        let board = Board::new_default_starting_board();
+        let first_move = GameState::new(board.clone()).legal_moves()[0];
+        // The same move can't be played twice in a row.
+        let error = GameRecord::new(board, vec![first_move, first_move], PgnResult::Unknown).unwrap_err();
+        assert_eq!(error.index, 1);
+    }
+
+    #[test]
+    fn records_survive_serialization()
+    {
+        let record = GameRecord::from_pgn(&parse_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0").unwrap()[0]).unwrap();
+        let loaded: GameRecord = postcard::from_bytes(&postcard::to_allocvec(&record).unwrap()).unwrap();
+        assert_eq!(loaded.len(), record.len());
+        assert_eq!(loaded.board_at(record.len() - 1), record.board_at(record.len() - 1));
+        assert_eq!(loaded.result(), record.result());
+    }
+
+    #[test]
+    fn pgn_records_start_from_the_fen_tag()
+    {
+        let pgn = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n\n1. e4 Kd7 2. e5 1-0";
+        let record = GameRecord::from_pgn(&parse_pgn(pgn).unwrap()[0]).unwrap();
+        assert_eq!(record.len(), 3);
+        assert_eq!(record.board_at(0).unwrap().board_configuration().to_string(), "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        assert_eq!(record.result(), PgnResult::WhiteWin);
+        assert_eq!(record.termination(), None);
+    }
+}
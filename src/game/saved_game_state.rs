@@ -0,0 +1,69 @@
+//! Implements [SavedGameState], the form a [GameState] takes when it's serialized.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{board::{Board, BoardResult, DrawRules, MoveError, Move, PlayerColor}, rules::rules_by_name};
+
+use super::{Clock, GameState};
+
+/// Everything about a [GameState] that can't be worked out again from the rest of it: where the
+/// game started, the variant, the moves, and how things stood off the board. The current board
+/// and the position history come from replaying the moves, which also checks they're all legal,
+/// so a corrupt or doctored save can't sneak an illegal position in.
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedGameState
+{
+    starting_board: Board,
+    /// The [name](crate::rules::Rules::name) of the rules the game is played by.
+    variant: String,
+    moves: Vec<Move>,
+    draw_offer: Option<PlayerColor>,
+    outcome: Option<BoardResult>,
+    clock: Option<Clock>,
+    /// The [DrawRules] aren't part of a board's FEN, so they're saved on their own.
+    draw_rules: DrawRules,
+}
+
+/// Why a [SavedGameState] couldn't be turned back into a [GameState].
+#[derive(Debug, Error)]
+pub(super) enum InvalidSavedGameState
+{
+    /// The game is played by rules this crate doesn't know.
+    #[error("Unknown variant {0}")]
+    UnknownVariant(String),
+    /// One of the moves is impossible or illegal.
+    #[error("The game has an illegal move: {0}")]
+    IllegalMove(#[from] MoveError),
+}
+
+impl From<GameState> for SavedGameState
+{
+    fn from(game_state: GameState) -> Self {
+        Self
+        {
+            variant: game_state.rules.name().to_string(),
+            draw_rules: game_state.draw_rules(),
+            starting_board: game_state.starting_board,
+            moves: game_state.moves,
+            draw_offer: game_state.draw_offer,
+            outcome: game_state.outcome,
+            clock: game_state.clock,
+        }
+    }
+}
+
+impl TryFrom<SavedGameState> for GameState
+{
+    type Error = InvalidSavedGameState;
+
+    fn try_from(saved_game_state: SavedGameState) -> Result<Self, Self::Error> {
+        let rules = rules_by_name(&saved_game_state.variant).ok_or(InvalidSavedGameState::UnknownVariant(saved_game_state.variant))?;
+        let mut game_state = saved_game_state.moves.iter()
+            .try_fold(GameState::with_shared_rules(saved_game_state.starting_board.with_draw_rules(saved_game_state.draw_rules), rules), |game_state, r#move| game_state.update(r#move))?;
+        game_state.draw_offer = saved_game_state.draw_offer;
+        game_state.outcome = saved_game_state.outcome;
+        game_state.clock = saved_game_state.clock;
+        Ok(game_state)
+    }
+}
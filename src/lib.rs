@@ -7,7 +7,16 @@ pub mod board;
 pub mod bitboard;
 pub mod agent;
 pub mod game;
-mod hashmap_diff;
+pub mod opening_explorer;
+pub mod experience_book;
+pub mod opening_book;
+pub mod search;
+pub mod match_runner;
+pub mod tournament;
+pub mod rules;
+pub mod tablebase;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
 /// Defines the size of unsigned integers used to store and calculate square coordinates.
 /// a 8x8 grid of squares can be described using only u8s, but in theory if we were to use larger
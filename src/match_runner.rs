@@ -0,0 +1,411 @@
+//! Plays matches between two [Agent]s without anyone watching, for testing whether a change to
+//! an engine makes it stronger.
+//!
+//! A [MatchRunner] plays a number of games between two agents, switching colors every game, and
+//! sums the results up into a [MatchSummary]. It can also keep playing until a [Sprt] can tell
+//! whether one agent is stronger than the other.
+
+use std::{fmt::Display, time::Duration};
+
+use log::info;
+
+use crate::{agent::Agent, board::{Board, PlayerColor}, game::{Game, GameRecord, GameState, TimeControl}};
+
+mod sprt;
+
+pub use sprt::{Sprt, SprtDecision};
+pub(crate) use sprt::elo_from_score;
+
+/// The [log](mod@log) target for match progress, like each finished game. Use it to turn match logging up
+/// or down on its own, i.e `RUST_LOG=rust_chess_engine::match_runner=info` with
+/// [env_logger](https://docs.rs/env_logger).
+pub const LOG_TARGET: &str = "rust_chess_engine::match_runner";
+
+/// Plays a match between two [Agent]s, called A and B.
+///
+/// A plays White in the first game, and the agents switch colors every game. Games can start
+/// from an opening suite (see [Self::with_openings]), in which case each opening is played
+/// twice in a row, once with each agent as White, so neither agent gets the better side of an
+/// opening more often than the other.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::{GreedyAgent, WorstMoveAgent};
+/// # use rust_chess_engine::match_runner::MatchRunner;
+/// let mut runner = MatchRunner::new(GreedyAgent::new(), WorstMoveAgent::new(0), 2);
+/// let summary = runner.run();
+/// assert_eq!(summary.games(), 2);
+/// assert_eq!(summary.wins() + summary.draws() + summary.losses(), 2);
+/// println!("{}", summary);
+/// ```
+pub struct MatchRunner<A, B>
+where A: Agent, B: Agent
+{
+    /// The agents are only taken out while they're playing a game.
+    agents: Option<(A, B)>,
+    games: usize,
+    openings: Vec<GameState>,
+    time_control: Option<TimeControl>,
+}
+
+impl<A, B> MatchRunner<A, B>
+where A: Agent, B: Agent
+{
+    /// Creates a [MatchRunner] for a match of `games` games between `agent_a` and `agent_b`,
+    /// played from the default starting position without a clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent_a` - The agent whose results the summary is written from.
+    /// * `agent_b` - The opponent.
+    /// * `games` - How many games to play.
+    pub fn new(agent_a: A, agent_b: B, games: usize) -> Self
+    {
+        Self
+        {
+            agents: Some((agent_a, agent_b)),
+            games,
+            openings: Vec::new(),
+            time_control: None,
+        }
+    }
+
+    /// Returns the same runner, but starting games from `openings` in turn. Each opening is
+    /// played once with each agent as White before moving on to the next one, and the suite
+    /// starts over if there are more games than that.
+    ///
+    /// # Arguments
+    ///
+    /// * `openings` - The positions to start games from, along with the moves that led up to
+    ///   them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::agent::GreedyAgent;
+    /// # use rust_chess_engine::game::GameState;
+    /// # use rust_chess_engine::match_runner::MatchRunner;
+    /// # use rust_chess_engine::parse::parse_pgn;
+    /// let openings = parse_pgn("1. e4 c5 *\n\n1. d4 Nf6 2. c4 e6 *").unwrap().iter()
+    ///     .map(|game| GameState::from_pgn(game).unwrap())
+    ///     .collect();
+    /// let runner = MatchRunner::new(GreedyAgent::new(), GreedyAgent::new(), 4).with_openings(openings);
+    /// ```
+    pub fn with_openings(self, openings: Vec<GameState>) -> Self
+    {
+        Self
+        {
+            openings,
+            ..self
+        }
+    }
+
+    /// Returns the same runner, but playing every game with a chess clock. See
+    /// [Game::set_time_control].
+    ///
+    /// # Arguments
+    ///
+    /// * `time_control` - How much time each agent gets per game.
+    pub fn with_time_control(self, time_control: TimeControl) -> Self
+    {
+        Self
+        {
+            time_control: Some(time_control),
+            ..self
+        }
+    }
+
+    /// Plays every game of the match and returns a summary of the results, from agent A's
+    /// point of view.
+    pub fn run(&mut self) -> MatchSummary
+    {
+        let mut summary = MatchSummary::default();
+        for index in 0..self.games
+        {
+            self.play_and_add_game(index, &mut summary);
+        }
+        summary
+    }
+
+    /// Plays games until `sprt` can decide whether agent A is stronger than agent B, and returns
+    /// the decision along with a summary of the games. The number of games the runner was
+    /// created with is the most that will be played, and if they run out before the test is
+    /// decided the decision is [SprtDecision::Continue].
+    ///
+    /// The test is only checked after both games of a pair (one with each agent as White), so
+    /// neither color gets played more often.
+    ///
+    /// # Arguments
+    ///
+    /// * `sprt` - The test to run.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use rust_chess_engine::agent::MinmaxAgent;
+    /// # use rust_chess_engine::board::EvaluationWeights;
+    /// # use rust_chess_engine::game::TimeControl;
+    /// # use rust_chess_engine::match_runner::{MatchRunner, Sprt, SprtDecision};
+    /// // Does caring more about king safety gain at least 5 Elo?
+    /// let mut candidate = MinmaxAgent::new(6);
+    /// candidate.set_evaluation_weights(EvaluationWeights::default().with_king_safety_weight(2.0));
+    /// let baseline = MinmaxAgent::new(6);
+    /// let mut runner = MatchRunner::new(candidate, baseline, 20000)
+    ///     .with_time_control(TimeControl::new(Duration::from_secs(10), Duration::from_millis(100)));
+    /// let (decision, summary) = runner.run_sprt(&Sprt::new(0.0, 5.0, 0.05, 0.05));
+    /// println!("{:?} after {}", decision, summary);
+    /// ```
+    pub fn run_sprt(&mut self, sprt: &Sprt) -> (SprtDecision, MatchSummary)
+    {
+        let mut summary = MatchSummary::default();
+        for index in 0..self.games
+        {
+            self.play_and_add_game(index, &mut summary);
+            if !index.is_multiple_of(2)
+            {
+                let decision = sprt.decision(summary.wins(), summary.draws(), summary.losses());
+                info!(target: LOG_TARGET, "LLR {:.2} {:?}", sprt.llr(summary.wins(), summary.draws(), summary.losses()), sprt.bounds());
+                if decision != SprtDecision::Continue
+                {
+                    return (decision, summary);
+                }
+            }
+        }
+        (SprtDecision::Continue, summary)
+    }
+
+    /// Hands back both agents, i.e to look at what they learned during the match.
+    pub fn into_agents(self) -> (A, B)
+    {
+        self.agents.expect("The agents are back once a game is over")
+    }
+
+    /// Plays the game with the given index, and adds it to `summary`.
+    fn play_and_add_game(&mut self, index: usize, summary: &mut MatchSummary)
+    {
+        let opening = match self.openings.len()
+        {
+            0 => GameState::new(Board::new_default_starting_board()),
+            openings => self.openings[(index / 2) % openings].clone(),
+        };
+        let opening_plies = opening.moves().len();
+        let (record, a_color) = self.play_game(index, opening);
+        summary.add_game(record, a_color, opening_plies);
+        info!(target: LOG_TARGET, "Game {} of {} finished, {}", index + 1, self.games, summary);
+    }
+
+    /// Plays the game with the given index from `opening`, and returns its record along with the
+    /// color agent A played.
+    fn play_game(&mut self, index: usize, opening: GameState) -> (GameRecord, PlayerColor)
+    {
+        let (agent_a, agent_b) = self.agents.take().expect("The agents are back once a game is over");
+        if index.is_multiple_of(2)
+        {
+            let (record, agent_a, agent_b) = play(opening, agent_a, agent_b, self.time_control);
+            self.agents = Some((agent_a, agent_b));
+            (record, PlayerColor::White)
+        }
+        else
+        {
+            let (record, agent_b, agent_a) = play(opening, agent_b, agent_a, self.time_control);
+            self.agents = Some((agent_a, agent_b));
+            (record, PlayerColor::Black)
+        }
+    }
+}
+
+/// Plays one game from `opening` to the end, and hands the agents back along with the record.
+fn play<W: Agent, K: Agent>(opening: GameState, white: W, black: K, time_control: Option<TimeControl>) -> (GameRecord, W, K)
+{
+    let mut game = Game::resume(opening, white, black);
+    if let Some(time_control) = time_control
+    {
+        game.set_time_control(time_control);
+    }
+    let record = game.run();
+    let (_, white, black) = game.adjourn();
+    (record, white, black)
+}
+
+/// The results of a match played by a [MatchRunner], from agent A's point of view.
+///
+/// Move times only count the moves the agents actually played, not the moves of the opening
+/// the game started from.
+#[derive(Debug, Clone, Default)]
+pub struct MatchSummary
+{
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    total_plies: usize,
+    a_time: Duration,
+    a_moves: u32,
+    b_time: Duration,
+    b_moves: u32,
+    records: Vec<GameRecord>,
+}
+
+impl MatchSummary
+{
+    /// Adds a finished game, which agent A played as `a_color` after `opening_plies` moves of
+    /// the opening.
+    fn add_game(&mut self, record: GameRecord, a_color: PlayerColor, opening_plies: usize)
+    {
+        match record.board_result().map(|result| result.get_winner())
+        {
+            Some(Ok(winner)) if winner == a_color => self.wins += 1,
+            Some(Ok(_)) => self.losses += 1,
+            _ => self.draws += 1,
+        }
+        self.total_plies += record.len();
+        let starting_color = record.board_at(0).expect("Every record has a starting board").active_color();
+        for (ply, move_time) in record.move_times().iter().enumerate().skip(opening_plies)
+        {
+            let mover = if ply.is_multiple_of(2) { starting_color } else { !starting_color };
+            if mover == a_color
+            {
+                self.a_time += *move_time;
+                self.a_moves += 1;
+            }
+            else
+            {
+                self.b_time += *move_time;
+                self.b_moves += 1;
+            }
+        }
+        self.records.push(record);
+    }
+
+    /// Returns how many games agent A won.
+    pub fn wins(&self) -> u32
+    {
+        self.wins
+    }
+
+    /// Returns how many games were drawn.
+    pub fn draws(&self) -> u32
+    {
+        self.draws
+    }
+
+    /// Returns how many games agent A lost.
+    pub fn losses(&self) -> u32
+    {
+        self.losses
+    }
+
+    /// Returns how many games were played.
+    pub fn games(&self) -> usize
+    {
+        self.records.len()
+    }
+
+    /// Returns agent A's score as a fraction from 0 to 1, counting a win as 1 point and a draw as
+    /// half a point. A match without any games scores 0.5.
+    pub fn score(&self) -> f64
+    {
+        match self.games()
+        {
+            0 => 0.5,
+            games => (self.wins as f64 + self.draws as f64 / 2.0) / games as f64,
+        }
+    }
+
+    /// Returns how much stronger agent A played than agent B, in Elo, going by [Self::score].
+    /// A perfect or zero score is infinitely many Elo ahead or behind.
+    pub fn elo_difference(&self) -> f64
+    {
+        sprt::elo_from_score(self.score())
+    }
+
+    /// Returns how many plies the games lasted on average, opening moves included.
+    pub fn average_game_length(&self) -> f64
+    {
+        match self.games()
+        {
+            0 => 0.0,
+            games => self.total_plies as f64 / games as f64,
+        }
+    }
+
+    /// Returns how long agent A took per move on average.
+    pub fn average_move_time_a(&self) -> Duration
+    {
+        self.a_time.checked_div(self.a_moves).unwrap_or_default()
+    }
+
+    /// Returns how long agent B took per move on average.
+    pub fn average_move_time_b(&self) -> Duration
+    {
+        self.b_time.checked_div(self.b_moves).unwrap_or_default()
+    }
+
+    /// Returns the record of every game, in the order they were played.
+    pub fn records(&self) -> &[GameRecord]
+    {
+        &self.records
+    }
+}
+
+impl Display for MatchSummary
+{
+    /// Writes the summary on one line, like
+    /// `6.5/10 (+5 =3 -2), 84.2 plies per game, A 12.0ms/move, B 3.1ms/move`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} (+{} ={} -{}), {:.1} plies per game, A {:.1?}/move, B {:.1?}/move",
+            self.wins as f64 + self.draws as f64 / 2.0, self.games(),
+            self.wins, self.draws, self.losses,
+            self.average_game_length(),
+            self.average_move_time_a(), self.average_move_time_b())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{agent::ScriptedAgent, parse::parse_pgn};
+
+    use super::*;
+
+    #[test]
+    fn agents_switch_colors_every_game()
+    {
+        // Fool's mate: whoever plays Black wins, so with colors switching each agent wins once.
+        let agent_a = ScriptedAgent::from_san(&["f3", "g4", "e5", "Qh4#"]).unwrap();
+        let agent_b = ScriptedAgent::from_san(&["e5", "Qh4#", "f3", "g4"]).unwrap();
+        let summary = MatchRunner::new(agent_a, agent_b, 2).run();
+        assert_eq!((summary.wins(), summary.draws(), summary.losses()), (1, 0, 1));
+        assert_eq!(summary.score(), 0.5);
+        assert_eq!(summary.average_game_length(), 4.0);
+    }
+
+    #[test]
+    fn sprt_needs_mixed_results_to_decide()
+    {
+        // The agents play the same two games over and over, so agent A always scores 50%.
+        let agent_a = ScriptedAgent::from_san(&["f3", "g4", "e5", "Qh4#"].repeat(5)).unwrap();
+        let agent_b = ScriptedAgent::from_san(&["e5", "Qh4#", "f3", "g4"].repeat(5)).unwrap();
+        let (decision, summary) = MatchRunner::new(agent_a, agent_b, 10).run_sprt(&Sprt::new(0.0, 5.0, 0.05, 0.05));
+        assert_eq!(decision, SprtDecision::Continue);
+        assert_eq!(summary.games(), 10);
+        assert_eq!(summary.elo_difference(), 0.0);
+    }
+
+    #[test]
+    fn openings_are_played_from_both_sides()
+    {
+        let openings: Vec<GameState> = parse_pgn("1. f3 e5 *").unwrap().iter()
+            .map(|game| GameState::from_pgn(game).unwrap())
+            .collect();
+        let agent_a = ScriptedAgent::from_san(&["g4", "Qh4#"]).unwrap();
+        let agent_b = ScriptedAgent::from_san(&["Qh4#", "g4"]).unwrap();
+        let mut runner = MatchRunner::new(agent_a, agent_b, 2).with_openings(openings);
+        let summary = runner.run();
+        assert_eq!((summary.wins(), summary.losses()), (1, 1));
+        assert!(summary.records().iter().all(|record| record.len() == 4));
+        let (agent_a, agent_b) = runner.into_agents();
+        assert_eq!((agent_a.moves_left(), agent_b.moves_left()), (0, 0));
+    }
+}
@@ -0,0 +1,175 @@
+//! Implements [Sprt], the sequential probability ratio test used to decide when a match has
+//! played enough games to tell which agent is stronger.
+
+/// The outcome of a [Sprt].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SprtDecision
+{
+    /// Agent A is no stronger than the lower Elo bound: the change being tested doesn't help.
+    AcceptH0,
+    /// Agent A is at least as strong as the upper Elo bound: the change being tested helps.
+    AcceptH1,
+    /// There aren't enough games to tell yet.
+    Continue,
+}
+
+/// A sequential probability ratio test (SPRT), for comparing two agents with as few games as
+/// possible.
+///
+/// The test weighs two hypotheses against each other: H0, that agent A is `elo0` Elo stronger
+/// than agent B, and H1, that it's `elo1` Elo stronger. After every game the log-likelihood ratio
+/// (LLR) of H1 over H0 is worked out from the wins, draws and losses so far, and the match stops
+/// as soon as it crosses one of the [bounds](Self::bounds). Those are set so that H1 is wrongly
+/// accepted at most `alpha` of the time, and H0 at most `beta` of the time.
+///
+/// Bounds like `[0, 5]` are the usual choice for checking that a change gains something, and
+/// `[-5, 0]` for checking that a simplification doesn't lose anything. The narrower the bounds,
+/// the more games the test takes.
+///
+/// The LLR uses the normal approximation for game results that fishtest and cutechess use, with
+/// Elo on the logistic scale.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::match_runner::{Sprt, SprtDecision};
+/// let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+/// assert_eq!(sprt.decision(10, 10, 10), SprtDecision::Continue);
+/// assert_eq!(sprt.decision(1200, 2000, 1000), SprtDecision::AcceptH1);
+/// assert_eq!(sprt.decision(1000, 2000, 1100), SprtDecision::AcceptH0);
+/// ```
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Sprt
+{
+    elo0: f64,
+    elo1: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl Sprt
+{
+    /// Creates a new [Sprt].
+    ///
+    /// # Arguments
+    ///
+    /// * `elo0` - How much stronger agent A is under H0, in Elo.
+    /// * `elo1` - How much stronger agent A is under H1, in Elo. Should be more than `elo0`.
+    /// * `alpha` - The chance of accepting H1 when H0 is true.
+    /// * `beta` - The chance of accepting H0 when H1 is true.
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self
+    {
+        Self
+        {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        }
+    }
+
+    /// Returns the lower and upper bounds for the LLR. The test accepts H0 once the LLR drops
+    /// below the lower bound, and H1 once it goes above the upper one.
+    pub fn bounds(&self) -> (f64, f64)
+    {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Returns the log-likelihood ratio of H1 over H0, given agent A's results so far. Returns 0
+    /// until the results have some variance, i.e while every game has ended the same way.
+    ///
+    /// # Arguments
+    ///
+    /// * `wins` - How many games agent A won.
+    /// * `draws` - How many games were drawn.
+    /// * `losses` - How many games agent A lost.
+    pub fn llr(&self, wins: u32, draws: u32, losses: u32) -> f64
+    {
+        let games = (wins + draws + losses) as f64;
+        if games == 0.0
+        {
+            return 0.0;
+        }
+        let (wins, draws, losses) = (wins as f64 / games, draws as f64 / games, losses as f64 / games);
+        let score = wins + draws / 2.0;
+        let variance = wins * (1.0 - score).powi(2) + draws * (0.5 - score).powi(2) + losses * score.powi(2);
+        if variance <= 0.0
+        {
+            return 0.0;
+        }
+        let (score0, score1) = (score_from_elo(self.elo0), score_from_elo(self.elo1));
+        games * (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Returns what the test makes of agent A's results so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `wins` - How many games agent A won.
+    /// * `draws` - How many games were drawn.
+    /// * `losses` - How many games agent A lost.
+    pub fn decision(&self, wins: u32, draws: u32, losses: u32) -> SprtDecision
+    {
+        let llr = self.llr(wins, draws, losses);
+        let (lower, upper) = self.bounds();
+        if llr >= upper
+        {
+            SprtDecision::AcceptH1
+        }
+        else if llr <= lower
+        {
+            SprtDecision::AcceptH0
+        }
+        else
+        {
+            SprtDecision::Continue
+        }
+    }
+}
+
+/// Returns the expected score of a player who is `elo` Elo stronger than their opponent.
+pub(super) fn score_from_elo(elo: f64) -> f64
+{
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Returns how many Elo stronger a player who scores `score` (from 0 to 1) is than their
+/// opponent.
+pub(crate) fn elo_from_score(score: f64) -> f64
+{
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn elo_and_score_convert_back_and_forth()
+    {
+        assert_eq!(score_from_elo(0.0), 0.5);
+        assert!((elo_from_score(score_from_elo(100.0)) - 100.0).abs() < 1e-9);
+        assert!(elo_from_score(0.75) > 190.0 && elo_from_score(0.75) < 192.0);
+        assert_eq!(elo_from_score(1.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn bounds_follow_the_error_rates()
+    {
+        let (lower, upper) = Sprt::new(0.0, 5.0, 0.05, 0.05).bounds();
+        assert!((lower + 2.944).abs() < 1e-3);
+        assert!((upper - 2.944).abs() < 1e-3);
+    }
+
+    #[test]
+    fn llr_favours_the_closer_hypothesis()
+    {
+        let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+        // A 55% score is about 35 Elo, which is closer to H1.
+        assert!(sprt.llr(350, 400, 250) > 0.0);
+        // An even score is exactly H0.
+        assert!(sprt.llr(300, 400, 300) < 0.0);
+        assert_eq!(sprt.llr(5, 0, 0), 0.0);
+    }
+}
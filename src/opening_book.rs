@@ -0,0 +1,478 @@
+//! This module implements [OpeningBook], which reads opening books in the
+//! [Polyglot](http://hgm.nubati.net/book_format.html) `.bin` format.
+//!
+//! Polyglot is the most common format for engine opening books, and there are plenty of free ones
+//! built from millions of strong games. Unlike an [OpeningExplorer](crate::opening_explorer::OpeningExplorer),
+//! which is built from PGN files at runtime, a Polyglot book is a sorted list of fixed-size
+//! entries keyed by a hash of the position, so it can be loaded straight from disk and searched
+//! with a binary search.
+
+use std::{fs, io, path::Path};
+
+use getset::CopyGetters;
+use rand::{distr::{weighted::WeightedIndex, Distribution}, rngs::StdRng, SeedableRng};
+use thiserror::Error;
+
+use crate::{agent::MoveSource, board::{Board, CastlingDirection, Move, MoveData, PieceType, PlayerColor, Square}, game::GameState};
+
+mod polyglot_random;
+
+use polyglot_random::POLYGLOT_RANDOM;
+
+/// Every entry in a Polyglot book takes up this many bytes.
+const ENTRY_SIZE: usize = 16;
+
+/// Where the castling rights start in [POLYGLOT_RANDOM].
+const CASTLING_OFFSET: usize = 768;
+/// Where the en passant files start in [POLYGLOT_RANDOM].
+const EN_PASSANT_OFFSET: usize = 772;
+/// Where the side to move is in [POLYGLOT_RANDOM].
+const TURN_OFFSET: usize = 780;
+
+/// The errors that can happen when loading an [OpeningBook].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum OpeningBookError
+{
+    /// Reading the file failed.
+    #[error("Couldn't read the opening book: {0}")]
+    Io(#[from] io::Error),
+    /// The file isn't made of whole 16 byte entries, so it isn't a Polyglot book.
+    #[error("The opening book is {0} bytes long, which isn't a multiple of 16")]
+    InvalidLength(usize),
+}
+
+/// How an [OpeningBook] picks between the moves it has for a position.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum BookMoveSelection
+{
+    /// Always play the move with the highest weight. This plays the book's main line every time.
+    Best,
+    /// Pick a move at random, weighted by the move weights, so a move with twice the weight gets
+    /// played twice as often. This gives the engine some variety in the opening.
+    #[default]
+    Weighted,
+}
+
+/// One entry of a Polyglot book, suggesting one move from one position.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct BookEntry
+{
+    /// The [polyglot_key] of the position.
+    key: u64,
+    /// The move, in Polyglot's own encoding. See [OpeningBook::moves] to get it as a [Move].
+    raw_move: u16,
+    /// How good the move is compared to the other moves from the same position. Usually based on
+    /// how often it was played and how well it scored.
+    weight: u16,
+    /// Used by some tools to remember how the move did in their own games. Ignored here.
+    learn: u32,
+}
+
+impl BookEntry
+{
+    /// Reads an entry from its 16 bytes, which are stored big-endian.
+    fn from_bytes(bytes: &[u8; ENTRY_SIZE]) -> Self
+    {
+        Self
+        {
+            key: u64::from_be_bytes(bytes[0..8].try_into().expect("8 bytes")),
+            raw_move: u16::from_be_bytes(bytes[8..10].try_into().expect("2 bytes")),
+            weight: u16::from_be_bytes(bytes[10..12].try_into().expect("2 bytes")),
+            learn: u32::from_be_bytes(bytes[12..16].try_into().expect("4 bytes")),
+        }
+    }
+}
+
+/// An opening book in the Polyglot format.
+///
+/// The book is a [MoveSource], so it can be put in front of a search with a
+/// [PipelineAgent](crate::agent::PipelineAgent), or given to a
+/// [MinmaxAgent](crate::agent::MinmaxAgent) directly with
+/// [MinmaxAgent::set_opening_book](crate::agent::MinmaxAgent::set_opening_book).
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::opening_book::{polyglot_key, BookMoveSelection, OpeningBook};
+/// let board = Board::new_default_starting_board();
+/// // A tiny book which only knows 1. e4 (e2 to e4 is encoded as 0x031c) and 1. d4 (0x02db).
+/// let mut bytes = Vec::new();
+/// for (raw_move, weight) in [(0x031c_u16, 3_u16), (0x02db, 1)]
+/// {
+///     bytes.extend(polyglot_key(&board).to_be_bytes());
+///     bytes.extend(raw_move.to_be_bytes());
+///     bytes.extend(weight.to_be_bytes());
+///     bytes.extend(0_u32.to_be_bytes());
+/// }
+/// let mut book = OpeningBook::from_bytes(&bytes).unwrap()
+///     .with_selection(BookMoveSelection::Best);
+/// let e4 = book.choose_move(&board).unwrap();
+/// assert_eq!(board.to_san(&e4), "e4");
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpeningBook
+{
+    entries: Vec<BookEntry>,
+    selection: BookMoveSelection,
+    rng: StdRng,
+}
+
+impl OpeningBook
+{
+    /// Loads a Polyglot book from a `.bin` file. The whole file is read into memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The book to load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [OpeningBookError] if the file couldn't be read or isn't a Polyglot book.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OpeningBookError>
+    {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Reads a Polyglot book from the contents of a `.bin` file.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The contents of the book.
+    ///
+    /// # Errors
+    ///
+    /// Returns [OpeningBookError::InvalidLength] if `bytes` isn't made of whole entries.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OpeningBookError>
+    {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE)
+        {
+            return Err(OpeningBookError::InvalidLength(bytes.len()));
+        }
+        let mut entries: Vec<BookEntry> = bytes.chunks_exact(ENTRY_SIZE)
+            .map(|chunk| BookEntry::from_bytes(chunk.try_into().expect("Chunks are exactly one entry long")))
+            .collect();
+        // Books are supposed to be sorted already, but the binary search falls apart if one
+        // isn't, so make sure. The sort is stable so moves keep the order the book gave them.
+        entries.sort_by_key(|entry| entry.key);
+        Ok(Self
+        {
+            entries,
+            selection: BookMoveSelection::default(),
+            rng: StdRng::from_rng(&mut rand::rng()),
+        })
+    }
+
+    /// Sets how the book picks between moves. Defaults to [BookMoveSelection::Weighted].
+    ///
+    /// # Arguments
+    ///
+    /// * `selection` - How to pick between moves.
+    pub fn with_selection(mut self, selection: BookMoveSelection) -> Self
+    {
+        self.selection = selection;
+        self
+    }
+
+    /// Uses a fixed seed for [BookMoveSelection::Weighted], so the same moves get picked every
+    /// time.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed for the random number generator.
+    pub fn with_seed(mut self, seed: u64) -> Self
+    {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns how many entries the book has.
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    /// Returns true if the book has no entries.
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    /// Returns every entry for the position with the given [polyglot_key].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The [polyglot_key] of the position.
+    pub fn entries(&self, key: u64) -> &[BookEntry]
+    {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = start + self.entries[start..].partition_point(|entry| entry.key == key);
+        &self.entries[start..end]
+    }
+
+    /// Returns every legal move the book has for the position, with its weight, in the order the
+    /// book lists them. Entries whose move isn't legal on `board` are skipped, which can happen
+    /// when two positions share a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look up.
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)>
+    {
+        let entries = self.entries(polyglot_key(board));
+        if entries.is_empty()
+        {
+            return Vec::new();
+        }
+        let legal_moves = board.generate_moves_for_side(board.active_color());
+        entries.iter()
+            .filter_map(|entry| decode_move(board, entry.raw_move)
+                .and_then(|r#move| legal_moves.iter().find(|legal_move| same_move(legal_move, &r#move)).copied())
+                .map(|r#move| (r#move, entry.weight)))
+            .collect()
+    }
+
+    /// Picks a move for the position, the way set by [Self::with_selection]. Returns [None] if
+    /// the position isn't in the book.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to pick a move for.
+    pub fn choose_move(&mut self, board: &Board) -> Option<Move>
+    {
+        let moves = self.moves(board);
+        match self.selection
+        {
+            // max_by_key returns the last of equal elements, and the first listed move should win
+            // ties.
+            BookMoveSelection::Best => moves.iter().rev().max_by_key(|(_, weight)| *weight).map(|(r#move, _)| *r#move),
+            BookMoveSelection::Weighted => {
+                // Fails if every weight is 0, in which case we still play the first move.
+                match WeightedIndex::new(moves.iter().map(|(_, weight)| *weight as u32))
+                {
+                    Ok(distribution) => Some(moves[distribution.sample(&mut self.rng)].0),
+                    Err(_) => moves.first().map(|(r#move, _)| *r#move),
+                }
+            }
+        }
+    }
+}
+
+impl MoveSource for OpeningBook
+{
+    /// Picks a book move for the current position, if the position is in the book.
+    fn probe(&mut self, game_state: &GameState) -> Option<Move> {
+        self.choose_move(game_state.current_board())
+    }
+}
+
+/// Computes the Polyglot key of a position, which is how positions are looked up in a Polyglot
+/// book.
+///
+/// This is a Zobrist hash like [Board::zobrist_key], but with the random numbers from the Polyglot
+/// specification so it matches books made by other tools. One difference is that the en passant
+/// file only counts if a pawn can actually capture en passant.
+///
+/// # Arguments
+///
+/// * `board` - The position to hash.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::opening_book::polyglot_key;
+/// assert_eq!(polyglot_key(&Board::new_default_starting_board()), 0x463b96181691fc9c);
+/// ```
+pub fn polyglot_key(board: &Board) -> u64
+{
+    let board_configuration = board.board_configuration();
+    let mut key = 0;
+    for (square, piece) in board_configuration.iter_pieces()
+    {
+        key ^= POLYGLOT_RANDOM[64 * piece_index(piece.piece_type(), piece.color()) + square.index() as usize];
+    }
+
+    let castling_availability = board_configuration.castling_availability();
+    let castling_rights = [
+        *castling_availability.white_castle_kingside(),
+        *castling_availability.white_castle_queenside(),
+        *castling_availability.black_castle_kingside(),
+        *castling_availability.black_castle_queenside(),
+    ];
+    for (offset, _) in castling_rights.iter().enumerate().filter(|(_, can_castle)| **can_castle)
+    {
+        key ^= POLYGLOT_RANDOM[CASTLING_OFFSET + offset];
+    }
+
+    if let Some(en_passant_square) = board_configuration.en_passant_target_square()
+    {
+        // The pawn that would capture stands on the same rank as the pawn that just moved.
+        let capturing_rank = match board.active_color()
+        {
+            PlayerColor::White => en_passant_square.rank - 1,
+            PlayerColor::Black => en_passant_square.rank + 1,
+        };
+        let can_capture = [en_passant_square.file.checked_sub(1), Some(en_passant_square.file + 1)].into_iter()
+            .flatten()
+            .filter(|file| *file < 8)
+            .any(|file| board_configuration.piece_at(&Square::new(capturing_rank, file))
+                .is_some_and(|piece| piece.piece_type() == PieceType::Pawn && piece.color() == board.active_color()));
+        if can_capture
+        {
+            key ^= POLYGLOT_RANDOM[EN_PASSANT_OFFSET + en_passant_square.file as usize];
+        }
+    }
+
+    if board.active_color() == PlayerColor::White
+    {
+        key ^= POLYGLOT_RANDOM[TURN_OFFSET];
+    }
+    key
+}
+
+/// Polyglot orders pieces black pawn, white pawn, black knight, white knight and so on.
+fn piece_index(piece_type: PieceType, color: PlayerColor) -> usize
+{
+    let kind = match piece_type
+    {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    kind * 2 + usize::from(color == PlayerColor::White)
+}
+
+/// Turns a move in Polyglot's encoding into a [Move]. The capture flag isn't known from the
+/// encoding, so it's always false here, see [same_move].
+///
+/// The bits are, from lowest to highest: 3 for the target file, 3 for the target rank, 3 for the
+/// starting file, 3 for the starting rank and 3 for the promotion piece. Castling is written as
+/// the king capturing its own rook, i.e e1h1 for White castling kingside.
+fn decode_move(board: &Board, raw_move: u16) -> Option<Move>
+{
+    let field = |shift: u16| ((raw_move >> shift) & 0b111) as u8;
+    let target_square = Square::new(field(3), field(0));
+    let starting_square = Square::new(field(9), field(6));
+    let promotion = match field(12)
+    {
+        0 => None,
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => return None,
+    };
+
+    let moving_piece = board.piece_at(&starting_square)?;
+    let castling_rook = board.piece_at(&target_square)
+        .is_some_and(|piece| piece.piece_type() == PieceType::Rook && piece.color() == moving_piece.color());
+    if moving_piece.piece_type() == PieceType::King && castling_rook && starting_square.file == 4
+    {
+        return Some(Move::Castle(match target_square.file
+        {
+            7 => CastlingDirection::Kingside,
+            _ => CastlingDirection::Queenside,
+        }));
+    }
+
+    let move_data = MoveData::new(starting_square, target_square, false);
+    Some(Move::NormalMove(match promotion
+    {
+        Some(piece_type) => move_data.with_promotion(piece_type),
+        None => move_data,
+    }))
+}
+
+/// Compares a generated move to a decoded one, ignoring the capture flag.
+fn same_move(legal_move: &Move, decoded_move: &Move) -> bool
+{
+    match (legal_move, decoded_move)
+    {
+        (Move::Castle(a), Move::Castle(b)) => a == b,
+        (Move::NormalMove(a), Move::NormalMove(b)) => a.starting_square() == b.starting_square()
+            && a.target_square() == b.target_square()
+            && a.promotion() == b.promotion(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::test_support::board_from_fen;
+
+    use super::*;
+
+    fn book(entries: &[(u64, u16, u16)]) -> OpeningBook
+    {
+        let bytes: Vec<u8> = entries.iter()
+            .flat_map(|(key, raw_move, weight)| [&key.to_be_bytes()[..], &raw_move.to_be_bytes(), &weight.to_be_bytes(), &[0; 4]].concat())
+            .collect();
+        OpeningBook::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn keys_match_the_polyglot_test_positions()
+    {
+        // From the Polyglot book format specification.
+        let positions = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+            ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+            ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+            ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+            ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+            ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+            ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560),
+        ];
+        for (fen, key) in positions
+        {
+            assert_eq!(polyglot_key(&board_from_fen(fen)), key, "{fen}");
+        }
+    }
+
+    #[test]
+    fn castling_is_decoded_from_king_takes_rook()
+    {
+        let board = board_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let key = polyglot_key(&board);
+        // e1h1 and e1a1.
+        let book = book(&[(key, 0x0107, 2), (key, 0x0100, 1), (key + 1, 0x0107, 1)]);
+        let moves: Vec<Move> = book.moves(&board).into_iter().map(|(r#move, _)| r#move).collect();
+        assert_eq!(moves, [Move::Castle(CastlingDirection::Kingside), Move::Castle(CastlingDirection::Queenside)]);
+    }
+
+    #[test]
+    fn best_selection_plays_the_heaviest_move_and_weighted_plays_them_all()
+    {
+        let board = Board::new_default_starting_board();
+        let key = polyglot_key(&board);
+        // e2e4, d2d4, and an illegal e2e5 which is skipped.
+        let entries = [(key, 0x031c, 5), (key, 0x02db, 5), (key, 0x0324, 100)];
+        let mut best = book(&entries).with_selection(BookMoveSelection::Best);
+        assert_eq!(best.moves(&board).len(), 2);
+        assert_eq!(board.to_san(&best.choose_move(&board).unwrap()), "e4");
+
+        let mut weighted = book(&entries).with_seed(3);
+        let mut picked: Vec<String> = (0..50).map(|_| board.to_san(&weighted.choose_move(&board).unwrap())).collect();
+        picked.sort();
+        picked.dedup();
+        assert_eq!(picked, ["d4", "e4"]);
+
+        assert!(best.choose_move(&board.attempt_move(&best.moves(&board)[0].0).unwrap()).is_none());
+    }
+
+    #[test]
+    fn books_must_be_whole_entries()
+    {
+        assert!(matches!(OpeningBook::from_bytes(&[0; 17]), Err(OpeningBookError::InvalidLength(17))));
+        assert!(OpeningBook::from_bytes(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+//! This module implements [OpeningExplorer], a tree of opening moves built from a collection of
+//! games.
+//!
+//! For every position reached in the opening of any game that was added, the explorer records
+//! which moves were played from that position, how often, and how the games went afterwards.
+//! This answers questions like "what's usually played here and how does it score?", and can be
+//! used by agents as a simple opening book.
+
+use std::collections::HashMap;
+
+use getset::CopyGetters;
+use log::warn;
+
+use crate::{board::{ApplyMovesError, Board, Move, PlayerColor}, parse::{parse_pgn, PgnGame, PgnParseError, PgnResult}};
+
+/// By default only the first 20 moves (40 ply) of each game are recorded.
+const DEFAULT_MAX_PLY: usize = 40;
+
+/// How often a move was played from a position and how those games ended.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct MoveStatistics
+{
+    /// How many games this move was played in.
+    games: u32,
+    /// How many of those games White won.
+    white_wins: u32,
+    /// How many of those games were drawn.
+    draws: u32,
+    /// How many of those games Black won.
+    black_wins: u32,
+}
+
+impl MoveStatistics
+{
+    /// Returns the average score (1 for a win, 0.5 for a draw, 0 for a loss) of this move from
+    /// the point of view of `color`.
+    ///
+    /// Games with an unknown result are not counted. If none of the games have a known result,
+    /// this returns [None].
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The player to score the move for.
+    pub fn score(&self, color: PlayerColor) -> Option<f64>
+    {
+        let decided_games = self.white_wins + self.draws + self.black_wins;
+        if decided_games == 0
+        {
+            return None;
+        }
+        let wins = match color
+        {
+            PlayerColor::White => self.white_wins,
+            PlayerColor::Black => self.black_wins,
+        };
+        Some((wins as f64 + self.draws as f64 / 2.0) / decided_games as f64)
+    }
+
+    fn record(&mut self, result: PgnResult)
+    {
+        self.games += 1;
+        match result
+        {
+            PgnResult::WhiteWin => self.white_wins += 1,
+            PgnResult::BlackWin => self.black_wins += 1,
+            PgnResult::Draw => self.draws += 1,
+            PgnResult::Unknown => (),
+        }
+    }
+}
+
+/// A tree of opening positions and the moves played from them.
+///
+/// Positions are looked up by [Board] equality, so transpositions (the same position reached by
+/// different move orders) share their statistics.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::{Board, PlayerColor};
+/// # use rust_chess_engine::opening_explorer::OpeningExplorer;
+/// let mut explorer = OpeningExplorer::new();
+/// explorer.add_pgn("1. e4 e5 2. Nf3 1-0\n\n1. e4 c5 0-1\n\n1. d4 d5 1/2-1/2").unwrap();
+///
+/// let start = Board::new_default_starting_board();
+/// let moves = explorer.moves(&start);
+/// // e4 was played twice, d4 once.
+/// assert_eq!(moves.len(), 2);
+/// assert_eq!(moves[0].1.games(), 2);
+/// assert_eq!(moves[0].1.score(PlayerColor::White), Some(0.5));
+/// assert_eq!(moves[1].1.games(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpeningExplorer
+{
+    positions: HashMap<Board, HashMap<Move, MoveStatistics>>,
+    max_ply: usize,
+    games: u32,
+}
+
+impl OpeningExplorer
+{
+    /// Creates a new, empty [OpeningExplorer] which records the first 40 ply of every game.
+    pub fn new() -> Self
+    {
+        Self::with_max_ply(DEFAULT_MAX_PLY)
+    }
+
+    /// Creates a new, empty [OpeningExplorer] which records the first `max_ply` ply (half moves)
+    /// of every game.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_ply` - How deep into each game to record moves.
+    pub fn with_max_ply(max_ply: usize) -> Self
+    {
+        Self
+        {
+            positions: HashMap::new(),
+            max_ply,
+            games: 0,
+        }
+    }
+
+    /// Returns how many games have been added to the explorer.
+    pub fn games(&self) -> u32
+    {
+        self.games
+    }
+
+    /// Returns how many distinct positions the explorer knows about.
+    pub fn positions(&self) -> usize
+    {
+        self.positions.len()
+    }
+
+    /// Adds a single game to the explorer, starting from the default starting position.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The game to add.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [ApplyMovesError] if one of the game's moves couldn't be made. The moves before
+    /// it are still recorded.
+    pub fn add_game(&mut self, game: &PgnGame) -> Result<(), ApplyMovesError>
+    {
+        self.games += 1;
+        let mut board = Board::new_default_starting_board();
+        for (index, move_command) in game.moves().iter().take(self.max_ply).enumerate()
+        {
+            let r#move = board.get_move(move_command)
+                .map_err(|error| ApplyMovesError { index, error })?;
+            let next_board = board.attempt_move(&r#move)
+                .map_err(|error| ApplyMovesError { index, error })?;
+            self.positions.entry(board).or_default()
+                .entry(r#move).or_default()
+                .record(*game.result());
+            board = next_board;
+        }
+        Ok(())
+    }
+
+    /// Parses a PGN string and adds every game in it to the explorer.
+    ///
+    /// Games containing moves that can't be made are still added up to the bad move, and a
+    /// warning is logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `pgn` - The contents of a PGN file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [PgnParseError] if the PGN is malformed, in which case nothing is added.
+    pub fn add_pgn(&mut self, pgn: &str) -> Result<(), PgnParseError>
+    {
+        for game in parse_pgn(pgn)?
+        {
+            if let Err(error) = self.add_game(&game)
+            {
+                warn!("Stopped reading game {} early: {}", self.games, error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns every move played from the given position along with its statistics, most
+    /// popular first. Returns an empty [Vec] if the position is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look up.
+    pub fn moves(&self, board: &Board) -> Vec<(Move, MoveStatistics)>
+    {
+        let mut moves: Vec<(Move, MoveStatistics)> = self.positions.get(board)
+            .map(|moves| moves.iter().map(|(r#move, statistics)| (*r#move, *statistics)).collect())
+            .unwrap_or_default();
+        moves.sort_by_key(|(_, statistics)| std::cmp::Reverse(statistics.games));
+        moves
+    }
+
+    /// Returns the combined statistics of every game that reached the given position, or [None]
+    /// if no game did.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look up.
+    pub fn position_statistics(&self, board: &Board) -> Option<MoveStatistics>
+    {
+        self.positions.get(board).map(|moves| moves.values().fold(MoveStatistics::default(), |total, statistics|
+            MoveStatistics
+            {
+                games: total.games + statistics.games,
+                white_wins: total.white_wins + statistics.white_wins,
+                draws: total.draws + statistics.draws,
+                black_wins: total.black_wins + statistics.black_wins,
+            }
+        ))
+    }
+
+    /// Returns the most commonly played move from the given position, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look up.
+    pub fn most_played_move(&self, board: &Board) -> Option<Move>
+    {
+        self.moves(board).first().map(|(r#move, _)| *r#move)
+    }
+}
+
+impl Default for OpeningExplorer
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::parse::MoveCommand;
+
+    use super::*;
+
+    #[test]
+    fn transpositions_share_statistics()
+    {
+        let mut explorer = OpeningExplorer::new();
+        explorer.add_pgn("1. Nf3 Nf6 2. Nc3 1-0\n\n1. Nc3 Nf6 2. Nf3 Nc6 0-1").unwrap();
+        let board = Board::new_default_starting_board()
+            .attempt_move_from_command(&MoveCommand::from_str("Nc3").unwrap()).unwrap()
+            .attempt_move_from_command(&MoveCommand::from_str("Nf6").unwrap()).unwrap()
+            .attempt_move_from_command(&MoveCommand::from_str("Nf3").unwrap()).unwrap();
+        let statistics = explorer.position_statistics(&board).unwrap();
+        // Only the second game continued from here, but the first game reached it too.
+        assert_eq!(statistics.games(), 1);
+        assert_eq!(statistics.black_wins(), 1);
+        assert_eq!(explorer.games(), 2);
+    }
+
+    #[test]
+    fn max_ply_limits_recorded_moves()
+    {
+        let mut explorer = OpeningExplorer::with_max_ply(1);
+        explorer.add_pgn("1. e4 e5 2. Nf3 *").unwrap();
+        assert_eq!(explorer.positions(), 1);
+    }
+
+    #[test]
+    fn illegal_moves_stop_recording_the_game()
+    {
+        let mut explorer = OpeningExplorer::new();
+        explorer.add_pgn("1. e4 e5 2. e5 *").unwrap();
+        assert_eq!(explorer.positions(), 2);
+        assert_eq!(explorer.games(), 1);
+    }
+
+    #[test]
+    fn score_ignores_unknown_results()
+    {
+        let mut explorer = OpeningExplorer::new();
+        explorer.add_pgn("1. e4 1-0\n\n1. e4 *").unwrap();
+        let statistics = explorer.position_statistics(&Board::new_default_starting_board()).unwrap();
+        assert_eq!(statistics.games(), 2);
+        assert_eq!(statistics.score(PlayerColor::White), Some(1.0));
+        assert_eq!(statistics.score(PlayerColor::Black), Some(0.0));
+    }
+}
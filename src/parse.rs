@@ -32,10 +32,14 @@
 //! ## Exceptions
 //! There are lots of specific moves that don't follow the rules above.
 //! - Castling is indicated by O-O (kingside) or O-O-O (queenside).
-//! - Sometimes checks and checkmate are given special symbols. These do not actually add
-//! additional information in determining the move made but are standard in chess notation anyways.
-//! They're not used or expected here however.
-//! - This library currently doesn't handle pawn promotions haha whoops, we'll add that later.
+//! - Sometimes checks and checkmate are given special symbols, "+" and "#". These do not actually
+//! add additional information in determining the move made but are standard in chess notation
+//! anyways. They're accepted but optional here, see [CheckAnnotation].
+//! - A pawn reaching the last rank says what it promotes to after an "=", i.e e8=Q or bxa1=N.
+//!
+//! Engines and GUIs talking [UCI](https://www.chessprogramming.org/UCI) don't use algebraic
+//! notation at all, and write moves as the starting and target squares instead, i.e `e2e4` or
+//! `e7e8q`. Those can be parsed with [UciMove].
 //!
 //! # Examples
 //! You probably want to start by creating a [MoveCommand] from an algebraically notated string.
@@ -50,13 +54,22 @@
 //! ```
 
 mod move_command;
+mod uci_move;
 mod coordinates;
 mod error;
+mod pgn;
+mod pgn_writer;
+mod analysis_url;
 
 // Re-exports
 pub use move_command::MoveCommand;
-pub use move_command::MoveCommandData;
-pub use error::NotationParseError;
+pub use move_command::{CheckAnnotation, MoveCommandData, ParseMode};
+pub use uci_move::UciMove;
+pub use error::{NotationParseError, PgnParseError};
+pub use pgn::{parse_pgn, PgnGame, PgnResult};
+pub use pgn_writer::{write_pgn, AnnotatedMove};
+pub use analysis_url::{lichess_analysis_url, lichess_game_analysis_url};
 pub use coordinates::alphabetic_file_to_numeric;
 pub use coordinates::algebraic_to_square;
+pub use coordinates::square_to_algebraic;
 pub use coordinates::rank_to_numeric;
@@ -0,0 +1,107 @@
+//! Builds links that open a position or a whole game on
+//! [lichess's analysis board](https://lichess.org/analysis), so a game can be reviewed with a
+//! stronger engine in one click.
+
+use crate::board::{ApplyMovesError, Board, Move};
+
+use super::{write_pgn, AnnotatedMove, PgnResult};
+
+const LICHESS_ANALYSIS_URL: &str = "https://lichess.org/analysis";
+
+/// Returns a lichess analysis URL for the given position, built from its FEN.
+///
+/// # Arguments
+///
+/// * `board` - The position to analyse.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::parse::lichess_analysis_url;
+/// let url = lichess_analysis_url(&Board::new_default_starting_board());
+/// assert_eq!(url, "https://lichess.org/analysis/standard/rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR_w_KQkq_-_0_1");
+/// ```
+pub fn lichess_analysis_url(board: &Board) -> String
+{
+    // Lichess takes the FEN right in the path, with underscores instead of spaces.
+    let fen = board.board_configuration().to_string().replace(' ', "_");
+    format!("{}/standard/{}", LICHESS_ANALYSIS_URL, fen)
+}
+
+/// Returns a lichess analysis URL for a whole game, built from its PGN, so every move can be
+/// stepped through.
+///
+/// Games that don't start from the standard position include their starting FEN.
+///
+/// # Arguments
+///
+/// * `starting_board` - The position the game started from.
+/// * `moves` - The moves of the game, in order.
+///
+/// # Errors
+///
+/// Returns an [ApplyMovesError] if one of the moves can't be made.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::lichess_game_analysis_url;
+/// let board = Board::new_default_starting_board();
+/// let first_move = GameState::new(board.clone()).legal_moves()[0];
+/// let url = lichess_game_analysis_url(&board, &[first_move]).unwrap();
+/// assert!(url.starts_with("https://lichess.org/analysis/pgn/"));
+/// ```
+pub fn lichess_game_analysis_url(starting_board: &Board, moves: &[Move]) -> Result<String, ApplyMovesError>
+{
+    let mut tags = Vec::new();
+    if *starting_board != Board::new_default_starting_board()
+    {
+        tags.push(("SetUp".to_string(), "1".to_string()));
+        tags.push(("FEN".to_string(), starting_board.board_configuration().to_string()));
+    }
+    let moves: Vec<AnnotatedMove> = moves.iter().copied().map(AnnotatedMove::from).collect();
+    let pgn = write_pgn(&tags, starting_board, &moves, PgnResult::Unknown)?;
+    Ok(format!("{}/pgn/{}", LICHESS_ANALYSIS_URL, percent_encode(&pgn)))
+}
+
+/// Percent-encodes everything except the characters that are always safe in a URL path.
+fn percent_encode(s: &str) -> String
+{
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes()
+    {
+        match byte
+        {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::board::BoardConfiguration;
+
+    use super::*;
+
+    #[test]
+    fn reserved_characters_are_encoded()
+    {
+        assert_eq!(percent_encode("1. e4 *\n[FEN \"8/8\"]"), "1.%20e4%20%2A%0A%5BFEN%20%228%2F8%22%5D");
+    }
+
+    #[test]
+    fn custom_starting_positions_include_the_fen()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap());
+        let url = lichess_game_analysis_url(&board, &[]).unwrap();
+        assert!(url.contains(&percent_encode("[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]")));
+    }
+}
@@ -36,6 +36,26 @@ pub fn algebraic_to_square(algebraic_notated_string: &str) -> Result<Square, Not
     Square::from_str(algebraic_notated_string)
 }
 
+/// Converts a [Square] into algebraic notation (such as "a5", "c8", etc), the inverse of
+/// [algebraic_to_square]. Only the standard files a-h are supported.
+///
+/// # Arguments
+///
+/// * `square` - The square to convert. Its file must be less than 8.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::parse::square_to_algebraic;
+/// # use rust_chess_engine::board::Square;
+/// assert_eq!(square_to_algebraic(Square::new(3, 0)), "a4");
+/// assert_eq!(square_to_algebraic(Square::new(7, 7)), "h8");
+/// ```
+pub fn square_to_algebraic(square: Square) -> String
+{
+    format!("{}{}", char::from(b'a' + square.file), square.rank + 1)
+}
+
 /// Converts an alphabetical string into its numeric representation as if 
 /// the string were a file on a chessboard. 
 ///
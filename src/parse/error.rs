@@ -1,4 +1,5 @@
-//! Defines error types encountered when parsing a string into a [super::MoveCommand].
+//! Defines error types encountered when parsing a string into a [super::MoveCommand] or a
+//! [super::PgnGame].
 
 use std::num::ParseIntError;
 
@@ -36,3 +37,25 @@ pub enum NotationParseError
     #[error("Integer overflow trying to convert {0} to a number.")]
     Overflow(String),
 }
+
+/// The error type returned by [parse_pgn](super::parse_pgn) when a PGN file is malformed.
+#[derive(Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum PgnParseError
+{
+    /// A tag pair wasn't of the form `[Name "Value"]`.
+    #[error("`{0}` is not a valid tag pair")]
+    InvalidTagPair(String),
+    /// A move in the movetext couldn't be parsed.
+    #[error("`{0}` is not a valid move: {1}")]
+    InvalidMove(String, NotationParseError),
+    /// The game termination marker wasn't one of `1-0`, `0-1`, `1/2-1/2` or `*`.
+    #[error("`{0}` is not a valid game result")]
+    InvalidResult(String),
+    /// A `{` comment was never closed.
+    #[error("A comment was never closed")]
+    UnterminatedComment,
+    /// A variation was closed without being opened, or was never closed.
+    #[error("Unbalanced parentheses in variations")]
+    UnbalancedVariation,
+}
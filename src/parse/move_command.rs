@@ -2,7 +2,7 @@
 
 use std::str::FromStr;
 use getset::CopyGetters;
-use regex::RegexBuilder;
+use regex::{Regex, RegexBuilder};
 
 use crate::board::{Line, PieceType, Square};
 
@@ -18,7 +18,7 @@ use super::NotationParseError;
 /// - A kingside castle (O-O)
 /// - A queenside castle (O-O-O)
 /// - Basically everything else.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum MoveCommand
 {
     /// A "normal" chess move, which is basically any move that isn't a castle.
@@ -90,6 +90,120 @@ impl MoveCommand
         }
     }
 
+    /// Parses a move in algebraic notation, following the rules of the given [ParseMode]. A
+    /// check or checkmate suffix is allowed, but thrown away.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The move, i.e `"e4"`, `"Qxf7#"` or `"O-O"`.
+    /// * `mode` - How closely the move has to follow standard algebraic notation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [NotationParseError] if the move isn't valid algebraic notation in this mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::parse::{MoveCommand, ParseMode};
+    /// assert!(MoveCommand::parse("Nf3", ParseMode::Strict).is_ok());
+    /// assert!(MoveCommand::parse("nf3", ParseMode::Strict).is_err());
+    /// assert!(MoveCommand::parse("nf3", ParseMode::Lenient).is_ok());
+    /// ```
+    pub fn parse(s: &str, mode: ParseMode) -> Result<Self, NotationParseError>
+    {
+        Self::parse_with_check_annotation(s, mode).map(|(move_command, _)| move_command)
+    }
+
+    /// Parses a move in algebraic notation like [MoveCommand::parse] does, and also returns the
+    /// check or checkmate suffix it was written with, if it had one.
+    ///
+    /// The suffix is only recorded here, not checked. To make sure it matches what the move
+    /// actually does, see [Board::attempt_move_san_strict](crate::board::Board::attempt_move_san_strict).
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The move, i.e `"e4"`, `"Qxf7#"` or `"O-O+"`.
+    /// * `mode` - How closely the move has to follow standard algebraic notation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [NotationParseError] if the move (without its suffix) isn't valid algebraic
+    /// notation in this mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::parse::{CheckAnnotation, MoveCommand, ParseMode};
+    /// let (move_command, check_annotation) = MoveCommand::parse_with_check_annotation("Qxf7#", ParseMode::Strict).unwrap();
+    /// assert!(move_command.get_move_data().unwrap().capture());
+    /// assert_eq!(check_annotation, Some(CheckAnnotation::Checkmate));
+    ///
+    /// let (move_command, check_annotation) = MoveCommand::parse_with_check_annotation("O-O+", ParseMode::Strict).unwrap();
+    /// assert!(move_command.is_kingside_castle());
+    /// assert_eq!(check_annotation, Some(CheckAnnotation::Check));
+    /// ```
+    pub fn parse_with_check_annotation(s: &str, mode: ParseMode) -> Result<(Self, Option<CheckAnnotation>), NotationParseError>
+    {
+        let s = match mode
+        {
+            ParseMode::Strict => s,
+            ParseMode::Lenient => s.trim(),
+        };
+        let (s, check_annotation) = match s.chars().last()
+        {
+            Some('+') => (&s[..s.len() - 1], Some(CheckAnnotation::Check)),
+            Some('#') => (&s[..s.len() - 1], Some(CheckAnnotation::Checkmate)),
+            _ => (s, None),
+        };
+        let move_command = match mode
+        {
+            ParseMode::Strict => match s
+            {
+                "O-O" => Self::KingsideCastle,
+                "O-O-O" => Self::QueensideCastle,
+                _ => Self::NormalMove(MoveCommandData::parse(s, mode)?),
+            },
+            // Some programs (and people) write castling with zeroes.
+            ParseMode::Lenient => match &s.to_lowercase().replace('0', "o") as &str
+            {
+                "o-o" => Self::KingsideCastle,
+                "o-o-o" => Self::QueensideCastle,
+                _ => Self::NormalMove(MoveCommandData::parse(s, mode)?),
+            },
+        };
+        Ok((move_command, check_annotation))
+    }
+}
+
+/// How closely a move has to follow standard algebraic notation to be parsed, see
+/// [MoveCommand::parse].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode
+{
+    /// Only accept standard algebraic notation exactly as it's written in a PGN file: piece
+    /// letters are upper case and files are lower case (so `bxc6` is always a pawn capture and
+    /// `Bxc6` is always a bishop move), promotions are written with an `=`, castling is written
+    /// with capital letter O's and there's nothing else before or after the move.
+    Strict,
+    /// Accept the kinds of mistakes people make typing moves in: any case, surrounding
+    /// whitespace, a promotion without the `=`, and castling with zeroes. A move starting with a
+    /// lower case `b` is read as a pawn on the b file if it captures onto the a or c file (like
+    /// `bxc6`) or promotes, and as a bishop move otherwise. The board isn't consulted, so `bxc6`
+    /// is a pawn capture even if a bishop could take on c6 and no pawn can.
+    #[default]
+    Lenient,
+}
+
+/// The suffix a move can be written with in algebraic notation to say that it gives check or
+/// checkmate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckAnnotation
+{
+    /// The move gives check, written as a `+`.
+    Check,
+    /// The move gives checkmate, written as a `#`.
+    Checkmate,
 }
 
 /// Represents the information included with what we might consider to be a 
@@ -110,10 +224,13 @@ impl MoveCommand
 /// - Capture is false
 /// - Target square is a3.
 ///
-/// There are some standard notation elements that are ignored: namely it is common
-/// to indicate whether a move placed a king in check by suffixing the move with a "+".
-/// This doesn't actually add any new information, however, so is ignored in our parser
-/// as it might make it harder for beginners to input moves.
+/// Pawn promotions name the piece the pawn turns into after the target square, i.e e8=Q (or just
+/// e8Q) promotes the pawn on e7 to a queen.
+///
+/// It is common to indicate whether a move placed a king in check by suffixing the move with a
+/// "+", or checkmate with a "#". This doesn't actually add any new information about the move,
+/// so it's optional and isn't part of the [MoveCommandData], see
+/// [MoveCommand::parse_with_check_annotation].
 #[derive(Debug, Copy, Clone, CopyGetters)]
 #[getset(get_copy="pub")]
 pub struct MoveCommandData
@@ -127,30 +244,108 @@ pub struct MoveCommandData
     capture: bool,
     /// The destination square.
     target_square: Square,
+    /// The piece a pawn promotes to, if one was given.
+    promotion: Option<PieceType>,
 }
 
 impl FromStr for MoveCommand
 {
     type Err = NotationParseError;
 
+    /// Parses a move in algebraic notation in [ParseMode::Lenient]. A check or checkmate suffix
+    /// is allowed, but thrown away, see [MoveCommand::parse_with_check_annotation] to keep it.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let new_s = s.trim().to_lowercase();
-        match &new_s as &str
-        {
-            "o-o" => Ok(Self::KingsideCastle),
-            "o-o-o" => Ok(Self::QueensideCastle),
-            other => Ok(Self::NormalMove(MoveCommandData::from_str(other)?)),
-        }
+        Self::parse(s, ParseMode::Lenient)
     }
 }
 
-impl FromStr for MoveCommandData
+impl MoveCommandData
 {
-    type Err = NotationParseError;
+    /// Parses a normal (not castling) move in algebraic notation, following the rules of the
+    /// given [ParseMode]. Unlike [MoveCommand::parse], a check or checkmate suffix isn't allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The move, i.e `"e4"`, `"Nbd2"` or `"exd8=Q"`.
+    /// * `mode` - How closely the move has to follow standard algebraic notation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [NotationParseError::InvalidFormat] if the move isn't valid algebraic notation in
+    /// this mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Line, PieceType};
+    /// # use rust_chess_engine::parse::{MoveCommandData, ParseMode};
+    /// let pawn_capture = MoveCommandData::parse("bxc6", ParseMode::Strict).unwrap();
+    /// assert_eq!(pawn_capture.piece_type(), PieceType::Pawn);
+    /// assert_eq!(pawn_capture.discriminant(), Some(Line::File(1)));
+    /// let bishop_capture = MoveCommandData::parse("Bxc6", ParseMode::Strict).unwrap();
+    /// assert_eq!(bishop_capture.piece_type(), PieceType::Bishop);
+    ///
+    /// assert!(MoveCommandData::parse("e8Q", ParseMode::Strict).is_err());
+    /// assert!(MoveCommandData::parse("e8Q", ParseMode::Lenient).is_ok());
+    /// ```
+    pub fn parse(s: &str, mode: ParseMode) -> Result<Self, NotationParseError>
+    {
+        match mode
+        {
+            ParseMode::Strict => Self::parse_strict(s),
+            ParseMode::Lenient => Self::parse_lenient(s),
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err>
+    fn parse_strict(s: &str) -> Result<Self, NotationParseError>
     {
-        let re = RegexBuilder::new(r"(?<piece>[nbrqk]?)(?<discriminant>[a-h]?[0-9]?)?(?<capture>x?)(?<destination>[a-h][0-9])")
+        let re = Regex::new(r"^(?<piece>[NBRQK]?)(?<discriminant>[a-h]?[1-8]?)(?<capture>x?)(?<destination>[a-h][1-8])(=(?<promotion>[NBRQ]))?$")
+            .expect("Invalid regex");
+        let invalid_format = || NotationParseError::InvalidFormat(s.to_string());
+        let captures = re.captures(s).ok_or_else(invalid_format)?;
+
+        let piece_type = PieceType::from_str(captures.name("piece").unwrap().as_str())?;
+        let discriminant = captures.name("discriminant").unwrap().as_str();
+        let capture = !captures.name("capture").unwrap().is_empty();
+        let target_square = Square::from_str(captures.name("destination").unwrap().as_str())?;
+        let promotion = captures.name("promotion").map(|promotion| PieceType::from_str(promotion.as_str())).transpose()?;
+
+        // Pawns are written differently from the other pieces: a capture always names the file
+        // the pawn came from and nothing else, a move never names it, and only pawns promote.
+        let well_formed = match piece_type
+        {
+            PieceType::Pawn if capture => discriminant.len() == 1 && discriminant.chars().all(|c| c.is_ascii_lowercase()),
+            PieceType::Pawn => discriminant.is_empty(),
+            _ => promotion.is_none(),
+        };
+        if !well_formed
+        {
+            return Err(invalid_format());
+        }
+        let discriminant = match discriminant.is_empty()
+        {
+            true => None,
+            false => Some(Line::from_str(discriminant)?),
+        };
+
+        Ok(Self
+        {
+            piece_type,
+            discriminant,
+            capture,
+            target_square,
+            promotion,
+        })
+    }
+
+    fn parse_lenient(s: &str) -> Result<Self, NotationParseError>
+    {
+        let s = s.trim();
+        // A lowercase b could be a bishop or the file of a pawn capture, so remember what it was
+        // before the case is gone.
+        let starts_with_lowercase_b = s.starts_with('b');
+        let s = &s.to_lowercase() as &str;
+        let re = RegexBuilder::new(r"^(?<piece>[nbrqk]?)(?<discriminant>[a-h]?[1-8]?)(?<capture>x?)(?<destination>[a-h][1-8])(=?(?<promotion>[nbrq]))?$")
             .case_insensitive(true)
             .build()
             .expect("Invalid regex");
@@ -167,6 +362,7 @@ impl FromStr for MoveCommandData
         let discriminant = captures.name("discriminant").unwrap().as_str();
         let capture = !captures.name("capture").unwrap().is_empty();
         let destination = captures.name("destination").unwrap().as_str();
+        let promotion = captures.name("promotion").map(|promotion| promotion.as_str());
 
         let piece_type = PieceType::from_str(piece_type)?;
         let discriminant: Option<Line> = match discriminant.is_empty()
@@ -175,6 +371,17 @@ impl FromStr for MoveCommandData
             false => Some(Line::from_str(discriminant)?),
         };
         let destination = Square::from_str(destination)?;
+        let promotion = promotion.map(PieceType::from_str).transpose()?;
+        // Once the case is gone, bxa8=Q looks like a bishop move. Only pawns promote though, so
+        // the b has to be the file the pawn is on. The same goes for a lowercase bxa3 or bxc3,
+        // which is how a pawn capture from the b file is written; bishops are always a capital B.
+        let pawn_capture_from_b_file = starts_with_lowercase_b && capture && matches!(destination.file, 0 | 2);
+        let (piece_type, discriminant) = match (piece_type, discriminant, promotion)
+        {
+            (PieceType::Bishop, None, Some(_)) => (PieceType::Pawn, Some(Line::File(1))),
+            (PieceType::Bishop, None, None) if pawn_capture_from_b_file => (PieceType::Pawn, Some(Line::File(1))),
+            _ => (piece_type, discriminant),
+        };
 
         Ok(Self
         {
@@ -182,10 +389,22 @@ impl FromStr for MoveCommandData
             discriminant,
             capture,
             target_square: destination,
+            promotion,
         })
     }
 }
 
+impl FromStr for MoveCommandData
+{
+    type Err = NotationParseError;
+
+    /// Parses a normal move in [ParseMode::Lenient], see [MoveCommandData::parse].
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        Self::parse(s, ParseMode::Lenient)
+    }
+}
+
 #[cfg(test)]
 mod tests
 {
@@ -313,5 +532,98 @@ mod tests
         assert!(move_data.capture);
         assert_eq!(Line::RankAndFile(3, 7), move_data.discriminant.unwrap());
     }
-}
 
+    #[test]
+    fn test_promotion()
+    {
+        let move_data = MoveCommand::from_str("e8=Q").unwrap().get_move_data().unwrap();
+        assert_eq!(PieceType::Pawn, move_data.piece_type);
+        assert_eq!(Square::new(7, 4), move_data.target_square);
+        assert_eq!(Some(PieceType::Queen), move_data.promotion);
+
+        let move_data = MoveCommand::from_str("dxe1n").unwrap().get_move_data().unwrap();
+        assert!(move_data.capture);
+        assert_eq!(Some(PieceType::Knight), move_data.promotion);
+
+        let move_data = MoveCommand::from_str("bxa8=Q").unwrap().get_move_data().unwrap();
+        assert_eq!(PieceType::Pawn, move_data.piece_type);
+        assert_eq!(Some(Line::File(1)), move_data.discriminant);
+
+        let move_data = MoveCommand::from_str("e4").unwrap().get_move_data().unwrap();
+        assert!(move_data.promotion.is_none());
+    }
+
+    #[test]
+    fn lowercase_b_captures_are_pawn_moves()
+    {
+        let move_data = MoveCommand::from_str("bxa3").unwrap().get_move_data().unwrap();
+        assert_eq!(PieceType::Pawn, move_data.piece_type);
+        assert_eq!(Some(Line::File(1)), move_data.discriminant);
+
+        let move_data = MoveCommand::from_str("Bxa3").unwrap().get_move_data().unwrap();
+        assert_eq!(PieceType::Bishop, move_data.piece_type);
+        assert!(move_data.discriminant.is_none());
+
+        // A b pawn can't capture onto the e file, so this has to be a bishop.
+        let move_data = MoveCommand::from_str("bxe5").unwrap().get_move_data().unwrap();
+        assert_eq!(PieceType::Bishop, move_data.piece_type);
+    }
+
+    #[test]
+    fn check_suffixes_are_recorded()
+    {
+        let (move_command, check_annotation) = MoveCommand::parse_with_check_annotation("Nf3", ParseMode::Strict).unwrap();
+        assert_eq!(Square::new(2, 5), move_command.get_move_data().unwrap().target_square);
+        assert_eq!(None, check_annotation);
+
+        let (move_command, check_annotation) = MoveCommand::parse_with_check_annotation("e8=Q+", ParseMode::Strict).unwrap();
+        assert_eq!(Some(PieceType::Queen), move_command.get_move_data().unwrap().promotion);
+        assert_eq!(Some(CheckAnnotation::Check), check_annotation);
+
+        let (move_command, check_annotation) = MoveCommand::parse_with_check_annotation("O-O-O#", ParseMode::Lenient).unwrap();
+        assert!(move_command.is_queenside_castle());
+        assert_eq!(Some(CheckAnnotation::Checkmate), check_annotation);
+
+        assert!(MoveCommand::from_str("O-O+").unwrap().is_kingside_castle());
+        assert!(MoveCommand::from_str("+").is_err());
+    }
+
+    #[test]
+    fn strict_mode_tells_pawns_and_bishops_apart_by_case()
+    {
+        let pawn_capture = MoveCommandData::parse("bxc6", ParseMode::Strict).unwrap();
+        assert_eq!(PieceType::Pawn, pawn_capture.piece_type);
+        assert_eq!(Some(Line::File(1)), pawn_capture.discriminant);
+        // Unlike lenient mode, this holds even when no pawn could make the capture.
+        assert_eq!(PieceType::Pawn, MoveCommandData::parse("bxe5", ParseMode::Strict).unwrap().piece_type);
+        assert_eq!(PieceType::Bishop, MoveCommandData::parse("Bxe5", ParseMode::Strict).unwrap().piece_type);
+        assert_eq!(PieceType::Bishop, MoveCommandData::parse("Bb5", ParseMode::Strict).unwrap().piece_type);
+        assert!(MoveCommandData::parse("bb5", ParseMode::Strict).is_err());
+    }
+
+    #[test]
+    fn strict_mode_rejects_anything_around_the_move()
+    {
+        for san in ["xxNf3", "Nf3xx", " Nf3", "Nf3 ", "nf3", "NF3", "e8Q", "e8=q", "Ne8=Q", "xd5", "e4xd5", "de4", "Pe4", "o-o", "0-0", "O-O++", "Nf3!"]
+        {
+            assert!(MoveCommand::parse(san, ParseMode::Strict).is_err(), "{san}");
+        }
+        for san in ["Nf3", "exd5", "e8=Q", "dxe8=N#", "Raxd1", "Qh4e1", "O-O", "O-O-O+"]
+        {
+            assert!(MoveCommand::parse(san, ParseMode::Strict).is_ok(), "{san}");
+        }
+    }
+
+    #[test]
+    fn lenient_mode_forgives_typing_mistakes_but_not_junk()
+    {
+        for san in [" nf3 ", "NF3", "e8q", "E8=Q", "0-0", "0-0-0+", "o-o"]
+        {
+            assert!(MoveCommand::parse(san, ParseMode::Lenient).is_ok(), "{san}");
+        }
+        for san in ["xxNf3", "Nf3xx", "please Nf3", "e4 e5", "e9"]
+        {
+            assert!(MoveCommand::parse(san, ParseMode::Lenient).is_err(), "{san}");
+        }
+    }
+}
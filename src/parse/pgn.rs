@@ -0,0 +1,343 @@
+//! Reads games in [PGN](https://en.wikipedia.org/wiki/Portable_Game_Notation) (Portable Game
+//! Notation) format. See [write_pgn](super::write_pgn) for writing them.
+//!
+//! A PGN file is a list of games, each of which is a list of tag pairs like `[White "Carlsen"]`
+//! followed by the movetext, i.e the moves of the game in algebraic notation with move numbers,
+//! comments, variations, and finally the result:
+//!
+//! ```none
+//! [Event "Casual game"]
+//! [Result "1-0"]
+//!
+//! 1. e4 e5 2. Nf3 {A comment} Nc6 (2... d6) 3. Bb5 1-0
+//! ```
+//!
+//! Comments, variations, and NAGs (`$1`, etc.) are skipped, only the main line is kept.
+
+use std::{fmt::Display, str::FromStr};
+
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+use crate::board::{BoardResult, PlayerColor};
+
+use super::{MoveCommand, ParseMode, PgnParseError};
+
+/// The result of a game as recorded in PGN.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Serialize, Deserialize)]
+pub enum PgnResult
+{
+    /// `1-0`
+    WhiteWin,
+    /// `0-1`
+    BlackWin,
+    /// `1/2-1/2`
+    Draw,
+    /// `*`, the game is unfinished or the result is unknown.
+    #[default]
+    Unknown,
+}
+
+impl FromStr for PgnResult
+{
+    type Err = PgnParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+        {
+            "1-0" => Ok(Self::WhiteWin),
+            "0-1" => Ok(Self::BlackWin),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Unknown),
+            other => Err(PgnParseError::InvalidResult(other.to_string())),
+        }
+    }
+}
+
+impl Display for PgnResult
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match self
+        {
+            Self::WhiteWin => "1-0",
+            Self::BlackWin => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Unknown => "*",
+        };
+        write!(f, "{}", result)
+    }
+}
+
+impl From<BoardResult> for PgnResult
+{
+    /// Converts the result of a game on a [Board](crate::board::Board). A game that is still in
+    /// progress becomes [PgnResult::Unknown].
+    fn from(result: BoardResult) -> Self {
+        match result
+        {
+            BoardResult::Win(PlayerColor::White) | BoardResult::LossOnTime(PlayerColor::Black) => Self::WhiteWin,
+            BoardResult::Win(PlayerColor::Black) | BoardResult::LossOnTime(PlayerColor::White) => Self::BlackWin,
+            BoardResult::Draw(_) => Self::Draw,
+            BoardResult::InProgress => Self::Unknown,
+        }
+    }
+}
+
+/// A single game read from a PGN file.
+#[derive(Debug, Getters, Default)]
+#[getset(get="pub")]
+pub struct PgnGame
+{
+    /// The tag pairs of the game, in the order they appeared.
+    tags: Vec<(String, String)>,
+    /// The main line moves of the game.
+    moves: Vec<MoveCommand>,
+    /// The result of the game. This is taken from the game termination marker at the end of
+    /// the movetext, or the `Result` tag if the movetext doesn't have one.
+    result: PgnResult,
+}
+
+impl PgnGame
+{
+    /// Returns the value of the tag with the given name, if the game has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::parse::parse_pgn;
+    /// let games = parse_pgn("[White \"Morphy\"]\n\n1. e4 e5 *").unwrap();
+    /// assert_eq!(games[0].tag("White"), Some("Morphy"));
+    /// assert_eq!(games[0].tag("Black"), None);
+    /// ```
+    pub fn tag(&self, name: &str) -> Option<&str>
+    {
+        self.tags.iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parses every game in a PGN string.
+///
+/// # Arguments
+///
+/// * `input` - The contents of a PGN file. This may contain any number of games.
+///
+/// # Errors
+///
+/// Returns a [PgnParseError] if a tag pair, move, or result is malformed, or if a comment or
+/// variation is never closed.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::parse::{parse_pgn, PgnResult};
+/// let pgn = "[Event \"Casual game\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 {A comment} Nc6 (2... d6) 3. Bb5 1-0";
+/// let games = parse_pgn(pgn).unwrap();
+/// assert_eq!(games.len(), 1);
+/// assert_eq!(games[0].moves().len(), 5);
+/// assert_eq!(*games[0].result(), PgnResult::WhiteWin);
+/// ```
+pub fn parse_pgn(input: &str) -> Result<Vec<PgnGame>, PgnParseError>
+{
+    let mut games = Vec::new();
+    let mut current_game = PgnGame::default();
+    // Whether the current game has any movetext yet. A tag pair after movetext starts a new game.
+    let mut in_movetext = false;
+    // How deeply nested in variations we are. Moves are only kept at depth 0.
+    let mut variation_depth: i32 = 0;
+    let mut in_comment = false;
+
+    for line in input.lines()
+    {
+        let trimmed = line.trim();
+        if !in_comment && variation_depth == 0 && trimmed.starts_with('[')
+        {
+            if in_movetext
+            {
+                games.push(std::mem::take(&mut current_game));
+                in_movetext = false;
+            }
+            current_game.tags.push(parse_tag_pair(trimmed)?);
+            continue;
+        }
+        // Lines starting with % are escaped and ignored entirely.
+        if trimmed.starts_with('%')
+        {
+            continue;
+        }
+
+        for token in tokenize_movetext(line, &mut in_comment)
+        {
+            match token
+            {
+                "(" => variation_depth += 1,
+                ")" => variation_depth -= 1,
+                _ if variation_depth > 0 => (),
+                _ if token.starts_with('$') => (),
+                "1-0" | "0-1" | "1/2-1/2" | "*" =>
+                {
+                    current_game.result = PgnResult::from_str(token)?;
+                    games.push(std::mem::take(&mut current_game));
+                    in_movetext = false;
+                },
+                _ =>
+                {
+                    in_movetext = true;
+                    if let Some(san) = strip_move_number(token)
+                    {
+                        current_game.moves.push(parse_san(san)?);
+                    }
+                },
+            }
+            if variation_depth < 0
+            {
+                return Err(PgnParseError::UnbalancedVariation);
+            }
+        }
+    }
+
+    if in_comment
+    {
+        return Err(PgnParseError::UnterminatedComment);
+    }
+    if variation_depth != 0
+    {
+        return Err(PgnParseError::UnbalancedVariation);
+    }
+    // A game without a termination marker at the end of the file still counts.
+    if in_movetext || !current_game.tags.is_empty()
+    {
+        if let Some(result) = current_game.tag("Result")
+        {
+            current_game.result = PgnResult::from_str(result).unwrap_or_default();
+        }
+        games.push(current_game);
+    }
+    Ok(games)
+}
+
+/// Parses a tag pair like `[White "Carlsen"]` into its name and value.
+fn parse_tag_pair(line: &str) -> Result<(String, String), PgnParseError>
+{
+    let invalid = || PgnParseError::InvalidTagPair(line.to_string());
+    let inner = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')).ok_or_else(invalid)?;
+    let (name, value) = inner.split_once(' ').ok_or_else(invalid)?;
+    let value = value.trim().strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or_else(invalid)?;
+    Ok((name.to_string(), value.replace("\\\"", "\"")))
+}
+
+/// Splits a line of movetext into tokens, dropping comments. Parentheses are returned as their
+/// own tokens so variations can be tracked. `in_comment` carries `{}` comments across lines.
+fn tokenize_movetext<'a>(line: &'a str, in_comment: &mut bool) -> Vec<&'a str>
+{
+    let mut tokens = Vec::new();
+    let mut token_start = None;
+    for (index, character) in line.char_indices()
+    {
+        if *in_comment
+        {
+            if character == '}'
+            {
+                *in_comment = false;
+            }
+            continue;
+        }
+        let ends_token = character.is_whitespace() || matches!(character, '{' | '(' | ')' | ';');
+        if ends_token
+        {
+            if let Some(start) = token_start.take()
+            {
+                tokens.push(&line[start..index]);
+            }
+        }
+        match character
+        {
+            '{' => *in_comment = true,
+            // The rest of the line is a comment.
+            ';' => return tokens,
+            '(' | ')' => tokens.push(&line[index..index + 1]),
+            _ if character.is_whitespace() => (),
+            _ => { token_start.get_or_insert(index); },
+        }
+    }
+    if let Some(start) = token_start
+    {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Removes a leading move number like `12.` or `12...` from a token, returning [None] if the
+/// token was *only* a move number.
+fn strip_move_number(token: &str) -> Option<&str>
+{
+    let without_number = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    if without_number.len() == token.len()
+    {
+        return Some(token);
+    }
+    let without_dots = without_number.trim_start_matches('.');
+    match without_dots.is_empty()
+    {
+        true => None,
+        false => Some(without_dots),
+    }
+}
+
+/// Parses a move in standard algebraic notation, ignoring check, mate and annotation suffixes.
+fn parse_san(san: &str) -> Result<MoveCommand, PgnParseError>
+{
+    // Lenient, since some programs write castling with zeroes.
+    MoveCommand::parse(san.trim_end_matches(['!', '?']), ParseMode::Lenient)
+        .map_err(|error| PgnParseError::InvalidMove(san.to_string(), error))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn parses_multiple_games()
+    {
+        let pgn = "[Event \"1\"]\n[Result \"0-1\"]\n\n1. d4 d5 0-1\n\n[Event \"2\"]\n\n1. e4 1/2-1/2\n";
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("1"));
+        assert_eq!(games[0].moves().len(), 2);
+        assert_eq!(*games[0].result(), PgnResult::BlackWin);
+        assert_eq!(games[1].tag("Event"), Some("2"));
+        assert_eq!(games[1].moves().len(), 1);
+        assert_eq!(*games[1].result(), PgnResult::Draw);
+    }
+
+    #[test]
+    fn skips_comments_variations_and_nags()
+    {
+        let pgn = "1. e4 $1 {best by test\nsays someone} e5 (1... c5 2. Nf3 (2. c3)) 2. Nf3; rest of line\nNc6 3.Bb5 a6 *";
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].moves().len(), 6);
+        assert_eq!(*games[0].result(), PgnResult::Unknown);
+    }
+
+    #[test]
+    fn game_without_termination_marker_uses_result_tag()
+    {
+        let games = parse_pgn("[Result \"1-0\"]\n\n1. e4 e5 2. O-O+").unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].moves().len(), 3);
+        assert!(games[0].moves()[2].is_kingside_castle());
+        assert_eq!(*games[0].result(), PgnResult::WhiteWin);
+    }
+
+    #[test]
+    fn malformed_input_is_an_error()
+    {
+        assert_eq!(parse_pgn("[Event Casual]").unwrap_err(), PgnParseError::InvalidTagPair("[Event Casual]".to_string()));
+        assert_eq!(parse_pgn("1. e4 {unfinished").unwrap_err(), PgnParseError::UnterminatedComment);
+        assert_eq!(parse_pgn("1. e4 (1. d4").unwrap_err(), PgnParseError::UnbalancedVariation);
+        assert!(matches!(parse_pgn("1. Zz9 *").unwrap_err(), PgnParseError::InvalidMove(_, _)));
+    }
+}
@@ -0,0 +1,274 @@
+//! Writes games in [PGN](https://en.wikipedia.org/wiki/Portable_Game_Notation) format, the
+//! counterpart to [parse_pgn](super::parse_pgn).
+//!
+//! Each move can carry a clock reading and an engine evaluation, which are written as the
+//! `[%clk H:MM:SS]` and `[%eval x.xx]` comment commands understood by lichess, chess.com and most
+//! other chess software:
+//!
+//! ```none
+//! [Result "*"]
+//!
+//! 1. e4 { [%eval 0.30] [%clk 0:05:00] } e5 { [%eval 0.25] [%clk 0:04:58] } *
+//! ```
+
+use std::{fmt::Write, time::Duration};
+
+use getset::CopyGetters;
+
+use crate::board::{ApplyMovesError, Board, Evaluation, Move, PlayerColor};
+
+use super::PgnResult;
+
+/// PGN readers are only required to handle lines up to 255 characters, but 80 is the convention.
+const MAX_LINE_LENGTH: usize = 79;
+
+/// A [Move] along with the annotations to write after it.
+#[derive(Debug, Clone, Copy, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct AnnotatedMove
+{
+    /// The move itself.
+    r#move: Move,
+    /// The time the player who moved had left on their clock after the move.
+    clock: Option<Duration>,
+    /// The engine's evaluation of the position after the move.
+    evaluation: Option<Evaluation>,
+}
+
+impl AnnotatedMove
+{
+    /// Creates an [AnnotatedMove] with no annotations.
+    ///
+    /// # Arguments
+    ///
+    /// * `r#move` - The move.
+    pub fn new(r#move: Move) -> Self
+    {
+        Self
+        {
+            r#move,
+            clock: None,
+            evaluation: None,
+        }
+    }
+
+    /// Adds the time the player had left on their clock after the move.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The remaining time.
+    pub fn with_clock(mut self, clock: Duration) -> Self
+    {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Adds the engine's evaluation of the position after the move.
+    ///
+    /// # Arguments
+    ///
+    /// * `evaluation` - The evaluation, from White's point of view.
+    pub fn with_evaluation(mut self, evaluation: Evaluation) -> Self
+    {
+        self.evaluation = Some(evaluation);
+        self
+    }
+}
+
+impl From<Move> for AnnotatedMove
+{
+    fn from(r#move: Move) -> Self {
+        Self::new(r#move)
+    }
+}
+
+/// Writes a single game as PGN.
+///
+/// The tag pairs are written in the order given, followed by a `Result` tag if there isn't one
+/// already. Moves are written in standard algebraic notation with their annotations as comments.
+///
+/// # Arguments
+///
+/// * `tags` - The tag pairs of the game, like `("White", "Carlsen")`.
+/// * `starting_board` - The position the game started from, used to number the moves and write
+///   them in algebraic notation.
+/// * `moves` - The moves of the game, in order.
+/// * `result` - The result of the game.
+///
+/// # Errors
+///
+/// Returns an [ApplyMovesError] if one of the moves can't be made.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rust_chess_engine::board::{Board, Evaluation};
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::{write_pgn, AnnotatedMove, PgnResult};
+/// let board = Board::new_default_starting_board();
+/// let e4 = GameState::new(board.clone()).legal_moves()[0];
+/// let annotated = AnnotatedMove::new(e4)
+///     .with_clock(Duration::from_secs(300))
+///     .with_evaluation(Evaluation::Score(0.3));
+/// let pgn = write_pgn(&[("Event".to_string(), "Casual game".to_string())], &board, &[annotated], PgnResult::Unknown).unwrap();
+/// assert!(pgn.contains("{ [%eval 0.30] [%clk 0:05:00] }"));
+/// ```
+pub fn write_pgn(tags: &[(String, String)], starting_board: &Board, moves: &[AnnotatedMove], result: PgnResult) -> Result<String, ApplyMovesError>
+{
+    let mut pgn = String::new();
+    for (name, value) in tags
+    {
+        // Writing to a String never fails.
+        let _ = writeln!(pgn, "[{} \"{}\"]", name, value.replace('"', "\\\""));
+    }
+    if !tags.iter().any(|(name, _)| name == "Result")
+    {
+        let _ = writeln!(pgn, "[Result \"{}\"]", result);
+    }
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    let mut board = starting_board.clone();
+    for (index, annotated_move) in moves.iter().enumerate()
+    {
+        let move_number = board.fullmove_number();
+        match board.active_color()
+        {
+            PlayerColor::White => tokens.push(format!("{}.", move_number)),
+            // A game starting with Black to move, or a move right after a comment, gets "N...".
+            PlayerColor::Black if index == 0 || has_comment(&moves[index - 1]) => tokens.push(format!("{}...", move_number)),
+            PlayerColor::Black => (),
+        }
+        tokens.push(board.to_san(&annotated_move.r#move));
+        if has_comment(annotated_move)
+        {
+            tokens.push(comment(annotated_move));
+        }
+        board = board.attempt_move(&annotated_move.r#move)
+            .map_err(|error| ApplyMovesError { index, error })?;
+    }
+    tokens.push(result.to_string());
+
+    let mut line_length = 0;
+    for token in tokens
+    {
+        if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH
+        {
+            pgn.push('\n');
+            line_length = 0;
+        }
+        if line_length > 0
+        {
+            pgn.push(' ');
+            line_length += 1;
+        }
+        line_length += token.len();
+        pgn.push_str(&token);
+    }
+    pgn.push('\n');
+    Ok(pgn)
+}
+
+fn has_comment(annotated_move: &AnnotatedMove) -> bool
+{
+    annotated_move.clock.is_some() || annotated_move.evaluation.is_some()
+}
+
+/// Writes the `{ [%eval ...] [%clk ...] }` comment for a move.
+fn comment(annotated_move: &AnnotatedMove) -> String
+{
+    let mut commands = Vec::new();
+    if let Some(evaluation) = annotated_move.evaluation
+    {
+        commands.push(format!("[%eval {}]", format_evaluation(evaluation)));
+    }
+    if let Some(clock) = annotated_move.clock
+    {
+        commands.push(format!("[%clk {}]", format_clock(clock)));
+    }
+    format!("{{ {} }}", commands.join(" "))
+}
+
+/// Formats an evaluation in pawns from White's point of view, or as `#N`/`#-N` for a forced mate.
+fn format_evaluation(evaluation: Evaluation) -> String
+{
+    match evaluation
+    {
+        Evaluation::Score(score) => format!("{:.2}", score),
+        Evaluation::Draw => "0.00".to_string(),
+        Evaluation::WhiteCheckmateIn(moves) => format!("#{}", moves),
+        Evaluation::BlackCheckmateIn(moves) => format!("#-{}", moves),
+        Evaluation::WhiteWin => "#0".to_string(),
+        Evaluation::BlackWin => "#-0".to_string(),
+        // There's no notation for a win without a mate distance, so use a score no position
+        // could reach on material.
+        Evaluation::WhiteTablebaseWin => "100.00".to_string(),
+        Evaluation::BlackTablebaseWin => "-100.00".to_string(),
+    }
+}
+
+/// Formats a clock reading as `H:MM:SS`, rounding down to the second.
+fn format_clock(clock: Duration) -> String
+{
+    let seconds = clock.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{game::GameState, parse::parse_pgn};
+
+    use super::*;
+
+    #[test]
+    fn clock_is_formatted_as_hours_minutes_seconds()
+    {
+        assert_eq!(format_clock(Duration::from_millis(3_723_900)), "1:02:03");
+        assert_eq!(format_clock(Duration::from_secs(59)), "0:00:59");
+    }
+
+    #[test]
+    fn mates_are_formatted_with_a_hash()
+    {
+        assert_eq!(format_evaluation(Evaluation::WhiteCheckmateIn(3)), "#3");
+        assert_eq!(format_evaluation(Evaluation::BlackCheckmateIn(2)), "#-2");
+        assert_eq!(format_evaluation(Evaluation::Score(-1.234)), "-1.23");
+    }
+
+    #[test]
+    fn black_moves_after_comments_are_numbered()
+    {
+        let mut game_state = GameState::new(Board::new_default_starting_board());
+        let mut moves = Vec::new();
+        for _ in 0..2
+        {
+            let r#move = game_state.legal_moves()[0];
+            moves.push(AnnotatedMove::new(r#move).with_clock(Duration::from_secs(60)));
+            game_state = game_state.update(&r#move).unwrap();
+        }
+        let pgn = write_pgn(&[], &Board::new_default_starting_board(), &moves, PgnResult::Draw).unwrap();
+        assert!(pgn.contains("} 1... "));
+        assert!(pgn.starts_with("[Result \"1/2-1/2\"]"));
+    }
+
+    #[test]
+    fn written_games_can_be_read_back()
+    {
+        let mut game_state = GameState::new(Board::new_default_starting_board());
+        let mut moves = Vec::new();
+        // Always playing the first legal move ends in a promotion with mate after 24 plies.
+        while moves.len() < 30
+        {
+            let Some(&r#move) = game_state.legal_moves().first() else { break };
+            moves.push(AnnotatedMove::new(r#move).with_evaluation(Evaluation::Score(0.1)));
+            game_state = game_state.update(&r#move).unwrap();
+        }
+        let pgn = write_pgn(&[], &Board::new_default_starting_board(), &moves, PgnResult::Unknown).unwrap();
+        assert!(pgn.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games[0].moves().len(), moves.len());
+        assert!(pgn.contains("=Q#"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Specifies the [UciMove] type.
+
+use std::{fmt::Display, str::FromStr};
+
+use getset::CopyGetters;
+use regex::RegexBuilder;
+
+use crate::board::{CastlingDirection, Move, PieceType, PlayerColor, Square};
+
+use super::NotationParseError;
+
+/// A move in the coordinate notation used by the
+/// [UCI protocol](https://www.chessprogramming.org/UCI) (sometimes called long algebraic
+/// notation): the starting square, the target square, and the piece a pawn promotes to if it
+/// does, i.e `e2e4` or `e7e8q`. Castling is written as the king moving two squares, i.e `e1g1`.
+///
+/// Unlike a [MoveCommand](super::MoveCommand), a [UciMove] already says exactly which piece moves
+/// where, so it doesn't need a discriminant or a capture marker. It still has to be checked against
+/// a board to turn it into a [Move], see [Board::get_uci_move](crate::board::Board::get_uci_move).
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{PieceType, Square};
+/// # use rust_chess_engine::parse::UciMove;
+/// let uci_move = UciMove::from_str("e7e8q").unwrap();
+/// assert_eq!(uci_move.starting_square(), Square::from_str("e7").unwrap());
+/// assert_eq!(uci_move.target_square(), Square::from_str("e8").unwrap());
+/// assert_eq!(uci_move.promotion(), Some(PieceType::Queen));
+/// assert_eq!(uci_move.to_string(), "e7e8q");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct UciMove
+{
+    /// The square the piece moves from.
+    starting_square: Square,
+    /// The square the piece moves to.
+    target_square: Square,
+    /// The piece a pawn promotes to, if it does.
+    promotion: Option<PieceType>,
+}
+
+impl UciMove
+{
+    /// Creates a new [UciMove].
+    ///
+    /// # Arguments
+    ///
+    /// * `starting_square` - The square the piece moves from.
+    /// * `target_square` - The square the piece moves to.
+    /// * `promotion` - The piece a pawn promotes to, if it does.
+    pub fn new(starting_square: Square, target_square: Square, promotion: Option<PieceType>) -> Self
+    {
+        Self
+        {
+            starting_square,
+            target_square,
+            promotion,
+        }
+    }
+
+    /// Writes a [Move] in coordinate notation, the reverse of
+    /// [Board::get_uci_move](crate::board::Board::get_uci_move).
+    ///
+    /// # Arguments
+    ///
+    /// * `r#move` - The move to write.
+    /// * `moving_color` - The color of the player making the move, which says which rank a castle
+    ///   happens on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{CastlingDirection, Move, PlayerColor};
+    /// # use rust_chess_engine::parse::UciMove;
+    /// let castle = Move::Castle(CastlingDirection::Queenside);
+    /// assert_eq!(UciMove::from_move(&castle, PlayerColor::Black).to_string(), "e8c8");
+    /// ```
+    pub fn from_move(r#move: &Move, moving_color: PlayerColor) -> Self
+    {
+        match r#move
+        {
+            Move::NormalMove(move_data) => Self::new(move_data.starting_square(), move_data.target_square(), move_data.promotion()),
+            Move::Castle(direction) =>
+            {
+                let rank = moving_color.home_rank();
+                let king_to_file = match direction
+                {
+                    CastlingDirection::Kingside => 6,
+                    CastlingDirection::Queenside => 2,
+                };
+                Self::new(Square::new(rank, 4), Square::new(rank, king_to_file), None)
+            },
+        }
+    }
+}
+
+impl FromStr for UciMove
+{
+    type Err = NotationParseError;
+
+    /// Parses a move in coordinate notation, i.e `e2e4` or `e7e8q`. Upper case letters are
+    /// accepted too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [NotationParseError::InvalidFormat] if the string isn't two squares on the board
+    /// followed by an optional promotion piece, or [NotationParseError::InvalidPieceCharacter] if
+    /// the promotion piece is a pawn.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let re = RegexBuilder::new(r"^([a-h][1-8])([a-h][1-8])([nbrqkp])?$")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let s = s.trim();
+        let captures = re.captures(s).ok_or_else(|| NotationParseError::InvalidFormat(s.to_string()))?;
+        let starting_square = Square::from_str(captures.get(1).unwrap().as_str())?;
+        let target_square = Square::from_str(captures.get(2).unwrap().as_str())?;
+        let promotion = match captures.get(3).map(|promotion| promotion.as_str())
+        {
+            // A blank string would parse as a pawn, so this has to be checked first.
+            None => None,
+            Some(promotion) => match PieceType::from_str(promotion)?
+            {
+                PieceType::Pawn => return Err(NotationParseError::InvalidPieceCharacter(promotion.to_string())),
+                piece_type => Some(piece_type),
+            },
+        };
+        Ok(Self::new(starting_square, target_square, promotion))
+    }
+}
+
+impl Display for UciMove
+{
+    /// Writes the move in coordinate notation, with the promotion piece in lower case.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.starting_square, self.target_square)?;
+        match self.promotion
+        {
+            Some(PieceType::Knight) => write!(f, "n"),
+            Some(PieceType::Bishop) => write!(f, "b"),
+            Some(PieceType::Rook) => write!(f, "r"),
+            Some(PieceType::Queen) => write!(f, "q"),
+            Some(PieceType::King) => write!(f, "k"),
+            Some(PieceType::Pawn) => write!(f, "p"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn parses_and_writes_moves()
+    {
+        for uci_move in ["e2e4", "g1f3", "a7a8n", "h2h1q", "e1g1"]
+        {
+            assert_eq!(UciMove::from_str(uci_move).unwrap().to_string(), uci_move);
+        }
+        assert_eq!(UciMove::from_str("E7E8Q").unwrap().to_string(), "e7e8q");
+    }
+
+    #[test]
+    fn rejects_malformed_moves()
+    {
+        for uci_move in ["", "e2", "e2e", "e2e9", "i2i4", "e2e4x", "e7e8p", "Nf3", "e2-e4"]
+        {
+            assert!(UciMove::from_str(uci_move).is_err(), "{uci_move}");
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Defines the [Rules] trait, which lets a [GameState] be played under the rules of a chess
+//! variant instead of standard chess, and implements the variants this crate knows about.
+
+use std::sync::Arc;
+
+use crate::{board::{Board, BoardResult, Move, MoveError, PieceType, PROMOTION_PIECE_TYPES}, game::GameState};
+
+mod king_of_the_hill;
+mod three_check;
+mod antichess;
+
+pub use king_of_the_hill::KingOfTheHill;
+pub use three_check::ThreeCheck;
+pub use antichess::Antichess;
+
+/// The rules a game is played by.
+///
+/// Most variants are standard chess with a few changes: some moves aren't allowed, pawns promote
+/// to different pieces, or there are extra ways to win. A [Rules] implementation only describes
+/// those changes, and the [Board]'s own move generator does the rest. A [GameState] asks its
+/// rules for the legal moves and whether the game is over, and checks every move against them
+/// before making it, so any [Agent](crate::agent::Agent) that picks from
+/// [GameState::legal_moves] plays the variant correctly.
+///
+/// Every hook defaults to standard chess.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::{Board, Move, PieceType};
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::rules::Rules;
+/// /// Standard chess, but pawns can only promote to queens.
+/// struct QueensOnly;
+///
+/// impl Rules for QueensOnly
+/// {
+///     fn name(&self) -> &'static str {
+///         "Queens only"
+///     }
+///
+///     fn promotion_piece_types(&self) -> &[PieceType] {
+///         &[PieceType::Queen]
+///     }
+/// }
+///
+/// let game_state = GameState::with_rules(Board::new_default_starting_board(), QueensOnly);
+/// assert_eq!(game_state.rules().name(), "Queens only");
+/// ```
+pub trait Rules: Send + Sync
+{
+    /// Returns the name of the variant, as used in the PGN `Variant` tag. See [rules_by_name].
+    fn name(&self) -> &'static str;
+
+    /// Returns true if the variant allows a move that's legal in standard chess. Moves that
+    /// aren't legal in standard chess are never asked about. Allows everything by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board the move would be made on.
+    /// * `r#move` - The move to check.
+    fn allows_move(&self, board: &Board, r#move: &Move) -> bool
+    {
+        let _ = (board, r#move);
+        true
+    }
+
+    /// Returns the pieces a pawn can promote to. Defaults to a knight, bishop, rook or queen.
+    fn promotion_piece_types(&self) -> &[PieceType]
+    {
+        &PROMOTION_PIECE_TYPES
+    }
+
+    /// Returns every legal move the side to move has on `board`. By default, these are the
+    /// standard legal moves the variant [allows](Self::allows_move), promoting only to the
+    /// [allowed pieces](Self::promotion_piece_types).
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board to generate moves for.
+    fn legal_moves(&self, board: &Board) -> Vec<Move>
+    {
+        let mut moves = board.generate_moves_for_side(board.active_color());
+        moves.retain(|r#move| promotion_allowed(self.promotion_piece_types(), r#move) && self.allows_move(board, r#move));
+        moves
+    }
+
+    /// Checks that a move is legal on `board`. This should agree with [Self::legal_moves], but
+    /// only has to look at the one move.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board the move would be made on.
+    /// * `r#move` - The move to check.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [MoveError] if the move isn't legal in standard chess (see [Board::is_legal]),
+    /// [MoveError::InvalidPromotion] if it promotes to a piece the variant doesn't allow, or
+    /// [MoveError::NotAllowedByRules] if the variant doesn't [allow](Self::allows_move) it.
+    fn check_move(&self, board: &Board, r#move: &Move) -> Result<(), MoveError>
+    {
+        board.is_legal(r#move)?;
+        if !promotion_allowed(self.promotion_piece_types(), r#move)
+        {
+            return Err(MoveError::InvalidPromotion);
+        }
+        match self.allows_move(board, r#move)
+        {
+            true => Ok(()),
+            false => Err(MoveError::NotAllowedByRules),
+        }
+    }
+
+    /// Makes a move that [Self::check_move] allows, and returns the board after it. Defaults to
+    /// [Board::attempt_move].
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The board to make the move on.
+    /// * `r#move` - The move to make.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [MoveError] if the move can't be made on `board`.
+    fn make_move(&self, board: &Board, r#move: &Move) -> Result<Board, MoveError>
+    {
+        board.attempt_move(r#move)
+    }
+
+    /// Returns how the game ended if it ended by one of the variant's own rules, or [None] to
+    /// leave it to the standard rules (checkmate, stalemate, repetition and so on). Never ends
+    /// the game by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game so far.
+    fn variant_result(&self, game_state: &GameState) -> Option<BoardResult>
+    {
+        let _ = game_state;
+        None
+    }
+
+    /// Returns a line about the state of the game that the board doesn't show, i.e how many
+    /// checks each side has given, for showing alongside the board. Returns [None] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game so far.
+    fn status(&self, game_state: &GameState) -> Option<String>
+    {
+        let _ = game_state;
+        None
+    }
+
+    /// Returns extra tag pairs to write when the game is saved as PGN, after the `Variant` tag.
+    /// Returns none by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `game_state` - The game so far.
+    fn pgn_tags(&self, game_state: &GameState) -> Vec<(String, String)>
+    {
+        let _ = game_state;
+        Vec::new()
+    }
+}
+
+/// The rules of standard chess, which every [GameState] is played by unless it's given other
+/// [Rules].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StandardRules;
+
+impl Rules for StandardRules
+{
+    fn name(&self) -> &'static str {
+        "Standard"
+    }
+}
+
+/// Returns the built-in [Rules] with the given [name](Rules::name), or [None] if there aren't
+/// any. This is how a game's variant is found again when it's loaded from PGN or a saved game.
+///
+/// # Arguments
+///
+/// * `name` - The name of the variant, i.e `"King of the Hill"`.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::rules::rules_by_name;
+/// assert_eq!(rules_by_name("Standard").unwrap().name(), "Standard");
+/// assert!(rules_by_name("Calvinball").is_none());
+/// ```
+pub fn rules_by_name(name: &str) -> Option<Arc<dyn Rules>>
+{
+    let rules: [Arc<dyn Rules>; 4] = [Arc::new(StandardRules), Arc::new(KingOfTheHill), Arc::new(ThreeCheck), Arc::new(Antichess)];
+    rules.into_iter().find(|rules| rules.name() == name)
+}
+
+/// Returns true if `r#move` isn't a promotion, or promotes to one of `promotion_piece_types`.
+fn promotion_allowed(promotion_piece_types: &[PieceType], r#move: &Move) -> bool
+{
+    match r#move
+    {
+        Move::NormalMove(move_data) => move_data.promotion().is_none_or(|piece_type| promotion_piece_types.contains(&piece_type)),
+        Move::Castle(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::BoardConfiguration, parse::MoveCommand};
+
+    use super::*;
+
+    struct NoKnightPromotions;
+
+    impl Rules for NoKnightPromotions
+    {
+        fn name(&self) -> &'static str {
+            "No knight promotions"
+        }
+
+        fn promotion_piece_types(&self) -> &[PieceType] {
+            &PROMOTION_PIECE_TYPES[..3]
+        }
+
+        fn allows_move(&self, board: &Board, r#move: &Move) -> bool {
+            // No castling either.
+            let _ = board;
+            !matches!(r#move, Move::Castle(_))
+        }
+    }
+
+    #[test]
+    fn variants_can_forbid_moves_and_promotions()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap());
+        let standard = StandardRules.legal_moves(&board);
+        let variant = NoKnightPromotions.legal_moves(&board);
+        // Knight promotions on b8, and O-O.
+        assert_eq!(standard.len() - variant.len(), 2);
+
+        let knight_promotion = board.get_move(&MoveCommand::from_str("b8=N").unwrap()).unwrap();
+        assert!(StandardRules.check_move(&board, &knight_promotion).is_ok());
+        assert!(matches!(NoKnightPromotions.check_move(&board, &knight_promotion), Err(MoveError::InvalidPromotion)));
+        let castle = board.get_move(&MoveCommand::from_str("O-O").unwrap()).unwrap();
+        assert!(matches!(NoKnightPromotions.check_move(&board, &castle), Err(MoveError::NotAllowedByRules)));
+    }
+}
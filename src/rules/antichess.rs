@@ -0,0 +1,167 @@
+//! Implements [Antichess], the variant where the aim is to lose all your pieces.
+
+use crate::{board::{Board, BoardResult, Move, MoveError, PieceType}, game::GameState};
+
+use super::Rules;
+
+/// The pieces a pawn can promote to in antichess, which includes the king.
+const ANTICHESS_PROMOTION_PIECE_TYPES: [PieceType; 5] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::King];
+
+/// Antichess (also called giveaway or losing chess): the first player to lose all their pieces,
+/// or to have no moves left, wins.
+///
+/// Capturing is compulsory: if a player can capture anything, they have to, although they can
+/// pick which capture to make. The king is just another piece, so there's no check or
+/// checkmate, the king can be captured, and pawns can promote to a king. There's no castling.
+/// Games are still drawn by repetition and the seventy-five-move rule, see
+/// [DrawRules](crate::board::DrawRules).
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::MoveCommand;
+/// # use rust_chess_engine::rules::Antichess;
+/// let mut game_state = GameState::with_rules(Board::new_default_starting_board(), Antichess);
+/// for r#move in ["e4", "d5"]
+/// {
+///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+///     game_state = game_state.update(&r#move).unwrap();
+/// }
+/// // White has to take on d5.
+/// assert_eq!(game_state.legal_moves().len(), 1);
+/// assert!(game_state.legal_move(&MoveCommand::from_str("Nf3").unwrap()).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Antichess;
+
+impl Rules for Antichess
+{
+    fn name(&self) -> &'static str {
+        "Antichess"
+    }
+
+    fn promotion_piece_types(&self) -> &[PieceType] {
+        &ANTICHESS_PROMOTION_PIECE_TYPES
+    }
+
+    fn legal_moves(&self, board: &Board) -> Vec<Move> {
+        let mut moves = board.generate_pseudo_legal_moves(self.promotion_piece_types());
+        if moves.iter().any(is_capture)
+        {
+            moves.retain(is_capture);
+        }
+        moves
+    }
+
+    fn check_move(&self, board: &Board, r#move: &Move) -> Result<(), MoveError> {
+        let moves = board.generate_pseudo_legal_moves(self.promotion_piece_types());
+        if !moves.contains(r#move)
+        {
+            return Err(MoveError::IllegalMove);
+        }
+        match !is_capture(r#move) && moves.iter().any(is_capture)
+        {
+            true => Err(MoveError::NotAllowedByRules),
+            false => Ok(()),
+        }
+    }
+
+    fn make_move(&self, board: &Board, r#move: &Move) -> Result<Board, MoveError> {
+        // The king can be left en prise, so the usual legality check doesn't apply.
+        let mut next_board = board.clone();
+        next_board.make_move_in_place(r#move)?;
+        Ok(next_board)
+    }
+
+    fn variant_result(&self, game_state: &GameState) -> Option<BoardResult> {
+        let board = game_state.current_board();
+        let result = if board.pieces_of_color(board.active_color()).is_empty() || game_state.legal_moves().is_empty()
+        {
+            BoardResult::Win(board.active_color())
+        }
+        else if let Some(reason) = game_state.draw_rules().automatic_draw(board.halfmove_clock(), game_state.repetition_count())
+        {
+            BoardResult::Draw(reason)
+        }
+        else
+        {
+            // Checkmate, stalemate and dead positions don't mean anything here, so the standard
+            // rules don't get a say.
+            BoardResult::InProgress
+        };
+        Some(result)
+    }
+}
+
+/// Returns true if `r#move` captures a piece.
+fn is_capture(r#move: &Move) -> bool
+{
+    matches!(r#move, Move::NormalMove(move_data) if move_data.capture())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::{BoardConfiguration, PlayerColor}, game::Termination, parse::MoveCommand};
+
+    use super::*;
+
+    fn game_state(fen: &str) -> GameState
+    {
+        GameState::with_rules(Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap()), Antichess)
+    }
+
+    fn play(game_state: &GameState, r#move: &str) -> GameState
+    {
+        let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+        game_state.update(&r#move).unwrap()
+    }
+
+    #[test]
+    fn kings_can_be_left_en_prise_and_captured()
+    {
+        // The white king stays on the rook's rank, which would be illegal in standard chess.
+        let game_state = play(&game_state("4k3/8/8/8/8/8/8/r1K5 w - - 0 1"), "Kd1");
+        // Taking it is Black's only capture, so it's Black's only move.
+        assert_eq!(game_state.legal_moves().len(), 1);
+        assert!(game_state.legal_move(&MoveCommand::from_str("Kd7").unwrap()).is_err());
+        let game_state = play(&game_state, "Rxd1");
+        assert_eq!(game_state.game_result(), BoardResult::Win(PlayerColor::White));
+    }
+
+    #[test]
+    fn losing_every_piece_wins()
+    {
+        let game_state = play(&game_state("8/8/8/8/8/8/1k6/K7 w - - 0 1"), "Kxb2");
+        assert_eq!(game_state.game_result(), BoardResult::Win(PlayerColor::Black));
+        assert_eq!(game_state.termination(), Some(Termination::VariantWin));
+    }
+
+    #[test]
+    fn being_stalemated_wins()
+    {
+        // Black's pawn is blocked, and it's Black's only piece.
+        let game_state = game_state("8/8/8/8/8/p7/P7/8 b - - 0 1");
+        assert_eq!(game_state.game_result(), BoardResult::Win(PlayerColor::Black));
+    }
+
+    #[test]
+    fn pawns_promote_to_kings()
+    {
+        let game_state = game_state("8/P7/8/8/8/8/8/7k w - - 0 1");
+        assert_eq!(game_state.legal_moves().len(), 5);
+        let promoted = game_state.legal_moves().iter().find(|r#move| matches!(r#move, Move::NormalMove(move_data) if move_data.promotion() == Some(PieceType::King))).unwrap();
+        let game_state = game_state.update(promoted).unwrap();
+        assert_eq!(game_state.current_board().pieces_of_type(PieceType::King).squares().count(), 2);
+    }
+}
@@ -0,0 +1,42 @@
+//! Implements [KingOfTheHill], the variant where getting your king to the centre wins.
+
+use crate::{bitboard::Bitboard, board::{BoardResult, PieceType, PlayerColor}, game::GameState};
+
+use super::Rules;
+
+/// The four centre squares, d4, e4, d5 and e5.
+const HILL: Bitboard = Bitboard::new(0x0000_0018_1800_0000);
+
+/// King of the Hill: standard chess, except a player also wins by getting their king onto one
+/// of the four centre squares (d4, e4, d5 or e5). The king still can't walk into check on the
+/// way there.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Board, BoardConfiguration, BoardResult, PlayerColor};
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::MoveCommand;
+/// # use rust_chess_engine::rules::KingOfTheHill;
+/// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/8/8/4K3/8/8 w - - 0 1").unwrap());
+/// let game_state = GameState::with_rules(board, KingOfTheHill);
+/// let ke4 = game_state.legal_move(&MoveCommand::from_str("Ke4").unwrap()).unwrap();
+/// assert_eq!(game_state.update(&ke4).unwrap().game_result(), BoardResult::Win(PlayerColor::White));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KingOfTheHill;
+
+impl Rules for KingOfTheHill
+{
+    fn name(&self) -> &'static str {
+        "King of the Hill"
+    }
+
+    fn variant_result(&self, game_state: &GameState) -> Option<BoardResult> {
+        let board = game_state.current_board();
+        [PlayerColor::White, PlayerColor::Black].into_iter()
+            .find(|color| !(board.query().color(*color).piece_type(PieceType::King).result() & HILL).is_empty())
+            .map(BoardResult::Win)
+    }
+}
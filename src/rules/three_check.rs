@@ -0,0 +1,92 @@
+//! Implements [ThreeCheck], the variant where giving check three times wins.
+
+use crate::{board::{BoardResult, PlayerColor}, game::GameState};
+
+use super::Rules;
+
+/// How many checks it takes to win.
+const CHECKS_TO_WIN: u32 = 3;
+
+/// Three-check: standard chess, except a player also wins by giving check for the third time.
+/// Checkmate still wins straight away. See [GameState::checks_given] for the check counts.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Board, BoardResult};
+/// # use rust_chess_engine::game::GameState;
+/// # use rust_chess_engine::parse::MoveCommand;
+/// # use rust_chess_engine::rules::{Rules, ThreeCheck};
+/// let mut game_state = GameState::with_rules(Board::new_default_starting_board(), ThreeCheck);
+/// for r#move in ["e4", "f5", "Qh5+"]
+/// {
+///     let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+///     game_state = game_state.update(&r#move).unwrap();
+/// }
+/// assert_eq!(game_state.rules().status(&game_state).unwrap(), "Checks given: White 1, Black 0");
+/// assert_eq!(game_state.game_result(), BoardResult::InProgress);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThreeCheck;
+
+impl Rules for ThreeCheck
+{
+    fn name(&self) -> &'static str {
+        "Three-check"
+    }
+
+    fn variant_result(&self, game_state: &GameState) -> Option<BoardResult> {
+        PlayerColor::both()
+            .find(|color| game_state.checks_given(*color) >= CHECKS_TO_WIN)
+            .map(BoardResult::Win)
+    }
+
+    fn status(&self, game_state: &GameState) -> Option<String> {
+        Some(format!("Checks given: White {}, Black {}", game_state.checks_given(PlayerColor::White), game_state.checks_given(PlayerColor::Black)))
+    }
+
+    fn pgn_tags(&self, game_state: &GameState) -> Vec<(String, String)> {
+        vec![
+            ("WhiteChecks".to_string(), game_state.checks_given(PlayerColor::White).to_string()),
+            ("BlackChecks".to_string(), game_state.checks_given(PlayerColor::Black).to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::{Board, BoardConfiguration}, game::Termination, parse::MoveCommand};
+
+    use super::*;
+
+    fn play(fen: &str, moves: &[&str]) -> GameState
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap());
+        moves.iter().fold(GameState::with_rules(board, ThreeCheck), |game_state, r#move|
+        {
+            let r#move = game_state.legal_move(&MoveCommand::from_str(r#move).unwrap()).unwrap();
+            game_state.update(&r#move).unwrap()
+        })
+    }
+
+    #[test]
+    fn third_check_wins()
+    {
+        // The rook checks along the back rank, the king steps up, and so on.
+        let game_state = play("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", &["Ra8+", "Kd7", "Ra7+", "Kc6"]);
+        assert_eq!(game_state.checks_given(PlayerColor::White), 2);
+        assert_eq!(game_state.game_result(), BoardResult::InProgress);
+        let game_state = play("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", &["Ra8+", "Kd7", "Ra7+", "Kc6", "Ra6+"]);
+        assert_eq!(game_state.game_result(), BoardResult::Win(PlayerColor::White));
+        assert_eq!(game_state.termination(), Some(Termination::VariantWin));
+
+        let pgn = game_state.to_pgn(&[]);
+        assert!(pgn.contains("[Variant \"Three-check\"]"));
+        assert!(pgn.contains("[WhiteChecks \"3\"]"));
+        assert!(pgn.contains("[BlackChecks \"0\"]"));
+    }
+}
@@ -0,0 +1,25 @@
+//! Shared building blocks for the engine's search, like time limits, move buffers, move
+//! ordering, search statistics and the transposition table.
+//!
+//! The search itself lives in [MinmaxAgent](crate::agent::MinmaxAgent), this module holds the
+//! pieces that aren't specific to one agent.
+
+/// The [log](mod@log) target for search progress, like each finished iteration and the chosen move. Use it
+/// to turn search logging up or down on its own, i.e
+/// `RUST_LOG=rust_chess_engine::search=debug` with [env_logger](https://docs.rs/env_logger).
+pub const LOG_TARGET: &str = "rust_chess_engine::search";
+
+mod time_management;
+mod move_stack;
+mod move_ordering;
+mod search_result;
+mod search_stats;
+mod transposition_table;
+
+pub use time_management::{Deadline, apply_move_overhead, DEFAULT_MOVE_OVERHEAD, MINIMUM_THINK_TIME};
+pub use time_management::{ClockState, TimeAllocation, TimeManager, allocate_time};
+pub use move_stack::{MoveStack, MOVE_BUFFER_CAPACITY};
+pub use move_ordering::{mvv_lva_score, MoveOrdering, KILLERS_PER_PLY};
+pub use search_result::SearchResult;
+pub use search_stats::{NodeType, NodeTypeCounts, SearchStats};
+pub use transposition_table::{Bound, TranspositionEntry, TranspositionTable, DEFAULT_TRANSPOSITION_TABLE_SIZE};
@@ -0,0 +1,313 @@
+//! Sorts the moves at each node so the ones most likely to cause a cutoff are searched first.
+
+use std::cmp::Reverse;
+
+use crate::board::{Board, Move, PieceType, PlayerColor};
+
+/// How many killer moves are remembered for each ply.
+pub const KILLERS_PER_PLY: usize = 2;
+
+/// Captures and promotions are sorted above every quiet move, which is anything below this.
+const CAPTURE_SCORE: i32 = 1 << 24;
+
+/// Killer moves are sorted right below captures, with the newest killer first.
+const KILLER_SCORES: [i32; KILLERS_PER_PLY] = [CAPTURE_SCORE - 1, CAPTURE_SCORE - 2];
+
+/// History scores are capped below the killers, so a quiet move with a long history of cutoffs
+/// still doesn't jump ahead of a killer.
+const MAX_HISTORY_SCORE: u32 = (CAPTURE_SCORE - KILLERS_PER_PLY as i32 - 1) as u32;
+
+/// Remembers which moves caused cutoffs during a search, and uses that together with what a move
+/// captures to decide what order to search moves in.
+///
+/// Alpha-beta pruning only skips moves after a good one has already been found, so the earlier
+/// the best move is searched, the more of the tree gets pruned. Moves are tried in this order:
+/// 1. The hash move, i.e the best move the [TranspositionTable](super::TranspositionTable)
+///    remembers from an earlier search of the same position.
+/// 2. Captures (and promotions) that don't lose material, ordered by most valuable victim, least
+///    valuable attacker ([MVV-LVA](https://www.chessprogramming.org/MVV-LVA)). See
+///    [mvv_lva_score].
+/// 3. Killer moves, quiet moves that caused a cutoff in a sibling position at the same ply.
+/// 4. Every other quiet move, by its history score, which goes up every time the move causes a
+///    cutoff anywhere in the tree.
+/// 5. Captures that lose material once the exchange is played out (see [Board::see]), the ones
+///    that lose the least first.
+///
+/// Each search thread keeps its own [MoveOrdering], since it's updated at every cutoff.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Board, BoardConfiguration};
+/// # use rust_chess_engine::search::MoveOrdering;
+/// // The knight on c3 can take the queen on b5 or the pawn on e4.
+/// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/1q6/4p3/2N5/8/4K3 w - - 0 1").unwrap());
+/// let mut moves = board.generate_moves_for_side(board.active_color());
+/// MoveOrdering::with_max_ply(4).order_moves(&board, &mut moves, None, 0);
+/// assert_eq!(board.to_san(&moves[0]), "Nxb5");
+/// assert_eq!(board.to_san(&moves[1]), "Nxe4");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MoveOrdering
+{
+    killers: Vec<[Option<Move>; KILLERS_PER_PLY]>,
+    /// Indexed by the moving side, the starting square and the target square.
+    history: Vec<u32>,
+}
+
+impl MoveOrdering
+{
+    /// Creates a [MoveOrdering] that remembers killer moves for up to `max_ply` plies. Cutoffs
+    /// deeper than that still count towards the history scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_ply` - How many plies deep the search can go.
+    pub fn with_max_ply(max_ply: usize) -> Self
+    {
+        Self
+        {
+            killers: vec![[None; KILLERS_PER_PLY]; max_ply],
+            history: vec![0; 2 * 64 * 64],
+        }
+    }
+
+    /// Sorts `moves` so the most promising ones come first. See [MoveOrdering] for the order.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position the moves are played from.
+    /// * `moves` - The moves to sort.
+    /// * `hash_move` - The best move from an earlier search of this position, if there was one.
+    /// * `ply` - How many plies from the root the position is.
+    pub fn order_moves(&self, board: &Board, moves: &mut [Move], hash_move: Option<Move>, ply: usize)
+    {
+        moves.sort_by_cached_key(|r#move| Reverse(self.score(board, r#move, hash_move, ply)));
+    }
+
+    /// Sorts captures for a quiescence search by [mvv_lva_score], without any of the quiet move
+    /// heuristics.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position the captures are played from.
+    /// * `captures` - The captures to sort.
+    pub fn order_captures(board: &Board, captures: &mut [Move])
+    {
+        captures.sort_by_cached_key(|capture| Reverse(mvv_lva_score(board, capture).unwrap_or(0)));
+    }
+
+    /// Remembers that `r#move` caused a cutoff. Only quiet moves are remembered, captures are
+    /// already sorted to the front by what they capture.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position the move was played from.
+    /// * `r#move` - The move that caused the cutoff.
+    /// * `ply` - How many plies from the root the position is.
+    /// * `depth` - How many plies deep the position was searched. Cutoffs further from the
+    ///   leaves prune more, so they count for more.
+    pub fn record_cutoff(&mut self, board: &Board, r#move: Move, ply: usize, depth: usize)
+    {
+        if mvv_lva_score(board, &r#move).is_some()
+        {
+            return;
+        }
+        if let Some(killers) = self.killers.get_mut(ply)
+        {
+            if killers[0] != Some(r#move)
+            {
+                killers.rotate_right(1);
+                killers[0] = Some(r#move);
+            }
+        }
+        if let Some(index) = history_index(board.active_color(), &r#move)
+        {
+            let bonus = u32::try_from(depth * depth).unwrap_or(u32::MAX);
+            self.history[index] = self.history[index].saturating_add(bonus).min(MAX_HISTORY_SCORE);
+        }
+    }
+
+    /// Returns the killer moves for a ply, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `ply` - How many plies from the root.
+    pub fn killers(&self, ply: usize) -> [Option<Move>; KILLERS_PER_PLY]
+    {
+        self.killers.get(ply).copied().unwrap_or_default()
+    }
+
+    /// Returns the history score of a quiet move for the given side. Castling doesn't have a
+    /// history score.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The side making the move.
+    /// * `r#move` - The move.
+    pub fn history_score(&self, color: PlayerColor, r#move: &Move) -> u32
+    {
+        history_index(color, r#move).map_or(0, |index| self.history[index])
+    }
+
+    /// Forgets every killer move and history score, i.e before starting a new game.
+    pub fn clear(&mut self)
+    {
+        self.killers.fill([None; KILLERS_PER_PLY]);
+        self.history.fill(0);
+    }
+
+    fn score(&self, board: &Board, r#move: &Move, hash_move: Option<Move>, ply: usize) -> i32
+    {
+        if hash_move == Some(*r#move)
+        {
+            return i32::MAX;
+        }
+        if let Some(score) = mvv_lva_score(board, r#move)
+        {
+            let Move::NormalMove(move_data) = r#move else { unreachable!("Castles are never captures") };
+            let see = board.see(move_data.target_square(), r#move);
+            // Losing captures go below every quiet move, whose scores are never negative.
+            return match see < 0
+            {
+                true => see,
+                false => CAPTURE_SCORE + score,
+            };
+        }
+        match self.killers(ply).iter().position(|killer| *killer == Some(*r#move))
+        {
+            Some(index) => KILLER_SCORES[index],
+            // The history score is capped, so this always fits.
+            None => self.history_score(board.active_color(), r#move) as i32,
+        }
+    }
+}
+
+/// Returns how promising a capture or promotion looks by what it wins: captures of more valuable
+/// pieces come first, and among those, captures with less valuable pieces come first, since
+/// they're less likely to lose material if the capturing piece is taken back. Promotions add the
+/// value of the new piece. Returns [None] for quiet moves.
+///
+/// # Arguments
+///
+/// * `board` - The position the move is played from.
+/// * `r#move` - The move.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{Board, BoardConfiguration};
+/// # use rust_chess_engine::parse::MoveCommand;
+/// # use rust_chess_engine::search::mvv_lva_score;
+/// // The queen on d5 can be taken by the pawn on e4 or the knight on c3.
+/// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/3q4/4P3/2N5/8/4K3 w - - 0 1").unwrap());
+/// let pawn_takes = board.get_move(&MoveCommand::from_str("exd5").unwrap()).unwrap();
+/// let knight_takes = board.get_move(&MoveCommand::from_str("Nxd5").unwrap()).unwrap();
+/// assert!(mvv_lva_score(&board, &pawn_takes) > mvv_lva_score(&board, &knight_takes));
+/// let quiet = board.get_move(&MoveCommand::from_str("Kd2").unwrap()).unwrap();
+/// assert_eq!(mvv_lva_score(&board, &quiet), None);
+/// ```
+pub fn mvv_lva_score(board: &Board, r#move: &Move) -> Option<i32>
+{
+    let Move::NormalMove(move_data) = r#move else { return None };
+    if !move_data.capture() && move_data.promotion().is_none()
+    {
+        return None;
+    }
+    // An en passant capture has nothing on its target square, but always takes a pawn.
+    let victim_value = match move_data.capture()
+    {
+        true => ordering_value(board.piece_at(&move_data.target_square()).map_or(PieceType::Pawn, |piece| piece.piece_type())),
+        false => 0,
+    };
+    let attacker = board.piece_at(&move_data.starting_square()).map_or(PieceType::Pawn, |piece| piece.piece_type());
+    let promotion_value = move_data.promotion().map_or(0, ordering_value);
+    Some((victim_value + promotion_value) * 16 - ordering_value(attacker))
+}
+
+/// A rough value for each piece type that's only used to order moves. The king is worth the most
+/// as an attacker, since it can never be traded.
+fn ordering_value(piece_type: PieceType) -> i32
+{
+    match piece_type
+    {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 3,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Queen => 9,
+        PieceType::King => 15,
+    }
+}
+
+fn history_index(color: PlayerColor, r#move: &Move) -> Option<usize>
+{
+    let Move::NormalMove(move_data) = r#move else { return None };
+    Some((color.index() * 64 + move_data.starting_square().index() as usize) * 64 + move_data.target_square().index() as usize)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use crate::{board::BoardConfiguration, parse::MoveCommand};
+
+    use super::*;
+
+    fn parse(board: &Board, r#move: &str) -> Move
+    {
+        board.get_move(&MoveCommand::from_str(r#move).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn hash_move_then_captures_then_killers_then_history()
+    {
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/1q6/4p3/2N5/8/R3K3 w - - 0 1").unwrap());
+        let mut move_ordering = MoveOrdering::with_max_ply(4);
+        move_ordering.record_cutoff(&board, parse(&board, "Ra7"), 1, 3);
+        move_ordering.record_cutoff(&board, parse(&board, "Kf2"), 2, 3);
+        move_ordering.record_cutoff(&board, parse(&board, "Kf2"), 2, 3);
+        // Captures are never killers.
+        move_ordering.record_cutoff(&board, parse(&board, "Nxe4"), 1, 3);
+        assert_eq!(move_ordering.killers(1), [Some(parse(&board, "Ra7")), None]);
+
+        let mut moves = board.generate_moves_for_side(board.active_color());
+        move_ordering.order_moves(&board, &mut moves, Some(parse(&board, "Rb1")), 1);
+        let order: Vec<String> = moves.iter().take(5).map(|r#move| board.to_san(r#move)).collect();
+        assert_eq!(order, ["Rb1", "Nxb5", "Nxe4", "Ra7", "Kf2"]);
+        assert!(move_ordering.history_score(PlayerColor::White, &parse(&board, "Kf2")) > move_ordering.history_score(PlayerColor::White, &parse(&board, "Ra7")));
+
+        move_ordering.clear();
+        assert_eq!(move_ordering.killers(1), [None, None]);
+        assert_eq!(move_ordering.history_score(PlayerColor::White, &parse(&board, "Kf2")), 0);
+    }
+
+    #[test]
+    fn losing_captures_come_after_quiet_moves()
+    {
+        // The queen on d5 defends e4, so Nxe4 loses the knight for a pawn.
+        let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("4k3/8/8/3q4/4p3/2N5/8/4K3 w - - 0 1").unwrap());
+        let mut moves = board.generate_moves_for_side(board.active_color());
+        MoveOrdering::with_max_ply(4).order_moves(&board, &mut moves, None, 0);
+        assert_eq!(board.to_san(&moves[0]), "Nxd5");
+        assert_eq!(moves.last().map(|r#move| board.to_san(r#move)).as_deref(), Some("Nxe4"));
+    }
+
+    #[test]
+    fn newer_killers_push_out_older_ones()
+    {
+        let board = Board::new_default_starting_board();
+        let mut move_ordering = MoveOrdering::with_max_ply(1);
+        for r#move in ["e4", "d4", "d4", "Nf3"]
+        {
+            move_ordering.record_cutoff(&board, parse(&board, r#move), 0, 1);
+        }
+        assert_eq!(move_ordering.killers(0), [Some(parse(&board, "Nf3")), Some(parse(&board, "d4"))]);
+        // Plies past the end are ignored instead of panicking.
+        move_ordering.record_cutoff(&board, parse(&board, "e4"), 5, 1);
+        assert_eq!(move_ordering.killers(5), [None, None]);
+    }
+}
@@ -0,0 +1,86 @@
+//! Reusable per-ply move buffers, so the search doesn't allocate a new [Vec] at every node.
+
+use crate::board::Move;
+
+/// Enough room for the moves of almost any real position, so the buffers rarely have to grow.
+pub const MOVE_BUFFER_CAPACITY: usize = 128;
+
+/// One move buffer per ply of the search.
+///
+/// Generating the moves at every node used to allocate a fresh [Vec], and at higher depths the
+/// allocator ended up dominating profiles. Instead each search thread makes one [MoveStack] up
+/// front, sized by the maximum ply, and every node writes its moves into the buffer for its ply
+/// with [Board::generate_moves_for_side_into](crate::board::Board::generate_moves_for_side_into).
+///
+/// The buffers are handed out as a slice with [Self::plies]. A node takes the first buffer with
+/// [slice::split_first_mut] and passes the rest down to its children, so each ply only ever
+/// touches its own buffer.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::Board;
+/// # use rust_chess_engine::search::MoveStack;
+/// let board = Board::new_default_starting_board();
+/// let mut move_stack = MoveStack::with_max_ply(4);
+/// let (moves, deeper_plies) = move_stack.plies().split_first_mut().unwrap();
+/// board.generate_moves_for_side_into(board.active_color(), moves);
+/// assert_eq!(moves.len(), 20);
+/// assert_eq!(deeper_plies.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MoveStack
+{
+    plies: Vec<Vec<Move>>,
+}
+
+impl MoveStack
+{
+    /// Creates a [MoveStack] with a buffer for each of `max_ply` plies, each with room for
+    /// [MOVE_BUFFER_CAPACITY] moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_ply` - How many plies deep the search can go.
+    pub fn with_max_ply(max_ply: usize) -> Self
+    {
+        Self
+        {
+            plies: (0..max_ply).map(|_| Vec::with_capacity(MOVE_BUFFER_CAPACITY)).collect(),
+        }
+    }
+
+    /// Returns how many plies this [MoveStack] has buffers for.
+    pub fn max_ply(&self) -> usize
+    {
+        self.plies.len()
+    }
+
+    /// Returns the buffers, starting with the one for the root.
+    pub fn plies(&mut self) -> &mut [Vec<Move>]
+    {
+        &mut self.plies
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::board::Board;
+
+    use super::*;
+
+    #[test]
+    fn buffers_keep_their_capacity()
+    {
+        let board = Board::new_default_starting_board();
+        let mut move_stack = MoveStack::with_max_ply(2);
+        for _ in 0..3
+        {
+            let moves = &mut move_stack.plies()[0];
+            board.generate_moves_for_side_into(board.active_color(), moves);
+            assert_eq!(moves.len(), 20);
+            assert_eq!(moves.capacity(), MOVE_BUFFER_CAPACITY);
+        }
+    }
+}
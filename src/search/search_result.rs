@@ -0,0 +1,157 @@
+//! The [SearchResult] a search hands back to whoever asked for it.
+
+use getset::{CopyGetters, Getters};
+
+use crate::board::{Board, Evaluation, Move};
+
+use super::{NodeTypeCounts, SearchStats};
+
+/// What a finished search found: the move to play, what it's worth, and the line of play the
+/// search expects to follow from it (the principal variation), along with how much work it took
+/// and how settled the search was on its move.
+///
+/// The principal variation starts with the best move, followed by the best reply, the best answer
+/// to that, and so on. It can be shorter than the search depth, i.e when the game ends along the
+/// way, or when part of the line came out of the transposition table.
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::agent::MinmaxAgent;
+/// # use rust_chess_engine::board::{Board, BoardConfiguration};
+/// # use rust_chess_engine::game::GameState;
+/// // White mates with Rh8.
+/// let board = Board::new_board_with_configuration(&BoardConfiguration::from_str("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap());
+/// let mut minmax_agent = MinmaxAgent::new(1);
+/// let search_result = minmax_agent.search(&GameState::new(board.clone())).unwrap();
+/// assert_eq!(search_result.pv()[0], search_result.best_move());
+/// assert_eq!(search_result.pv_to_san(&board), ["Rh8#"]);
+/// assert!(search_result.nodes() > 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Getters, CopyGetters)]
+pub struct SearchResult
+{
+    /// The move the search settled on.
+    #[getset(get_copy="pub")]
+    best_move: Move,
+    /// The evaluation of the best move.
+    #[getset(get_copy="pub")]
+    score: Evaluation,
+    /// The expected line of play, starting with the best move.
+    #[getset(get="pub")]
+    pv: Vec<Move>,
+    /// Node counts and timing for the search, up to and including the deepest iteration that
+    /// finished.
+    #[getset(get_copy="pub")]
+    stats: SearchStats,
+    /// How many nodes of each type were visited, over every iteration.
+    #[getset(get_copy="pub")]
+    node_types: NodeTypeCounts,
+    /// How many iterations of iterative deepening finished. This is 1 when searching straight to a
+    /// fixed depth.
+    #[getset(get_copy="pub")]
+    iterations: usize,
+    /// How many times the best move changed from one iteration to the next.
+    #[getset(get_copy="pub")]
+    best_move_changes: usize,
+}
+
+impl SearchResult
+{
+    /// Creates a new [SearchResult] for a search that went straight to its depth in one
+    /// iteration. See [Self::with_iterations] for iterative deepening.
+    ///
+    /// # Arguments
+    ///
+    /// * `best_move` - The move the search settled on.
+    /// * `score` - The evaluation of the best move.
+    /// * `pv` - The expected line of play. This should start with `best_move`.
+    /// * `stats` - Node counts and timing for the search.
+    /// * `node_types` - How many nodes of each type were visited.
+    pub fn new(best_move: Move, score: Evaluation, pv: Vec<Move>, stats: SearchStats, node_types: NodeTypeCounts) -> Self
+    {
+        Self
+        {
+            best_move,
+            score,
+            pv,
+            stats,
+            node_types,
+            iterations: 1,
+            best_move_changes: 0,
+        }
+    }
+
+    /// Sets how many iterations of iterative deepening the search took to get here, and how many
+    /// times the best move changed along the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `iterations` - How many iterations finished.
+    /// * `best_move_changes` - How many times the best move changed between iterations.
+    pub fn with_iterations(mut self, iterations: usize, best_move_changes: usize) -> Self
+    {
+        self.iterations = iterations;
+        self.best_move_changes = best_move_changes;
+        self
+    }
+
+    /// Returns the deepest search that finished.
+    pub fn depth(&self) -> usize
+    {
+        self.stats.depth()
+    }
+
+    /// Returns how many nodes were searched in total, including the quiescence search.
+    pub fn nodes(&self) -> u64
+    {
+        self.stats.nodes()
+    }
+
+    /// Returns how stable the best move was between iterations, from 0 (it changed every time) to
+    /// 1 (it never changed).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Evaluation, Move, CastlingDirection};
+    /// # use rust_chess_engine::search::{NodeTypeCounts, SearchResult, SearchStats};
+    /// let r#move = Move::Castle(CastlingDirection::Kingside);
+    /// let search_result = SearchResult::new(r#move, Evaluation::Draw, vec![r#move], SearchStats::default(), NodeTypeCounts::default())
+    ///     .with_iterations(5, 1);
+    /// // The best move changed once out of four chances.
+    /// assert_eq!(search_result.best_move_stability(), 0.75);
+    /// ```
+    pub fn best_move_stability(&self) -> f64
+    {
+        match self.iterations
+        {
+            0 | 1 => 1.0,
+            iterations => 1.0 - self.best_move_changes as f64 / (iterations - 1) as f64,
+        }
+    }
+
+    /// Writes the principal variation in standard algebraic notation, playing it out from
+    /// `board`, which should be the position that was searched. Stops early at the first move
+    /// that isn't legal on the board it reaches.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position that was searched.
+    pub fn pv_to_san(&self, board: &Board) -> Vec<String>
+    {
+        let mut board = board.clone();
+        let mut sans = Vec::with_capacity(self.pv.len());
+        for r#move in &self.pv
+        {
+            if board.is_legal(r#move).is_err()
+            {
+                break;
+            }
+            sans.push(board.to_san(r#move));
+            board = board.attempt_move(r#move).expect("The move was just checked");
+        }
+        sans
+    }
+}
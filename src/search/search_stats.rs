@@ -0,0 +1,220 @@
+//! Counters the search keeps as it goes, for profiling the engine and reporting progress.
+
+use std::{ops::{Add, AddAssign}, time::Duration};
+
+use getset::CopyGetters;
+
+/// How many nodes of each type the search visited.
+///
+/// With alpha-beta pruning, every node that isn't a leaf ends up as one of three types:
+/// - A **PV** node has a score strictly inside the window, so it's part of a best line.
+/// - A **cut** node caused a cutoff: one move was good enough that the opponent would never let
+///   the position happen, so the rest of the moves were skipped.
+/// - An **all** node had every move searched and none of them improved the score.
+///
+/// Good move ordering shows up as most cutoffs happening early, and few PV nodes.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, CopyGetters, derive_more::Add, derive_more::AddAssign)]
+#[getset(get_copy="pub")]
+pub struct NodeTypeCounts
+{
+    /// The number of PV nodes.
+    pv: u64,
+    /// The number of cut nodes.
+    cut: u64,
+    /// The number of all nodes.
+    all: u64,
+}
+
+/// The type of a searched node. See [NodeTypeCounts].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum NodeType
+{
+    /// The score was strictly inside the window.
+    Pv,
+    /// A move caused a cutoff.
+    Cut,
+    /// No move improved the score.
+    All,
+}
+
+impl NodeTypeCounts
+{
+    /// Counts one more node of the given type.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_type` - The type of the node.
+    pub fn record(&mut self, node_type: NodeType)
+    {
+        match node_type
+        {
+            NodeType::Pv => self.pv += 1,
+            NodeType::Cut => self.cut += 1,
+            NodeType::All => self.all += 1,
+        }
+    }
+
+    /// Returns the total number of nodes counted.
+    pub fn total(&self) -> u64
+    {
+        self.pv + self.cut + self.all
+    }
+}
+
+/// How much work a search did, and how fast.
+///
+/// Every search thread counts into its own [SearchStats], which are added together when the
+/// threads finish. Adding takes the sum of the counters, and the larger of the depths and elapsed
+/// times.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rust_chess_engine::search::SearchStats;
+/// let mut stats = SearchStats::default();
+/// stats.record_node(0);
+/// stats.record_node(3);
+/// stats.record_tt_probe(true);
+/// stats.record_tt_probe(false);
+/// let stats = stats.at_depth(2, Duration::from_millis(500));
+/// assert_eq!(stats.nodes(), 2);
+/// assert_eq!(stats.seldepth(), 4);
+/// assert_eq!(stats.nps(), 4);
+/// assert_eq!(stats.tt_hit_rate(), 0.5);
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct SearchStats
+{
+    /// The depth the search was asked to go to.
+    depth: usize,
+    /// The deepest ply any node was searched at, counting the quiescence search, which can go
+    /// well past [Self::depth].
+    seldepth: usize,
+    /// How many positions were searched, including the quiescence search.
+    nodes: u64,
+    /// How many times the transposition table was looked at.
+    tt_probes: u64,
+    /// How many of those looks found the position.
+    tt_hits: u64,
+    /// How long the search took.
+    elapsed: Duration,
+}
+
+impl SearchStats
+{
+    /// Counts one more searched position.
+    ///
+    /// # Arguments
+    ///
+    /// * `ply` - How many plies from the root the position is, where the root's children are at
+    ///   ply 0.
+    pub fn record_node(&mut self, ply: usize)
+    {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply + 1);
+    }
+
+    /// Counts one more look at the transposition table.
+    ///
+    /// # Arguments
+    ///
+    /// * `hit` - Whether the position was in the table.
+    pub fn record_tt_probe(&mut self, hit: bool)
+    {
+        self.tt_probes += 1;
+        if hit
+        {
+            self.tt_hits += 1;
+        }
+    }
+
+    /// Returns the same counts, for a search to `depth` that took `elapsed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The depth the search was asked to go to.
+    /// * `elapsed` - How long the search took.
+    pub fn at_depth(self, depth: usize, elapsed: Duration) -> Self
+    {
+        Self
+        {
+            depth,
+            elapsed,
+            ..self
+        }
+    }
+
+    /// Returns how many nodes were searched per second, or 0 if no time has passed.
+    pub fn nps(&self) -> u64
+    {
+        match self.elapsed.as_secs_f64()
+        {
+            0.0 => 0,
+            seconds => (self.nodes as f64 / seconds) as u64,
+        }
+    }
+
+    /// Returns how often the transposition table had the position, from 0 to 1. Returns 0 if the
+    /// table was never looked at.
+    pub fn tt_hit_rate(&self) -> f64
+    {
+        match self.tt_probes
+        {
+            0 => 0.0,
+            probes => self.tt_hits as f64 / probes as f64,
+        }
+    }
+}
+
+impl Add for SearchStats
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self
+        {
+            depth: self.depth.max(other.depth),
+            seldepth: self.seldepth.max(other.seldepth),
+            nodes: self.nodes + other.nodes,
+            tt_probes: self.tt_probes + other.tt_probes,
+            tt_hits: self.tt_hits + other.tt_hits,
+            elapsed: self.elapsed.max(other.elapsed),
+        }
+    }
+}
+
+impl AddAssign for SearchStats
+{
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn adding_sums_counters_and_keeps_the_deepest()
+    {
+        let mut a = SearchStats::default();
+        a.record_node(5);
+        a.record_tt_probe(true);
+        let mut b = SearchStats::default();
+        b.record_node(1);
+        b.record_node(2);
+        b.record_tt_probe(false);
+        let sum = a + b.at_depth(3, Duration::from_secs(1));
+        assert_eq!(sum.nodes(), 3);
+        assert_eq!(sum.seldepth(), 6);
+        assert_eq!(sum.depth(), 3);
+        assert_eq!(sum.tt_probes(), 2);
+        assert_eq!(sum.tt_hits(), 1);
+        assert_eq!(sum.elapsed(), Duration::from_secs(1));
+        assert_eq!(SearchStats::default().nps(), 0);
+        assert_eq!(SearchStats::default().tt_hit_rate(), 0.0);
+    }
+}
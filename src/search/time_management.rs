@@ -0,0 +1,383 @@
+//! Keeps track of how long the search is allowed to run for.
+
+use std::{sync::atomic::{AtomicBool, Ordering}, time::{Duration, Instant}};
+
+use getset::CopyGetters;
+
+/// The default time set aside for network and GUI latency on every move.
+pub const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+/// No matter how little time is left, the search always gets at least this long to come up with
+/// *something*.
+pub const MINIMUM_THINK_TIME: Duration = Duration::from_millis(10);
+
+/// Subtracts the move overhead from a time budget.
+///
+/// The clock keeps running while a move travels over the network or through a GUI, so if the
+/// engine used its entire budget to think it would regularly run out of time in fast games. The
+/// move overhead is an estimate of that latency which we simply don't use for thinking.
+///
+/// The result is never less than [MINIMUM_THINK_TIME].
+///
+/// # Arguments
+///
+/// * `budget` - How much time the search would like to use.
+/// * `move_overhead` - How much time to set aside for latency.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rust_chess_engine::search::{apply_move_overhead, MINIMUM_THINK_TIME};
+/// assert_eq!(apply_move_overhead(Duration::from_millis(500), Duration::from_millis(100)), Duration::from_millis(400));
+/// assert_eq!(apply_move_overhead(Duration::from_millis(50), Duration::from_millis(100)), MINIMUM_THINK_TIME);
+/// ```
+pub fn apply_move_overhead(budget: Duration, move_overhead: Duration) -> Duration
+{
+    budget.saturating_sub(move_overhead).max(MINIMUM_THINK_TIME)
+}
+
+/// The point in time at which a search has to stop.
+///
+/// A [Deadline] is shared between all of the threads searching a position. Once any of them
+/// notices the deadline has passed it is marked as reached for everyone, so the search unwinds as
+/// quickly as possible.
+#[derive(Debug, Default)]
+pub struct Deadline
+{
+    stop_at: Option<Instant>,
+    reached: AtomicBool,
+}
+
+impl Deadline
+{
+    /// Returns a [Deadline] that is never reached, for searches without a time limit.
+    pub fn none() -> Self
+    {
+        Self::default()
+    }
+
+    /// Returns a [Deadline] that is reached `duration` from now.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long until the deadline.
+    pub fn after(duration: Duration) -> Self
+    {
+        Self
+        {
+            stop_at: Some(Instant::now() + duration),
+            reached: AtomicBool::new(false),
+        }
+    }
+
+    /// Checks the clock and returns true if the deadline has been reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rust_chess_engine::search::Deadline;
+    /// assert!(!Deadline::none().is_reached());
+    /// assert!(!Deadline::after(Duration::from_secs(60)).is_reached());
+    /// assert!(Deadline::after(Duration::ZERO).is_reached());
+    /// ```
+    pub fn is_reached(&self) -> bool
+    {
+        if self.reached.load(Ordering::Relaxed)
+        {
+            return true;
+        }
+        match self.stop_at
+        {
+            Some(stop_at) if Instant::now() >= stop_at =>
+            {
+                self.reached.store(true, Ordering::Relaxed);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Marks the deadline as reached right away, so a search running on another thread stops as
+    /// soon as it next checks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::search::Deadline;
+    /// let deadline = Deadline::none();
+    /// deadline.stop();
+    /// assert!(deadline.is_reached());
+    /// ```
+    pub fn stop(&self)
+    {
+        self.reached.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the deadline was reached the last time anyone checked, without checking
+    /// the clock again. This is used to find out whether a search finished or was cut short.
+    pub fn was_reached(&self) -> bool
+    {
+        self.reached.load(Ordering::Relaxed)
+    }
+}
+
+/// How many moves we assume are left in the game at the very start.
+const MAXIMUM_EXPECTED_MOVES_LEFT: u32 = 50;
+
+/// However long the game has gone on, we always plan for at least this many more moves.
+const MINIMUM_EXPECTED_MOVES_LEFT: u32 = 20;
+
+/// We never plan to use more than this fraction of the remaining time on a single move.
+const MAXIMUM_TIME_FRACTION: u32 = 3;
+
+/// How many times longer than planned we're willing to think when the best move is unstable.
+const MAXIMUM_STRETCH: u32 = 5;
+
+/// The state of a player's clock.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct ClockState
+{
+    /// How much time the player has left.
+    remaining: Duration,
+    /// How much time is added to the clock after every move.
+    increment: Duration,
+    /// How many moves are left until the next time control, if the time control has one.
+    /// [None] means the remaining time has to last for the rest of the game.
+    moves_to_go: Option<u32>,
+}
+
+impl ClockState
+{
+    /// Creates a new [ClockState].
+    ///
+    /// # Arguments
+    ///
+    /// * `remaining` - How much time the player has left.
+    /// * `increment` - How much time is added after every move.
+    /// * `moves_to_go` - How many moves until the next time control, if any.
+    pub fn new(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self
+    {
+        Self
+        {
+            remaining,
+            increment,
+            moves_to_go,
+        }
+    }
+
+    /// Returns the clock after a move that took `elapsed` to make, i.e with the elapsed time
+    /// taken off and the increment added.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - How long the move took.
+    pub fn after_move(&self, elapsed: Duration) -> Self
+    {
+        Self
+        {
+            remaining: self.remaining.saturating_sub(elapsed) + self.increment,
+            increment: self.increment,
+            moves_to_go: self.moves_to_go.map(|moves| moves.saturating_sub(1)),
+        }
+    }
+}
+
+/// How long the search should spend on a move.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct TimeAllocation
+{
+    /// How long we'd normally like to think. The search may go past this if the position is
+    /// tricky.
+    optimum: Duration,
+    /// The hard limit. The search is stopped as soon as this is reached.
+    maximum: Duration,
+}
+
+impl TimeAllocation
+{
+    /// Creates a [TimeAllocation] that always uses exactly `move_time`, after the move overhead.
+    ///
+    /// # Arguments
+    ///
+    /// * `move_time` - How long to think.
+    /// * `move_overhead` - How much time to set aside for latency. See [apply_move_overhead].
+    pub fn fixed(move_time: Duration, move_overhead: Duration) -> Self
+    {
+        let move_time = apply_move_overhead(move_time, move_overhead);
+        Self
+        {
+            optimum: move_time,
+            maximum: move_time,
+        }
+    }
+}
+
+/// Decides how much of the remaining time to spend on the next move.
+///
+/// The remaining time is split evenly over the moves we expect are left (fewer and fewer as the
+/// game goes on), and most of the increment is added on top since we get it back after moving
+/// anyways. The search may stretch past that if the best move keeps changing, but never past a
+/// third of the remaining time.
+///
+/// # Arguments
+///
+/// * `clock` - The moving player's clock.
+/// * `fullmove_number` - The current move number.
+/// * `move_overhead` - How much time to set aside for latency. See [apply_move_overhead].
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use rust_chess_engine::search::{allocate_time, ClockState};
+/// let clock = ClockState::new(Duration::from_secs(300), Duration::from_secs(2), None);
+/// let early = allocate_time(&clock, 1, Duration::ZERO);
+/// let late = allocate_time(&clock, 40, Duration::ZERO);
+/// // The same amount of time has to last for fewer moves later in the game.
+/// assert!(late.optimum() > early.optimum());
+/// assert!(early.maximum() >= early.optimum());
+/// assert!(early.maximum() <= Duration::from_secs(100));
+/// ```
+pub fn allocate_time(clock: &ClockState, fullmove_number: u16, move_overhead: Duration) -> TimeAllocation
+{
+    let moves_left = clock.moves_to_go.unwrap_or_else(||
+        MAXIMUM_EXPECTED_MOVES_LEFT.saturating_sub(fullmove_number as u32).max(MINIMUM_EXPECTED_MOVES_LEFT)
+    ).max(1);
+
+    let hard_limit = clock.remaining / MAXIMUM_TIME_FRACTION;
+    let optimum = (clock.remaining / moves_left + clock.increment * 3 / 4).min(hard_limit);
+    let maximum = (optimum * MAXIMUM_STRETCH).min(hard_limit);
+    TimeAllocation
+    {
+        optimum: apply_move_overhead(optimum, move_overhead),
+        maximum: apply_move_overhead(maximum, move_overhead),
+    }
+}
+
+/// Decides when an iterative deepening search should stop starting new iterations.
+///
+/// If the best move changes from one depth to the next, the position is probably tricky and it's
+/// worth thinking longer, so every change stretches the time we're willing to spend (up to the
+/// maximum of the [TimeAllocation]). The effect of a change fades as later iterations agree with
+/// each other.
+#[derive(Debug)]
+pub struct TimeManager<M>
+{
+    allocation: TimeAllocation,
+    start: Instant,
+    last_best_move: Option<M>,
+    /// How many times the best move changed, with older changes counting for less.
+    instability: f64,
+    best_move_changes: usize,
+}
+
+impl<M: PartialEq> TimeManager<M>
+{
+    /// Starts timing a search with the given [TimeAllocation].
+    pub fn new(allocation: TimeAllocation) -> Self
+    {
+        Self
+        {
+            allocation,
+            start: Instant::now(),
+            last_best_move: None,
+            instability: 0.0,
+            best_move_changes: 0,
+        }
+    }
+
+    /// Returns a [Deadline] at the hard limit of this search.
+    pub fn deadline(&self) -> Deadline
+    {
+        Deadline::after(self.allocation.maximum.saturating_sub(self.start.elapsed()))
+    }
+
+    /// Records the best move found by an iteration that finished.
+    pub fn record_iteration(&mut self, best_move: M)
+    {
+        self.instability /= 2.0;
+        if self.last_best_move.as_ref().is_some_and(|last_best_move| *last_best_move != best_move)
+        {
+            self.instability += 1.0;
+            self.best_move_changes += 1;
+        }
+        self.last_best_move = Some(best_move);
+    }
+
+    /// Returns how many times the best move changed from one recorded iteration to the next.
+    pub fn best_move_changes(&self) -> usize
+    {
+        self.best_move_changes
+    }
+
+    /// Returns how long we're currently willing to think, taking the best move's instability into
+    /// account.
+    pub fn soft_limit(&self) -> Duration
+    {
+        self.allocation.optimum.mul_f64(1.0 + self.instability).min(self.allocation.maximum)
+    }
+
+    /// Returns true if there's enough time left to start another iteration.
+    ///
+    /// An iteration takes longer than all the previous ones put together, so there's no point
+    /// starting one once we're more than halfway through the soft limit.
+    pub fn should_start_next_iteration(&self) -> bool
+    {
+        self.start.elapsed() < self.soft_limit() / 2
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn moves_to_go_splits_the_remaining_time()
+    {
+        let clock = ClockState::new(Duration::from_secs(100), Duration::ZERO, Some(10));
+        assert_eq!(allocate_time(&clock, 1, Duration::ZERO).optimum(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn never_plans_more_than_a_third_of_the_clock()
+    {
+        let clock = ClockState::new(Duration::from_secs(3), Duration::from_secs(10), Some(1));
+        let allocation = allocate_time(&clock, 1, Duration::ZERO);
+        assert_eq!(allocation.optimum(), Duration::from_secs(1));
+        assert_eq!(allocation.maximum(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn clock_after_move_adds_increment()
+    {
+        let clock = ClockState::new(Duration::from_secs(60), Duration::from_secs(2), Some(5));
+        let clock = clock.after_move(Duration::from_secs(10));
+        assert_eq!(clock.remaining(), Duration::from_secs(52));
+        assert_eq!(clock.moves_to_go(), Some(4));
+    }
+
+    #[test]
+    fn unstable_best_move_stretches_the_soft_limit()
+    {
+        let allocation = TimeAllocation { optimum: Duration::from_secs(1), maximum: Duration::from_secs(2) };
+        let mut time_manager = TimeManager::new(allocation);
+        time_manager.record_iteration(1);
+        assert_eq!(time_manager.soft_limit(), Duration::from_secs(1));
+        time_manager.record_iteration(2);
+        assert_eq!(time_manager.soft_limit(), Duration::from_secs(2));
+        time_manager.record_iteration(3);
+        // Capped at the maximum.
+        assert_eq!(time_manager.soft_limit(), Duration::from_secs(2));
+        // Once the best move settles down the soft limit shrinks again.
+        time_manager.record_iteration(3);
+        time_manager.record_iteration(3);
+        time_manager.record_iteration(3);
+        assert!(time_manager.soft_limit() < Duration::from_millis(1500));
+        assert_eq!(time_manager.best_move_changes(), 2);
+    }
+}
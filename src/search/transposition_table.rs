@@ -0,0 +1,219 @@
+//! A fixed-size [TranspositionTable] that remembers positions the search has already looked at.
+
+use std::sync::Mutex;
+
+use getset::CopyGetters;
+
+use crate::board::{Evaluation, Move};
+
+/// How many entries a [TranspositionTable] made with [TranspositionTable::default] has room for.
+pub const DEFAULT_TRANSPOSITION_TABLE_SIZE: usize = 1 << 16;
+
+/// How a stored [Evaluation] relates to the real value of the position, which depends on the
+/// alpha-beta window the position was searched with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound
+{
+    /// Every move was searched inside the window, so the evaluation is exact.
+    Exact,
+    /// The search was cut off because the evaluation was already too good for the side to move,
+    /// so the real evaluation is at least this good for White.
+    Lower,
+    /// Nothing got inside the window, so the real evaluation is at most this good for White.
+    Upper,
+}
+
+/// What a [TranspositionTable] remembers about one position.
+#[derive(Debug, Copy, Clone, CopyGetters)]
+#[getset(get_copy="pub")]
+pub struct TranspositionEntry
+{
+    /// The [Zobrist key](crate::board::Board::zobrist_key) of the position.
+    key: u64,
+    /// How many plies deep the position was searched.
+    depth: usize,
+    /// The evaluation the search came up with.
+    evaluation: Evaluation,
+    /// Whether the evaluation is exact or only a bound.
+    bound: Bound,
+    /// The best move found, if the position had any moves.
+    best_move: Option<Move>,
+}
+
+impl TranspositionEntry
+{
+    /// Creates a new [TranspositionEntry].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The [Zobrist key](crate::board::Board::zobrist_key) of the position.
+    /// * `depth` - How many plies deep the position was searched.
+    /// * `evaluation` - The evaluation the search came up with.
+    /// * `bound` - Whether the evaluation is exact or only a bound.
+    /// * `best_move` - The best move found, if any.
+    pub fn new(key: u64, depth: usize, evaluation: Evaluation, bound: Bound, best_move: Option<Move>) -> Self
+    {
+        Self
+        {
+            key,
+            depth,
+            evaluation,
+            bound,
+            best_move,
+        }
+    }
+}
+
+/// A fixed-size hash table from positions to what the search found out about them.
+///
+/// The same position can often be reached through different move orders (transpositions), and
+/// iterative deepening searches the same positions again at every depth. Looking a position up
+/// here first lets the search skip positions it has already searched deeply enough, and try the
+/// best move from last time first otherwise.
+///
+/// Each position maps to one slot based on its Zobrist key. When two positions want the same
+/// slot, the one that was searched deeper wins (replace-by-depth), since it saved more work.
+///
+/// The table can be shared between search threads. Each slot has its own lock, so threads only
+/// wait on each other when they hit the same slot at the same time.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::{Board, Evaluation};
+/// # use rust_chess_engine::search::{Bound, TranspositionEntry, TranspositionTable};
+/// let board = Board::new_default_starting_board();
+/// let transposition_table = TranspositionTable::new(1024);
+/// assert!(transposition_table.probe(board.zobrist_key()).is_none());
+///
+/// transposition_table.store(TranspositionEntry::new(board.zobrist_key(), 3, Evaluation::Score(0.2), Bound::Exact, None));
+/// let entry = transposition_table.probe(board.zobrist_key()).unwrap();
+/// assert_eq!(entry.depth(), 3);
+/// ```
+#[derive(Debug)]
+pub struct TranspositionTable
+{
+    entries: Vec<Mutex<Option<TranspositionEntry>>>,
+}
+
+impl TranspositionTable
+{
+    /// Creates an empty [TranspositionTable] with room for at least `capacity` entries. The
+    /// capacity is rounded up to a power of two.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - How many positions the table can hold.
+    pub fn new(capacity: usize) -> Self
+    {
+        Self
+        {
+            entries: (0..capacity.max(1).next_power_of_two()).map(|_| Mutex::new(None)).collect(),
+        }
+    }
+
+    /// Returns how many entries the table has room for.
+    pub fn capacity(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    /// Looks up the position with the given Zobrist key. Returns [None] if the position isn't in
+    /// the table, either because it was never stored or because another position replaced it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The [Zobrist key](crate::board::Board::zobrist_key) of the position.
+    pub fn probe(&self, key: u64) -> Option<TranspositionEntry>
+    {
+        let slot = self.slot(key).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        slot.filter(|entry| entry.key == key)
+    }
+
+    /// Stores an entry, unless its slot already holds a different position that was searched
+    /// deeper. An entry for the same position always replaces the old one.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to store.
+    pub fn store(&self, entry: TranspositionEntry)
+    {
+        let mut slot = self.slot(entry.key).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let replace = match *slot
+        {
+            None => true,
+            Some(existing) => existing.key == entry.key || entry.depth >= existing.depth,
+        };
+        if replace
+        {
+            *slot = Some(entry);
+        }
+    }
+
+    /// Forgets every stored position, i.e before starting a new game.
+    pub fn clear(&mut self)
+    {
+        for slot in &mut self.entries
+        {
+            *slot.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+        }
+    }
+
+    fn slot(&self, key: u64) -> &Mutex<Option<TranspositionEntry>>
+    {
+        // The capacity is a power of two, so masking off the low bits picks a slot.
+        &self.entries[(key as usize) & (self.entries.len() - 1)]
+    }
+}
+
+impl Default for TranspositionTable
+{
+    /// Creates a table with room for [DEFAULT_TRANSPOSITION_TABLE_SIZE] entries.
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSPOSITION_TABLE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn entry(key: u64, depth: usize) -> TranspositionEntry
+    {
+        TranspositionEntry::new(key, depth, Evaluation::Score(depth as f64), Bound::Exact, None)
+    }
+
+    #[test]
+    fn capacity_is_rounded_up_to_a_power_of_two()
+    {
+        assert_eq!(TranspositionTable::new(1000).capacity(), 1024);
+        assert_eq!(TranspositionTable::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn deeper_entries_win_the_slot()
+    {
+        let table = TranspositionTable::new(16);
+        // Both keys land in slot 1.
+        table.store(entry(1, 4));
+        table.store(entry(17, 2));
+        assert!(table.probe(17).is_none());
+        assert_eq!(table.probe(1).unwrap().depth(), 4);
+        table.store(entry(17, 5));
+        assert!(table.probe(1).is_none());
+        assert_eq!(table.probe(17).unwrap().depth(), 5);
+        // The same position is always updated, even by a shallower search.
+        table.store(entry(17, 1));
+        assert_eq!(table.probe(17).unwrap().depth(), 1);
+    }
+
+    #[test]
+    fn clear_forgets_everything()
+    {
+        let mut table = TranspositionTable::new(16);
+        table.store(entry(3, 1));
+        table.clear();
+        assert!(table.probe(3).is_none());
+    }
+}
@@ -0,0 +1,272 @@
+//! This module defines the [Tablebase] trait for probing endgame tablebases like
+//! [Syzygy](https://www.chessprogramming.org/Syzygy_Bases), which know the exact result of every
+//! position with only a few pieces left.
+//!
+//! With the `syzygy` feature, `SyzygyTablebase` reads the Syzygy `.rtbw` (WDL) and `.rtbz`
+//! (DTZ) files. Anything else that knows exact endgame results can implement [Tablebase] to
+//! plug into the engine the same way.
+//!
+//! A [MinmaxAgent](crate::agent::MinmaxAgent) given a tablebase with
+//! [MinmaxAgent::set_tablebase](crate::agent::MinmaxAgent::set_tablebase) plays covered positions
+//! perfectly using [best_root_move], and scores covered positions inside its search as exact wins,
+//! draws and losses instead of guessing. A shared tablebase is also a [MoveSource], so it can be
+//! a stage of a [PipelineAgent](crate::agent::PipelineAgent).
+
+use std::sync::Arc;
+
+#[cfg(feature = "syzygy")]
+mod syzygy;
+
+#[cfg(feature = "syzygy")]
+pub use syzygy::SyzygyTablebase;
+
+use crate::{agent::MoveSource, board::{Board, CastlingAvailability, Evaluation, Move, PlayerColor}, game::GameState};
+
+/// The result of a position with perfect play, from the point of view of the side to move, as
+/// stored in Syzygy WDL (win/draw/loss) tables.
+///
+/// Syzygy tables take the fifty-move rule into account, which is where the cursed wins and
+/// blessed losses come from. Variants are ordered from worst to best for the side to move.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
+pub enum Wdl
+{
+    /// The side to move gets mated.
+    Loss,
+    /// The side to move would get mated, but the fifty-move rule saves them.
+    BlessedLoss,
+    /// Nobody can force a win.
+    Draw,
+    /// The side to move could mate, but not before the fifty-move rule makes the game a draw.
+    CursedWin,
+    /// The side to move mates.
+    Win,
+}
+
+impl Wdl
+{
+    /// Returns the same result from the other side's point of view.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::tablebase::Wdl;
+    /// assert_eq!(Wdl::Win.flip(), Wdl::Loss);
+    /// assert_eq!(Wdl::Draw.flip(), Wdl::Draw);
+    /// ```
+    pub fn flip(self) -> Self
+    {
+        match self
+        {
+            Self::Loss => Self::Win,
+            Self::BlessedLoss => Self::CursedWin,
+            Self::Draw => Self::Draw,
+            Self::CursedWin => Self::BlessedLoss,
+            Self::Win => Self::Loss,
+        }
+    }
+
+    /// Turns the result into an [Evaluation]. Cursed wins and blessed losses are draws, since the
+    /// game will be drawn by the fifty-move rule before the mate.
+    ///
+    /// # Arguments
+    ///
+    /// * `side_to_move` - The side the result is from the point of view of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_chess_engine::board::{Evaluation, PlayerColor};
+    /// # use rust_chess_engine::tablebase::Wdl;
+    /// assert_eq!(Wdl::Win.to_evaluation(PlayerColor::Black), Evaluation::BlackTablebaseWin);
+    /// assert_eq!(Wdl::CursedWin.to_evaluation(PlayerColor::White), Evaluation::Draw);
+    /// ```
+    pub fn to_evaluation(self, side_to_move: PlayerColor) -> Evaluation
+    {
+        match (self, side_to_move)
+        {
+            (Self::Win, PlayerColor::White) | (Self::Loss, PlayerColor::Black) => Evaluation::WhiteTablebaseWin,
+            (Self::Win, PlayerColor::Black) | (Self::Loss, PlayerColor::White) => Evaluation::BlackTablebaseWin,
+            _ => Evaluation::Draw,
+        }
+    }
+}
+
+/// An endgame tablebase that can be asked for the exact result of positions with few pieces.
+///
+/// This is what the search needs from a Syzygy reader, which answers from the `.rtbw` (WDL) and
+/// `.rtbz` (DTZ) files. Like the Syzygy tables, positions where castling is still allowed are
+/// never covered.
+pub trait Tablebase: Send + Sync
+{
+    /// Returns the most pieces, counting both kings, that a covered position can have.
+    fn max_pieces(&self) -> usize;
+
+    /// Returns the result of the position with perfect play, from the side to move's point of
+    /// view, or [None] if the table for this material isn't available.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look up.
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    /// Returns the distance to zeroing (DTZ) of the position: how many plies until the next
+    /// capture or pawn move on the way to the best result. Positive for wins and negative for
+    /// losses from the side to move's point of view, and 0 for draws. Returns [None] if the table
+    /// for this material isn't available.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to look up.
+    fn probe_dtz(&self, board: &Board) -> Option<i32>;
+
+    /// Returns true if the position is small enough to look up, and can't castle.
+    ///
+    /// # Arguments
+    ///
+    /// * `board` - The position to check.
+    fn covers(&self, board: &Board) -> bool
+    {
+        board.query().result().count() as usize <= self.max_pieces()
+            && board.castling_availability() == CastlingAvailability::new(false, false, false, false)
+    }
+}
+
+/// Picks the move that keeps the best result in a position the tablebase covers, and returns it
+/// with that result. Returns [None] if the tablebase doesn't cover the position, or if a table
+/// needed to decide is missing.
+///
+/// Winning moves that mate or reset the fifty-move counter are preferred, then the ones with the
+/// shortest DTZ, so the win actually gets converted. When losing, the move with the longest DTZ
+/// is played, to make the win as hard as possible.
+///
+/// # Arguments
+///
+/// * `tablebase` - The tablebase to look moves up in.
+/// * `board` - The position to pick a move for. The side to move needs a legal move.
+pub fn best_root_move(tablebase: &dyn Tablebase, board: &Board) -> Option<(Move, Wdl)>
+{
+    if !tablebase.covers(board)
+    {
+        return None;
+    }
+
+    let mut best: Option<(Move, Wdl, i32)> = None;
+    for r#move in board.generate_moves_for_side(board.active_color())
+    {
+        let child = board.attempt_move(&r#move).ok()?;
+        let (wdl, tiebreak) = if child.game_result().has_winner()
+        {
+            // Mating is as good as it gets.
+            (Wdl::Win, i32::MAX)
+        }
+        else
+        {
+            let wdl = tablebase.probe_wdl(&child)?.flip();
+            let dtz = tablebase.probe_dtz(&child)?.abs();
+            let zeroing = child.halfmove_clock() == 0;
+            let tiebreak = match wdl
+            {
+                Wdl::Win | Wdl::CursedWin if zeroing => i32::MAX - 1,
+                Wdl::Win | Wdl::CursedWin => -dtz,
+                // Resetting the counter only gives the winning side more time.
+                Wdl::Loss | Wdl::BlessedLoss if zeroing => -1,
+                Wdl::Loss | Wdl::BlessedLoss => dtz,
+                Wdl::Draw => 0,
+            };
+            (wdl, tiebreak)
+        };
+        if best.is_none_or(|(_, best_wdl, best_tiebreak)| (wdl, tiebreak) > (best_wdl, best_tiebreak))
+        {
+            best = Some((r#move, wdl, tiebreak));
+        }
+    }
+    best.map(|(r#move, wdl, _)| (r#move, wdl))
+}
+
+impl MoveSource for Arc<dyn Tablebase>
+{
+    /// Plays the move that keeps the best result, if the tablebase covers the current position.
+    fn probe(&mut self, game_state: &GameState) -> Option<Move> {
+        best_root_move(self.as_ref(), game_state.current_board()).map(|(r#move, _)| r#move)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests
+{
+    use crate::{board::PieceType, test_support::board_from_fen};
+
+    use super::*;
+
+    /// A stand-in for real tables which only knows about queens and rooks: whoever has more of
+    /// them wins, with a DTZ of one move.
+    pub(crate) struct MaterialTablebase;
+
+    impl MaterialTablebase
+    {
+        fn heavy_pieces(board: &Board, color: PlayerColor) -> usize
+        {
+            [PieceType::Queen, PieceType::Rook].into_iter()
+                .map(|piece_type| board.query().color(color).piece_type(piece_type).result().squares().count())
+                .sum()
+        }
+    }
+
+    impl Tablebase for MaterialTablebase
+    {
+        fn max_pieces(&self) -> usize {
+            4
+        }
+
+        fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+            let ours = Self::heavy_pieces(board, board.active_color());
+            let theirs = Self::heavy_pieces(board, !board.active_color());
+            Some(match ours.cmp(&theirs)
+            {
+                std::cmp::Ordering::Greater => Wdl::Win,
+                std::cmp::Ordering::Equal => Wdl::Draw,
+                std::cmp::Ordering::Less => Wdl::Loss,
+            })
+        }
+
+        fn probe_dtz(&self, board: &Board) -> Option<i32> {
+            Some(match self.probe_wdl(board)?
+            {
+                Wdl::Win | Wdl::CursedWin => 1,
+                Wdl::Draw => 0,
+                Wdl::Loss | Wdl::BlessedLoss => -1,
+            })
+        }
+    }
+
+    #[test]
+    fn root_move_keeps_the_best_result()
+    {
+        // Taking the queen wins, anything else is a draw.
+        let board = board_from_fen("8/8/8/2k5/8/8/2r1Q3/K7 b - - 0 1");
+        let (r#move, wdl) = best_root_move(&MaterialTablebase, &board).unwrap();
+        assert_eq!(board.to_san(&r#move), "Rxe2");
+        assert_eq!(wdl, Wdl::Win);
+    }
+
+    #[test]
+    fn tablebase_is_a_pipeline_stage()
+    {
+        use crate::agent::{Agent, PipelineAgent, WorstMoveAgent};
+
+        let tablebase: Arc<dyn Tablebase> = Arc::new(MaterialTablebase);
+        let mut agent = PipelineAgent::new(WorstMoveAgent::new(1)).add_stage(tablebase);
+        let game_state = GameState::new(board_from_fen("8/8/8/2k5/8/8/2r1Q3/K7 b - - 0 1"));
+        let r#move = agent.agent_move_request(&game_state).played_move().unwrap();
+        assert_eq!(game_state.current_board().to_san(&r#move), "Rxe2");
+    }
+
+    #[test]
+    fn positions_that_can_castle_or_are_too_big_are_not_covered()
+    {
+        assert!(!MaterialTablebase.covers(&board_from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")));
+        assert!(!MaterialTablebase.covers(&board_from_fen("4k3/8/8/8/8/8/PP6/R3K3 w - - 0 1")));
+        assert!(MaterialTablebase.covers(&board_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")));
+        assert!(best_root_move(&MaterialTablebase, &Board::new_default_starting_board()).is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! Implements [SyzygyTablebase], which reads Syzygy `.rtbw` and `.rtbz` files with the
+//! [shakmaty-syzygy](https://docs.rs/shakmaty-syzygy) crate.
+
+use std::{io, path::Path, str::FromStr};
+
+use shakmaty::{fen::Fen, CastlingMode, Chess};
+use shakmaty_syzygy::AmbiguousWdl;
+
+use crate::board::Board;
+
+use super::{Tablebase, Wdl};
+
+/// A [Tablebase] backed by Syzygy table files on disk.
+///
+/// Tables are added a directory at a time with [SyzygyTablebase::add_directory]. WDL (`.rtbw`)
+/// and DTZ (`.rtbz`) files are both needed to probe a material combination, since the WDL result
+/// takes the halfmove clock into account, which needs the DTZ.
+///
+/// DTZ tables round some distances to whole moves, so right at the edge of the fifty-move rule
+/// a win can't always be told apart from a cursed win. Those positions are reported as
+/// [Wdl::CursedWin] (or [Wdl::BlessedLoss] for the other side), i.e a draw, rather than
+/// guessing.
+///
+/// Only available with the `syzygy` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use rust_chess_engine::agent::MinmaxAgent;
+/// # use rust_chess_engine::tablebase::SyzygyTablebase;
+/// let mut tablebase = SyzygyTablebase::new();
+/// tablebase.add_directory("/path/to/syzygy/3-4-5").unwrap();
+/// let mut minmax_agent = MinmaxAgent::new(6);
+/// minmax_agent.set_tablebase(Some(Arc::new(tablebase)));
+/// ```
+#[derive(Default)]
+pub struct SyzygyTablebase
+{
+    tables: shakmaty_syzygy::Tablebase<Chess>,
+}
+
+impl SyzygyTablebase
+{
+    /// Creates a tablebase without any tables, which covers nothing until tables are added.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Adds every table file in a directory, and returns how many files were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory to look for `.rtbw` and `.rtbz` files in. Subdirectories aren't
+    ///   searched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [io::Error] if the directory can't be read.
+    pub fn add_directory(&mut self, path: impl AsRef<Path>) -> io::Result<usize>
+    {
+        self.tables.add_directory(path)
+    }
+
+    /// Converts the board to a shakmaty position, going through its FEN.
+    fn position(board: &Board) -> Option<Chess>
+    {
+        Fen::from_str(&board.board_configuration().to_string()).ok()?
+            .into_position(CastlingMode::Standard).ok()
+    }
+}
+
+impl Tablebase for SyzygyTablebase
+{
+    fn max_pieces(&self) -> usize {
+        self.tables.max_pieces()
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        let wdl = self.tables.probe_wdl(&Self::position(board)?).ok()?;
+        Some(match wdl
+        {
+            AmbiguousWdl::Loss => Wdl::Loss,
+            AmbiguousWdl::MaybeLoss | AmbiguousWdl::BlessedLoss => Wdl::BlessedLoss,
+            AmbiguousWdl::Draw => Wdl::Draw,
+            AmbiguousWdl::CursedWin | AmbiguousWdl::MaybeWin => Wdl::CursedWin,
+            AmbiguousWdl::Win => Wdl::Win,
+        })
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        let dtz = self.tables.probe_dtz(&Self::position(board)?).ok()?;
+        Some(dtz.ignore_rounding().0)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{tablebase::best_root_move, test_support::board_from_fen};
+
+    use super::*;
+
+    #[test]
+    fn missing_tables_cover_nothing()
+    {
+        let mut tablebase = SyzygyTablebase::new();
+        assert!(tablebase.add_directory("/this/directory/does/not/exist").is_err());
+        let board = board_from_fen("8/8/8/2k5/8/8/2r1Q3/K7 b - - 0 1");
+        assert_eq!(tablebase.max_pieces(), 0);
+        assert!(!tablebase.covers(&board));
+        assert_eq!(tablebase.probe_wdl(&board), None);
+        assert_eq!(tablebase.probe_dtz(&board), None);
+    }
+
+    #[test]
+    fn boards_convert_to_the_same_position()
+    {
+        let fen = "r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1";
+        let position = SyzygyTablebase::position(&board_from_fen(fen)).unwrap();
+        assert_eq!(Fen::from_position(&position, shakmaty::EnPassantMode::Always).to_string(), fen);
+    }
+
+    /// Needs the 3-4-5 piece tables, in the directory named by the `SYZYGY_PATH` environment
+    /// variable.
+    #[test]
+    #[ignore = "needs Syzygy tables, see SYZYGY_PATH"]
+    fn real_tables_are_probed()
+    {
+        let mut tablebase = SyzygyTablebase::new();
+        tablebase.add_directory(std::env::var("SYZYGY_PATH").unwrap()).unwrap();
+        assert_eq!(tablebase.max_pieces(), 5);
+
+        // King and queen against king is a win for the side with the queen.
+        let board = board_from_fen("8/8/8/8/8/2k5/8/KQ6 w - - 0 1");
+        assert_eq!(tablebase.probe_wdl(&board), Some(Wdl::Win));
+        assert!(tablebase.probe_dtz(&board).unwrap() > 0);
+        assert_eq!(best_root_move(&tablebase, &board).map(|(_, wdl)| wdl), Some(Wdl::Win));
+
+        // Two knights can't force mate.
+        let board = board_from_fen("8/8/8/8/8/2k5/8/KNN5 b - - 0 1");
+        assert_eq!(tablebase.probe_wdl(&board), Some(Wdl::Draw));
+    }
+}
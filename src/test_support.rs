@@ -0,0 +1,13 @@
+//! Helpers for writing tests against boards.
+//!
+//! This module is only compiled for this crate's own tests or when the `test-support` feature is
+//! enabled, so that other crates (agents, front ends, etc.) can use the same helpers in their own
+//! tests.
+
+mod hashmap_diff;
+mod board_diff;
+mod fixtures;
+
+pub use hashmap_diff::{compare_hashmaps, print_hashmap_differences};
+pub use board_diff::{BoardDiff, SquareDifference, diff_board_configurations, diff_boards, print_board_differences};
+pub use fixtures::board_from_fen;
@@ -0,0 +1,184 @@
+//! Compares two [BoardConfiguration]s (or [Board]s) and reports every way they differ.
+//!
+//! When a board test fails, `assert_eq!` on two configurations dumps two giant piece maps which
+//! are difficult to compare by eye. [BoardDiff] instead lists only the squares and state fields
+//! that are different.
+
+use std::fmt::Display;
+
+use crate::board::{Board, BoardConfiguration, CastlingAvailability, Piece, PlayerColor, Square};
+
+use super::compare_hashmaps;
+
+/// A single square whose contents differ between two boards.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SquareDifference
+{
+    /// The square that differs.
+    pub square: Square,
+    /// What is on the square on the left (first) board, if anything.
+    pub left: Option<Piece>,
+    /// What is on the square on the right (second) board, if anything.
+    pub right: Option<Piece>,
+}
+
+/// All of the differences between two board configurations.
+///
+/// Fields that are the same on both boards are [None] (or an empty [Vec]), fields that are
+/// different are stored as `(left, right)` pairs.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct BoardDiff
+{
+    /// The squares that have different pieces on them, sorted by rank and then file.
+    pub squares: Vec<SquareDifference>,
+    /// The active color, if it differs.
+    pub active_color: Option<(PlayerColor, PlayerColor)>,
+    /// The castling availability, if it differs.
+    pub castling_availability: Option<(CastlingAvailability, CastlingAvailability)>,
+    /// The en passant target square, if it differs.
+    pub en_passant_target_square: Option<(Option<Square>, Option<Square>)>,
+    /// The halfmove clock, if it differs.
+    pub halfmove_clock: Option<(u16, u16)>,
+    /// The fullmove number, if it differs.
+    pub fullmove_number: Option<(u16, u16)>,
+}
+
+impl BoardDiff
+{
+    /// Returns true if the two boards that were compared were identical.
+    pub fn is_empty(&self) -> bool
+    {
+        *self == Self::default()
+    }
+}
+
+/// Compares two [BoardConfiguration]s, returning a [BoardDiff] of their differences.
+///
+/// # Arguments
+///
+/// * `left` - The first configuration
+/// * `right` - The second configuration
+///
+/// # Examples
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use rust_chess_engine::board::{BoardConfiguration, Square};
+/// # use rust_chess_engine::test_support::diff_board_configurations;
+/// let left = BoardConfiguration::default();
+/// let right = BoardConfiguration::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+/// let diff = diff_board_configurations(&left, &right);
+/// assert_eq!(diff.squares.len(), 2);
+/// assert_eq!(diff.squares[0].square, Square::new(1, 4));
+/// assert!(diff.active_color.is_some());
+/// assert!(diff.castling_availability.is_none());
+/// ```
+pub fn diff_board_configurations(left: &BoardConfiguration, right: &BoardConfiguration) -> BoardDiff
+{
+    let (left_only, right_only) = compare_hashmaps(left.pieces(), right.pieces());
+    let mut squares: Vec<Square> = left_only.iter().map(|(square, _)| **square)
+        .chain(right_only.iter().map(|(square, _)| **square))
+        .collect();
+    squares.sort_by_key(|square| (square.rank, square.file));
+    squares.dedup();
+
+    BoardDiff
+    {
+        squares: squares.into_iter().map(|square| SquareDifference
+        {
+            square,
+            left: left.pieces().get(&square).copied(),
+            right: right.pieces().get(&square).copied(),
+        }).collect(),
+        active_color: field_difference(left.active_color(), right.active_color()),
+        castling_availability: field_difference(left.castling_availability(), right.castling_availability()),
+        en_passant_target_square: field_difference(left.en_passant_target_square(), right.en_passant_target_square()),
+        halfmove_clock: field_difference(left.halfmove_clock(), right.halfmove_clock()),
+        fullmove_number: field_difference(left.fullmove_number(), right.fullmove_number()),
+    }
+}
+
+/// Same as [diff_board_configurations] but takes two [Board]s.
+pub fn diff_boards(left: &Board, right: &Board) -> BoardDiff
+{
+    diff_board_configurations(&left.board_configuration(), &right.board_configuration())
+}
+
+/// Prints the differences between two boards to stdout. Prints nothing if they're identical.
+pub fn print_board_differences(left: &Board, right: &Board)
+{
+    let diff = diff_boards(left, right);
+    if !diff.is_empty()
+    {
+        print!("{}", diff);
+    }
+}
+
+fn field_difference<T: PartialEq>(left: T, right: T) -> Option<(T, T)>
+{
+    match left == right
+    {
+        true => None,
+        false => Some((left, right)),
+    }
+}
+
+impl Display for BoardDiff
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for difference in self.squares.iter()
+        {
+            let file = (b'a' + difference.square.file) as char;
+            let rank = difference.square.rank + 1;
+            let left = difference.left.map_or("(empty)".to_string(), |piece| piece.to_string());
+            let right = difference.right.map_or("(empty)".to_string(), |piece| piece.to_string());
+            writeln!(f, "{}{}: {} -> {}", file, rank, left, right)?;
+        }
+        if let Some((left, right)) = self.active_color
+        {
+            writeln!(f, "active color: {} -> {}", left, right)?;
+        }
+        if let Some((left, right)) = self.castling_availability
+        {
+            writeln!(f, "castling availability: {:?} -> {:?}", left, right)?;
+        }
+        if let Some((left, right)) = self.en_passant_target_square
+        {
+            writeln!(f, "en passant target square: {:?} -> {:?}", left, right)?;
+        }
+        if let Some((left, right)) = self.halfmove_clock
+        {
+            writeln!(f, "halfmove clock: {} -> {}", left, right)?;
+        }
+        if let Some((left, right)) = self.fullmove_number
+        {
+            writeln!(f, "fullmove number: {} -> {}", left, right)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn identical_boards_have_no_differences()
+    {
+        let diff = diff_boards(&Board::new_default_starting_board(), &Board::new_default_starting_board());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn display_lists_changed_squares_and_state()
+    {
+        let left = BoardConfiguration::default();
+        let right = BoardConfiguration::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let diff = diff_board_configurations(&left, &right);
+        assert_eq!(diff.to_string(), "e2: P -> (empty)\ne4: (empty) -> P\nactive color: White -> Black\n");
+    }
+}
@@ -0,0 +1,29 @@
+//! Shortcuts for setting up the boards tests start from.
+
+use std::str::FromStr;
+
+use crate::board::{Board, BoardConfiguration};
+
+/// Returns the board described by a FEN record.
+///
+/// # Arguments
+///
+/// * `fen` - The position, in Forsyth-Edwards notation.
+///
+/// # Panics
+///
+/// Panics if `fen` isn't a valid position, since a test with a broken setup can't tell us
+/// anything.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::board::{Board, PlayerColor};
+/// # use rust_chess_engine::test_support::board_from_fen;
+/// let board = board_from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1");
+/// assert_eq!(board.active_color(), PlayerColor::Black);
+/// ```
+pub fn board_from_fen(fen: &str) -> Board
+{
+    Board::new_board_with_configuration(&BoardConfiguration::from_str(fen).unwrap())
+}
@@ -1,9 +1,28 @@
-//! This is a private module that returns the differences between hashmaps.
+//! Returns the differences between hashmaps.
 //! This is useful to see how two piece mailboxes differ for testing.
 
 use std::{collections::HashMap, hash::Hash};
 use std::fmt::Debug;
 
+/// Compares two hashmaps and returns the entries that only exist in (or have a different value
+/// in) the first and second hashmap respectively.
+///
+/// # Arguments
+///
+/// * `hashmap_1` - The first hashmap
+/// * `hashmap_2` - The second hashmap
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use rust_chess_engine::test_support::compare_hashmaps;
+/// let hashmap_1 = HashMap::from([(1, "a"), (2, "b")]);
+/// let hashmap_2 = HashMap::from([(1, "a"), (2, "c")]);
+/// let (only_1, only_2) = compare_hashmaps(&hashmap_1, &hashmap_2);
+/// assert_eq!(only_1, vec![(&2, &"b")]);
+/// assert_eq!(only_2, vec![(&2, &"c")]);
+/// ```
 pub fn compare_hashmaps<'a, 'b, K, V>(hashmap_1: &'a HashMap<K, V>, hashmap_2: &'b HashMap<K, V>) -> (Vec<(&'a K, &'a V)>, Vec<(&'b K, &'b V)>)
 where K: Eq + Hash, V: PartialEq
 {
@@ -13,6 +32,7 @@ where K: Eq + Hash, V: PartialEq
     (hashmap_1_only.collect(), hashmap_2_only.collect())
 }
 
+/// Prints the output of [compare_hashmaps] to stdout.
 pub fn print_hashmap_differences<K, V>(hashmap_1: &HashMap<K, V>, hashmap_2: &HashMap<K, V>)
 where K: Eq + Hash + Debug, V: PartialEq + Debug
 {
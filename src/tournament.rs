@@ -0,0 +1,303 @@
+//! Runs round-robin tournaments between any number of [Agent]s, and sums them up in a
+//! [Crosstable].
+
+use std::fmt::Display;
+
+use log::info;
+
+use crate::{agent::Agent, game::{GameState, TimeControl}, match_runner::{elo_from_score, MatchRunner, LOG_TARGET}};
+
+/// The most a performance rating can be above or below the field, in Elo. A perfect score would
+/// otherwise be infinitely far ahead, so like FIDE we cap it.
+const MAXIMUM_PERFORMANCE: f64 = 800.0;
+
+/// An all-play-all (round-robin) tournament: every player plays every other player the same
+/// number of times.
+///
+/// Each round, every pair of players plays two games, one with each player as White, so nobody
+/// gets White more often than anyone else. The games of a pairing are played by a
+/// [MatchRunner], so they can start from an opening suite and be played with a clock.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_chess_engine::agent::{Agent, GreedyAgent, MinmaxAgent, WorstMoveAgent};
+/// # use rust_chess_engine::tournament::Tournament;
+/// let players: Vec<(String, Box<dyn Agent>)> = vec![
+///     ("Greedy".to_string(), Box::new(GreedyAgent::new())),
+///     ("Worst".to_string(), Box::new(WorstMoveAgent::new(0))),
+///     ("Minmax".to_string(), Box::new(MinmaxAgent::new(1))),
+/// ];
+/// let mut tournament = Tournament::new(players, 1);
+/// let crosstable = tournament.run();
+/// assert_eq!(crosstable.games(0), 4);
+/// // Every game hands out one point between the two players.
+/// assert_eq!((0..3).map(|player| crosstable.points(player)).sum::<f64>(), 6.0);
+/// println!("{}", crosstable);
+/// ```
+pub struct Tournament
+{
+    names: Vec<String>,
+    /// The agents are only taken out while they're playing a pairing.
+    agents: Vec<Option<Box<dyn Agent>>>,
+    rounds: usize,
+    openings: Vec<GameState>,
+    time_control: Option<TimeControl>,
+}
+
+impl Tournament
+{
+    /// Creates a [Tournament] between the given players, played from the default starting
+    /// position without a clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `players` - The name and agent of every player.
+    /// * `rounds` - How many rounds to play. Each round is two games between every pair of
+    ///   players.
+    pub fn new(players: Vec<(String, Box<dyn Agent>)>, rounds: usize) -> Self
+    {
+        let (names, agents) = players.into_iter().map(|(name, agent)| (name, Some(agent))).unzip();
+        Self
+        {
+            names,
+            agents,
+            rounds,
+            openings: Vec::new(),
+            time_control: None,
+        }
+    }
+
+    /// Returns the same tournament, but starting every pairing's games from `openings`. See
+    /// [MatchRunner::with_openings].
+    ///
+    /// # Arguments
+    ///
+    /// * `openings` - The positions to start games from.
+    pub fn with_openings(self, openings: Vec<GameState>) -> Self
+    {
+        Self
+        {
+            openings,
+            ..self
+        }
+    }
+
+    /// Returns the same tournament, but playing every game with a chess clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `time_control` - How much time each player gets per game.
+    pub fn with_time_control(self, time_control: TimeControl) -> Self
+    {
+        Self
+        {
+            time_control: Some(time_control),
+            ..self
+        }
+    }
+
+    /// Plays every pairing of the tournament, and returns the crosstable.
+    pub fn run(&mut self) -> Crosstable
+    {
+        let players = self.names.len();
+        let mut crosstable = Crosstable
+        {
+            names: self.names.clone(),
+            points: vec![vec![0.0; players]; players],
+            games: vec![vec![0; players]; players],
+        };
+        for first in 0..players
+        {
+            for second in first + 1..players
+            {
+                let agent_a = self.agents[first].take().expect("Agents are put back after every pairing");
+                let agent_b = self.agents[second].take().expect("Agents are put back after every pairing");
+                let mut runner = MatchRunner::new(agent_a, agent_b, 2 * self.rounds).with_openings(self.openings.clone());
+                if let Some(time_control) = self.time_control
+                {
+                    runner = runner.with_time_control(time_control);
+                }
+                let summary = runner.run();
+                info!(target: LOG_TARGET, "{} vs {}: {}", self.names[first], self.names[second], summary);
+                let first_points = summary.wins() as f64 + summary.draws() as f64 / 2.0;
+                crosstable.points[first][second] += first_points;
+                crosstable.points[second][first] += summary.games() as f64 - first_points;
+                crosstable.games[first][second] += summary.games() as u32;
+                crosstable.games[second][first] += summary.games() as u32;
+                let (agent_a, agent_b) = runner.into_agents();
+                self.agents[first] = Some(agent_a);
+                self.agents[second] = Some(agent_b);
+            }
+        }
+        crosstable
+    }
+
+    /// Hands back every player's name and agent, in the order they were given.
+    pub fn into_players(self) -> Vec<(String, Box<dyn Agent>)>
+    {
+        self.names.into_iter()
+            .zip(self.agents.into_iter().map(|agent| agent.expect("Agents are put back after every pairing")))
+            .collect()
+    }
+}
+
+/// The results of a [Tournament]: how many points every player scored against every other
+/// player. Players are numbered in the order they were given to the tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crosstable
+{
+    names: Vec<String>,
+    /// `points[a][b]` is how many points player `a` scored against player `b`.
+    points: Vec<Vec<f64>>,
+    /// `games[a][b]` is how many games players `a` and `b` played against each other.
+    games: Vec<Vec<u32>>,
+}
+
+impl Crosstable
+{
+    /// Returns the names of the players.
+    pub fn names(&self) -> &[String]
+    {
+        &self.names
+    }
+
+    /// Returns how many points `player` scored against `opponent`, counting a win as 1 point and
+    /// a draw as half a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - The player whose points to count.
+    /// * `opponent` - The opponent they scored them against.
+    pub fn points_against(&self, player: usize, opponent: usize) -> f64
+    {
+        self.points[player][opponent]
+    }
+
+    /// Returns how many points `player` scored in total.
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - The player whose points to count.
+    pub fn points(&self, player: usize) -> f64
+    {
+        self.points[player].iter().sum()
+    }
+
+    /// Returns how many games `player` played in total.
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - The player whose games to count.
+    pub fn games(&self, player: usize) -> u32
+    {
+        self.games[player].iter().sum()
+    }
+
+    /// Returns how strongly `player` played compared to the average player in the tournament,
+    /// in Elo, going by their score. It's capped at 800 Elo either way, since a perfect score
+    /// says the player is stronger, but not how much.
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - The player to rate.
+    pub fn performance(&self, player: usize) -> f64
+    {
+        match self.games(player)
+        {
+            0 => 0.0,
+            games => elo_from_score(self.points(player) / games as f64).clamp(-MAXIMUM_PERFORMANCE, MAXIMUM_PERFORMANCE),
+        }
+    }
+
+    /// Returns the players, best first. Players on the same number of points stay in the order
+    /// they were given.
+    pub fn standings(&self) -> Vec<usize>
+    {
+        let mut standings: Vec<usize> = (0..self.names.len()).collect();
+        standings.sort_by(|a, b| self.points(*b).total_cmp(&self.points(*a)));
+        standings
+    }
+}
+
+impl Display for Crosstable
+{
+    /// Writes the crosstable with the players ranked by score. Each row shows a player's points
+    /// against everyone else, with the columns in the same order as the rows.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let standings = self.standings();
+        let name_width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max("Player".len());
+        write!(f, "{:>3} {:<name_width$} {:>9} {:>6}", "#", "Player", "Score", "Perf")?;
+        for rank in 1..=standings.len()
+        {
+            write!(f, " {:>4}", rank)?;
+        }
+        writeln!(f)?;
+        for (rank, player) in standings.iter().enumerate()
+        {
+            let score = format!("{}/{}", self.points(*player), self.games(*player));
+            write!(f, "{:>3} {:<name_width$} {:>9} {:>+6.0}", rank + 1, self.names[*player], score, self.performance(*player))?;
+            for opponent in standings.iter()
+            {
+                if opponent == player
+                {
+                    write!(f, " {:>4}", "*")?;
+                }
+                else
+                {
+                    write!(f, " {:>4}", self.points_against(*player, *opponent))?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::agent::ScriptedAgent;
+
+    use super::*;
+
+    #[test]
+    fn pairings_split_the_points_evenly_with_colors_balanced()
+    {
+        // Everyone plays Fool's mate, so whoever has Black wins. The first player of a pairing
+        // has White in its first game.
+        let script = |sans: &[&str]| Box::new(ScriptedAgent::from_san(sans).unwrap()) as Box<dyn Agent>;
+        let players = vec![
+            ("A".to_string(), script(&["f3", "g4", "e5", "Qh4#", "f3", "g4", "e5", "Qh4#"])),
+            ("B".to_string(), script(&["e5", "Qh4#", "f3", "g4", "f3", "g4", "e5", "Qh4#"])),
+            ("C".to_string(), script(&["e5", "Qh4#", "f3", "g4", "e5", "Qh4#", "f3", "g4"])),
+        ];
+        let mut tournament = Tournament::new(players, 1);
+        let crosstable = tournament.run();
+        for player in 0..3
+        {
+            assert_eq!(crosstable.points(player), 2.0);
+            assert_eq!(crosstable.games(player), 4);
+            assert_eq!(crosstable.performance(player), 0.0);
+        }
+        assert_eq!(crosstable.points_against(0, 1), 1.0);
+        assert_eq!(crosstable.standings(), vec![0, 1, 2]);
+        let players = tournament.into_players();
+        assert_eq!(players.len(), 3);
+    }
+
+    #[test]
+    fn performance_is_capped()
+    {
+        let crosstable = Crosstable
+        {
+            names: vec!["Winner".to_string(), "Loser".to_string()],
+            points: vec![vec![0.0, 2.0], vec![0.0, 0.0]],
+            games: vec![vec![0, 2], vec![2, 0]],
+        };
+        assert_eq!(crosstable.performance(0), 800.0);
+        assert_eq!(crosstable.performance(1), -800.0);
+        assert_eq!(crosstable.standings(), vec![0, 1]);
+        assert!(crosstable.to_string().contains("Winner"));
+    }
+}